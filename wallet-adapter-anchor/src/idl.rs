@@ -0,0 +1,210 @@
+//! Build program instructions straight from an Anchor IDL without depending on anchor-client.
+
+use std::collections::HashMap;
+
+use anchor_lang_idl::types::{Idl, IdlInstruction, IdlInstructionAccountItem};
+use anyhow::{bail, Context, Result};
+use borsh::BorshSerialize;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+/// Find an instruction in the IDL by its name (eg. `initialize`)
+pub fn find_instruction<'a>(idl: &'a Idl, name: &str) -> Result<&'a IdlInstruction> {
+    idl.instructions
+        .iter()
+        .find(|ix| ix.name == name)
+        .with_context(|| format!("instruction {name} not found in idl"))
+}
+
+/// Encode the instruction data: the 8 byte discriminator followed by the borsh encoded args.
+/// Multiple args can be passed as a tuple, borsh encodes tuples as their fields in order.
+pub fn instruction_data<T: BorshSerialize>(
+    instruction: &IdlInstruction,
+    args: &T,
+) -> Result<Vec<u8>> {
+    let mut data = instruction.discriminator.clone();
+    args.serialize(&mut data)?;
+    Ok(data)
+}
+
+/// Resolve the account metas of an instruction in IDL order.
+///
+/// Accounts are looked up by name in `accounts`, falling back to a constant address from the IDL.
+/// Optional accounts that aren't provided are replaced by the program id, like anchor does.
+pub fn account_metas(
+    program_id: &Pubkey,
+    instruction: &IdlInstruction,
+    accounts: &HashMap<String, Pubkey>,
+) -> Result<Vec<AccountMeta>> {
+    let mut metas = vec![];
+    push_account_metas(program_id, &instruction.accounts, accounts, &mut metas)?;
+    Ok(metas)
+}
+
+fn push_account_metas(
+    program_id: &Pubkey,
+    items: &[IdlInstructionAccountItem],
+    accounts: &HashMap<String, Pubkey>,
+    metas: &mut Vec<AccountMeta>,
+) -> Result<()> {
+    for item in items {
+        match item {
+            IdlInstructionAccountItem::Composite(composite) => {
+                push_account_metas(program_id, &composite.accounts, accounts, metas)?;
+            }
+            IdlInstructionAccountItem::Single(account) => {
+                let pubkey = match (accounts.get(&account.name), &account.address) {
+                    (Some(pubkey), _) => *pubkey,
+                    (None, Some(address)) => address.parse()?,
+                    (None, None) if account.optional => {
+                        metas.push(AccountMeta::new_readonly(*program_id, false));
+                        continue;
+                    }
+                    (None, None) => bail!("missing account {}", account.name),
+                };
+
+                metas.push(if account.writable {
+                    AccountMeta::new(pubkey, account.signer)
+                } else {
+                    AccountMeta::new_readonly(pubkey, account.signer)
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Build the instruction `name` of the program described by `idl`
+pub fn build_instruction<T: BorshSerialize>(
+    idl: &Idl,
+    name: &str,
+    args: &T,
+    accounts: &HashMap<String, Pubkey>,
+) -> Result<Instruction> {
+    let program_id: Pubkey = idl.address.parse()?;
+    let instruction = find_instruction(idl, name)?;
+
+    Ok(Instruction {
+        program_id,
+        accounts: account_metas(&program_id, instruction, accounts)?,
+        data: instruction_data(instruction, args)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// The playground IDL with a `transfer` instruction covering every kind of account
+    fn idl() -> Idl {
+        let mut idl: Idl = serde_json::from_slice(include_bytes!(
+            "../../examples/all-wallets-base-ui/test_data/anchor_playground.json"
+        ))
+        .unwrap();
+        idl.instructions.push(
+            serde_json::from_value(json!({
+                "name": "transfer",
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [
+                    { "name": "authority", "writable": true, "signer": true },
+                    {
+                        "name": "vault",
+                        "accounts": [
+                            { "name": "vault_account", "writable": true },
+                            { "name": "mint" }
+                        ]
+                    },
+                    { "name": "referrer", "optional": true },
+                    {
+                        "name": "system_program",
+                        "address": "11111111111111111111111111111111"
+                    }
+                ],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "memo", "type": "string" }
+                ]
+            }))
+            .unwrap(),
+        );
+        idl
+    }
+
+    fn accounts(names: &[&str]) -> HashMap<String, Pubkey> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), Pubkey::new_unique()))
+            .collect()
+    }
+
+    #[test]
+    fn builds_the_playground_initialize() {
+        let instruction = build_instruction(&idl(), "initialize", &(), &HashMap::new()).unwrap();
+
+        assert_eq!(
+            instruction.program_id.to_string(),
+            "FeMv1J2sekHZUpaRmaJYYhABZ6EKADD6bG3MCs2f8HH2"
+        );
+        assert_eq!(instruction.data, [175, 175, 109, 31, 13, 152, 155, 237]);
+        assert!(instruction.accounts.is_empty());
+    }
+
+    #[test]
+    fn encodes_the_args_after_the_discriminator() {
+        let idl = idl();
+        let instruction = find_instruction(&idl, "transfer").unwrap();
+
+        let data = instruction_data(instruction, &(42u64, "hi".to_string())).unwrap();
+        assert_eq!(
+            data,
+            [1, 2, 3, 4, 5, 6, 7, 8, 42, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, b'h', b'i']
+        );
+    }
+
+    #[test]
+    fn resolves_the_accounts_in_idl_order() {
+        let idl = idl();
+        let program_id: Pubkey = idl.address.parse().unwrap();
+        let instruction = find_instruction(&idl, "transfer").unwrap();
+        let accounts = accounts(&["authority", "vault_account", "mint", "referrer"]);
+
+        let metas = account_metas(&program_id, instruction, &accounts).unwrap();
+        assert_eq!(
+            metas,
+            [
+                AccountMeta::new(accounts["authority"], true),
+                AccountMeta::new(accounts["vault_account"], false),
+                AccountMeta::new_readonly(accounts["mint"], false),
+                AccountMeta::new_readonly(accounts["referrer"], false),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn replaces_missing_optional_accounts_with_the_program_id() {
+        let idl = idl();
+        let program_id: Pubkey = idl.address.parse().unwrap();
+        let instruction = find_instruction(&idl, "transfer").unwrap();
+
+        let metas = account_metas(
+            &program_id,
+            instruction,
+            &accounts(&["authority", "vault_account", "mint"]),
+        )
+        .unwrap();
+        assert_eq!(metas[3], AccountMeta::new_readonly(program_id, false));
+
+        let err = account_metas(&program_id, instruction, &accounts(&["authority"])).unwrap_err();
+        assert_eq!(err.to_string(), "missing account vault_account");
+    }
+
+    #[test]
+    fn rejects_unknown_instructions() {
+        let err = build_instruction(&idl(), "withdraw", &(), &HashMap::new()).unwrap_err();
+        assert_eq!(err.to_string(), "instruction withdraw not found in idl");
+    }
+}
@@ -0,0 +1,23 @@
+//! Bridge that lets a [`BaseSignerWalletAdapter`] act as the payer/wallet of an
+//! `anchor-client` [`Client`](https://docs.rs/anchor-client/latest/anchor_client/struct.Client.html).
+//!
+//! anchor-client expects a synchronous `solana_sdk::signer::Signer`, while the adapters sign
+//! asynchronously. [`AnchorWallet`] implements `Signer` with a `BlockingSigner`, which
+//! rebuilds the transaction from the message bytes anchor hands it, lets the adapter sign it
+//! and blocks on the result.
+//!
+//! ```ignore
+//! let wallet = Rc::new(AnchorWallet::new(burner_wallet));
+//! let client = Client::new(Cluster::Devnet, wallet);
+//! let program = client.program(program_id)?;
+//! ```
+//!
+//! Blocking is only possible on native targets, so the signer bridge isn't available on wasm.
+//! The [`idl`] module builds instructions from an IDL and works everywhere.
+
+pub mod idl;
+#[cfg(not(target_arch = "wasm32"))]
+mod wallet;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use wallet::AnchorWallet;
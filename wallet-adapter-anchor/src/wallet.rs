@@ -0,0 +1,40 @@
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use wallet_adapter_base::{BaseSignerWalletAdapter, BlockingSigner};
+
+/// An anchor-client payer, the [`BlockingSigner`] of the adapter
+#[derive(Debug, Clone)]
+pub struct AnchorWallet<W: BaseSignerWalletAdapter> {
+    signer: BlockingSigner<W>,
+}
+
+impl<W: BaseSignerWalletAdapter> AnchorWallet<W> {
+    pub fn new(adapter: W) -> Self {
+        Self {
+            signer: BlockingSigner::new(adapter),
+        }
+    }
+
+    pub fn adapter(&self) -> &W {
+        self.signer.adapter()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.signer.into_inner()
+    }
+}
+
+impl<W: BaseSignerWalletAdapter> Signer for AnchorWallet<W> {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        self.signer.try_pubkey()
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.signer.try_sign_message(message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.signer.is_interactive()
+    }
+}
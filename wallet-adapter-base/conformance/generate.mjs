@@ -0,0 +1,126 @@
+// Builds the golden vectors of `src/conformance.rs` with @solana/web3.js.
+//
+//   node generate.mjs          print the vectors as Rust, to paste into `GOLDEN_VECTORS`
+//   node generate.mjs --check  fail unless web3.js serializes to the committed bytes
+
+import { readFileSync } from "node:fs";
+
+import {
+  Keypair,
+  Message,
+  MessageV0,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  VersionedTransaction,
+} from "@solana/web3.js";
+import bs58 from "bs58";
+import nacl from "tweetnacl";
+
+const signer = Keypair.fromSeed(new Uint8Array(32).fill(1));
+const recipient = new PublicKey(new Uint8Array(32).fill(2));
+const lookupTable = new PublicKey(new Uint8Array(32).fill(4));
+const recentBlockhash = bs58.encode(new Uint8Array(32).fill(3));
+const header = {
+  numRequiredSignatures: 1,
+  numReadonlySignedAccounts: 0,
+  numReadonlyUnsignedAccounts: 1,
+};
+const transfer = SystemProgram.transfer({
+  fromPubkey: signer.publicKey,
+  toPubkey: recipient,
+  lamports: 1_000_000,
+});
+
+const legacyMessage = new Message({
+  header,
+  accountKeys: [signer.publicKey, recipient, SystemProgram.programId],
+  recentBlockhash,
+  instructions: [{ programIdIndex: 2, accounts: [0, 1], data: bs58.encode(transfer.data) }],
+});
+
+function legacy(signed) {
+  const signatures = signed
+    ? [bs58.encode(nacl.sign.detached(legacyMessage.serialize(), signer.secretKey))]
+    : [];
+  return Transaction.populate(legacyMessage, signatures).serialize({
+    requireAllSignatures: false,
+    verifySignatures: false,
+  });
+}
+
+function v0(message) {
+  const transaction = new VersionedTransaction(new MessageV0(message));
+  transaction.sign([signer]);
+  return transaction.serialize();
+}
+
+const vectors = [
+  { name: "legacy_unsigned", versioned: false, bytes: legacy(false) },
+  { name: "legacy_signed", versioned: false, bytes: legacy(true) },
+  {
+    name: "v0_signed",
+    versioned: true,
+    bytes: v0({
+      header,
+      staticAccountKeys: [signer.publicKey, recipient, SystemProgram.programId],
+      recentBlockhash,
+      compiledInstructions: [{ programIdIndex: 2, accountKeyIndexes: [0, 1], data: transfer.data }],
+      addressTableLookups: [],
+    }),
+  },
+  {
+    name: "v0_lookup_table_signed",
+    versioned: true,
+    bytes: v0({
+      header,
+      staticAccountKeys: [signer.publicKey, SystemProgram.programId],
+      recentBlockhash,
+      compiledInstructions: [
+        { programIdIndex: 1, accountKeyIndexes: [0, 2, 3], data: transfer.data },
+      ],
+      addressTableLookups: [{ accountKey: lookupTable, writableIndexes: [5], readonlyIndexes: [7] }],
+    }),
+  },
+];
+
+const hex = (bytes) => Buffer.from(bytes).toString("hex");
+
+if (process.argv.includes("--check")) {
+  const source = readFileSync(new URL("../src/conformance.rs", import.meta.url), "utf8");
+  const committed = new Map(
+    [...source.matchAll(/name: "(\w+)",\s*versioned: (true|false),\s*hex: "([0-9a-f]+)"/g)].map(
+      ([, name, versioned, bytes]) => [name, { versioned: versioned === "true", bytes }],
+    ),
+  );
+
+  let failed = false;
+  for (const { name, versioned, bytes } of vectors) {
+    const expected = committed.get(name);
+    const actual = hex(bytes);
+    if (!expected) {
+      console.error(`${name}: missing from src/conformance.rs`);
+      failed = true;
+    } else if (expected.versioned !== versioned) {
+      console.error(`${name}: expected versioned = ${versioned}`);
+      failed = true;
+    } else if (expected.bytes !== actual) {
+      let index = 0;
+      while (expected.bytes.substr(2 * index, 2) === actual.substr(2 * index, 2)) {
+        index += 1;
+      }
+      console.error(`${name}: web3.js differs from byte ${index} on`);
+      console.error(`  web3.js   ${actual}\n  committed ${expected.bytes}`);
+      failed = true;
+    }
+  }
+  process.exit(failed ? 1 : 0);
+} else {
+  for (const { name, versioned, bytes } of vectors) {
+    console.log(`    GoldenVector {
+        name: "${name}",
+        versioned: ${versioned},
+        hex: "${hex(bytes)}",
+    },`);
+  }
+}
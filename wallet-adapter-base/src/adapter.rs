@@ -6,24 +6,122 @@ use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
 use solana_sdk::transaction::Transaction;
-use wallet_adapter_common::connection::Connection;
+use solana_system_interface::instruction as system_instruction;
+use wallet_adapter_common::connection::{Connection, LatestBlockhash};
+use wallet_adapter_common::maybe_send::{MaybeSend, MaybeSync};
+use wallet_adapter_common::standard::{SolanaChain, WalletAccount, WalletFeature};
 use wallet_adapter_common::types::{SendOptions, SendTransactionOptions};
 
-use crate::transaction::{SupportedTransactionVersions, TransactionOrVersionedTransaction};
-use crate::WalletError;
+use crate::transaction::{
+    append_instruction, with_fee_payer, SupportedTransactionVersions,
+    TransactionOrVersionedTransaction,
+};
+use crate::{TransactionBuilder, WalletError};
 
 #[derive(Debug)]
 pub enum WalletAdapterEvent {
     Connect(Pubkey),
+    /// `disconnect` is waiting on the wallet, `Disconnect` follows once it is done
+    Disconnecting,
+    /// The wallet switched to another account while connected
+    AccountChanged(Pubkey),
     Disconnect,
-    Error(WalletError),
+    /// The wallet was locked, it stays connected but won't sign until unlocked
+    Locked,
+    Unlocked,
+    /// An airdrop to the connected account was requested, eg. by a burner wallet funding itself
+    AirdropRequested(Signature),
+    /// The requested airdrop reached the requested commitment
+    AirdropConfirmed(Signature),
+    /// The transaction's blockhash expired before it was sent, `attempt` signs it again
+    /// with a fresh one, see [`BaseWalletAdapter::send_transaction_with_blockhash_retry`]
+    BlockhashExpired {
+        attempt: u32,
+    },
+    /// A failure that had no caller to return it to, eg. a rejected connect
+    Error {
+        /// Name of the failing wallet, to tell adapters sharing one stream apart
+        wallet: String,
+        operation: WalletOperation,
+        error: WalletError,
+    },
     ReadyStateChange(WalletReadyState),
+    /// The session went idle for longer than its timeout and the wallet is being disconnected,
+    /// see [`SessionTimeoutWalletAdapter`](crate::SessionTimeoutWalletAdapter)
+    SessionExpired,
+    /// A wallet-specific event of the provider, eg. `chainChanged`, with its first argument
+    /// as JSON. See [`BaseWalletAdapter::subscribe_provider_event`].
+    ProviderEvent {
+        name: String,
+        payload: serde_json::Value,
+    },
+}
+
+/// What a wallet was doing when it emitted [`WalletAdapterEvent::Error`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletOperation {
+    Connect,
+    AutoConnect,
+    Disconnect,
+    /// Reconnecting after the user switched to an account the app wasn't authorized for
+    AccountChange,
+    /// Funding a burner wallet with an airdrop
+    Airdrop,
+}
+
+type Listener = Box<dyn FnMut(&WalletAdapterEvent) + Send>;
+
+#[derive(Default)]
+struct Listeners {
+    next_id: u64,
+    listeners: Vec<(u64, Listener)>,
+}
+
+impl std::fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Listeners")
+            .field("count", &self.listeners.len())
+            .finish()
+    }
+}
+
+/// Keeps a listener registered with [`WalletAdapterEventEmitter::on`], removes it when dropped
+#[must_use = "the listener is removed when the subscription is dropped"]
+#[derive(Debug)]
+pub struct EventSubscription {
+    id: u64,
+    listeners: std::sync::Weak<std::sync::Mutex<Listeners>>,
+}
+
+impl EventSubscription {
+    /// Keep the listener for as long as the emitter lives
+    pub fn forget(self) {
+        std::mem::forget(self)
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        if let Some(listeners) = self.listeners.upgrade() {
+            if let Ok(mut listeners) = listeners.lock() {
+                listeners.listeners.retain(|(id, _)| *id != self.id);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct WalletAdapterEventEmitter {
     tx: tokio::sync::mpsc::Sender<WalletAdapterEvent>,
     rx: std::sync::Arc<tokio::sync::Mutex<tokio::sync::mpsc::Receiver<WalletAdapterEvent>>>,
+    listeners: std::sync::Arc<std::sync::Mutex<Listeners>>,
+    ready_state: std::sync::Arc<tokio::sync::watch::Sender<WalletReadyState>>,
+}
+
+impl Default for WalletAdapterEventEmitter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WalletAdapterEventEmitter {
@@ -32,16 +130,70 @@ impl WalletAdapterEventEmitter {
         Self {
             tx,
             rx: std::sync::Arc::new(tokio::sync::Mutex::new(rx)),
+            listeners: Default::default(),
+            ready_state: std::sync::Arc::new(
+                tokio::sync::watch::channel(WalletReadyState::NotDetected).0,
+            ),
+        }
+    }
+
+    /// The ready state of the last `ReadyStateChange` emitted, see [`BaseWalletAdapter::watch_ready_state`]
+    pub fn watch_ready_state(&self) -> tokio::sync::watch::Receiver<WalletReadyState> {
+        self.ready_state.subscribe()
+    }
+
+    fn set_ready_state(&self, ready_state: WalletReadyState) {
+        self.ready_state.send_if_modified(|current| {
+            let modified = *current != ready_state;
+            *current = ready_state;
+            modified
+        });
+    }
+
+    /// Call `listener` with every emitted event until the returned subscription is dropped.
+    /// Listeners run synchronously inside `emit`, they must not emit events themselves.
+    pub fn on(
+        &self,
+        listener: impl FnMut(&WalletAdapterEvent) + Send + 'static,
+    ) -> EventSubscription {
+        let mut listeners = self.listeners.lock().unwrap_or_else(|err| err.into_inner());
+        let id = listeners.next_id;
+        listeners.next_id += 1;
+        listeners.listeners.push((id, Box::new(listener)));
+
+        EventSubscription {
+            id,
+            listeners: std::sync::Arc::downgrade(&self.listeners),
+        }
+    }
+
+    fn notify(&self, event: &WalletAdapterEvent) {
+        if let WalletAdapterEvent::ReadyStateChange(ready_state) = event {
+            self.set_ready_state(*ready_state);
+        }
+        if let Ok(mut listeners) = self.listeners.lock() {
+            for (_, listener) in listeners.listeners.iter_mut() {
+                listener(event);
+            }
         }
     }
 
     pub async fn emit(&self, event: WalletAdapterEvent) -> Result<()> {
+        self.notify(&event);
         Ok(self.tx.send(event).await?)
     }
     pub fn emit_sync(&self, event: WalletAdapterEvent) -> Result<()> {
+        self.notify(&event);
         Ok(self.tx.blocking_send(event)?)
     }
 
+    /// Emit without blocking, fails if the channel is full.
+    /// Use this from JS callbacks where `emit_sync` could block or panic.
+    pub fn try_emit(&self, event: WalletAdapterEvent) -> Result<()> {
+        self.notify(&event);
+        Ok(self.tx.try_send(event)?)
+    }
+
     pub async fn recv(&self) -> Option<WalletAdapterEvent> {
         self.rx.lock().await.recv().await
     }
@@ -49,6 +201,15 @@ impl WalletAdapterEventEmitter {
     pub fn try_recv(&self) -> Option<WalletAdapterEvent> {
         self.rx.try_lock().ok()?.try_recv().ok()
     }
+
+    /// The events of [`recv`](Self::recv) as a stream, eg. to `filter` or `select` them.
+    /// It ends once the emitter and all its clones are dropped.
+    pub fn events(&self) -> impl futures::Stream<Item = WalletAdapterEvent> {
+        futures::stream::unfold(self.rx.clone(), |rx| async move {
+            let event = rx.lock().await.recv().await?;
+            Some((event, rx))
+        })
+    }
 }
 
 /**
@@ -81,21 +242,114 @@ pub enum WalletReadyState {
     Unsupported,
 }
 
-#[async_trait::async_trait(?Send)]
-pub trait BaseWalletAdapter: DynClone {
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait BaseWalletAdapter: DynClone + MaybeSend + MaybeSync {
     fn event_emitter(&self) -> WalletAdapterEventEmitter;
     fn name(&self) -> String;
     fn url(&self) -> String;
+    /// A data URI, empty when the wallet crate is built without its `icon` feature
     fn icon(&self) -> String;
     fn ready_state(&self) -> WalletReadyState;
     fn public_key(&self) -> Option<Pubkey>;
     fn connecting(&self) -> bool;
+
+    /// Whether `disconnect` is waiting on the wallet, always false for wallets disconnecting at once
+    fn disconnecting(&self) -> bool {
+        false
+    }
+
     fn supported_transaction_versions(&self) -> Option<SupportedTransactionVersions>;
 
     fn connected(&self) -> bool {
         self.public_key().is_some()
     }
 
+    /// Wallet Standard features this adapter supports
+    fn features(&self) -> Vec<WalletFeature> {
+        vec![
+            WalletFeature::StandardConnect,
+            WalletFeature::StandardDisconnect,
+            WalletFeature::StandardEvents,
+        ]
+    }
+
+    /// The connected account in its Wallet Standard shape
+    fn account(&self) -> Option<WalletAccount> {
+        let public_key = self.public_key()?;
+        Some(WalletAccount::new(
+            public_key,
+            SolanaChain::ALL.to_vec(),
+            self.features(),
+        ))
+    }
+
+    /// Watch the ready state, eg. to reorder a wallet list as wallets are detected
+    /// instead of polling `ready_state()`
+    fn watch_ready_state(&self) -> tokio::sync::watch::Receiver<WalletReadyState> {
+        let event_emitter = self.event_emitter();
+        event_emitter.set_ready_state(self.ready_state());
+        event_emitter.watch_ready_state()
+    }
+
+    /// Call `f` with the public key every time the wallet connects
+    fn on_connect(&self, mut f: Box<dyn FnMut(Pubkey) + Send>) -> EventSubscription {
+        self.event_emitter().on(move |event| {
+            if let WalletAdapterEvent::Connect(public_key) = event {
+                f(*public_key)
+            }
+        })
+    }
+
+    fn on_disconnect(&self, mut f: Box<dyn FnMut() + Send>) -> EventSubscription {
+        self.event_emitter().on(move |event| {
+            if let WalletAdapterEvent::Disconnect = event {
+                f()
+            }
+        })
+    }
+
+    fn on_error(&self, mut f: Box<dyn FnMut(&WalletError) + Send>) -> EventSubscription {
+        self.event_emitter().on(move |event| {
+            if let WalletAdapterEvent::Error { error, .. } = event {
+                f(error)
+            }
+        })
+    }
+
+    /// Forward the provider's `event` as [`WalletAdapterEvent::ProviderEvent`], for events the
+    /// adapter doesn't handle itself. Fails for wallets without a provider emitting events.
+    fn subscribe_provider_event(&self, event: &str) -> crate::Result<()> {
+        Err(anyhow::anyhow!("{} has no provider to subscribe to {event}", self.name()).into())
+    }
+
+    /// Stop forwarding the provider's `event`
+    fn unsubscribe_provider_event(&self, event: &str) -> crate::Result<()> {
+        Err(anyhow::anyhow!(
+            "{} has no provider to unsubscribe from {event}",
+            self.name()
+        )
+        .into())
+    }
+
+    /// Call `f` with the payload every time the provider emits `event`,
+    /// see [`Self::subscribe_provider_event`]
+    fn on_provider_event(
+        &self,
+        event: &str,
+        mut f: Box<dyn FnMut(&serde_json::Value) + Send>,
+    ) -> crate::Result<EventSubscription> {
+        self.subscribe_provider_event(event)?;
+        let event = event.to_string();
+        Ok(self.event_emitter().on(move |wallet_event| {
+            if let WalletAdapterEvent::ProviderEvent { name, payload } = wallet_event {
+                if *name == event {
+                    f(payload)
+                }
+            }
+        }))
+    }
+
     async fn disconnect(&self) -> Result<()>;
     async fn auto_connect(&mut self) -> crate::Result<()> {
         self.connect().await
@@ -110,34 +364,140 @@ pub trait BaseWalletAdapter: DynClone {
         options: Option<SendTransactionOptions>,
     ) -> crate::Result<Signature>;
 
+    /// Send `transactions` in order, wallets that can approve them together ask the user once.
+    /// Returns the signature of each transaction, `None` for the ones that weren't sent, and
+    /// fails when none was.
+    ///
+    /// The default sends them one after the other, each with its own approval, and stops at
+    /// the first one failing.
+    async fn send_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+        connection: &dyn Connection,
+        options: Option<SendOptions>,
+    ) -> crate::Result<Vec<Option<Signature>>> {
+        send_all_in_turn(self, transactions, connection, options).await
+    }
+
+    /// Send `transaction`, and when the cluster refuses it for an expired blockhash, eg. because
+    /// the wallet popup stayed open too long, fetch a fresh one and have the wallet sign again.
+    ///
+    /// Tries up to `max_attempts` times, emitting [`WalletAdapterEvent::BlockhashExpired`]
+    /// before each retry. Extra signers aren't supported as they would have to sign again too,
+    /// and versioned transactions are sent once since their blockhash isn't filled in.
+    async fn send_transaction_with_blockhash_retry(
+        &self,
+        mut transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendOptions>,
+        max_attempts: u32,
+    ) -> crate::Result<Signature> {
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .send_transaction(
+                    transaction.clone(),
+                    connection,
+                    options.clone().map(SendTransactionOptions::from),
+                )
+                .await;
+
+            match (result, &mut transaction) {
+                (Err(err), TransactionOrVersionedTransaction::Transaction(tx))
+                    if err.is_blockhash_expired() && attempt < max_attempts =>
+                {
+                    attempt += 1;
+                    tracing::debug!("blockhash expired, signing again (attempt {attempt})");
+                    self.event_emitter()
+                        .try_emit(WalletAdapterEvent::BlockhashExpired { attempt })?;
+
+                    tx.message.recent_blockhash = Hash::default();
+                    tx.signatures = vec![Signature::default(); tx.signatures.len()];
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+
+    /// Transfer `lamports` from the connected wallet to `to` using the system program
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn transfer_sol(
+        &self,
+        to: Pubkey,
+        lamports: u64,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> crate::Result<Signature> {
+        let Some(public_key) = self.public_key() else {
+            return Err(crate::WalletError::WalletNotConnected);
+        };
+
+        let transaction = TransactionBuilder::new()
+            .fee_payer(public_key)
+            .add_instruction(system_instruction::transfer(&public_key, &to, lamports))
+            .build_legacy()?;
+
+        self.send_transaction(transaction, connection, options)
+            .await
+    }
+
     async fn prepare_transaction(
         &self,
-        mut transaction: Transaction,
+        transaction: Transaction,
         connection: &dyn Connection,
         options: Option<&SendOptions>,
     ) -> crate::Result<Transaction> {
+        Ok(self
+            .prepare_transaction_with_context(transaction, connection, options)
+            .await?
+            .0)
+    }
+
+    /// [`prepare_transaction`](Self::prepare_transaction) also returning the blockhash it fetched,
+    /// `None` when the transaction already had one
+    async fn prepare_transaction_with_context(
+        &self,
+        mut transaction: Transaction,
+        connection: &dyn Connection,
+        options: Option<&SendOptions>,
+    ) -> crate::Result<(Transaction, Option<LatestBlockhash>)> {
         let Some(public_key) = self.public_key() else {
             return Err(crate::WalletError::WalletNotConnected);
         };
 
-        // TODO: double check if this works and makes sense
-        if transaction.message.signer_keys().is_empty() {
-            transaction.message.account_keys.push(public_key);
+        // the fee payer is the first account, rebuilding the message keeps the accounts ordered
+        match options.and_then(|o| o.fee_payer) {
+            Some(fee_payer) if transaction.message.account_keys.first() != Some(&fee_payer) => {
+                transaction = with_fee_payer(transaction, &fee_payer)?;
+            }
+            None if transaction.message.signer_keys().is_empty() => {
+                transaction = with_fee_payer(transaction, &public_key)?;
+            }
+            _ => {}
         }
 
+        if let Some(memo) = options.and_then(|o| o.memo.as_ref()) {
+            transaction = append_instruction(transaction, crate::build_memo(memo.as_bytes(), &[]))?;
+        }
+
+        let mut latest_blockhash = None;
         if transaction.message.recent_blockhash == Hash::default() {
             let blockhash = connection
-                .get_recent_blockhash(
-                    options.map(|o| o.preflight_commitment).flatten(),
-                    options.map(|o| o.min_context_slots).flatten(),
+                .get_latest_blockhash_with_context(
+                    options.and_then(|o| o.preflight_commitment),
+                    options.and_then(|o| o.min_context_slots),
                 )
                 .await?;
-            transaction.message.recent_blockhash = blockhash;
+            transaction.message.recent_blockhash = blockhash.blockhash;
+            latest_blockhash = Some(blockhash);
 
-            tracing::debug!("recent_blockhash: {}", blockhash);
+            tracing::debug!("recent_blockhash: {:?}", blockhash);
         }
 
-        Ok(transaction)
+        Ok((transaction, latest_blockhash))
     }
 
     /// Check if the transaction is supported by the wallet
@@ -169,6 +529,34 @@ pub trait BaseWalletAdapter: DynClone {
 
 dyn_clone::clone_trait_object!(BaseWalletAdapter);
 
+/// Send `transactions` one after the other with [`BaseWalletAdapter::send_transaction`] until
+/// one fails, the default of [`BaseWalletAdapter::send_all_transactions`]
+pub async fn send_all_in_turn<W: BaseWalletAdapter + ?Sized>(
+    wallet: &W,
+    transactions: Vec<TransactionOrVersionedTransaction>,
+    connection: &dyn Connection,
+    options: Option<SendOptions>,
+) -> crate::Result<Vec<Option<Signature>>> {
+    let count = transactions.len();
+    let mut signatures = Vec::with_capacity(count);
+    for transaction in transactions {
+        let options = options.clone().map(SendTransactionOptions::from);
+        match wallet
+            .send_transaction(transaction, connection, options)
+            .await
+        {
+            Ok(signature) => signatures.push(Some(signature)),
+            Err(err) if signatures.is_empty() => return Err(err),
+            Err(err) => {
+                tracing::warn!("stopped after {} transactions: {err}", signatures.len());
+                break;
+            }
+        }
+    }
+    signatures.resize(count, None);
+    Ok(signatures)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,4 +565,56 @@ mod tests {
     fn can_base_wallet_adapter_trait_be_made_into_object() {
         let _wallet_adapter: Option<Box<dyn BaseWalletAdapter>> = None;
     }
+
+    #[test]
+    fn listeners_are_removed_when_the_subscription_is_dropped() {
+        let emitter = WalletAdapterEventEmitter::new();
+        let count = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let counter = count.clone();
+        let subscription = emitter.on(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+        emitter.try_emit(WalletAdapterEvent::Disconnect).unwrap();
+        drop(subscription);
+        emitter.try_emit(WalletAdapterEvent::Disconnect).unwrap();
+
+        assert_eq!(count.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn the_event_stream_ends_with_the_emitter() {
+        use futures::StreamExt;
+
+        let emitter = WalletAdapterEventEmitter::new();
+        let events = emitter.events();
+        emitter.try_emit(WalletAdapterEvent::Locked).unwrap();
+        emitter.try_emit(WalletAdapterEvent::Unlocked).unwrap();
+        drop(emitter);
+
+        let events = futures::executor::block_on(events.collect::<Vec<_>>());
+        assert!(matches!(
+            events[..],
+            [WalletAdapterEvent::Locked, WalletAdapterEvent::Unlocked]
+        ));
+    }
+
+    #[test]
+    fn ready_state_watchers_see_every_change() {
+        let emitter = WalletAdapterEventEmitter::new();
+        let mut ready_state = emitter.watch_ready_state();
+        assert_eq!(*ready_state.borrow(), WalletReadyState::NotDetected);
+
+        emitter
+            .try_emit(WalletAdapterEvent::ReadyStateChange(
+                WalletReadyState::Installed,
+            ))
+            .unwrap();
+
+        assert!(ready_state.has_changed().unwrap());
+        assert_eq!(
+            *ready_state.borrow_and_update(),
+            WalletReadyState::Installed
+        );
+    }
 }
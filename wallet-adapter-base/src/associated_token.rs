@@ -0,0 +1,90 @@
+use anyhow::{ensure, Result};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_system_interface::program as system_program;
+use wallet_adapter_common::connection::Connection;
+
+use crate::solana_pay::{associated_token_address, ASSOCIATED_TOKEN_PROGRAM_ID};
+
+/// `CreateIdempotent` of the associated token account program
+const CREATE_IDEMPOTENT_INSTRUCTION: u8 = 1;
+
+/// Create the associated token account of `wallet` for `mint`, funded by `payer`.
+/// Succeeds without doing anything when the account exists already.
+pub fn build_create_ata_idempotent(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(wallet, mint, token_program), false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![CREATE_IDEMPOTENT_INSTRUCTION],
+    }
+}
+
+/// The associated token account of `wallet` for `mint`, with the instruction creating it
+/// if it doesn't exist yet. Fails if the address is taken by an account of another program.
+pub async fn get_or_create_ata(
+    connection: &dyn Connection,
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<(Pubkey, Option<Instruction>)> {
+    let address = associated_token_address(wallet, mint, token_program);
+    match connection.get_account_info(&address).await? {
+        Some(account) => {
+            ensure!(
+                account.owner == *token_program,
+                "{address} is owned by {} instead of {token_program}",
+                account.owner
+            );
+            Ok((address, None))
+        }
+        None => Ok((
+            address,
+            Some(build_create_ata_idempotent(
+                payer,
+                wallet,
+                mint,
+                token_program,
+            )),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wallet_adapter_common::token::TOKEN_2022_PROGRAM_ID;
+
+    use super::*;
+
+    #[test]
+    fn creates_the_account_of_the_mint_program() {
+        let (payer, wallet, mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let instruction =
+            build_create_ata_idempotent(&payer, &wallet, &mint, &TOKEN_2022_PROGRAM_ID);
+
+        assert_eq!(instruction.program_id, ASSOCIATED_TOKEN_PROGRAM_ID);
+        assert_eq!(instruction.data, [1]);
+        assert!(instruction.accounts[0].is_signer && instruction.accounts[0].is_writable);
+        assert_eq!(
+            instruction.accounts[1].pubkey,
+            associated_token_address(&wallet, &mint, &TOKEN_2022_PROGRAM_ID)
+        );
+        assert_eq!(instruction.accounts[5].pubkey, TOKEN_2022_PROGRAM_ID);
+    }
+}
@@ -0,0 +1,143 @@
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+
+use crate::{BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter};
+use crate::{Result, TransactionOrVersionedTransaction};
+
+/// Blocking `sign_message` of the adapter
+type SignMessage<W> = fn(&W, &[u8]) -> Result<Vec<u8>>;
+
+/// A synchronous `solana_sdk` [`Signer`] backed by an adapter, for libraries that take a
+/// `&dyn Signer` like anchor-client or the spl helpers.
+///
+/// Messages of transactions the wallet signs are rebuilt into the transaction and signed with
+/// `sign_transaction`, blocking the thread until the wallet answers. Other messages can only be
+/// signed when created [`with_message_signing`](Self::with_message_signing).
+/// Blocking isn't possible on wasm, so it is only available on native targets.
+#[derive(Debug, Clone)]
+pub struct BlockingSigner<W> {
+    adapter: W,
+    sign_message: Option<SignMessage<W>>,
+}
+
+impl<W: BaseSignerWalletAdapter> BlockingSigner<W> {
+    /// A signer for transaction messages only
+    pub fn new(adapter: W) -> Self {
+        Self {
+            adapter,
+            sign_message: None,
+        }
+    }
+
+    pub fn adapter(&self) -> &W {
+        &self.adapter
+    }
+
+    pub fn into_inner(self) -> W {
+        self.adapter
+    }
+
+    fn sign_transaction_message(
+        &self,
+        public_key: Pubkey,
+        message: VersionedMessage,
+    ) -> std::result::Result<Signature, SignerError> {
+        let signer_keys =
+            &message.static_account_keys()[..message.header().num_required_signatures as usize];
+
+        let index = signer_keys
+            .iter()
+            .position(|key| key == &public_key)
+            .ok_or(SignerError::KeypairPubkeyMismatch)?;
+
+        let transaction = match message {
+            VersionedMessage::Legacy(message) => {
+                TransactionOrVersionedTransaction::Transaction(Transaction::new_unsigned(message))
+            }
+            VersionedMessage::V0(_) => {
+                TransactionOrVersionedTransaction::VersionedTransaction(VersionedTransaction {
+                    signatures: vec![Signature::default(); signer_keys.len()],
+                    message,
+                })
+            }
+        };
+
+        let signed = futures::executor::block_on(self.adapter.sign_transaction(transaction))
+            .map_err(|err| SignerError::Custom(format!("{err}: {err:?}")))?;
+
+        let signatures = match signed {
+            TransactionOrVersionedTransaction::Transaction(tx) => tx.signatures,
+            TransactionOrVersionedTransaction::VersionedTransaction(tx) => tx.signatures,
+        };
+
+        let signature = signatures
+            .get(index)
+            .copied()
+            .ok_or(SignerError::NotEnoughSigners)?;
+
+        if signature == Signature::default() {
+            return Err(SignerError::NotEnoughSigners);
+        }
+
+        Ok(signature)
+    }
+}
+
+impl<W: BaseMessageSignerWalletAdapter> BlockingSigner<W> {
+    /// A signer that signs the bytes of messages which aren't transactions as they are with
+    /// `sign_message`, like a `Keypair` would. Off-chain messages have to be serialized first,
+    /// eg. with [`serialize_message`](wallet_adapter_common::offchain_message::serialize_message).
+    pub fn with_message_signing(adapter: W) -> Self {
+        Self {
+            adapter,
+            sign_message: Some(|adapter, message| {
+                futures::executor::block_on(adapter.sign_message(message))
+            }),
+        }
+    }
+}
+
+impl<W: BaseSignerWalletAdapter> Signer for BlockingSigner<W> {
+    fn try_pubkey(&self) -> std::result::Result<Pubkey, SignerError> {
+        self.adapter
+            .public_key()
+            .ok_or_else(|| SignerError::Custom("wallet not connected".to_string()))
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> std::result::Result<Signature, SignerError> {
+        let public_key = self.try_pubkey()?;
+
+        let transaction_message = bincode::deserialize::<VersionedMessage>(message)
+            .ok()
+            .filter(|transaction_message| {
+                let signers = transaction_message.header().num_required_signatures as usize;
+                transaction_message
+                    .static_account_keys()
+                    .get(..signers)
+                    .is_some_and(|signer_keys| signer_keys.contains(&public_key))
+            });
+
+        match (transaction_message, self.sign_message) {
+            (Some(transaction_message), _) => {
+                tracing::debug!("blocking signer signing transaction for {}", public_key);
+                self.sign_transaction_message(public_key, transaction_message)
+            }
+            (None, Some(sign_message)) => {
+                let signature = sign_message(&self.adapter, message)
+                    .map_err(|err| SignerError::Custom(format!("{err}: {err:?}")))?;
+                Signature::try_from(signature.as_slice())
+                    .map_err(|err| SignerError::Custom(err.to_string()))
+            }
+            (None, None) => Err(SignerError::InvalidInput(
+                "not a transaction message signed by the wallet".to_string(),
+            )),
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
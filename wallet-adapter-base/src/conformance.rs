@@ -0,0 +1,195 @@
+//! Golden wire-format vectors to catch serialization differences with web3.js early.
+//!
+//! The bytes are laid out by hand following the transaction format web3.js' `serialize()`
+//! produces for an already compiled message: compact-u16 lengths, the `0x80` prefix of v0
+//! messages and the address table lookups after the instructions. All vectors use
+//!
+//! - the payer/signer: the ed25519 keypair of the seed `[1; 32]`
+//! - the recipient `[2; 32]`, the system program and the blockhash `[3; 32]`
+//! - a system transfer of 1_000_000 lamports
+//! - the lookup table `[4; 32]`, loading its index 5 writable and 7 readonly
+//!
+//! `conformance/generate.mjs` rebuilds them with web3.js' `Message`/`MessageV0`, printing them
+//! for this file or, with `--check`, failing unless `serialize()` gives the same bytes.
+
+use anyhow::{bail, Result};
+
+use crate::TransactionOrVersionedTransaction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoldenVector {
+    pub name: &'static str,
+    /// Whether web3.js reads it as a `VersionedTransaction`
+    pub versioned: bool,
+    /// Hex of the serialized transaction
+    pub hex: &'static str,
+}
+
+impl GoldenVector {
+    pub fn bytes(&self) -> Vec<u8> {
+        hex::decode(self.hex).expect("golden vectors are valid hex")
+    }
+
+    pub fn transaction(&self) -> Result<TransactionOrVersionedTransaction> {
+        TransactionOrVersionedTransaction::deserialize(&self.bytes())
+    }
+}
+
+pub const GOLDEN_VECTORS: &[GoldenVector] = &[
+    GoldenVector {
+        name: "legacy_unsigned",
+        versioned: false,
+        hex: "0100000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010001038a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c02020202020202020202020202020202020202020202020202020202020202020000000000000000000000000000000000000000000000000000000000000000030303030303030303030303030303030303030303030303030303030303030301020200010c0200000040420f0000000000",
+    },
+    GoldenVector {
+        name: "legacy_signed",
+        versioned: false,
+        hex: "01b033059fc60d833f1027350d31401c321c45b7e54477ae7c2fa0211592a57b3592bdea62c63e1173d707a6904197cb25b7087d090d360a7caa6e4ab28da12f0d010001038a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c02020202020202020202020202020202020202020202020202020202020202020000000000000000000000000000000000000000000000000000000000000000030303030303030303030303030303030303030303030303030303030303030301020200010c0200000040420f0000000000",
+    },
+    GoldenVector {
+        name: "v0_signed",
+        versioned: true,
+        hex: "01dfae1f8582b6365c307adfcbabe447bd758b7c3b04be9866542b6360dc671106a3f4e460367e317eed77f06b70cef4a674d855d51a9273e28f17316385cf550280010001038a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c02020202020202020202020202020202020202020202020202020202020202020000000000000000000000000000000000000000000000000000000000000000030303030303030303030303030303030303030303030303030303030303030301020200010c0200000040420f000000000000",
+    },
+    GoldenVector {
+        name: "v0_lookup_table_signed",
+        versioned: true,
+        hex: "012ef0bd022812c5abdd805cb5648cf0017be879c2ad000f31dc37354873a410676b4402245c6133361dcb5bf5f288ac5e725baf95ed94d2d9cbf247f31ede590a80010001028a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c000000000000000000000000000000000000000000000000000000000000000003030303030303030303030303030303030303030303030303030303030303030101030002030c0200000040420f000000000001040404040404040404040404040404040404040404040404040404040404040401050107",
+    },
+];
+
+/// Fails unless `transaction` serializes to exactly `expected`,
+/// reporting the first differing byte
+pub fn check_serializes_to(
+    transaction: &TransactionOrVersionedTransaction,
+    expected: &[u8],
+) -> Result<()> {
+    let actual = transaction.serialize()?;
+    if let Some(index) = actual
+        .iter()
+        .zip(expected)
+        .position(|(actual, expected)| actual != expected)
+    {
+        bail!(
+            "byte {index} differs: {:#04x} instead of {:#04x}",
+            actual[index],
+            expected[index]
+        );
+    }
+    if actual.len() != expected.len() {
+        bail!("{} bytes instead of {}", actual.len(), expected.len());
+    }
+    Ok(())
+}
+
+/// Fails unless `vector` deserializes to the expected kind of transaction
+/// and serializes back to the same bytes
+pub fn check_vector(vector: &GoldenVector) -> Result<()> {
+    let transaction = vector.transaction()?;
+    if transaction.is_versioned() != vector.versioned {
+        bail!("{}: expected versioned = {}", vector.name, vector.versioned);
+    }
+
+    check_serializes_to(&transaction, &vector.bytes()).map_err(|err| err.context(vector.name))
+}
+
+/// Panics with the name of the first golden vector that doesn't round trip
+pub fn assert_conformance() {
+    for vector in GOLDEN_VECTORS {
+        if let Err(err) = check_vector(vector) {
+            panic!("{err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::CompiledInstruction;
+    use solana_sdk::message::{v0, Message, MessageHeader, VersionedMessage};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signer::keypair::keypair_from_seed;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::{Transaction, VersionedTransaction};
+    use solana_system_interface::program as system_program;
+
+    use super::*;
+
+    #[test]
+    fn golden_vectors_match_the_messages_solana_sdk_builds() {
+        assert_conformance();
+
+        let signer = keypair_from_seed(&[1; 32]).unwrap();
+        let header = MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 1,
+        };
+        let mut data = vec![2, 0, 0, 0];
+        data.extend_from_slice(&1_000_000u64.to_le_bytes());
+
+        let legacy = Message {
+            header,
+            account_keys: vec![
+                signer.pubkey(),
+                Pubkey::new_from_array([2; 32]),
+                system_program::id(),
+            ],
+            recent_blockhash: Hash::new_from_array([3; 32]),
+            instructions: vec![CompiledInstruction::new_from_raw_parts(
+                2,
+                data.clone(),
+                vec![0, 1],
+            )],
+        };
+        let lookup = v0::Message {
+            header,
+            account_keys: vec![signer.pubkey(), system_program::id()],
+            recent_blockhash: Hash::new_from_array([3; 32]),
+            instructions: vec![CompiledInstruction::new_from_raw_parts(
+                1,
+                data,
+                vec![0, 2, 3],
+            )],
+            address_table_lookups: vec![v0::MessageAddressTableLookup {
+                account_key: Pubkey::new_from_array([4; 32]),
+                writable_indexes: vec![5],
+                readonly_indexes: vec![7],
+            }],
+        };
+
+        let unsigned = TransactionOrVersionedTransaction::Transaction(Transaction::new_unsigned(
+            legacy.clone(),
+        ));
+        let signed = TransactionOrVersionedTransaction::Transaction(Transaction::new(
+            &[&signer],
+            legacy.clone(),
+            legacy.recent_blockhash,
+        ));
+        let v0 = TransactionOrVersionedTransaction::VersionedTransaction(
+            VersionedTransaction::try_new(
+                VersionedMessage::V0(v0::Message {
+                    header,
+                    account_keys: legacy.account_keys.clone(),
+                    recent_blockhash: legacy.recent_blockhash,
+                    instructions: legacy.instructions.clone(),
+                    address_table_lookups: vec![],
+                }),
+                &[&signer],
+            )
+            .unwrap(),
+        );
+        let v0_lookup = TransactionOrVersionedTransaction::VersionedTransaction(
+            VersionedTransaction::try_new(VersionedMessage::V0(lookup), &[&signer]).unwrap(),
+        );
+
+        for (vector, transaction) in GOLDEN_VECTORS.iter().zip([unsigned, signed, v0, v0_lookup]) {
+            assert_eq!(
+                hex::encode(transaction.serialize().unwrap()),
+                vector.hex,
+                "{}",
+                vector.name
+            );
+        }
+    }
+}
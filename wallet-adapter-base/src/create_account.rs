@@ -0,0 +1,73 @@
+use anyhow::Result;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_system_interface::instruction as system_instruction;
+use wallet_adapter_common::connection::Connection;
+
+/// Create `new_account` with `space` bytes owned by `owner`, funded by `payer` with the
+/// rent-exempt minimum of the public clusters. Both `payer` and `new_account` sign.
+pub fn build_create_account(
+    payer: &Pubkey,
+    new_account: &Pubkey,
+    space: usize,
+    owner: &Pubkey,
+) -> Instruction {
+    system_instruction::create_account(
+        payer,
+        new_account,
+        Rent::default().minimum_balance(space),
+        space as u64,
+        owner,
+    )
+}
+
+/// [`build_create_account`] funded with the rent-exempt minimum `connection` reports,
+/// for clusters with their own rent
+pub async fn build_create_account_with_rent(
+    connection: &dyn Connection,
+    payer: &Pubkey,
+    new_account: &Pubkey,
+    space: usize,
+    owner: &Pubkey,
+) -> Result<Instruction> {
+    let lamports = connection
+        .get_minimum_balance_for_rent_exemption(space)
+        .await?;
+
+    Ok(system_instruction::create_account(
+        payer,
+        new_account,
+        lamports,
+        space as u64,
+        owner,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_system_interface::instruction::SystemInstruction;
+
+    use super::*;
+
+    #[test]
+    fn funds_new_accounts_with_the_rent_exempt_minimum() {
+        let token_program = wallet_adapter_common::token::TOKEN_PROGRAM_ID;
+        let instruction = build_create_account(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            165,
+            &token_program,
+        );
+
+        assert_eq!(
+            bincode::deserialize::<SystemInstruction>(&instruction.data).unwrap(),
+            SystemInstruction::CreateAccount {
+                lamports: 2_039_280,
+                space: 165,
+                owner: token_program,
+            }
+        );
+        assert!(instruction.accounts.iter().all(|account| account.is_signer));
+    }
+}
@@ -0,0 +1,426 @@
+//! Forwarding of the adapter traits for wrappers like [`crate::MiddlewareWalletAdapter`]
+
+/// Implements an adapter trait for a wrapper, forwarding the listed methods to a field and
+/// adding the methods written after them:
+///
+/// ```ignore
+/// delegate_adapter! {
+///     impl[W: BaseWalletAdapter + ?Sized + 'static] BaseWalletAdapter for Wrapper<W>;
+///     to inner: name, url, icon;
+///
+///     async fn connect(&mut self) -> crate::Result<()> { .. }
+/// }
+/// ```
+///
+/// The forwarded methods are expanded in place so `async_trait` sees them.
+/// Wrappers with hooks around sending or signing implement the methods that send or sign
+/// themselves instead of forwarding them, which would skip the hooks.
+macro_rules! delegate_adapter {
+    (
+        impl[$($generics:tt)*] $trait:ident for $ty:ty;
+        to $field:ident: $($method:ident),* $(,)?;
+        $($item:tt)*
+    ) => {
+        $crate::delegate::delegate_adapter!(
+            @munch $trait $field { impl<$($generics)*> $crate::$trait for $ty } [$($item)*]
+            $($method)*
+        );
+    };
+
+    (@munch $trait:ident $field:ident { $($header:tt)* } [$($done:tt)*]) => {
+        #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+        #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+        $($header)* {
+            $($done)*
+        }
+    };
+
+    // BaseWalletAdapter
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] event_emitter $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn event_emitter(&self) -> $crate::WalletAdapterEventEmitter {
+                self.$f.event_emitter()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] name $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn name(&self) -> String {
+                self.$f.name()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] url $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn url(&self) -> String {
+                self.$f.url()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] icon $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn icon(&self) -> String {
+                self.$f.icon()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] ready_state $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn ready_state(&self) -> $crate::WalletReadyState {
+                self.$f.ready_state()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] public_key $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn public_key(&self) -> Option<::solana_sdk::pubkey::Pubkey> {
+                self.$f.public_key()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] connecting $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn connecting(&self) -> bool {
+                self.$f.connecting()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] disconnecting $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn disconnecting(&self) -> bool {
+                self.$f.disconnecting()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        supported_transaction_versions $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn supported_transaction_versions(
+                &self,
+            ) -> Option<$crate::SupportedTransactionVersions> {
+                self.$f.supported_transaction_versions()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] connected $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn connected(&self) -> bool {
+                self.$f.connected()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] features $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn features(&self) -> Vec<::wallet_adapter_common::standard::WalletFeature> {
+                self.$f.features()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] account $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn account(&self) -> Option<::wallet_adapter_common::standard::WalletAccount> {
+                self.$f.account()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] watch_ready_state $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn watch_ready_state(
+                &self,
+            ) -> ::tokio::sync::watch::Receiver<$crate::WalletReadyState> {
+                self.$f.watch_ready_state()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] on_connect $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn on_connect(
+                &self,
+                f: Box<dyn FnMut(::solana_sdk::pubkey::Pubkey) + Send>,
+            ) -> $crate::EventSubscription {
+                self.$f.on_connect(f)
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] on_disconnect $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn on_disconnect(&self, f: Box<dyn FnMut() + Send>) -> $crate::EventSubscription {
+                self.$f.on_disconnect(f)
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] on_error $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn on_error(
+                &self,
+                f: Box<dyn FnMut(&$crate::WalletError) + Send>,
+            ) -> $crate::EventSubscription {
+                self.$f.on_error(f)
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] subscribe_provider_event $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn subscribe_provider_event(&self, event: &str) -> $crate::Result<()> {
+                self.$f.subscribe_provider_event(event)
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        unsubscribe_provider_event $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn unsubscribe_provider_event(&self, event: &str) -> $crate::Result<()> {
+                self.$f.unsubscribe_provider_event(event)
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] on_provider_event $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn on_provider_event(
+                &self,
+                event: &str,
+                f: Box<dyn FnMut(&::serde_json::Value) + Send>,
+            ) -> $crate::Result<$crate::EventSubscription> {
+                self.$f.on_provider_event(event, f)
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] disconnect $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn disconnect(&self) -> ::anyhow::Result<()> {
+                self.$f.disconnect().await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] auto_connect $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn auto_connect(&mut self) -> $crate::Result<()> {
+                self.$f.auto_connect().await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] connect $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn connect(&mut self) -> $crate::Result<()> {
+                self.$f.connect().await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] send_transaction $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn send_transaction(
+                &self,
+                transaction: $crate::TransactionOrVersionedTransaction,
+                connection: &dyn ::wallet_adapter_common::connection::Connection,
+                options: Option<::wallet_adapter_common::types::SendTransactionOptions>,
+            ) -> $crate::Result<::solana_sdk::signature::Signature> {
+                self.$f
+                    .send_transaction(transaction, connection, options)
+                    .await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] send_all_transactions $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn send_all_transactions(
+                &self,
+                transactions: Vec<$crate::TransactionOrVersionedTransaction>,
+                connection: &dyn ::wallet_adapter_common::connection::Connection,
+                options: Option<::wallet_adapter_common::types::SendOptions>,
+            ) -> $crate::Result<Vec<Option<::solana_sdk::signature::Signature>>> {
+                self.$f
+                    .send_all_transactions(transactions, connection, options)
+                    .await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        send_transaction_with_blockhash_retry $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn send_transaction_with_blockhash_retry(
+                &self,
+                transaction: $crate::TransactionOrVersionedTransaction,
+                connection: &dyn ::wallet_adapter_common::connection::Connection,
+                options: Option<::wallet_adapter_common::types::SendOptions>,
+                max_attempts: u32,
+            ) -> $crate::Result<::solana_sdk::signature::Signature> {
+                self.$f
+                    .send_transaction_with_blockhash_retry(
+                        transaction,
+                        connection,
+                        options,
+                        max_attempts,
+                    )
+                    .await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] transfer_sol $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn transfer_sol(
+                &self,
+                to: ::solana_sdk::pubkey::Pubkey,
+                lamports: u64,
+                connection: &dyn ::wallet_adapter_common::connection::Connection,
+                options: Option<::wallet_adapter_common::types::SendTransactionOptions>,
+            ) -> $crate::Result<::solana_sdk::signature::Signature> {
+                self.$f
+                    .transfer_sol(to, lamports, connection, options)
+                    .await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*] prepare_transaction $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn prepare_transaction(
+                &self,
+                transaction: ::solana_sdk::transaction::Transaction,
+                connection: &dyn ::wallet_adapter_common::connection::Connection,
+                options: Option<&::wallet_adapter_common::types::SendOptions>,
+            ) -> $crate::Result<::solana_sdk::transaction::Transaction> {
+                self.$f
+                    .prepare_transaction(transaction, connection, options)
+                    .await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        prepare_transaction_with_context $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            async fn prepare_transaction_with_context(
+                &self,
+                transaction: ::solana_sdk::transaction::Transaction,
+                connection: &dyn ::wallet_adapter_common::connection::Connection,
+                options: Option<&::wallet_adapter_common::types::SendOptions>,
+            ) -> $crate::Result<(
+                ::solana_sdk::transaction::Transaction,
+                Option<::wallet_adapter_common::connection::LatestBlockhash>,
+            )> {
+                self.$f
+                    .prepare_transaction_with_context(transaction, connection, options)
+                    .await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        check_if_transaction_is_supported $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseWalletAdapter $f $h [$($d)*
+            fn check_if_transaction_is_supported(
+                &self,
+                transaction: &$crate::TransactionOrVersionedTransaction,
+            ) -> $crate::Result<()> {
+                self.$f.check_if_transaction_is_supported(transaction)
+            }
+        ] $($r)*);
+    };
+
+    // BaseSignerWalletAdapter, `send_transaction` is named after the trait as
+    // BaseWalletAdapter has one too
+    (@munch BaseSignerWalletAdapter $f:ident $h:tt [$($d:tt)*] wallet_signer $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseSignerWalletAdapter $f $h [$($d)*
+            fn wallet_signer(
+                &self,
+            ) -> Option<Box<::wallet_adapter_common::types::DynSigner>> {
+                self.$f.wallet_signer()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseSignerWalletAdapter $f:ident $h:tt [$($d:tt)*] send_transaction $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseSignerWalletAdapter $f $h [$($d)*
+            async fn send_transaction(
+                &self,
+                transaction: $crate::TransactionOrVersionedTransaction,
+                connection: &dyn ::wallet_adapter_common::connection::Connection,
+                options: Option<::wallet_adapter_common::types::SendTransactionOptions>,
+            ) -> $crate::Result<::solana_sdk::signature::Signature> {
+                $crate::BaseSignerWalletAdapter::send_transaction(
+                    &*self.$f,
+                    transaction,
+                    connection,
+                    options,
+                )
+                .await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseSignerWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        send_sponsored_transaction $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseSignerWalletAdapter $f $h [$($d)*
+            async fn send_sponsored_transaction(
+                &self,
+                transaction: $crate::TransactionOrVersionedTransaction,
+                connection: &dyn ::wallet_adapter_common::connection::Connection,
+                sponsor: &dyn ::wallet_adapter_common::relay::FeePayerService,
+                options: Option<::wallet_adapter_common::types::SendTransactionOptions>,
+            ) -> $crate::Result<::solana_sdk::signature::Signature> {
+                self.$f
+                    .send_sponsored_transaction(transaction, connection, sponsor, options)
+                    .await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseSignerWalletAdapter $f:ident $h:tt [$($d:tt)*] sign_transaction $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseSignerWalletAdapter $f $h [$($d)*
+            async fn sign_transaction(
+                &self,
+                transaction: $crate::TransactionOrVersionedTransaction,
+            ) -> $crate::Result<$crate::TransactionOrVersionedTransaction> {
+                self.$f.sign_transaction(transaction).await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseSignerWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        sign_partial_transaction $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseSignerWalletAdapter $f $h [$($d)*
+            async fn sign_partial_transaction(&self, encoded: &str) -> $crate::Result<String> {
+                self.$f.sign_partial_transaction(encoded).await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseSignerWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        sign_all_transactions $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseSignerWalletAdapter $f $h [$($d)*
+            async fn sign_all_transactions(
+                &self,
+                transactions: Vec<$crate::TransactionOrVersionedTransaction>,
+            ) -> $crate::Result<Vec<$crate::TransactionOrVersionedTransaction>> {
+                self.$f.sign_all_transactions(transactions).await
+            }
+        ] $($r)*);
+    };
+
+    // BaseMessageSignerWalletAdapter
+    (@munch BaseMessageSignerWalletAdapter $f:ident $h:tt [$($d:tt)*]
+        sign_message $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseMessageSignerWalletAdapter $f $h [$($d)*
+            async fn sign_message(&self, message: &[u8]) -> $crate::Result<Vec<u8>> {
+                self.$f.sign_message(message).await
+            }
+        ] $($r)*);
+    };
+
+    // BaseLockableWalletAdapter
+    (@munch BaseLockableWalletAdapter $f:ident $h:tt [$($d:tt)*] locked $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseLockableWalletAdapter $f $h [$($d)*
+            fn locked(&self) -> bool {
+                self.$f.locked()
+            }
+        ] $($r)*);
+    };
+    (@munch BaseLockableWalletAdapter $f:ident $h:tt [$($d:tt)*] lock $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseLockableWalletAdapter $f $h [$($d)*
+            async fn lock(&self) -> $crate::Result<()> {
+                self.$f.lock().await
+            }
+        ] $($r)*);
+    };
+    (@munch BaseLockableWalletAdapter $f:ident $h:tt [$($d:tt)*] unlock $($r:ident)*) => {
+        $crate::delegate::delegate_adapter!(@munch BaseLockableWalletAdapter $f $h [$($d)*
+            async fn unlock(&self, credentials: &str) -> $crate::Result<()> {
+                self.$f.unlock(credentials).await
+            }
+        ] $($r)*);
+    };
+}
+
+pub(crate) use delegate_adapter;
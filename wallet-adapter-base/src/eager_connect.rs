@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use wallet_adapter_common::storage::SettingsStorage;
+
+use crate::delegate::delegate_adapter;
+use crate::{
+    BaseLockableWalletAdapter, BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter,
+    BaseWalletAdapter,
+};
+
+/// Remembers in `settings` that the wrapped wallet was connected, so `auto_connect` only
+/// reconnects wallets the user connected and didn't disconnect since, like the eager
+/// connection of the web wallet-adapter.
+///
+/// The flag follows whether the wallet is connected after `connect`, and is cleared by
+/// `disconnect`, not when the wallet disconnects on its own.
+pub struct EagerConnectWalletAdapter<W: ?Sized> {
+    inner: Box<W>,
+    settings: Arc<dyn SettingsStorage>,
+}
+
+impl<W: BaseWalletAdapter + ?Sized> EagerConnectWalletAdapter<W> {
+    pub fn new(inner: Box<W>, settings: impl SettingsStorage + 'static) -> Self {
+        Self {
+            inner,
+            settings: Arc::new(settings),
+        }
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Box<W> {
+        self.inner
+    }
+
+    /// Whether the wallet was connected and not disconnected since
+    pub fn was_connected(&self) -> bool {
+        match self.settings.get_setting(&self.key()) {
+            Ok(flag) => flag.is_some(),
+            Err(err) => {
+                tracing::warn!(
+                    "could not read whether {} was connected: {err}",
+                    self.inner.name()
+                );
+                false
+            }
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("walletAdapter.connected.{}", self.inner.name())
+    }
+
+    fn remember(&self, connected: bool) {
+        let key = self.key();
+        let result = if connected {
+            self.settings.set_setting(&key, "true")
+        } else {
+            self.settings.remove_setting(&key)
+        };
+        if let Err(err) = result {
+            tracing::warn!(
+                "could not remember whether {} is connected: {err}",
+                self.inner.name()
+            );
+        }
+    }
+}
+
+impl<W: BaseWalletAdapter + ?Sized> Clone for EagerConnectWalletAdapter<W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: dyn_clone::clone_box(&*self.inner),
+            settings: self.settings.clone(),
+        }
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseWalletAdapter + ?Sized + 'static] BaseWalletAdapter
+        for EagerConnectWalletAdapter<W>;
+    to inner:
+        event_emitter, name, url, icon, ready_state, public_key, connecting, disconnecting,
+        supported_transaction_versions, connected, features, account, watch_ready_state,
+        on_connect, on_disconnect, on_error, subscribe_provider_event, unsubscribe_provider_event,
+        on_provider_event, send_transaction, send_all_transactions,
+        send_transaction_with_blockhash_retry, transfer_sol, prepare_transaction,
+        prepare_transaction_with_context, check_if_transaction_is_supported;
+
+    async fn disconnect(&self) -> Result<()> {
+        self.inner.disconnect().await?;
+        self.remember(false);
+        Ok(())
+    }
+
+    async fn auto_connect(&mut self) -> crate::Result<()> {
+        if !self.was_connected() {
+            return Ok(());
+        }
+        self.inner.auto_connect().await
+    }
+
+    async fn connect(&mut self) -> crate::Result<()> {
+        let result = self.inner.connect().await;
+        // a rejected connect forgets the wallet, the user didn't want it connected
+        self.remember(self.inner.connected());
+        result
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseSignerWalletAdapter + ?Sized + 'static] BaseSignerWalletAdapter
+        for EagerConnectWalletAdapter<W>;
+    to inner:
+        wallet_signer, send_transaction, send_sponsored_transaction, sign_transaction,
+        sign_partial_transaction, sign_all_transactions;
+}
+
+delegate_adapter! {
+    impl[W: BaseMessageSignerWalletAdapter + ?Sized + 'static] BaseMessageSignerWalletAdapter
+        for EagerConnectWalletAdapter<W>;
+    to inner: sign_message;
+}
+
+delegate_adapter! {
+    impl[W: BaseLockableWalletAdapter + ?Sized + 'static] BaseLockableWalletAdapter
+        for EagerConnectWalletAdapter<W>;
+    to inner: locked, lock, unlock;
+}
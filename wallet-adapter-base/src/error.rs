@@ -1,3 +1,7 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use wallet_adapter_common::connection::RpcError;
+
 pub type Result<T> = std::result::Result<T, WalletError>;
 
 #[derive(Debug, strum::Display)]
@@ -12,11 +16,128 @@ pub enum WalletError {
     WalletSendTransactionError(String),
     BincodeSerializationError(bincode::Error),
     Anyhow(anyhow::Error),
+    /// The user declined the request in the wallet (EIP-1193 code 4001)
+    WalletUserRejected(String),
+    /// The wallet failed internally (JSON-RPC code -32603)
+    WalletInternalError(String),
+    /// Any other error object with a code returned by the wallet
+    WalletProviderError((i64, String)),
+    /// The wallet is locked and has to be unlocked before signing
+    WalletLocked,
+    /// A [`TransactionPolicy`](crate::TransactionPolicy) refused the transaction
+    WalletPolicyViolation(String),
+}
+
+impl WalletError {
+    /// Stable numeric code, new variants get a new number and existing ones are never renumbered
+    pub fn code(&self) -> u32 {
+        match self {
+            WalletError::WalletNotReady => 1,
+            WalletError::WalletLoad => 2,
+            WalletError::WalletConfig => 3,
+            WalletError::WalletConnection(_) => 4,
+            WalletError::WalletDisconnected => 5,
+            WalletError::WalletDisconnection(_) => 6,
+            WalletError::WalletNotConnected => 7,
+            WalletError::WalletSendTransactionError(_) => 8,
+            WalletError::BincodeSerializationError(_) => 9,
+            WalletError::Anyhow(_) => 10,
+            WalletError::WalletUserRejected(_) => 11,
+            WalletError::WalletInternalError(_) => 12,
+            WalletError::WalletProviderError(_) => 13,
+            WalletError::WalletLocked => 14,
+            WalletError::WalletPolicyViolation(_) => 15,
+        }
+    }
+
+    /// Stable string code, eg. `WALLET_NOT_CONNECTED`
+    pub fn code_name(&self) -> &'static str {
+        match self {
+            WalletError::WalletNotReady => "WALLET_NOT_READY",
+            WalletError::WalletLoad => "WALLET_LOAD",
+            WalletError::WalletConfig => "WALLET_CONFIG",
+            WalletError::WalletConnection(_) => "WALLET_CONNECTION",
+            WalletError::WalletDisconnected => "WALLET_DISCONNECTED",
+            WalletError::WalletDisconnection(_) => "WALLET_DISCONNECTION",
+            WalletError::WalletNotConnected => "WALLET_NOT_CONNECTED",
+            WalletError::WalletSendTransactionError(_) => "WALLET_SEND_TRANSACTION_ERROR",
+            WalletError::BincodeSerializationError(_) => "BINCODE_SERIALIZATION_ERROR",
+            WalletError::Anyhow(_) => "UNKNOWN",
+            WalletError::WalletUserRejected(_) => "WALLET_USER_REJECTED",
+            WalletError::WalletInternalError(_) => "WALLET_INTERNAL_ERROR",
+            WalletError::WalletProviderError(_) => "WALLET_PROVIDER_ERROR",
+            WalletError::WalletLocked => "WALLET_LOCKED",
+            WalletError::WalletPolicyViolation(_) => "WALLET_POLICY_VIOLATION",
+        }
+    }
+
+    /// Rebuild an error from its serialized `code` and `message`, eg. after crossing an IPC boundary.
+    /// Variants whose details can't be recovered from the message end up in `Anyhow`.
+    pub fn from_code(code: u32, message: String) -> Self {
+        match code {
+            1 => WalletError::WalletNotReady,
+            2 => WalletError::WalletLoad,
+            3 => WalletError::WalletConfig,
+            5 => WalletError::WalletDisconnected,
+            7 => WalletError::WalletNotConnected,
+            8 => WalletError::WalletSendTransactionError(message),
+            11 => WalletError::WalletUserRejected(message),
+            12 => WalletError::WalletInternalError(message),
+            14 => WalletError::WalletLocked,
+            15 => WalletError::WalletPolicyViolation(message),
+            _ => WalletError::Anyhow(anyhow::anyhow!(message)),
+        }
+    }
+
+    /// Whether the cluster refused the transaction because its blockhash expired,
+    /// eg. while the wallet waited for the user's approval
+    pub fn is_blockhash_expired(&self) -> bool {
+        match self {
+            WalletError::Anyhow(error) => error.chain().any(|cause| {
+                cause
+                    .downcast_ref::<RpcError>()
+                    .is_some_and(RpcError::is_blockhash_not_found)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Human readable details, the variant name for errors without any
+    pub fn message(&self) -> String {
+        match self {
+            WalletError::WalletConnection((name, error))
+            | WalletError::WalletDisconnection((name, error)) => format!("{name}: {error}"),
+            WalletError::WalletSendTransactionError(error)
+            | WalletError::WalletUserRejected(error)
+            | WalletError::WalletInternalError(error)
+            | WalletError::WalletPolicyViolation(error) => error.clone(),
+            WalletError::WalletProviderError((code, error)) => format!("{error} ({code})"),
+            WalletError::BincodeSerializationError(error) => error.to_string(),
+            WalletError::Anyhow(error) => format!("{error:#}"),
+            _ => self.to_string(),
+        }
+    }
 }
 
+impl Serialize for WalletError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("WalletError", 3)?;
+        state.serialize_field("code", &self.code())?;
+        state.serialize_field("name", self.code_name())?;
+        state.serialize_field("message", &self.message())?;
+        state.end()
+    }
+}
+
+impl std::error::Error for WalletError {}
+
 impl From<anyhow::Error> for WalletError {
     fn from(e: anyhow::Error) -> Self {
-        Self::Anyhow(e)
+        // typed errors passed through anyhow (eg. from the wasm wallets) keep their variant
+        match e.downcast::<WalletError>() {
+            Ok(e) => e,
+            Err(e) => Self::Anyhow(e),
+        }
     }
 }
 
@@ -25,3 +146,41 @@ impl From<bincode::Error> for WalletError {
         Self::BincodeSerializationError(e)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_name_and_message() {
+        let error = WalletError::WalletConnection(("Phantom".into(), "User rejected".into()));
+
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": 4,
+                "name": "WALLET_CONNECTION",
+                "message": "Phantom: User rejected",
+            })
+        );
+    }
+
+    #[test]
+    fn from_code_restores_the_variant() {
+        let error = WalletError::WalletUserRejected("rejected".into());
+        let restored = WalletError::from_code(error.code(), error.message());
+
+        assert_eq!(restored.code(), error.code());
+        assert_eq!(restored.message(), "rejected");
+    }
+
+    #[test]
+    fn keeps_typed_errors_passed_through_anyhow() {
+        let error = anyhow::Error::from(WalletError::WalletUserRejected("rejected".into()));
+
+        assert!(matches!(
+            WalletError::from(error),
+            WalletError::WalletUserRejected(_)
+        ));
+    }
+}
@@ -0,0 +1,59 @@
+use solana_sdk::transaction::Transaction;
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::jito::{JitoBlockEngine, MAX_BUNDLE_LEN};
+use wallet_adapter_common::types::SendOptions;
+
+use crate::signer::BaseSignerWalletAdapter;
+use crate::transaction::{append_instruction, TransactionOrVersionedTransaction};
+use crate::WalletError;
+
+/// Sign `transactions` with the connected wallet and submit them as one Jito bundle, landing
+/// together and in order or not at all. The last transaction tips `tip_lamports` to the block
+/// engine, a single transaction gets MEV protection this way.
+///
+/// Returns the bundle id to poll with [`JitoBlockEngine::get_bundle_statuses`].
+#[cfg_attr(
+    feature = "instrument",
+    tracing::instrument(skip_all, fields(wallet = %adapter.name()))
+)]
+pub async fn send_jito_bundle(
+    adapter: &(impl BaseSignerWalletAdapter + ?Sized),
+    mut transactions: Vec<Transaction>,
+    tip_lamports: u64,
+    connection: &dyn Connection,
+    block_engine: &JitoBlockEngine,
+    options: Option<&SendOptions>,
+) -> crate::Result<String> {
+    let payer = adapter
+        .public_key()
+        .ok_or(WalletError::WalletNotConnected)?;
+    if transactions.is_empty() || transactions.len() > MAX_BUNDLE_LEN {
+        return Err(WalletError::WalletSendTransactionError(format!(
+            "A bundle holds 1 to {MAX_BUNDLE_LEN} transactions, got {}",
+            transactions.len()
+        )));
+    }
+
+    let tip = block_engine.tip_instruction(&payer, tip_lamports).await?;
+    if let Some(last) = transactions.pop() {
+        transactions.push(append_instruction(last, tip)?);
+    }
+
+    let mut prepared = Vec::with_capacity(transactions.len());
+    for transaction in transactions {
+        prepared.push(TransactionOrVersionedTransaction::Transaction(
+            adapter
+                .prepare_transaction(transaction, connection, options)
+                .await?,
+        ));
+    }
+
+    let serialized = adapter
+        .sign_all_transactions(prepared)
+        .await?
+        .iter()
+        .map(TransactionOrVersionedTransaction::serialize)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(block_engine.send_bundle(&serialized).await?)
+}
@@ -1,12 +1,62 @@
 mod adapter;
+mod associated_token;
+#[cfg(not(target_arch = "wasm32"))]
+mod blocking_signer;
+#[cfg(any(test, feature = "test-support"))]
+pub mod conformance;
+mod create_account;
+mod delegate;
+mod eager_connect;
 mod error;
+mod jito;
+mod lockable;
+mod logging;
+mod memo;
+mod middleware;
+mod multi_wallet;
+mod policy;
+mod preview;
+mod send_sync;
+mod session_timeout;
 mod signer;
+mod solana_pay;
+mod stake;
 mod transaction;
+mod transaction_builder;
 
+pub use adapter::send_all_in_turn;
 pub use adapter::BaseWalletAdapter;
+pub use adapter::EventSubscription;
 pub use adapter::WalletAdapterEvent;
 pub use adapter::WalletAdapterEventEmitter;
+pub use adapter::WalletOperation;
 pub use adapter::WalletReadyState;
+pub use associated_token::{build_create_ata_idempotent, get_or_create_ata};
+#[cfg(not(target_arch = "wasm32"))]
+pub use blocking_signer::BlockingSigner;
+pub use create_account::{build_create_account, build_create_account_with_rent};
+pub use eager_connect::EagerConnectWalletAdapter;
 pub use error::{Result, WalletError};
+pub use jito::send_jito_bundle;
+pub use lockable::BaseLockableWalletAdapter;
+pub use logging::{CallLog, CallRecord, LoggingWalletAdapter};
+pub use memo::{build_memo, MEMO_PROGRAM_ID};
+pub use middleware::{MiddlewareWalletAdapter, WalletMiddleware};
+pub use multi_wallet::MultiWallet;
+pub use policy::{PolicyViolation, PolicyWalletAdapter, TransactionPolicy};
+pub use preview::{InstructionPreview, TransactionPreview};
+pub use send_sync::SendSyncWalletAdapter;
+pub use session_timeout::SessionTimeoutWalletAdapter;
 pub use signer::{BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter};
-pub use transaction::{SupportedTransactionVersions, TransactionOrVersionedTransaction};
+pub use solana_pay::{
+    associated_token_address, build_transfer_request, send_transfer_request,
+    sign_transaction_request, TokenMint, TransactionRequest, ASSOCIATED_TOKEN_PROGRAM_ID,
+};
+pub use stake::{
+    build_create_stake_account, build_deactivate_stake, build_delegate_stake, build_withdraw_stake,
+    stake_account_address, STAKE_PROGRAM_ID,
+};
+pub use transaction::{
+    check_versioned_send_options, SupportedTransactionVersions, TransactionOrVersionedTransaction,
+};
+pub use transaction_builder::TransactionBuilder;
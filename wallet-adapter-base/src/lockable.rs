@@ -0,0 +1,13 @@
+use crate::adapter::BaseWalletAdapter;
+
+/// Optional capability of wallets that can be locked without losing the session.
+///
+/// While locked the wallet stays connected and keeps its public key, but refuses to sign.
+/// `lock`/`unlock` emit `WalletAdapterEvent::Locked`/`Unlocked`.
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait BaseLockableWalletAdapter: BaseWalletAdapter {
+    fn locked(&self) -> bool;
+    async fn lock(&self) -> crate::Result<()>;
+    async fn unlock(&self, credentials: &str) -> crate::Result<()>;
+}
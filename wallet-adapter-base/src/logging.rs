@@ -0,0 +1,301 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use base64::prelude::*;
+use chrono::{DateTime, Utc};
+use solana_sdk::signature::Signature;
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::types::{SendOptions, SendTransactionOptions};
+
+use crate::delegate::delegate_adapter;
+use crate::{
+    BaseLockableWalletAdapter, BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter,
+    BaseWalletAdapter, TransactionOrVersionedTransaction, WalletError,
+};
+
+/// A call that went through a [`LoggingWalletAdapter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallRecord {
+    pub method: &'static str,
+    /// The base64 wire format of the transactions, or the base64 message of `sign_message`
+    pub payload: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    /// A short description of the result, eg. the signature, or the error message
+    pub result: std::result::Result<String, String>,
+}
+
+/// The last calls of a [`LoggingWalletAdapter`], oldest first. Clones share the records,
+/// so a UI can keep one to show them.
+#[derive(Debug, Clone)]
+pub struct CallLog {
+    records: Arc<Mutex<VecDeque<CallRecord>>>,
+    capacity: usize,
+}
+
+impl CallLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    pub fn records(&self) -> Vec<CallRecord> {
+        self.records
+            .lock()
+            .map(|records| records.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut records) = self.records.lock() {
+            records.clear();
+        }
+    }
+
+    fn push(&self, record: CallRecord) {
+        if let Ok(mut records) = self.records.lock() {
+            if records.len() == self.capacity {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+    }
+}
+
+impl Default for CallLog {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+fn encode(transaction: &TransactionOrVersionedTransaction) -> String {
+    transaction
+        .serialize()
+        .map(|bytes| BASE64_STANDARD.encode(bytes))
+        .unwrap_or_else(|err| format!("<unserializable: {err}>"))
+}
+
+/// Records every call to the wrapped adapter with its transactions, duration and result,
+/// to [`tracing`] and a [`CallLog`], eg. to debug "the wallet did nothing" reports.
+///
+/// The records contain the signed messages, only keep them where that is acceptable.
+pub struct LoggingWalletAdapter<W: ?Sized> {
+    inner: Box<W>,
+    log: CallLog,
+}
+
+impl<W: BaseWalletAdapter + ?Sized> LoggingWalletAdapter<W> {
+    pub fn new(inner: Box<W>) -> Self {
+        Self {
+            inner,
+            log: CallLog::default(),
+        }
+    }
+
+    /// Record into `log`, eg. one shared by several wallets or keeping more calls
+    pub fn with_log(mut self, log: CallLog) -> Self {
+        self.log = log;
+        self
+    }
+
+    pub fn log(&self) -> CallLog {
+        self.log.clone()
+    }
+
+    pub fn into_inner(self) -> Box<W> {
+        self.inner
+    }
+
+    async fn record<T>(
+        &self,
+        method: &'static str,
+        payload: Vec<String>,
+        describe: impl FnOnce(&T) -> String,
+        call: impl Future<Output = crate::Result<T>>,
+    ) -> crate::Result<T> {
+        let started_at = Utc::now();
+        let result = call.await;
+
+        let described = match &result {
+            Ok(value) => Ok(describe(value)),
+            Err(err) => Err(err.message()),
+        };
+        self.push(method, payload, started_at, described);
+        result
+    }
+
+    fn push(
+        &self,
+        method: &'static str,
+        payload: Vec<String>,
+        started_at: DateTime<Utc>,
+        result: std::result::Result<String, String>,
+    ) {
+        let duration_ms = (Utc::now() - started_at).num_milliseconds();
+        match &result {
+            Ok(value) => {
+                tracing::info!(wallet = %self.inner.name(), method, duration_ms, value, "wallet call")
+            }
+            Err(error) => {
+                tracing::warn!(wallet = %self.inner.name(), method, duration_ms, error, "wallet call failed")
+            }
+        }
+
+        self.log.push(CallRecord {
+            method,
+            payload,
+            started_at,
+            duration_ms,
+            result,
+        });
+    }
+
+    /// `connect` borrows the adapter mutably, so it can't go through `record`
+    fn push_connect(
+        &self,
+        method: &'static str,
+        started_at: DateTime<Utc>,
+        result: &crate::Result<()>,
+    ) {
+        let result = match result {
+            Ok(()) => Ok(self
+                .inner
+                .public_key()
+                .map(|key| key.to_string())
+                .unwrap_or_default()),
+            Err(err) => Err(err.message()),
+        };
+        self.push(method, vec![], started_at, result);
+    }
+}
+
+impl<W: BaseWalletAdapter + ?Sized> Clone for LoggingWalletAdapter<W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: dyn_clone::clone_box(&*self.inner),
+            log: self.log.clone(),
+        }
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseWalletAdapter + ?Sized + 'static] BaseWalletAdapter for LoggingWalletAdapter<W>;
+    to inner:
+        event_emitter, name, url, icon, ready_state, public_key, connecting, disconnecting,
+        supported_transaction_versions, connected, features, account, watch_ready_state,
+        on_connect, on_disconnect, on_error, subscribe_provider_event, unsubscribe_provider_event,
+        on_provider_event, prepare_transaction, prepare_transaction_with_context,
+        check_if_transaction_is_supported;
+
+    async fn disconnect(&self) -> Result<()> {
+        let call = async { self.inner.disconnect().await.map_err(WalletError::from) };
+        Ok(self
+            .record("disconnect", vec![], |_| "ok".to_string(), call)
+            .await?)
+    }
+
+    async fn auto_connect(&mut self) -> crate::Result<()> {
+        let started_at = Utc::now();
+        let result = self.inner.auto_connect().await;
+        self.push_connect("auto_connect", started_at, &result);
+        result
+    }
+
+    async fn connect(&mut self) -> crate::Result<()> {
+        let started_at = Utc::now();
+        let result = self.inner.connect().await;
+        self.push_connect("connect", started_at, &result);
+        result
+    }
+
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> crate::Result<Signature> {
+        let payload = vec![encode(&transaction)];
+        let call = self
+            .inner
+            .send_transaction(transaction, connection, options);
+        self.record("send_transaction", payload, ToString::to_string, call)
+            .await
+    }
+
+    async fn send_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+        connection: &dyn Connection,
+        options: Option<SendOptions>,
+    ) -> crate::Result<Vec<Option<Signature>>> {
+        let payload = transactions.iter().map(encode).collect();
+        let call = self
+            .inner
+            .send_all_transactions(transactions, connection, options);
+        self.record(
+            "send_all_transactions",
+            payload,
+            |signatures| format!("{signatures:?}"),
+            call,
+        )
+        .await
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseSignerWalletAdapter + ?Sized + 'static] BaseSignerWalletAdapter
+        for LoggingWalletAdapter<W>;
+    to inner: wallet_signer;
+
+    async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> crate::Result<TransactionOrVersionedTransaction> {
+        let payload = vec![encode(&transaction)];
+        let call = self.inner.sign_transaction(transaction);
+        self.record("sign_transaction", payload, encode, call).await
+    }
+
+    async fn sign_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+    ) -> crate::Result<Vec<TransactionOrVersionedTransaction>> {
+        let payload = transactions.iter().map(encode).collect();
+        let call = self.inner.sign_all_transactions(transactions);
+        self.record(
+            "sign_all_transactions",
+            payload,
+            |signed| format!("{} signed", signed.len()),
+            call,
+        )
+        .await
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseMessageSignerWalletAdapter + ?Sized + 'static] BaseMessageSignerWalletAdapter
+        for LoggingWalletAdapter<W>;
+    to inner:;
+
+    async fn sign_message(&self, message: &[u8]) -> crate::Result<Vec<u8>> {
+        let payload = vec![BASE64_STANDARD.encode(message)];
+        let call = self.inner.sign_message(message);
+        self.record(
+            "sign_message",
+            payload,
+            |signature| BASE64_STANDARD.encode(signature),
+            call,
+        )
+        .await
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseLockableWalletAdapter + ?Sized + 'static] BaseLockableWalletAdapter
+        for LoggingWalletAdapter<W>;
+    to inner: locked, lock, unlock;
+}
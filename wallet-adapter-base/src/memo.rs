@@ -0,0 +1,32 @@
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+pub const MEMO_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Build a memo instruction, every key in `signer_pubkeys` has to sign the transaction
+pub fn build_memo(memo: &[u8], signer_pubkeys: &[&Pubkey]) -> Instruction {
+    Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: signer_pubkeys
+            .iter()
+            .map(|pubkey| AccountMeta::new_readonly(**pubkey, true))
+            .collect(),
+        data: memo.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_memo_signers_are_readonly_signers() {
+        let signer = Pubkey::new_unique();
+        let memo = build_memo(b"order 42", &[&signer]);
+
+        assert_eq!(memo.program_id, MEMO_PROGRAM_ID);
+        assert_eq!(memo.data, b"order 42");
+        assert_eq!(memo.accounts, [AccountMeta::new_readonly(signer, true)]);
+    }
+}
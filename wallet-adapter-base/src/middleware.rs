@@ -0,0 +1,332 @@
+use std::sync::Arc;
+
+use solana_sdk::signature::Signature;
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::maybe_send::{MaybeSend, MaybeSync};
+use wallet_adapter_common::types::{SendOptions, SendTransactionOptions};
+
+use crate::delegate::delegate_adapter;
+use crate::{
+    check_versioned_send_options, BaseLockableWalletAdapter, BaseMessageSignerWalletAdapter,
+    BaseSignerWalletAdapter, BaseWalletAdapter, TransactionOrVersionedTransaction,
+};
+
+/// Hooks run by [`MiddlewareWalletAdapter`] around signing and sending, eg. for logging,
+/// analytics, adding priority fees or enforcing policies.
+///
+/// All hooks do nothing by default. An error from a `before_` hook aborts the operation
+/// before it reaches the wallet, the middleware whose `before_` hook already ran gets the
+/// error in its `after_` hook. `wallet` is the wrapping adapter and the `after_` hooks get the
+/// transaction as the `before_` hooks left it.
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait WalletMiddleware: MaybeSend + MaybeSync {
+    async fn before_sign(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        _transaction: &mut TransactionOrVersionedTransaction,
+    ) -> crate::Result<()> {
+        Ok(())
+    }
+
+    async fn after_sign(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        _transaction: &TransactionOrVersionedTransaction,
+        _result: &crate::Result<TransactionOrVersionedTransaction>,
+    ) {
+    }
+
+    async fn before_send(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        _transaction: &mut TransactionOrVersionedTransaction,
+        _options: &mut Option<SendTransactionOptions>,
+    ) -> crate::Result<()> {
+        Ok(())
+    }
+
+    async fn after_send(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        _transaction: &TransactionOrVersionedTransaction,
+        _result: &crate::Result<Signature>,
+    ) {
+    }
+}
+
+/// Wraps any adapter, boxed or not, and runs a chain of [`WalletMiddleware`] around it.
+///
+/// `before_` hooks run in the order the middleware was added, `after_` hooks in reverse.
+/// Legacy transactions are prepared before the send hooks run, so they see the fee payer,
+/// memo and blockhash the wallet signs. Transactions of a batch go through the hooks one at
+/// a time before the batch reaches the wallet, so wallets approving them together still ask
+/// once. Option changes of `before_send` only apply to single sends. Signer wallets sign
+/// inside their own `send_transaction`, so sends only run the send hooks.
+pub struct MiddlewareWalletAdapter<W: ?Sized> {
+    inner: Box<W>,
+    middleware: Vec<Arc<dyn WalletMiddleware>>,
+}
+
+impl<W: BaseWalletAdapter + ?Sized> MiddlewareWalletAdapter<W> {
+    pub fn new(inner: Box<W>) -> Self {
+        Self {
+            inner,
+            middleware: vec![],
+        }
+    }
+
+    /// Add `middleware` to the end of the chain
+    pub fn with(mut self, middleware: impl WalletMiddleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Box<W> {
+        self.inner
+    }
+}
+
+impl<W: BaseWalletAdapter + ?Sized + 'static> MiddlewareWalletAdapter<W> {
+    /// Add the fee payer, memo and blockhash of `options` to a legacy transaction
+    async fn prepare(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<&SendOptions>,
+    ) -> crate::Result<TransactionOrVersionedTransaction> {
+        Ok(match transaction {
+            TransactionOrVersionedTransaction::Transaction(tx) => {
+                TransactionOrVersionedTransaction::Transaction(
+                    self.inner
+                        .prepare_transaction(tx, connection, options)
+                        .await?,
+                )
+            }
+            versioned => {
+                check_versioned_send_options(options)?;
+                versioned
+            }
+        })
+    }
+
+    async fn before_send(
+        &self,
+        transaction: &mut TransactionOrVersionedTransaction,
+        options: &mut Option<SendTransactionOptions>,
+    ) -> crate::Result<()> {
+        for (index, middleware) in self.middleware.iter().enumerate() {
+            if let Err(err) = middleware.before_send(self, transaction, options).await {
+                let result = Err(err);
+                for middleware in self.middleware[..index].iter().rev() {
+                    middleware.after_send(self, transaction, &result).await;
+                }
+                return result.map(|_| ());
+            }
+        }
+        Ok(())
+    }
+
+    async fn after_send(
+        &self,
+        transaction: &TransactionOrVersionedTransaction,
+        result: &crate::Result<Signature>,
+    ) {
+        for middleware in self.middleware.iter().rev() {
+            middleware.after_send(self, transaction, result).await;
+        }
+    }
+
+    async fn before_sign(
+        &self,
+        transaction: &mut TransactionOrVersionedTransaction,
+    ) -> crate::Result<()> {
+        for (index, middleware) in self.middleware.iter().enumerate() {
+            if let Err(err) = middleware.before_sign(self, transaction).await {
+                let result = Err(err);
+                for middleware in self.middleware[..index].iter().rev() {
+                    middleware.after_sign(self, transaction, &result).await;
+                }
+                return result.map(|_| ());
+            }
+        }
+        Ok(())
+    }
+
+    async fn after_sign(
+        &self,
+        transaction: &TransactionOrVersionedTransaction,
+        result: &crate::Result<TransactionOrVersionedTransaction>,
+    ) {
+        for middleware in self.middleware.iter().rev() {
+            middleware.after_sign(self, transaction, result).await;
+        }
+    }
+}
+
+/// The memo and fee payer are part of the prepared transaction, the wallet mustn't add them again
+fn without_applied(mut options: SendOptions) -> SendOptions {
+    options.memo = None;
+    options.fee_payer = None;
+    options
+}
+
+impl<W: BaseWalletAdapter + ?Sized> Clone for MiddlewareWalletAdapter<W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: dyn_clone::clone_box(&*self.inner),
+            middleware: self.middleware.clone(),
+        }
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseWalletAdapter + ?Sized + 'static] BaseWalletAdapter for MiddlewareWalletAdapter<W>;
+    to inner:
+        event_emitter, name, url, icon, ready_state, public_key, connecting, disconnecting,
+        supported_transaction_versions, connected, features, account, watch_ready_state,
+        on_connect, on_disconnect, on_error, subscribe_provider_event, unsubscribe_provider_event,
+        on_provider_event, disconnect, auto_connect, connect, prepare_transaction,
+        prepare_transaction_with_context, check_if_transaction_is_supported;
+
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        mut options: Option<SendTransactionOptions>,
+    ) -> crate::Result<Signature> {
+        let send_options = options.as_ref().map(|o| o.send_options.clone());
+        let mut transaction = self
+            .prepare(transaction, connection, send_options.as_ref())
+            .await?;
+        if let Some(options) = options.as_mut() {
+            options.send_options = without_applied(options.send_options.clone());
+        }
+
+        self.before_send(&mut transaction, &mut options).await?;
+
+        let result = self
+            .inner
+            .send_transaction(transaction.clone(), connection, options)
+            .await;
+
+        self.after_send(&transaction, &result).await;
+        result
+    }
+
+    async fn send_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+        connection: &dyn Connection,
+        options: Option<SendOptions>,
+    ) -> crate::Result<Vec<Option<Signature>>> {
+        let mut prepared = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            prepared.push(self.prepare(transaction, connection, options.as_ref()).await?);
+        }
+        let options = options.map(without_applied);
+
+        for index in 0..prepared.len() {
+            let (checked, pending) = prepared.split_at_mut(index);
+            let mut transaction_options = options.clone().map(SendTransactionOptions::from);
+            if let Err(err) = self.before_send(&mut pending[0], &mut transaction_options).await {
+                let result = Err(err);
+                for transaction in checked.iter() {
+                    self.after_send(transaction, &result).await;
+                }
+                return result.map(|signature| vec![Some(signature)]);
+            }
+        }
+
+        let result = self
+            .inner
+            .send_all_transactions(prepared.clone(), connection, options)
+            .await;
+        match result {
+            Ok(signatures) => {
+                for (transaction, signature) in prepared.iter().zip(signatures.iter()) {
+                    let result = signature
+                        .ok_or_else(|| anyhow::anyhow!("the transaction was not sent").into());
+                    self.after_send(transaction, &result).await;
+                }
+                Ok(signatures)
+            }
+            Err(err) => {
+                let result = Err(err);
+                for transaction in prepared.iter() {
+                    self.after_send(transaction, &result).await;
+                }
+                result.map(|signature| vec![Some(signature)])
+            }
+        }
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseSignerWalletAdapter + ?Sized + 'static] BaseSignerWalletAdapter
+        for MiddlewareWalletAdapter<W>;
+    to inner: wallet_signer;
+
+    async fn sign_transaction(
+        &self,
+        mut transaction: TransactionOrVersionedTransaction,
+    ) -> crate::Result<TransactionOrVersionedTransaction> {
+        self.before_sign(&mut transaction).await?;
+
+        let result = self.inner.sign_transaction(transaction.clone()).await;
+
+        self.after_sign(&transaction, &result).await;
+        result
+    }
+
+    async fn sign_all_transactions(
+        &self,
+        mut transactions: Vec<TransactionOrVersionedTransaction>,
+    ) -> crate::Result<Vec<TransactionOrVersionedTransaction>> {
+        for index in 0..transactions.len() {
+            let (checked, pending) = transactions.split_at_mut(index);
+            if let Err(err) = self.before_sign(&mut pending[0]).await {
+                let result = Err(err);
+                for transaction in checked.iter() {
+                    self.after_sign(transaction, &result).await;
+                }
+                return result.map(|transaction| vec![transaction]);
+            }
+        }
+
+        let signed = match self.inner.sign_all_transactions(transactions.clone()).await {
+            Ok(signed) => signed,
+            Err(err) => {
+                let result = Err(err);
+                for transaction in transactions.iter() {
+                    self.after_sign(transaction, &result).await;
+                }
+                return result.map(|transaction| vec![transaction]);
+            }
+        };
+
+        let mut results = Vec::with_capacity(signed.len());
+        for (transaction, signed) in transactions.iter().zip(signed) {
+            let result = Ok(signed);
+            self.after_sign(transaction, &result).await;
+            results.push(result?);
+        }
+        Ok(results)
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseMessageSignerWalletAdapter + ?Sized + 'static] BaseMessageSignerWalletAdapter
+        for MiddlewareWalletAdapter<W>;
+    to inner: sign_message;
+}
+
+delegate_adapter! {
+    impl[W: BaseLockableWalletAdapter + ?Sized + 'static] BaseLockableWalletAdapter
+        for MiddlewareWalletAdapter<W>;
+    to inner: locked, lock, unlock;
+}
@@ -0,0 +1,138 @@
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::standard::WalletAccount;
+use wallet_adapter_common::types::SendTransactionOptions;
+
+use crate::{
+    BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+    TransactionOrVersionedTransaction, WalletError,
+};
+
+/// Several wallets connected at once, eg. Phantom next to a hardware wallet.
+///
+/// Requests go to the wallet owning the selected account. Use
+/// `MultiWallet<dyn BaseMessageSignerWalletAdapter>` to also route signing.
+pub struct MultiWallet<W: ?Sized = dyn BaseWalletAdapter> {
+    wallets: Vec<Box<W>>,
+    selected: Option<Pubkey>,
+}
+
+impl<W: ?Sized> Default for MultiWallet<W> {
+    fn default() -> Self {
+        Self {
+            wallets: vec![],
+            selected: None,
+        }
+    }
+}
+
+impl<W: BaseWalletAdapter + ?Sized> MultiWallet<W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `wallet`, selecting its account if none is selected yet
+    pub fn add(&mut self, wallet: Box<W>) {
+        if self.selected().is_none() {
+            self.selected = wallet.public_key();
+        }
+        self.wallets.push(wallet);
+    }
+
+    /// Remove the wallet called `name`, it is up to the caller to disconnect it
+    pub fn remove(&mut self, name: &str) -> Option<Box<W>> {
+        let index = self.wallets.iter().position(|w| w.name() == name)?;
+        Some(self.wallets.remove(index))
+    }
+
+    pub fn wallets(&self) -> &[Box<W>] {
+        &self.wallets
+    }
+
+    pub fn wallet_mut(&mut self, name: &str) -> Option<&mut W> {
+        self.wallets
+            .iter_mut()
+            .find(|w| w.name() == name)
+            .map(|w| &mut **w)
+    }
+
+    /// The accounts of all connected wallets, without duplicates
+    pub fn accounts(&self) -> Vec<WalletAccount> {
+        let mut accounts: Vec<WalletAccount> = vec![];
+        for account in self.wallets.iter().filter_map(|w| w.account()) {
+            if !accounts.iter().any(|a| a.address == account.address) {
+                accounts.push(account);
+            }
+        }
+        accounts
+    }
+
+    /// The wallet `public_key` is connected to
+    pub fn owner(&self, public_key: &Pubkey) -> Option<&W> {
+        self.wallets
+            .iter()
+            .find(|w| w.public_key().as_ref() == Some(public_key))
+            .map(|w| &**w)
+    }
+
+    /// Route the following requests to the wallet of `public_key`
+    pub fn select(&mut self, public_key: Pubkey) -> crate::Result<()> {
+        if self.owner(&public_key).is_none() {
+            return Err(WalletError::WalletNotConnected);
+        }
+        self.selected = Some(public_key);
+        Ok(())
+    }
+
+    /// The selected account, `None` once its wallet disconnected or was removed
+    pub fn selected(&self) -> Option<Pubkey> {
+        self.selected.filter(|key| self.owner(key).is_some())
+    }
+
+    /// The wallet owning the selected account
+    pub fn selected_wallet(&self) -> crate::Result<&W> {
+        self.selected()
+            .and_then(|key| self.owner(&key))
+            .ok_or(WalletError::WalletNotConnected)
+    }
+
+    pub async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> crate::Result<Signature> {
+        BaseWalletAdapter::send_transaction(
+            self.selected_wallet()?,
+            transaction,
+            connection,
+            options,
+        )
+        .await
+    }
+}
+
+impl<W: BaseSignerWalletAdapter + ?Sized> MultiWallet<W> {
+    pub async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> crate::Result<TransactionOrVersionedTransaction> {
+        self.selected_wallet()?.sign_transaction(transaction).await
+    }
+
+    pub async fn sign_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+    ) -> crate::Result<Vec<TransactionOrVersionedTransaction>> {
+        self.selected_wallet()?
+            .sign_all_transactions(transactions)
+            .await
+    }
+}
+
+impl<W: BaseMessageSignerWalletAdapter + ?Sized> MultiWallet<W> {
+    pub async fn sign_message(&self, message: &[u8]) -> crate::Result<Vec<u8>> {
+        self.selected_wallet()?.sign_message(message).await
+    }
+}
@@ -0,0 +1,446 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{NaiveDate, Utc};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_system_interface::instruction::SystemInstruction;
+use solana_system_interface::program as system_program;
+use wallet_adapter_common::types::SendTransactionOptions;
+
+use crate::middleware::{MiddlewareWalletAdapter, WalletMiddleware};
+use crate::{BaseWalletAdapter, TransactionOrVersionedTransaction, WalletError};
+
+/// A wallet only signing and sending what its [`TransactionPolicy`] allows
+pub type PolicyWalletAdapter<W> = MiddlewareWalletAdapter<W>;
+
+/// Why a [`TransactionPolicy`] refused a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// An instruction calls a program outside the allowlist
+    Program(Pubkey),
+    /// SOL goes to an account outside the allowlist
+    Recipient(Pubkey),
+    /// An instruction uses accounts of an address lookup table, which the policy can't resolve
+    UnresolvedAccount,
+    TransactionLimit {
+        lamports: u64,
+        limit: u64,
+    },
+    DailyLimit {
+        lamports: u64,
+        spent: u64,
+        limit: u64,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Program(program) => write!(f, "program {program} isn't allowed"),
+            Self::Recipient(recipient) => write!(f, "recipient {recipient} isn't allowed"),
+            Self::UnresolvedAccount => write!(f, "accounts from lookup tables can't be checked"),
+            Self::TransactionLimit { lamports, limit } => {
+                write!(f, "{lamports} lamports exceed the limit of {limit} per transaction")
+            }
+            Self::DailyLimit {
+                lamports,
+                spent,
+                limit,
+            } => write!(
+                f,
+                "{lamports} lamports on top of {spent} spent today exceed the limit of {limit} per day"
+            ),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type ConfirmFn = dyn Fn(&[PolicyViolation]) -> bool + Send + Sync;
+#[cfg(target_arch = "wasm32")]
+type ConfirmFn = dyn Fn(&[PolicyViolation]) -> bool;
+
+/// Lamports of the daily limit taken by a transaction, and the day they count for
+type Reservation = (NaiveDate, u64);
+
+/// Guardrails checked before a transaction reaches the wallet, eg. for kiosk or game-embedded
+/// wallets that sign without asking. Everything is allowed until restricted.
+///
+/// Limits count the SOL the wallet transfers or puts into new accounts through the system
+/// program. A transaction reserves its SOL of the daily limit when it is let through and gives
+/// it back if signing or sending fails. Clones share the daily spending.
+#[derive(Clone, Default)]
+pub struct TransactionPolicy {
+    allowed_programs: Option<HashSet<Pubkey>>,
+    allowed_recipients: Option<HashSet<Pubkey>>,
+    max_lamports_per_transaction: Option<u64>,
+    max_lamports_per_day: Option<u64>,
+    confirm: Option<Arc<ConfirmFn>>,
+    spent: Arc<Mutex<(NaiveDate, u64)>>,
+    /// What the transactions in flight reserved, by their message
+    reservations: Arc<Mutex<HashMap<Vec<u8>, Vec<Reservation>>>>,
+}
+
+impl TransactionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only allow instructions of `programs`, include the system program to allow transfers
+    pub fn allow_programs(mut self, programs: impl IntoIterator<Item = Pubkey>) -> Self {
+        self.allowed_programs
+            .get_or_insert_with(HashSet::new)
+            .extend(programs);
+        self
+    }
+
+    /// Only allow SOL transfers to `recipients`
+    pub fn allow_recipients(mut self, recipients: impl IntoIterator<Item = Pubkey>) -> Self {
+        self.allowed_recipients
+            .get_or_insert_with(HashSet::new)
+            .extend(recipients);
+        self
+    }
+
+    pub fn max_lamports_per_transaction(mut self, lamports: u64) -> Self {
+        self.max_lamports_per_transaction = Some(lamports);
+        self
+    }
+
+    /// Limit the SOL spent per UTC day
+    pub fn max_lamports_per_day(mut self, lamports: u64) -> Self {
+        self.max_lamports_per_day = Some(lamports);
+        self
+    }
+
+    /// Ask `confirm`, eg. a PIN prompt, instead of refusing violating transactions outright.
+    /// Returning true lets the transaction through.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn confirm_with(
+        mut self,
+        confirm: impl Fn(&[PolicyViolation]) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.confirm = Some(Arc::new(confirm));
+        self
+    }
+
+    /// Ask `confirm`, eg. a PIN prompt, instead of refusing violating transactions outright.
+    /// Returning true lets the transaction through.
+    #[cfg(target_arch = "wasm32")]
+    pub fn confirm_with(mut self, confirm: impl Fn(&[PolicyViolation]) -> bool + 'static) -> Self {
+        self.confirm = Some(Arc::new(confirm));
+        self
+    }
+
+    /// Enforce the policy on `adapter`
+    pub fn wrap<W: BaseWalletAdapter + ?Sized>(self, adapter: Box<W>) -> PolicyWalletAdapter<W> {
+        MiddlewareWalletAdapter::new(adapter).with(self)
+    }
+
+    /// Lamports let through today
+    pub fn spent_today(&self) -> u64 {
+        match self.spent.lock() {
+            Ok(spent) if spent.0 == Utc::now().date_naive() => spent.1,
+            _ => 0,
+        }
+    }
+
+    /// Today's spending, starting over on a new day
+    fn lock_spent(&self) -> crate::Result<MutexGuard<'_, (NaiveDate, u64)>> {
+        let mut spent = self
+            .spent
+            .lock()
+            .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+        let today = Utc::now().date_naive();
+        if spent.0 != today {
+            *spent = (today, 0);
+        }
+        Ok(spent)
+    }
+
+    /// What `transaction`, signed by `owner`, violates with `spent` lamports already let
+    /// through today and the lamports it spends
+    fn check(
+        &self,
+        owner: Option<Pubkey>,
+        transaction: &TransactionOrVersionedTransaction,
+        spent: u64,
+    ) -> (Vec<PolicyViolation>, u64) {
+        let (keys, instructions) = match transaction {
+            TransactionOrVersionedTransaction::Transaction(tx) => {
+                (&tx.message.account_keys[..], &tx.message.instructions[..])
+            }
+            TransactionOrVersionedTransaction::VersionedTransaction(vtx) => (
+                vtx.message.static_account_keys(),
+                vtx.message.instructions(),
+            ),
+        };
+
+        let mut violations = vec![];
+        let mut lamports = 0u64;
+        for instruction in instructions {
+            let accounts: Option<Vec<Pubkey>> = instruction
+                .accounts
+                .iter()
+                .map(|&i| keys.get(usize::from(i)).copied())
+                .collect();
+            let (Some(program_id), Some(accounts)) = (
+                keys.get(usize::from(instruction.program_id_index)),
+                accounts,
+            ) else {
+                violations.push(PolicyViolation::UnresolvedAccount);
+                continue;
+            };
+
+            if let Some(allowed) = &self.allowed_programs {
+                if !allowed.contains(program_id) {
+                    violations.push(PolicyViolation::Program(*program_id));
+                }
+            }
+
+            if !system_program::check_id(program_id) {
+                continue;
+            }
+            let (amount, from, to) = match bincode::deserialize(&instruction.data) {
+                Ok(SystemInstruction::Transfer { lamports })
+                | Ok(SystemInstruction::CreateAccount { lamports, .. }) => {
+                    (lamports, accounts.first(), accounts.get(1))
+                }
+                Ok(SystemInstruction::TransferWithSeed { lamports, .. }) => {
+                    (lamports, accounts.first(), accounts.get(2))
+                }
+                Ok(SystemInstruction::CreateAccountWithSeed { lamports, .. }) => {
+                    (lamports, accounts.first(), accounts.get(1))
+                }
+                _ => continue,
+            };
+
+            if let (Some(allowed), Some(to)) = (&self.allowed_recipients, to) {
+                if !allowed.contains(to) {
+                    violations.push(PolicyViolation::Recipient(*to));
+                }
+            }
+            if from.is_some() && from == owner.as_ref() {
+                lamports = lamports.saturating_add(amount);
+            }
+        }
+
+        if let Some(limit) = self.max_lamports_per_transaction {
+            if lamports > limit {
+                violations.push(PolicyViolation::TransactionLimit { lamports, limit });
+            }
+        }
+        if let Some(limit) = self.max_lamports_per_day {
+            if spent.saturating_add(lamports) > limit {
+                violations.push(PolicyViolation::DailyLimit {
+                    lamports,
+                    spent,
+                    limit,
+                });
+            }
+        }
+
+        (violations, lamports)
+    }
+
+    /// Reserve the lamports of `transaction` unless it violates the policy and isn't confirmed
+    fn enforce(
+        &self,
+        owner: Option<Pubkey>,
+        transaction: &TransactionOrVersionedTransaction,
+    ) -> crate::Result<()> {
+        // checking and reserving under one lock, concurrent sends can't both take the rest
+        let (violations, lamports) = {
+            let mut spent = self.lock_spent()?;
+            let (violations, lamports) = self.check(owner, transaction, spent.1);
+            if violations.is_empty() {
+                spent.1 = spent.1.saturating_add(lamports);
+                let reservation = (spent.0, lamports);
+                drop(spent);
+                return self.remember(transaction, reservation);
+            }
+            (violations, lamports)
+        };
+
+        // the prompt may take a while, confirmed transactions go over the limits anyway
+        if !self.confirm.as_ref().is_some_and(|f| f(&violations)) {
+            let reasons: Vec<String> = violations.iter().map(ToString::to_string).collect();
+            return Err(WalletError::WalletPolicyViolation(reasons.join(", ")));
+        }
+
+        let mut spent = self.lock_spent()?;
+        spent.1 = spent.1.saturating_add(lamports);
+        let reservation = (spent.0, lamports);
+        drop(spent);
+        self.remember(transaction, reservation)
+    }
+
+    fn remember(
+        &self,
+        transaction: &TransactionOrVersionedTransaction,
+        reservation: Reservation,
+    ) -> crate::Result<()> {
+        self.reservations
+            .lock()
+            .map_err(|err| anyhow::anyhow!("{err:?}"))?
+            .entry(transaction.message_data())
+            .or_default()
+            .push(reservation);
+        Ok(())
+    }
+
+    /// Forget the reservation of `transaction`, giving it back when it didn't go through.
+    /// Only the day it was made on gets it back.
+    fn settle(&self, transaction: &TransactionOrVersionedTransaction, went_through: bool) {
+        let reservation = self.reservations.lock().ok().and_then(|mut reservations| {
+            let key = transaction.message_data();
+            let pending = reservations.get_mut(&key)?;
+            let reservation = pending.pop();
+            if pending.is_empty() {
+                reservations.remove(&key);
+            }
+            reservation
+        });
+
+        let Some((day, lamports)) = reservation.filter(|_| !went_through) else {
+            return;
+        };
+        if let Ok(mut spent) = self.spent.lock() {
+            if spent.0 == day {
+                spent.1 = spent.1.saturating_sub(lamports);
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl WalletMiddleware for TransactionPolicy {
+    async fn before_sign(
+        &self,
+        wallet: &dyn BaseWalletAdapter,
+        transaction: &mut TransactionOrVersionedTransaction,
+    ) -> crate::Result<()> {
+        self.enforce(wallet.public_key(), transaction)
+    }
+
+    async fn after_sign(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        transaction: &TransactionOrVersionedTransaction,
+        result: &crate::Result<TransactionOrVersionedTransaction>,
+    ) {
+        self.settle(transaction, result.is_ok());
+    }
+
+    async fn before_send(
+        &self,
+        wallet: &dyn BaseWalletAdapter,
+        transaction: &mut TransactionOrVersionedTransaction,
+        _options: &mut Option<SendTransactionOptions>,
+    ) -> crate::Result<()> {
+        self.enforce(wallet.public_key(), transaction)
+    }
+
+    async fn after_send(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        transaction: &TransactionOrVersionedTransaction,
+        result: &crate::Result<Signature>,
+    ) {
+        self.settle(transaction, result.is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_system_interface::instruction as system_instruction;
+
+    use super::*;
+    use crate::TransactionBuilder;
+
+    fn transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> TransactionOrVersionedTransaction {
+        TransactionBuilder::new()
+            .fee_payer(*from)
+            .add_instruction(system_instruction::transfer(from, to, lamports))
+            .build_legacy()
+            .unwrap()
+    }
+
+    #[test]
+    fn refuses_what_the_policy_does_not_allow() {
+        let owner = Pubkey::new_unique();
+        let shop = Pubkey::new_unique();
+        let policy = TransactionPolicy::new()
+            .allow_programs([system_program::ID])
+            .allow_recipients([shop])
+            .max_lamports_per_transaction(100)
+            .max_lamports_per_day(150);
+
+        policy
+            .enforce(Some(owner), &transfer(&owner, &shop, 100))
+            .unwrap();
+        assert_eq!(policy.spent_today(), 100);
+
+        let (violations, _) = policy.check(Some(owner), &transfer(&owner, &owner, 101), 100);
+        assert_eq!(
+            violations,
+            vec![
+                PolicyViolation::Recipient(owner),
+                PolicyViolation::TransactionLimit {
+                    lamports: 101,
+                    limit: 100
+                },
+                PolicyViolation::DailyLimit {
+                    lamports: 101,
+                    spent: 100,
+                    limit: 150
+                },
+            ]
+        );
+
+        let memo = TransactionBuilder::new()
+            .fee_payer(owner)
+            .add_instruction(crate::build_memo(b"hi", &[]))
+            .build_legacy()
+            .unwrap();
+        assert!(matches!(
+            policy.enforce(Some(owner), &memo),
+            Err(WalletError::WalletPolicyViolation(_))
+        ));
+
+        let lenient = policy.confirm_with(|violations| violations.len() == 1);
+        lenient.enforce(Some(owner), &memo).unwrap();
+        assert_eq!(lenient.spent_today(), 100);
+    }
+
+    #[test]
+    fn gives_back_exactly_what_was_reserved() {
+        let owner = Pubkey::new_unique();
+        let policy = TransactionPolicy::new().max_lamports_per_day(1_000);
+        let sent = transfer(&owner, &Pubkey::new_unique(), 100);
+        let failed = transfer(&owner, &Pubkey::new_unique(), 200);
+
+        policy.enforce(Some(owner), &sent).unwrap();
+        policy.enforce(Some(owner), &failed).unwrap();
+        policy.settle(&sent, true);
+        policy.settle(&failed, false);
+        assert_eq!(policy.spent_today(), 100);
+
+        // settling again finds no reservation
+        policy.settle(&failed, false);
+        policy.settle(&sent, false);
+        assert_eq!(policy.spent_today(), 100);
+        assert!(policy.reservations.lock().unwrap().is_empty());
+
+        // a reservation of yesterday isn't taken from today's spending
+        policy.enforce(Some(owner), &failed).unwrap();
+        let yesterday = Utc::now().date_naive().pred_opt().unwrap();
+        for pending in policy.reservations.lock().unwrap().values_mut() {
+            pending[0].0 = yesterday;
+        }
+        policy.settle(&failed, false);
+        assert_eq!(policy.spent_today(), 300);
+    }
+}
@@ -0,0 +1,363 @@
+use std::fmt;
+
+use solana_sdk::compute_budget;
+use solana_sdk::instruction::CompiledInstruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_system_interface::instruction::SystemInstruction;
+use solana_system_interface::program as system_program;
+use wallet_adapter_common::amount::{Lamports, UiAmount};
+use wallet_adapter_common::token::TOKEN_PROGRAM_IDS;
+
+use crate::memo::MEMO_PROGRAM_ID;
+use crate::solana_pay::TRANSFER_CHECKED_INSTRUCTION;
+use crate::TransactionOrVersionedTransaction;
+
+const TOKEN_TRANSFER_INSTRUCTION: u8 = 3;
+const SET_COMPUTE_UNIT_LIMIT_INSTRUCTION: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE_INSTRUCTION: u8 = 3;
+
+/// What one instruction does, as far as it can be told without fetching accounts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionPreview {
+    SolTransfer {
+        from: Pubkey,
+        to: Pubkey,
+        lamports: u64,
+    },
+    CreateAccount {
+        from: Pubkey,
+        account: Pubkey,
+        lamports: u64,
+        owner: Pubkey,
+    },
+    /// An SPL token (or token-2022) transfer between token accounts. The mint and decimals are
+    /// only part of `TransferChecked`, `amount` is in base units.
+    TokenTransfer {
+        source: Pubkey,
+        destination: Pubkey,
+        authority: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+        decimals: Option<u8>,
+    },
+    Memo(String),
+    ComputeUnitLimit(u32),
+    ComputeUnitPrice {
+        micro_lamports: u64,
+    },
+    /// Any other instruction, accounts from address lookup tables are `None`
+    Unknown {
+        program_id: Pubkey,
+        accounts: Vec<Option<Pubkey>>,
+        data: Vec<u8>,
+    },
+}
+
+impl fmt::Display for InstructionPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SolTransfer { from, to, lamports } => {
+                write!(f, "Transfer {} from {from} to {to}", Lamports(*lamports))
+            }
+            Self::CreateAccount {
+                from,
+                account,
+                lamports,
+                owner,
+            } => write!(
+                f,
+                "Create account {account} owned by {owner}, funded with {} from {from}",
+                Lamports(*lamports)
+            ),
+            Self::TokenTransfer {
+                source,
+                destination,
+                mint,
+                amount,
+                decimals,
+                ..
+            } => {
+                let amount = match decimals {
+                    Some(decimals) => UiAmount::new(*amount, *decimals).to_string(),
+                    None => format!("{amount} base units"),
+                };
+                match mint {
+                    Some(mint) => write!(
+                        f,
+                        "Transfer {amount} of token {mint} from {source} to {destination}"
+                    ),
+                    None => write!(f, "Transfer {amount} tokens from {source} to {destination}"),
+                }
+            }
+            Self::Memo(memo) => write!(f, "Memo: {memo}"),
+            Self::ComputeUnitLimit(units) => write!(f, "Set compute unit limit to {units}"),
+            Self::ComputeUnitPrice { micro_lamports } => {
+                write!(
+                    f,
+                    "Set priority fee to {micro_lamports} micro-lamports per compute unit"
+                )
+            }
+            Self::Unknown {
+                program_id,
+                accounts,
+                data,
+            } => {
+                let accounts: Vec<String> = accounts
+                    .iter()
+                    .map(|account| match account {
+                        Some(account) => account.to_string(),
+                        None => "<lookup table>".to_string(),
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "Call program {program_id} with {} bytes of data and accounts [{}]",
+                    data.len(),
+                    accounts.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// A human-readable summary of a transaction, eg. for a wallet UI to show what the user is
+/// about to sign. Its `Display` lists one instruction per line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionPreview {
+    pub fee_payer: Option<Pubkey>,
+    pub instructions: Vec<InstructionPreview>,
+}
+
+impl TransactionPreview {
+    /// Lamports the transfers and new accounts take from `owner`, not counting fees
+    pub fn lamports_spent_by(&self, owner: &Pubkey) -> u64 {
+        self.instructions
+            .iter()
+            .map(|instruction| match instruction {
+                InstructionPreview::SolTransfer { from, lamports, .. }
+                | InstructionPreview::CreateAccount { from, lamports, .. }
+                    if from == owner =>
+                {
+                    *lamports
+                }
+                _ => 0,
+            })
+            .fold(0, u64::saturating_add)
+    }
+}
+
+impl fmt::Display for TransactionPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(fee_payer) = &self.fee_payer {
+            writeln!(f, "Fee payer: {fee_payer}")?;
+        }
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            writeln!(f, "{}. {instruction}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+fn decode(program_id: Pubkey, accounts: Vec<Option<Pubkey>>, data: &[u8]) -> InstructionPreview {
+    let resolved: Option<Vec<Pubkey>> = accounts.iter().copied().collect();
+    let decoded = match &resolved {
+        Some(keys) if system_program::check_id(&program_id) => decode_system(keys, data),
+        Some(keys) if TOKEN_PROGRAM_IDS.contains(&program_id.to_string().as_str()) => {
+            decode_token(keys, data)
+        }
+        _ if program_id == MEMO_PROGRAM_ID => std::str::from_utf8(data)
+            .ok()
+            .map(|memo| InstructionPreview::Memo(memo.to_string())),
+        _ if compute_budget::check_id(&program_id) => decode_compute_budget(data),
+        _ => None,
+    };
+
+    decoded.unwrap_or_else(|| InstructionPreview::Unknown {
+        program_id,
+        accounts,
+        data: data.to_vec(),
+    })
+}
+
+fn decode_system(accounts: &[Pubkey], data: &[u8]) -> Option<InstructionPreview> {
+    Some(match bincode::deserialize(data).ok()? {
+        SystemInstruction::Transfer { lamports } => InstructionPreview::SolTransfer {
+            from: *accounts.first()?,
+            to: *accounts.get(1)?,
+            lamports,
+        },
+        SystemInstruction::TransferWithSeed { lamports, .. } => InstructionPreview::SolTransfer {
+            from: *accounts.first()?,
+            to: *accounts.get(2)?,
+            lamports,
+        },
+        SystemInstruction::CreateAccount {
+            lamports, owner, ..
+        }
+        | SystemInstruction::CreateAccountWithSeed {
+            lamports, owner, ..
+        } => InstructionPreview::CreateAccount {
+            from: *accounts.first()?,
+            account: *accounts.get(1)?,
+            lamports,
+            owner,
+        },
+        _ => return None,
+    })
+}
+
+fn decode_token(accounts: &[Pubkey], data: &[u8]) -> Option<InstructionPreview> {
+    let (&tag, rest) = data.split_first()?;
+    let amount = u64::from_le_bytes(rest.get(..8)?.try_into().ok()?);
+    match tag {
+        TOKEN_TRANSFER_INSTRUCTION => Some(InstructionPreview::TokenTransfer {
+            source: *accounts.first()?,
+            destination: *accounts.get(1)?,
+            authority: *accounts.get(2)?,
+            mint: None,
+            amount,
+            decimals: None,
+        }),
+        TRANSFER_CHECKED_INSTRUCTION => Some(InstructionPreview::TokenTransfer {
+            source: *accounts.first()?,
+            mint: Some(*accounts.get(1)?),
+            destination: *accounts.get(2)?,
+            authority: *accounts.get(3)?,
+            amount,
+            decimals: Some(*rest.get(8)?),
+        }),
+        _ => None,
+    }
+}
+
+fn decode_compute_budget(data: &[u8]) -> Option<InstructionPreview> {
+    let (&tag, rest) = data.split_first()?;
+    match tag {
+        SET_COMPUTE_UNIT_LIMIT_INSTRUCTION => Some(InstructionPreview::ComputeUnitLimit(
+            u32::from_le_bytes(rest.get(..4)?.try_into().ok()?),
+        )),
+        SET_COMPUTE_UNIT_PRICE_INSTRUCTION => Some(InstructionPreview::ComputeUnitPrice {
+            micro_lamports: u64::from_le_bytes(rest.get(..8)?.try_into().ok()?),
+        }),
+        _ => None,
+    }
+}
+
+impl TransactionOrVersionedTransaction {
+    /// Decode the instructions into a [`TransactionPreview`]. Instructions using accounts from
+    /// address lookup tables can't be decoded without fetching the tables and stay `Unknown`.
+    pub fn preview(&self) -> TransactionPreview {
+        let (keys, instructions): (&[Pubkey], &[CompiledInstruction]) = match self {
+            Self::Transaction(tx) => (&tx.message.account_keys, &tx.message.instructions),
+            Self::VersionedTransaction(vtx) => (
+                vtx.message.static_account_keys(),
+                vtx.message.instructions(),
+            ),
+        };
+
+        let instructions = instructions
+            .iter()
+            .filter_map(|instruction| {
+                let program_id = *keys.get(usize::from(instruction.program_id_index))?;
+                let accounts = instruction
+                    .accounts
+                    .iter()
+                    .map(|&i| keys.get(usize::from(i)).copied())
+                    .collect();
+                Some(decode(program_id, accounts, &instruction.data))
+            })
+            .collect();
+
+        TransactionPreview {
+            fee_payer: keys.first().copied(),
+            instructions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_system_interface::instruction as system_instruction;
+
+    use super::*;
+    use crate::TransactionBuilder;
+
+    #[test]
+    fn previews_transfers_and_unknown_calls() {
+        let payer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let (source, mint, destination) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let token_program: Pubkey = TOKEN_PROGRAM_IDS[0].parse().unwrap();
+
+        let mut transfer_checked = vec![TRANSFER_CHECKED_INSTRUCTION];
+        transfer_checked.extend_from_slice(&1_500_000u64.to_le_bytes());
+        transfer_checked.push(6);
+        let unknown_program = Pubkey::new_unique();
+
+        let preview = TransactionBuilder::new()
+            .fee_payer(payer)
+            .add_instruction(system_instruction::transfer(
+                &payer,
+                &recipient,
+                250_000_000,
+            ))
+            .add_instruction(Instruction {
+                program_id: token_program,
+                accounts: vec![
+                    AccountMeta::new(source, false),
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new(destination, false),
+                    AccountMeta::new_readonly(payer, true),
+                ],
+                data: transfer_checked,
+            })
+            .add_instruction(crate::build_memo(b"order 42", &[]))
+            .add_instruction(Instruction {
+                program_id: unknown_program,
+                accounts: vec![AccountMeta::new(recipient, false)],
+                data: vec![1, 2, 3],
+            })
+            .build_legacy()
+            .unwrap()
+            .preview();
+
+        assert_eq!(preview.fee_payer, Some(payer));
+        assert_eq!(
+            preview.instructions,
+            vec![
+                InstructionPreview::SolTransfer {
+                    from: payer,
+                    to: recipient,
+                    lamports: 250_000_000,
+                },
+                InstructionPreview::TokenTransfer {
+                    source,
+                    destination,
+                    authority: payer,
+                    mint: Some(mint),
+                    amount: 1_500_000,
+                    decimals: Some(6),
+                },
+                InstructionPreview::Memo("order 42".to_string()),
+                InstructionPreview::Unknown {
+                    program_id: unknown_program,
+                    accounts: vec![Some(recipient)],
+                    data: vec![1, 2, 3],
+                },
+            ]
+        );
+        assert_eq!(preview.lamports_spent_by(&payer), 250_000_000);
+        assert_eq!(
+            preview.instructions[0].to_string(),
+            format!("Transfer 0.25 SOL from {payer} to {recipient}")
+        );
+        assert!(preview.instructions[1]
+            .to_string()
+            .starts_with("Transfer 1.5 of token"));
+    }
+}
@@ -0,0 +1,51 @@
+use send_wrapper::SendWrapper;
+
+use crate::delegate::delegate_adapter;
+use crate::BaseWalletAdapter;
+
+/// Makes any adapter `Send + Sync`, so browser wallets can be handed to engines
+/// like Bevy that require `Box<dyn BaseWalletAdapter + Send + Sync>`.
+///
+/// The adapter stays bound to the thread that created it: using it from another thread panics.
+/// wasm32 has a single thread, so there it can't happen.
+pub struct SendSyncWalletAdapter {
+    inner: SendWrapper<Box<dyn BaseWalletAdapter>>,
+}
+
+impl SendSyncWalletAdapter {
+    pub fn new(adapter: Box<dyn BaseWalletAdapter>) -> Self {
+        Self {
+            inner: SendWrapper::new(adapter),
+        }
+    }
+
+    pub fn boxed(adapter: Box<dyn BaseWalletAdapter>) -> Box<dyn BaseWalletAdapter + Send + Sync> {
+        Box::new(Self::new(adapter))
+    }
+
+    /// Whether the adapter can be used from the current thread
+    pub fn valid(&self) -> bool {
+        self.inner.valid()
+    }
+
+    pub fn into_inner(self) -> Box<dyn BaseWalletAdapter> {
+        self.inner.take()
+    }
+}
+
+impl Clone for SendSyncWalletAdapter {
+    fn clone(&self) -> Self {
+        Self::new((*self.inner).clone())
+    }
+}
+
+delegate_adapter! {
+    impl[] BaseWalletAdapter for SendSyncWalletAdapter;
+    to inner:
+        event_emitter, name, url, icon, ready_state, public_key, connecting, disconnecting,
+        supported_transaction_versions, connected, features, account, watch_ready_state,
+        on_connect, on_disconnect, on_error, subscribe_provider_event, unsubscribe_provider_event,
+        on_provider_event, disconnect, auto_connect, connect, send_transaction,
+        send_all_transactions, send_transaction_with_blockhash_retry, transfer_sol,
+        prepare_transaction, prepare_transaction_with_context, check_if_transaction_is_supported;
+}
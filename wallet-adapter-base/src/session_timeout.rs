@@ -0,0 +1,212 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use solana_sdk::signature::Signature;
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::types::{SendOptions, SendTransactionOptions};
+
+use crate::delegate::delegate_adapter;
+use crate::{
+    BaseLockableWalletAdapter, BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter,
+    BaseWalletAdapter, TransactionOrVersionedTransaction, WalletAdapterEvent, WalletError,
+};
+
+/// Disconnects the wrapped wallet once it went `timeout` without connecting, signing or
+/// sending, emitting [`WalletAdapterEvent::SessionExpired`], eg. for kiosks or games on
+/// shared machines.
+///
+/// Requests check the timeout before reaching the wallet and fail with `WalletDisconnected`
+/// once it expired. Run [`Self::run_timer`] to also disconnect while nothing is requested.
+/// Clones share the timer.
+pub struct SessionTimeoutWalletAdapter<W: ?Sized> {
+    inner: Box<W>,
+    timeout: Duration,
+    last_activity: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl<W: BaseWalletAdapter + ?Sized> SessionTimeoutWalletAdapter<W> {
+    pub fn new(inner: Box<W>, timeout: Duration) -> Self {
+        Self {
+            inner,
+            timeout,
+            last_activity: Arc::new(Mutex::new(Utc::now())),
+        }
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Box<W> {
+        self.inner
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Restart the timer, eg. on user input that doesn't reach the wallet
+    pub fn touch(&self) {
+        if let Ok(mut last_activity) = self.last_activity.lock() {
+            *last_activity = Utc::now();
+        }
+    }
+
+    /// Time left until the session expires, zero once it has
+    pub fn remaining(&self) -> Duration {
+        let Ok(last_activity) = self.last_activity.lock() else {
+            return Duration::ZERO;
+        };
+        let idle = (Utc::now() - *last_activity).to_std().unwrap_or_default();
+        self.timeout.saturating_sub(idle)
+    }
+
+    /// Disconnect the wallet if it is connected and the session expired, returns whether it did
+    pub async fn expire_if_idle(&self) -> crate::Result<bool> {
+        if !self.inner.connected() || !self.remaining().is_zero() {
+            return Ok(false);
+        }
+
+        tracing::info!(
+            "{} session expired after {:?}",
+            self.inner.name(),
+            self.timeout
+        );
+        self.inner
+            .event_emitter()
+            .emit(WalletAdapterEvent::SessionExpired)
+            .await?;
+        self.inner.disconnect().await?;
+        Ok(true)
+    }
+
+    /// Check the session whenever it could expire, until the returned future is dropped.
+    /// `sleep` is the timer of the runtime, eg. `tokio::time::sleep` or
+    /// `gloo_timers::future::sleep`.
+    pub async fn run_timer<F, Fut>(&self, mut sleep: F)
+    where
+        F: FnMut(Duration) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let remaining = self.remaining();
+            sleep(if remaining.is_zero() {
+                self.timeout
+            } else {
+                remaining
+            })
+            .await;
+
+            if let Err(err) = self.expire_if_idle().await {
+                tracing::warn!("could not end the expired session: {err}");
+            }
+        }
+    }
+
+    /// Fail requests of an expired session, or restart its timer
+    async fn check(&self) -> crate::Result<()> {
+        if self.expire_if_idle().await? {
+            return Err(WalletError::WalletDisconnected);
+        }
+        self.touch();
+        Ok(())
+    }
+}
+
+impl<W: BaseWalletAdapter + ?Sized> Clone for SessionTimeoutWalletAdapter<W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: dyn_clone::clone_box(&*self.inner),
+            timeout: self.timeout,
+            last_activity: self.last_activity.clone(),
+        }
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseWalletAdapter + ?Sized + 'static] BaseWalletAdapter
+        for SessionTimeoutWalletAdapter<W>;
+    to inner:
+        event_emitter, name, url, icon, ready_state, public_key, connecting, disconnecting,
+        supported_transaction_versions, connected, features, account, watch_ready_state,
+        on_connect, on_disconnect, on_error, subscribe_provider_event, unsubscribe_provider_event,
+        on_provider_event, disconnect, prepare_transaction, prepare_transaction_with_context,
+        check_if_transaction_is_supported;
+
+    async fn auto_connect(&mut self) -> crate::Result<()> {
+        self.inner.auto_connect().await?;
+        self.touch();
+        Ok(())
+    }
+
+    async fn connect(&mut self) -> crate::Result<()> {
+        self.inner.connect().await?;
+        self.touch();
+        Ok(())
+    }
+
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> crate::Result<Signature> {
+        self.check().await?;
+        self.inner
+            .send_transaction(transaction, connection, options)
+            .await
+    }
+
+    async fn send_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+        connection: &dyn Connection,
+        options: Option<SendOptions>,
+    ) -> crate::Result<Vec<Option<Signature>>> {
+        self.check().await?;
+        self.inner
+            .send_all_transactions(transactions, connection, options)
+            .await
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseSignerWalletAdapter + ?Sized + 'static] BaseSignerWalletAdapter
+        for SessionTimeoutWalletAdapter<W>;
+    to inner: wallet_signer;
+
+    async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> crate::Result<TransactionOrVersionedTransaction> {
+        self.check().await?;
+        self.inner.sign_transaction(transaction).await
+    }
+
+    async fn sign_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+    ) -> crate::Result<Vec<TransactionOrVersionedTransaction>> {
+        self.check().await?;
+        self.inner.sign_all_transactions(transactions).await
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseMessageSignerWalletAdapter + ?Sized + 'static] BaseMessageSignerWalletAdapter
+        for SessionTimeoutWalletAdapter<W>;
+    to inner:;
+
+    async fn sign_message(&self, message: &[u8]) -> crate::Result<Vec<u8>> {
+        self.check().await?;
+        self.inner.sign_message(message).await
+    }
+}
+
+delegate_adapter! {
+    impl[W: BaseLockableWalletAdapter + ?Sized + 'static] BaseLockableWalletAdapter
+        for SessionTimeoutWalletAdapter<W>;
+    to inner: locked, lock, unlock;
+}
@@ -1,19 +1,36 @@
 use solana_sdk::{signature::Signature, signer::Signer};
-use wallet_adapter_common::{connection::Connection, types::SendTransactionOptions};
-
-use crate::{adapter::BaseWalletAdapter, transaction::TransactionOrVersionedTransaction};
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::offchain_message;
+use wallet_adapter_common::relay::FeePayerService;
+use wallet_adapter_common::types::{DynSigner, SendTransactionOptions};
+
+use crate::adapter::BaseWalletAdapter;
+use crate::transaction::{
+    check_versioned_send_options, with_fee_payer, TransactionOrVersionedTransaction,
+};
 use anyhow::anyhow;
 
-#[async_trait::async_trait(?Send)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 pub trait BaseSignerWalletAdapter: BaseWalletAdapter {
-    fn wallet_signer(&self) -> Option<Box<dyn Signer>>;
+    fn wallet_signer(&self) -> Option<Box<DynSigner>>;
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn send_transaction(
         &self,
         transaction: TransactionOrVersionedTransaction,
         connection: &dyn Connection,
         options: Option<SendTransactionOptions>,
     ) -> crate::Result<Signature> {
+        if let Some(sponsor) = options.as_ref().and_then(|o| o.sponsor.clone()) {
+            return self
+                .send_sponsored_transaction(transaction, connection, sponsor.as_ref(), options)
+                .await;
+        }
+
         if self.wallet_signer().is_none()
             && options
                 .as_ref()
@@ -25,24 +42,27 @@ pub trait BaseSignerWalletAdapter: BaseWalletAdapter {
 
         match transaction {
             TransactionOrVersionedTransaction::Transaction(tx) => {
-                let mut signers: Vec<&dyn Signer> = vec![];
-
-                let opt_wallet_signer = self.wallet_signer();
-                if let Some(wallet_signer) = opt_wallet_signer.as_ref() {
-                    signers.push(wallet_signer.as_ref());
-                }
-
-                let send_options = options.as_ref().map(|o| o.send_options);
-
-                if let Some(ref options) = options {
-                    signers.extend(options.signers.iter().map(|s| s.as_ref()));
-                }
+                let send_options = options.as_ref().map(|o| o.send_options.clone());
 
                 let mut tx = self
                     .prepare_transaction(tx, connection, send_options.as_ref())
                     .await?;
 
-                tx.partial_sign(&signers, tx.message.recent_blockhash);
+                // signer references aren't held across awaits, keeping the future `Send` off wasm
+                {
+                    let mut signers: Vec<&dyn Signer> = vec![];
+
+                    let opt_wallet_signer = self.wallet_signer();
+                    if let Some(wallet_signer) = opt_wallet_signer.as_ref() {
+                        signers.push(wallet_signer.as_ref());
+                    }
+
+                    if let Some(ref options) = options {
+                        signers.extend(options.signers.iter().map(|s| s.as_ref() as &dyn Signer));
+                    }
+
+                    tx.partial_sign(&signers, tx.message.recent_blockhash);
+                }
 
                 let tx = self
                     .sign_transaction(TransactionOrVersionedTransaction::Transaction(tx))
@@ -62,6 +82,7 @@ pub trait BaseSignerWalletAdapter: BaseWalletAdapter {
             }
             TransactionOrVersionedTransaction::VersionedTransaction(ref _tx) => {
                 self.check_if_transaction_is_supported(&transaction)?;
+                check_versioned_send_options(options.as_ref().map(|o| &o.send_options))?;
 
                 let tx = self.sign_transaction(transaction).await?;
 
@@ -80,11 +101,95 @@ pub trait BaseSignerWalletAdapter: BaseWalletAdapter {
         }
     }
 
+    /// Send a legacy `transaction` whose fees `sponsor` pays: its key becomes the transaction's
+    /// fee payer, the wallet and the extra signers of `options` sign and the sponsor submits it.
+    ///
+    /// Called by `send_transaction` when [`SendTransactionOptions::sponsor`] is set.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn send_sponsored_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        sponsor: &dyn FeePayerService,
+        options: Option<SendTransactionOptions>,
+    ) -> crate::Result<Signature> {
+        let TransactionOrVersionedTransaction::Transaction(tx) = transaction else {
+            return Err(crate::WalletError::WalletSendTransactionError(
+                "Sponsored transactions must be legacy transactions".to_string(),
+            ));
+        };
+
+        let fee_payer = sponsor.fee_payer().await?;
+        let send_options = options.as_ref().map(|o| o.send_options.clone());
+        let tx = self
+            .prepare_transaction(tx, connection, send_options.as_ref())
+            .await?;
+        let mut tx = with_fee_payer(tx, &fee_payer)?;
+
+        if let Some(options) = options.as_ref().filter(|o| !o.signers.is_empty()) {
+            let signers: Vec<&dyn Signer> = options
+                .signers
+                .iter()
+                .map(|s| s.as_ref() as &dyn Signer)
+                .collect();
+            tx.try_partial_sign(&signers, tx.message.recent_blockhash)
+                .map_err(|err| anyhow!("{err}"))?;
+        }
+
+        let TransactionOrVersionedTransaction::Transaction(tx) = self
+            .sign_transaction(TransactionOrVersionedTransaction::Transaction(tx))
+            .await?
+        else {
+            return Err(crate::WalletError::WalletSendTransactionError(
+                "Expected Transaction".to_string(),
+            ));
+        };
+
+        let signature = sponsor.sign_and_send(&tx).await?;
+        tracing::debug!(%signature, %fee_payer, "sponsored transaction sent");
+
+        Ok(signature)
+    }
+
     async fn sign_transaction(
         &self,
         transaction: TransactionOrVersionedTransaction,
     ) -> crate::Result<TransactionOrVersionedTransaction>;
 
+    /// Add the wallet's signature to a transaction of
+    /// [`TransactionOrVersionedTransaction::serialize_partial`] that other parties sign too,
+    /// returning it serialized the same way for the next signer
+    async fn sign_partial_transaction(&self, encoded: &str) -> crate::Result<String> {
+        let transaction = TransactionOrVersionedTransaction::deserialize_partial(encoded)?;
+        let public_key = self
+            .public_key()
+            .ok_or(crate::WalletError::WalletNotConnected)?;
+        if !transaction.missing_signers().contains(&public_key) {
+            return Err(anyhow!("{public_key} is not a missing signer of the transaction").into());
+        }
+        self.check_if_transaction_is_supported(&transaction)?;
+
+        let signed = self.sign_transaction(transaction.clone()).await?;
+
+        // wallets adding eg. priority fees change the message, voiding the other signatures
+        let kept_signatures = transaction
+            .signatures()
+            .iter()
+            .zip(signed.signatures())
+            .all(|(before, after)| *before == Signature::default() || before == after);
+        if signed.message_data() != transaction.message_data() || !kept_signatures {
+            return Err(anyhow!(
+                "the wallet changed the transaction, voiding the other signatures"
+            )
+            .into());
+        }
+
+        Ok(signed.serialize_partial()?)
+    }
+
     async fn sign_all_transactions(
         &self,
         transactions: Vec<TransactionOrVersionedTransaction>,
@@ -101,7 +206,16 @@ pub trait BaseSignerWalletAdapter: BaseWalletAdapter {
     }
 }
 
-#[async_trait::async_trait(?Send)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 pub trait BaseMessageSignerWalletAdapter: BaseSignerWalletAdapter {
+    /// Sign the bytes of `message` as they are
     async fn sign_message(&self, message: &[u8]) -> crate::Result<Vec<u8>>;
+
+    /// Sign `message` wrapped in an off-chain message, the Ledger-safe format that can't be
+    /// replayed as a transaction, see [`offchain_message`]
+    async fn sign_offchain_message(&self, message: &[u8]) -> crate::Result<Vec<u8>> {
+        let message = offchain_message::serialize_message(message)?;
+        self.sign_message(&message).await
+    }
 }
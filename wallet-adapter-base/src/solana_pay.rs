@@ -0,0 +1,272 @@
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_system_interface::instruction as system_instruction;
+use url::Url;
+use wallet_adapter_common::amount::SOL_DECIMALS;
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::solana_pay::{SolanaPayClient, TransferRequest};
+use wallet_adapter_common::token::{Mint, MintExtension, TransferFee};
+use wallet_adapter_common::types::SendTransactionOptions;
+
+use crate::adapter::BaseWalletAdapter;
+use crate::signer::BaseSignerWalletAdapter;
+use crate::transaction::TransactionOrVersionedTransaction;
+use crate::{build_memo, TransactionBuilder, WalletError};
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// `TransferChecked` of the token programs
+pub(crate) const TRANSFER_CHECKED_INSTRUCTION: u8 = 12;
+/// `TransferFeeExtension::TransferCheckedWithFee` of Token-2022
+const TRANSFER_FEE_EXTENSION_INSTRUCTION: u8 = 26;
+const TRANSFER_CHECKED_WITH_FEE_INSTRUCTION: u8 = 1;
+
+/// The mint of an SPL token transfer request, read from its mint account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenMint {
+    /// The SPL Token or Token-2022 program owning the mint
+    pub program_id: Pubkey,
+    pub decimals: u8,
+    /// The fee of Token-2022 mints with the transfer fee extension, in the current epoch
+    pub transfer_fee: Option<TransferFee>,
+}
+
+impl TokenMint {
+    /// The token of a decoded mint account, `epoch` being the current epoch which picks the
+    /// transfer fee. Fails for mints a plain transfer can't pay, like transfer hook mints.
+    pub fn from_mint(mint: &Mint, epoch: u64) -> Result<Self> {
+        for extension in [MintExtension::NonTransferable, MintExtension::TransferHook] {
+            if mint.has_extension(extension) {
+                bail!("tokens of mints with the {extension:?} extension can't be transferred");
+            }
+        }
+
+        Ok(Self {
+            program_id: mint.program_id,
+            decimals: mint.decimals,
+            transfer_fee: mint
+                .transfer_fee_config
+                .map(|config| *config.epoch_fee(epoch)),
+        })
+    }
+}
+
+/// The token account of `wallet` for `mint`
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// The memo and transfer instructions paying `request` from `payer`,
+/// SPL token requests need the `token` mint and pay into the recipient's associated token account.
+///
+/// Mints with a transfer fee are paid with `TransferCheckedWithFee`, the fee is withheld from
+/// the amount the recipient receives.
+pub fn build_transfer_request(
+    payer: &Pubkey,
+    request: &TransferRequest,
+    token: Option<&TokenMint>,
+) -> Result<Vec<Instruction>> {
+    let mut transfer = match (request.spl_token, token) {
+        (None, _) => system_instruction::transfer(
+            payer,
+            &request.recipient,
+            request.base_units(SOL_DECIMALS)?,
+        ),
+        (Some(mint), Some(token)) => {
+            let amount = request.base_units(token.decimals)?;
+            let mut data = match token.transfer_fee {
+                Some(_) => vec![
+                    TRANSFER_FEE_EXTENSION_INSTRUCTION,
+                    TRANSFER_CHECKED_WITH_FEE_INSTRUCTION,
+                ],
+                None => vec![TRANSFER_CHECKED_INSTRUCTION],
+            };
+            data.extend_from_slice(&amount.to_le_bytes());
+            data.push(token.decimals);
+            if let Some(transfer_fee) = &token.transfer_fee {
+                data.extend_from_slice(&transfer_fee.fee(amount).to_le_bytes());
+            }
+
+            Instruction {
+                program_id: token.program_id,
+                accounts: vec![
+                    AccountMeta::new(
+                        associated_token_address(payer, &mint, &token.program_id),
+                        false,
+                    ),
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new(
+                        associated_token_address(&request.recipient, &mint, &token.program_id),
+                        false,
+                    ),
+                    AccountMeta::new_readonly(*payer, true),
+                ],
+                data,
+            }
+        }
+        (Some(mint), None) => bail!("the program and decimals of {mint} are required"),
+    };
+    transfer.accounts.extend(
+        request
+            .references
+            .iter()
+            .map(|reference| AccountMeta::new_readonly(*reference, false)),
+    );
+
+    // the memo goes first so the transfer stays the last instruction, as the spec requires
+    let mut instructions = Vec::new();
+    if let Some(memo) = &request.memo {
+        instructions.push(build_memo(memo.as_bytes(), &[]));
+    }
+    instructions.push(transfer);
+    Ok(instructions)
+}
+
+/// Pay `request` with the connected wallet, see [`build_transfer_request`]
+pub async fn send_transfer_request(
+    adapter: &(impl BaseWalletAdapter + ?Sized),
+    request: &TransferRequest,
+    token: Option<&TokenMint>,
+    connection: &dyn Connection,
+    options: Option<SendTransactionOptions>,
+) -> crate::Result<Signature> {
+    let payer = adapter
+        .public_key()
+        .ok_or(WalletError::WalletNotConnected)?;
+
+    let transaction = TransactionBuilder::new()
+        .fee_payer(payer)
+        .transfer_request(&payer, request, token)?
+        .build_legacy()?;
+
+    adapter
+        .send_transaction(transaction, connection, options)
+        .await
+}
+
+/// The transaction a Solana Pay merchant built for the wallet, eg. at a point of sale
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionRequest {
+    pub transaction: TransactionOrVersionedTransaction,
+    /// Describes the transaction to the user
+    pub message: Option<String>,
+}
+
+impl TransactionRequest {
+    /// POST `account` to the transaction request `link` and decode the transaction it answers
+    pub async fn fetch(client: &dyn SolanaPayClient, link: &Url, account: &Pubkey) -> Result<Self> {
+        let response = client.post_transaction_request(link, account).await?;
+        let transaction = base64::engine::general_purpose::STANDARD
+            .decode(&response.transaction)
+            .context("the transaction request answered an invalid base64 transaction")?;
+
+        Ok(Self {
+            transaction: TransactionOrVersionedTransaction::deserialize(&transaction)?,
+            message: response.message,
+        })
+    }
+}
+
+/// Fetch the transaction `link` builds for the connected wallet and have the wallet sign it,
+/// the merchant may have signed it already so its signatures are kept.
+///
+/// Show the label and icon of [`SolanaPayClient::get_transaction_request`] before calling this.
+pub async fn sign_transaction_request(
+    adapter: &(impl BaseSignerWalletAdapter + ?Sized),
+    client: &dyn SolanaPayClient,
+    link: &Url,
+) -> crate::Result<TransactionRequest> {
+    let account = adapter
+        .public_key()
+        .ok_or(WalletError::WalletNotConnected)?;
+
+    let request = TransactionRequest::fetch(client, link, &account).await?;
+    adapter.check_if_transaction_is_supported(&request.transaction)?;
+
+    Ok(TransactionRequest {
+        transaction: adapter.sign_transaction(request.transaction).await?,
+        message: request.message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use wallet_adapter_common::token::{TransferFeeConfig, TOKEN_2022_PROGRAM_ID};
+
+    use super::*;
+
+    #[test]
+    fn references_follow_the_transfer_accounts() {
+        let payer = Pubkey::new_unique();
+        let mut request = TransferRequest::new(Pubkey::new_unique());
+        request.amount = Some("1.5".to_string());
+        request.spl_token = Some(Pubkey::new_unique());
+        request.references = vec![Pubkey::new_unique()];
+        request.memo = Some("order 1".to_string());
+
+        let token = TokenMint {
+            program_id: Pubkey::new_unique(),
+            decimals: 6,
+            transfer_fee: None,
+        };
+        let instructions = build_transfer_request(&payer, &request, Some(&token)).unwrap();
+
+        assert_eq!(instructions[0].program_id, crate::MEMO_PROGRAM_ID);
+        let transfer = &instructions[1];
+        assert_eq!(transfer.data[1..9], 1_500_000u64.to_le_bytes());
+        assert_eq!(transfer.accounts.len(), 5);
+        assert_eq!(transfer.accounts[4].pubkey, request.references[0]);
+        assert!(build_transfer_request(&payer, &request, None).is_err());
+    }
+
+    #[test]
+    fn pays_transfer_fee_mints_with_the_fee() {
+        let payer = Pubkey::new_unique();
+        let mut request = TransferRequest::new(Pubkey::new_unique());
+        request.amount = Some("2".to_string());
+        request.spl_token = Some(Pubkey::new_unique());
+
+        let mint = Mint {
+            program_id: TOKEN_2022_PROGRAM_ID,
+            supply: 0,
+            decimals: 2,
+            extensions: vec![MintExtension::TransferFeeConfig],
+            transfer_fee_config: Some(TransferFeeConfig {
+                newer_transfer_fee: TransferFee {
+                    epoch: 5,
+                    maximum_fee: 1_000,
+                    transfer_fee_basis_points: 150,
+                },
+                ..Default::default()
+            }),
+        };
+        let token = TokenMint::from_mint(&mint, 7).unwrap();
+        let transfer = build_transfer_request(&payer, &request, Some(&token))
+            .unwrap()
+            .remove(0);
+
+        assert_eq!(transfer.program_id, TOKEN_2022_PROGRAM_ID);
+        assert_eq!(transfer.data[..2], [26, 1]);
+        assert_eq!(transfer.data[2..10], 200u64.to_le_bytes());
+        assert_eq!(transfer.data[10], 2);
+        assert_eq!(transfer.data[11..], 3u64.to_le_bytes());
+        assert_eq!(
+            transfer.accounts[0].pubkey,
+            associated_token_address(&payer, &request.spl_token.unwrap(), &TOKEN_2022_PROGRAM_ID)
+        );
+
+        let hooked = Mint {
+            extensions: vec![MintExtension::TransferHook],
+            ..mint
+        };
+        assert!(TokenMint::from_mint(&hooked, 7).is_err());
+    }
+}
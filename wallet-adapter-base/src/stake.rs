@@ -0,0 +1,86 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_stake_interface::instruction as stake_instruction;
+use solana_stake_interface::state::{Authorized, Lockup};
+
+pub const STAKE_PROGRAM_ID: Pubkey = solana_stake_interface::program::ID;
+
+/// The stake account [`build_create_stake_account`] creates for `authority` and `seed`
+pub fn stake_account_address(authority: &Pubkey, seed: &str) -> Result<Pubkey> {
+    Pubkey::create_with_seed(authority, seed, &STAKE_PROGRAM_ID)
+        .map_err(|err| anyhow!("invalid stake account seed {seed:?}: {err}"))
+}
+
+/// Create a stake account derived from `authority` and `seed`, so the wallet is the only signer.
+///
+/// `authority` funds it with `lamports`, which have to cover the rent exemption of the
+/// account, and becomes its staker and withdrawer.
+pub fn build_create_stake_account(
+    authority: &Pubkey,
+    seed: &str,
+    lamports: u64,
+) -> Result<Vec<Instruction>> {
+    let stake_account = stake_account_address(authority, seed)?;
+    Ok(stake_instruction::create_account_with_seed(
+        authority,
+        &stake_account,
+        authority,
+        seed,
+        &Authorized::auto(authority),
+        &Lockup::default(),
+        lamports,
+    ))
+}
+
+/// Delegate `stake_account` to the validator voting with `vote_account`
+pub fn build_delegate_stake(
+    stake_account: &Pubkey,
+    authority: &Pubkey,
+    vote_account: &Pubkey,
+) -> Instruction {
+    stake_instruction::delegate_stake(stake_account, authority, vote_account)
+}
+
+/// Start cooling `stake_account` down, it can be withdrawn from after the current epoch
+pub fn build_deactivate_stake(stake_account: &Pubkey, authority: &Pubkey) -> Instruction {
+    stake_instruction::deactivate_stake(stake_account, authority)
+}
+
+/// Withdraw `lamports` of the inactive stake to `to`, all of them closes the account
+pub fn build_withdraw_stake(
+    stake_account: &Pubkey,
+    authority: &Pubkey,
+    to: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    stake_instruction::withdraw(stake_account, authority, to, lamports, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{TransactionBuilder, TransactionOrVersionedTransaction};
+
+    use super::*;
+
+    #[test]
+    fn the_wallet_is_the_only_signer_of_a_new_stake() {
+        let wallet = Pubkey::new_unique();
+        let stake_account = stake_account_address(&wallet, "stake:0").unwrap();
+
+        let transaction = TransactionBuilder::new()
+            .fee_payer(wallet)
+            .create_stake_account(&wallet, "stake:0", 1_000_000_000)
+            .unwrap()
+            .delegate_stake(&stake_account, &wallet, &Pubkey::new_unique())
+            .build_legacy()
+            .unwrap();
+
+        let TransactionOrVersionedTransaction::Transaction(transaction) = transaction else {
+            panic!("expected a legacy transaction");
+        };
+        assert_eq!(transaction.message.header.num_required_signatures, 1);
+        assert_eq!(transaction.message.account_keys[0], wallet);
+        assert!(transaction.message.account_keys.contains(&stake_account));
+    }
+}
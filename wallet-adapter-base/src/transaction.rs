@@ -1,5 +1,12 @@
-use anyhow::Result;
+use anyhow::{anyhow, ensure, Result};
+use base64::prelude::*;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::{Message, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
 use solana_sdk::transaction::{Transaction, TransactionVersion, VersionedTransaction};
+use wallet_adapter_common::types::SendOptions;
 
 pub type SupportedTransactionVersions = Vec<TransactionVersion>;
 
@@ -20,4 +27,246 @@ impl TransactionOrVersionedTransaction {
             Self::VersionedTransaction(tx) => bincode::serialize(&tx)?,
         })
     }
+
+    /// Deserialize a wire-format transaction, legacy messages become a `Transaction`
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let tx: VersionedTransaction = bincode::deserialize(bytes)?;
+
+        Ok(match tx.message {
+            VersionedMessage::Legacy(message) => Self::Transaction(Transaction {
+                signatures: tx.signatures,
+                message,
+            }),
+            VersionedMessage::V0(_) => Self::VersionedTransaction(tx),
+        })
+    }
+
+    /// Base64 of the transaction with the signatures it has so far, the missing ones left
+    /// empty, to hand it to the next signer of eg. an escrow or multisig
+    pub fn serialize_partial(&self) -> Result<String> {
+        self.verify_partial()?;
+        Ok(BASE64_STANDARD.encode(self.serialize()?))
+    }
+
+    /// Read a transaction of [`Self::serialize_partial`], failing when a signature it has
+    /// is invalid
+    pub fn deserialize_partial(encoded: &str) -> Result<Self> {
+        let transaction = Self::deserialize(&BASE64_STANDARD.decode(encoded.trim())?)?;
+        transaction.verify_partial()?;
+        Ok(transaction)
+    }
+
+    /// The serialized message, what every signer signs
+    pub fn message_data(&self) -> Vec<u8> {
+        match self {
+            Self::Transaction(tx) => tx.message_data(),
+            Self::VersionedTransaction(tx) => tx.message.serialize(),
+        }
+    }
+
+    pub fn signatures(&self) -> &[Signature] {
+        match self {
+            Self::Transaction(tx) => &tx.signatures,
+            Self::VersionedTransaction(tx) => &tx.signatures,
+        }
+    }
+
+    /// The accounts that must sign, in the order of their signatures
+    pub fn signer_keys(&self) -> &[Pubkey] {
+        let (keys, num_required) = match self {
+            Self::Transaction(tx) => (
+                &tx.message.account_keys[..],
+                tx.message.header.num_required_signatures,
+            ),
+            Self::VersionedTransaction(tx) => (
+                tx.message.static_account_keys(),
+                tx.message.header().num_required_signatures,
+            ),
+        };
+        &keys[..usize::from(num_required).min(keys.len())]
+    }
+
+    /// Signers that haven't signed yet
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        let signatures = self.signatures();
+        self.signer_keys()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                signatures.get(*index).copied().unwrap_or_default() == Signature::default()
+            })
+            .map(|(_, key)| *key)
+            .collect()
+    }
+
+    /// Check the signatures it has, like `requireAllSignatures: false` of web3.js
+    fn verify_partial(&self) -> Result<()> {
+        let signer_keys = self.signer_keys();
+        ensure!(
+            self.signatures().len() == signer_keys.len(),
+            "expected {} signatures, got {}",
+            signer_keys.len(),
+            self.signatures().len()
+        );
+
+        let message_data = self.message_data();
+        for (signature, key) in self.signatures().iter().zip(signer_keys) {
+            ensure!(
+                *signature == Signature::default() || signature.verify(key.as_ref(), &message_data),
+                "invalid signature of {key}"
+            );
+        }
+        Ok(())
+    }
+
+    /// Sign with `signers` while keeping the signatures of the other required signers,
+    /// eg. a co-signing server keypair before the wallet adds its own signature
+    pub fn partial_sign(&mut self, signers: &[Box<dyn Signer>]) -> Result<()> {
+        match self {
+            Self::Transaction(tx) => {
+                tx.try_partial_sign(signers, tx.message.recent_blockhash)?;
+            }
+            Self::VersionedTransaction(tx) => {
+                let num_required = tx.message.header().num_required_signatures as usize;
+                tx.signatures.resize(num_required, Signature::default());
+
+                let message_data = tx.message.serialize();
+
+                for signer in signers {
+                    let pubkey = signer.try_pubkey()?;
+                    let index = tx.message.static_account_keys()[..num_required]
+                        .iter()
+                        .position(|key| key == &pubkey)
+                        .ok_or_else(|| anyhow!("{pubkey} is not a required signer"))?;
+
+                    tx.signatures[index] = signer.try_sign_message(&message_data)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Turn the compiled instructions of a message back into instructions so it can be rebuilt
+pub(crate) fn decompile_instructions(message: &Message) -> Vec<Instruction> {
+    message
+        .instructions
+        .iter()
+        .map(|ix| Instruction {
+            program_id: message.account_keys[ix.program_id_index as usize],
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|&i| AccountMeta {
+                    pubkey: message.account_keys[i as usize],
+                    is_signer: message.is_signer(i as usize),
+                    is_writable: message.is_maybe_writable(i as usize, None),
+                })
+                .collect(),
+            data: ix.data.clone(),
+        })
+        .collect()
+}
+
+/// Rebuilding the message voids the signatures, so it is refused once anyone signed
+fn check_unsigned(transaction: &Transaction, change: &str) -> crate::Result<()> {
+    if transaction
+        .signatures
+        .iter()
+        .any(|signature| *signature != Signature::default())
+    {
+        return Err(crate::WalletError::WalletSendTransactionError(format!(
+            "Can't {change} of a transaction that is already signed, it would void the signatures"
+        )));
+    }
+    Ok(())
+}
+
+/// Append an instruction to an unsigned transaction, keeping its fee payer and blockhash
+pub(crate) fn append_instruction(
+    transaction: Transaction,
+    instruction: Instruction,
+) -> crate::Result<Transaction> {
+    check_unsigned(&transaction, "add an instruction to the message")?;
+    let message = &transaction.message;
+    let mut instructions = decompile_instructions(message);
+    instructions.push(instruction);
+
+    Ok(Transaction::new_unsigned(Message::new_with_blockhash(
+        &instructions,
+        message.account_keys.first(),
+        &message.recent_blockhash,
+    )))
+}
+
+/// Rebuild an unsigned transaction with `fee_payer` paying its fees, keeping its blockhash
+pub(crate) fn with_fee_payer(
+    transaction: Transaction,
+    fee_payer: &Pubkey,
+) -> crate::Result<Transaction> {
+    check_unsigned(&transaction, "change the fee payer")?;
+    let message = &transaction.message;
+
+    Ok(Transaction::new_unsigned(Message::new_with_blockhash(
+        &decompile_instructions(message),
+        Some(fee_payer),
+        &message.recent_blockhash,
+    )))
+}
+
+/// Fails for `options` that change the message, which legacy transactions only support:
+/// a versioned transaction gets its fee payer and memo when it is built
+pub fn check_versioned_send_options(options: Option<&SendOptions>) -> crate::Result<()> {
+    let Some(options) = options else {
+        return Ok(());
+    };
+    if options.fee_payer.is_some() || options.memo.is_some() {
+        return Err(crate::WalletError::WalletSendTransactionError(
+            "A fee payer or memo can't be added to a versioned transaction, set them when \
+             building it"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::AccountMeta;
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+    use crate::TransactionBuilder;
+
+    #[test]
+    fn partial_sign_versioned_transaction_keeps_other_signatures() {
+        let wallet = Keypair::new();
+        let co_signer = Keypair::new();
+
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new_readonly(co_signer.pubkey(), true)],
+        );
+
+        let mut transaction = TransactionBuilder::new()
+            .fee_payer(wallet.pubkey())
+            .add_instruction(instruction)
+            .recent_blockhash(Hash::new_unique())
+            .build_v0()
+            .unwrap();
+
+        transaction
+            .partial_sign(&[Box::new(co_signer.insecure_clone()) as Box<dyn Signer>])
+            .unwrap();
+
+        let TransactionOrVersionedTransaction::VersionedTransaction(tx) = transaction else {
+            panic!("expected versioned transaction");
+        };
+
+        assert_eq!(tx.signatures[0], Signature::default());
+        assert!(tx.signatures[1].verify(co_signer.pubkey().as_ref(), &tx.message.serialize()));
+    }
 }
@@ -0,0 +1,245 @@
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::{v0, Message, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+
+use wallet_adapter_common::solana_pay::TransferRequest;
+
+use crate::associated_token;
+use crate::create_account;
+use crate::solana_pay::{self, TokenMint};
+use crate::stake;
+use crate::transaction::TransactionOrVersionedTransaction;
+
+/// Fluent builder producing unsigned transactions ready for `send_transaction`.
+///
+/// When no blockhash is set, legacy transactions get one during `prepare_transaction`.
+/// Versioned transactions aren't prepared, so `build_v0` requires one.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionBuilder {
+    instructions: Vec<Instruction>,
+    fee_payer: Option<Pubkey>,
+    compute_unit_limit: Option<u32>,
+    compute_unit_price: Option<u64>,
+    lookup_tables: Vec<AddressLookupTableAccount>,
+    recent_blockhash: Option<Hash>,
+}
+
+impl TransactionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn add_instructions(mut self, instructions: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    pub fn fee_payer(mut self, fee_payer: Pubkey) -> Self {
+        self.fee_payer = Some(fee_payer);
+        self
+    }
+
+    /// Maximum compute units the transaction may consume
+    pub fn compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    /// Priority fee in micro-lamports per compute unit
+    pub fn compute_unit_price(mut self, micro_lamports: u64) -> Self {
+        self.compute_unit_price = Some(micro_lamports);
+        self
+    }
+
+    /// Lookup tables used to compress the account keys of a v0 transaction
+    pub fn lookup_tables(mut self, lookup_tables: Vec<AddressLookupTableAccount>) -> Self {
+        self.lookup_tables = lookup_tables;
+        self
+    }
+
+    pub fn recent_blockhash(mut self, recent_blockhash: Hash) -> Self {
+        self.recent_blockhash = Some(recent_blockhash);
+        self
+    }
+
+    /// Pay a Solana Pay transfer request, see [`crate::build_transfer_request`]
+    pub fn transfer_request(
+        self,
+        payer: &Pubkey,
+        request: &TransferRequest,
+        token: Option<&TokenMint>,
+    ) -> Result<Self> {
+        Ok(self.add_instructions(solana_pay::build_transfer_request(payer, request, token)?))
+    }
+
+    /// Create the associated token account of `wallet` for `mint` unless it exists,
+    /// see [`crate::build_create_ata_idempotent`]
+    pub fn create_ata_idempotent(
+        self,
+        payer: &Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Self {
+        self.add_instruction(associated_token::build_create_ata_idempotent(
+            payer,
+            wallet,
+            mint,
+            token_program,
+        ))
+    }
+
+    /// Create `new_account` with `space` bytes owned by `owner`, funded with the rent-exempt
+    /// minimum, see [`crate::build_create_account`]
+    pub fn create_account(
+        self,
+        payer: &Pubkey,
+        new_account: &Pubkey,
+        space: usize,
+        owner: &Pubkey,
+    ) -> Self {
+        self.add_instruction(create_account::build_create_account(
+            payer,
+            new_account,
+            space,
+            owner,
+        ))
+    }
+
+    /// Create the stake account of `authority` and `seed`, see [`crate::build_create_stake_account`]
+    pub fn create_stake_account(
+        self,
+        authority: &Pubkey,
+        seed: &str,
+        lamports: u64,
+    ) -> Result<Self> {
+        Ok(self.add_instructions(stake::build_create_stake_account(
+            authority, seed, lamports,
+        )?))
+    }
+
+    pub fn delegate_stake(
+        self,
+        stake_account: &Pubkey,
+        authority: &Pubkey,
+        vote_account: &Pubkey,
+    ) -> Self {
+        self.add_instruction(stake::build_delegate_stake(
+            stake_account,
+            authority,
+            vote_account,
+        ))
+    }
+
+    pub fn deactivate_stake(self, stake_account: &Pubkey, authority: &Pubkey) -> Self {
+        self.add_instruction(stake::build_deactivate_stake(stake_account, authority))
+    }
+
+    pub fn withdraw_stake(
+        self,
+        stake_account: &Pubkey,
+        authority: &Pubkey,
+        to: &Pubkey,
+        lamports: u64,
+    ) -> Self {
+        self.add_instruction(stake::build_withdraw_stake(
+            stake_account,
+            authority,
+            to,
+            lamports,
+        ))
+    }
+
+    fn payer(&self) -> Result<Pubkey> {
+        self.fee_payer.context("fee payer is required")
+    }
+
+    /// The compute budget instructions followed by the user instructions
+    fn all_instructions(&self) -> Vec<Instruction> {
+        let mut instructions = vec![];
+
+        if let Some(units) = self.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(micro_lamports) = self.compute_unit_price {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+                micro_lamports,
+            ));
+        }
+
+        instructions.extend(self.instructions.iter().cloned());
+        instructions
+    }
+
+    pub fn build_legacy(self) -> Result<TransactionOrVersionedTransaction> {
+        let payer = self.payer()?;
+
+        let message = Message::new_with_blockhash(
+            &self.all_instructions(),
+            Some(&payer),
+            &self.recent_blockhash.unwrap_or_default(),
+        );
+
+        Ok(TransactionOrVersionedTransaction::Transaction(
+            Transaction::new_unsigned(message),
+        ))
+    }
+
+    pub fn build_v0(self) -> Result<TransactionOrVersionedTransaction> {
+        let payer = self.payer()?;
+        let recent_blockhash = self
+            .recent_blockhash
+            .context("recent blockhash is required, versioned transactions aren't prepared")?;
+
+        let message = v0::Message::try_compile(
+            &payer,
+            &self.all_instructions(),
+            &self.lookup_tables,
+            recent_blockhash,
+        )
+        .map_err(|err| anyhow!("failed to compile v0 message: {err}"))?;
+
+        let num_signatures = message.header.num_required_signatures as usize;
+
+        Ok(TransactionOrVersionedTransaction::VersionedTransaction(
+            VersionedTransaction {
+                signatures: vec![Signature::default(); num_signatures],
+                message: VersionedMessage::V0(message),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v0_transactions_require_a_blockhash() {
+        let builder = TransactionBuilder::new()
+            .fee_payer(Pubkey::new_unique())
+            .add_instruction(crate::build_memo(b"v0", &[]));
+
+        let err = builder.clone().build_v0().unwrap_err();
+        assert!(err.to_string().contains("recent blockhash is required"));
+
+        let blockhash = Hash::new_unique();
+        let TransactionOrVersionedTransaction::VersionedTransaction(tx) =
+            builder.recent_blockhash(blockhash).build_v0().unwrap()
+        else {
+            panic!("expected a versioned transaction");
+        };
+        assert_eq!(tx.message.recent_blockhash(), &blockhash);
+    }
+}
@@ -0,0 +1,385 @@
+use std::sync::{Arc, Mutex};
+
+use futures::executor::block_on;
+use solana_sdk::hash::Hash;
+use solana_sdk::message::{v0, Message, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+use wallet_adapter_base::{
+    build_memo, send_all_in_turn, BaseWalletAdapter, EagerConnectWalletAdapter,
+    LoggingWalletAdapter, MiddlewareWalletAdapter, SendSyncWalletAdapter,
+    SessionTimeoutWalletAdapter, TransactionBuilder, TransactionOrVersionedTransaction,
+    WalletAdapterEvent, WalletError,
+};
+use wallet_adapter_common::connection::{Connection, RpcError};
+use wallet_adapter_common::standard::SolanaChain;
+use wallet_adapter_common::storage::SettingsStorage;
+use wallet_adapter_common::types::{SendOptions, SendTransactionOptions};
+use wallet_adapter_mock::{MockConnection, MockRequest, MockStep, MockWalletAdapter};
+
+#[derive(Debug)]
+struct NoSettings;
+
+impl SettingsStorage for NoSettings {
+    fn get_setting(&self, _key: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn set_setting(&self, _key: &str, _value: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn remove_setting(&self, _key: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn signs_again_when_the_blockhash_expired() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let events = wallet.event_emitter();
+    while events.try_recv().is_some() {}
+
+    let transaction = TransactionBuilder::new()
+        .fee_payer(wallet.public_key().unwrap())
+        .add_instruction(build_memo(b"retry", &[]))
+        .build_legacy()
+        .unwrap();
+    let connection = MockConnection::new();
+
+    connection.expire_next(1);
+    assert!(block_on(wallet.send_transaction_with_blockhash_retry(
+        transaction.clone(),
+        &connection,
+        None,
+        1
+    ))
+    .unwrap_err()
+    .is_blockhash_expired());
+
+    connection.expire_next(1);
+    block_on(wallet.send_transaction_with_blockhash_retry(transaction, &connection, None, 2))
+        .unwrap();
+    assert!(matches!(
+        events.try_recv(),
+        Some(WalletAdapterEvent::BlockhashExpired { attempt: 2 })
+    ));
+    assert_eq!(connection.sent().len(), 1);
+}
+
+#[test]
+fn only_blockhash_rpc_errors_are_expired_blockhashes() {
+    let not_found = RpcError {
+        code: -32002,
+        message: "Transaction simulation failed: Blockhash not found".to_string(),
+        data: Some(serde_json::json!({ "err": "BlockhashNotFound" })),
+    };
+    let error = anyhow::Error::from(not_found.clone()).context("sendTransaction");
+    assert!(WalletError::from(error).is_blockhash_expired());
+
+    let other = RpcError {
+        data: Some(serde_json::json!({ "err": "AccountNotFound" })),
+        ..not_found.clone()
+    };
+    assert!(!WalletError::from(anyhow::Error::from(other)).is_blockhash_expired());
+    assert!(!WalletError::WalletSendTransactionError(not_found.message).is_blockhash_expired());
+}
+
+#[test]
+fn connected_accounts_have_the_standard_shape() {
+    let mut wallet = MockWalletAdapter::new();
+    assert_eq!(wallet.account(), None);
+
+    block_on(wallet.connect()).unwrap();
+    let account = wallet.account().unwrap();
+
+    assert_eq!(account.pubkey().unwrap(), wallet.public_key().unwrap());
+    assert_eq!(account.chains, SolanaChain::ALL.to_vec());
+    assert_eq!(account.features, wallet.features());
+}
+
+#[test]
+fn prepared_transactions_get_a_fee_payer() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let public_key = wallet.public_key().unwrap();
+    let connection = MockConnection::new();
+
+    let mut unpaid = Transaction::new_unsigned(Message::new(&[build_memo(b"fees", &[])], None));
+    unpaid.message.recent_blockhash = Hash::new_unique();
+    let prepared = block_on(wallet.prepare_transaction(unpaid.clone(), &connection, None)).unwrap();
+    assert_eq!(prepared.message.account_keys[0], public_key);
+    assert_eq!(prepared.message.header.num_required_signatures, 1);
+    assert_eq!(
+        prepared.message.recent_blockhash,
+        unpaid.message.recent_blockhash
+    );
+
+    let sponsor = Pubkey::new_unique();
+    let options = SendOptions::builder().fee_payer(sponsor).build();
+    let prepared =
+        block_on(wallet.prepare_transaction(unpaid, &connection, Some(&options))).unwrap();
+    assert_eq!(prepared.message.account_keys[0], sponsor);
+    assert_eq!(prepared.message.header.num_required_signatures, 1);
+}
+
+#[test]
+fn preparing_returns_the_fetched_blockhash() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let connection = MockConnection::new();
+    let transaction = Transaction::new_unsigned(Message::new(
+        &[build_memo(b"context", &[])],
+        Some(&wallet.public_key().unwrap()),
+    ));
+
+    let (prepared, latest_blockhash) =
+        block_on(wallet.prepare_transaction_with_context(transaction, &connection, None)).unwrap();
+    let latest_blockhash = latest_blockhash.unwrap();
+    assert_eq!(
+        prepared.message.recent_blockhash,
+        latest_blockhash.blockhash
+    );
+
+    // a transaction with a blockhash keeps it
+    let (kept, latest_blockhash) =
+        block_on(wallet.prepare_transaction_with_context(prepared.clone(), &connection, None))
+            .unwrap();
+    assert_eq!(
+        kept.message.recent_blockhash,
+        prepared.message.recent_blockhash
+    );
+    assert!(latest_blockhash.is_none());
+}
+
+#[test]
+fn prepared_transactions_end_with_the_memo() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let connection = MockConnection::new();
+
+    let transaction = Transaction::new_unsigned(Message::new(
+        &[build_memo(b"pay", &[])],
+        Some(&wallet.public_key().unwrap()),
+    ));
+    let options = SendOptions::builder().memo("order 42").build();
+    let prepared =
+        block_on(wallet.prepare_transaction(transaction, &connection, Some(&options))).unwrap();
+
+    let message = &prepared.message;
+    let memo = message.instructions.last().unwrap();
+    assert_eq!(message.instructions.len(), 2);
+    assert_eq!(
+        message.account_keys[memo.program_id_index as usize],
+        wallet_adapter_base::MEMO_PROGRAM_ID
+    );
+    assert_eq!(memo.data, b"order 42");
+}
+
+#[test]
+fn signed_transactions_keep_their_message() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let public_key = wallet.public_key().unwrap();
+    let connection = MockConnection::new();
+
+    let mut signed = Transaction::new_unsigned(Message::new_with_blockhash(
+        &[build_memo(b"signed", &[])],
+        Some(&public_key),
+        &Hash::new_unique(),
+    ));
+    signed.partial_sign(&[wallet.keypair()], signed.message.recent_blockhash);
+
+    let options = SendOptions::builder()
+        .fee_payer(Pubkey::new_unique())
+        .build();
+    assert!(
+        block_on(wallet.prepare_transaction(signed.clone(), &connection, Some(&options))).is_err()
+    );
+    let options = SendOptions::builder().memo("too late").build();
+    assert!(
+        block_on(wallet.prepare_transaction(signed.clone(), &connection, Some(&options))).is_err()
+    );
+
+    // without changes the signature stays valid
+    let prepared = block_on(wallet.prepare_transaction(signed.clone(), &connection, None)).unwrap();
+    assert_eq!(prepared, signed);
+}
+
+#[test]
+fn versioned_transactions_refuse_a_memo() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let public_key = wallet.public_key().unwrap();
+    let connection = MockConnection::new();
+
+    let message = v0::Message::try_compile(
+        &public_key,
+        &[build_memo(b"v0", &[])],
+        &[],
+        Hash::new_unique(),
+    )
+    .unwrap();
+    let transaction = VersionedTransaction {
+        signatures: vec![Default::default()],
+        message: VersionedMessage::V0(message),
+    };
+    let options = SendTransactionOptions::from(SendOptions::builder().memo("dropped").build());
+
+    let result = block_on(wallet.send_transaction(
+        TransactionOrVersionedTransaction::VersionedTransaction(transaction),
+        &connection,
+        Some(options),
+    ));
+    assert!(result.is_err());
+    assert!(connection.sent().is_empty());
+}
+
+#[test]
+fn forwards_subscribed_provider_events() {
+    let wallet = MockWalletAdapter::new();
+    let chains = Arc::new(Mutex::new(Vec::new()));
+
+    let received = chains.clone();
+    let _subscription = wallet
+        .on_provider_event(
+            "chainChanged",
+            Box::new(move |payload| received.lock().unwrap().push(payload.clone())),
+        )
+        .unwrap();
+    wallet
+        .emit_provider_event("chainChanged", serde_json::json!("solana:devnet"))
+        .unwrap();
+    wallet
+        .emit_provider_event("accountsChanged", serde_json::json!([]))
+        .unwrap();
+
+    wallet.unsubscribe_provider_event("chainChanged").unwrap();
+    wallet
+        .emit_provider_event("chainChanged", serde_json::json!("solana:mainnet"))
+        .unwrap();
+
+    assert_eq!(
+        *chains.lock().unwrap(),
+        vec![serde_json::json!("solana:devnet")]
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn adapters_and_connections_run_on_other_threads() {
+    let mut wallet: Box<dyn BaseWalletAdapter> = Box::new(MockWalletAdapter::new());
+    let connection: Box<dyn Connection> = Box::new(MockConnection::new());
+
+    // spawning needs the futures of both to be Send
+    let signature = std::thread::spawn(move || {
+        block_on(async move {
+            wallet.connect().await.unwrap();
+            let transaction = TransactionBuilder::new()
+                .fee_payer(wallet.public_key().unwrap())
+                .add_instruction(build_memo(b"thread", &[]))
+                .build_legacy()
+                .unwrap();
+            wallet
+                .send_transaction(transaction, connection.as_ref(), None)
+                .await
+        })
+    })
+    .join()
+    .unwrap();
+
+    assert!(signature.is_ok());
+}
+
+#[test]
+fn send_sync_adapters_stay_on_their_thread() {
+    let mock = MockWalletAdapter::new();
+    let mut wrapped = SendSyncWalletAdapter::new(Box::new(mock.clone()));
+    block_on(wrapped.connect()).unwrap();
+
+    let wrapped = std::thread::spawn(move || {
+        assert!(!wrapped.valid());
+        wrapped
+    })
+    .join()
+    .unwrap();
+
+    assert!(wrapped.valid());
+    assert_eq!(wrapped.public_key(), Some(mock.keypair().pubkey()));
+    assert_eq!(wrapped.clone().into_inner().name(), mock.name());
+}
+
+#[test]
+fn wrapped_adapters_still_send_in_one_batch() {
+    let mock = MockWalletAdapter::new();
+    let wrapped: Vec<Box<dyn BaseWalletAdapter>> = vec![
+        SendSyncWalletAdapter::boxed(Box::new(mock.clone())),
+        Box::new(EagerConnectWalletAdapter::new(
+            Box::new(mock.clone()),
+            NoSettings,
+        )),
+        Box::new(MiddlewareWalletAdapter::new(Box::new(mock.clone()))),
+        Box::new(SessionTimeoutWalletAdapter::new(
+            Box::new(mock.clone()),
+            std::time::Duration::from_secs(60),
+        )),
+        Box::new(LoggingWalletAdapter::new(Box::new(mock.clone()))),
+    ];
+    block_on(mock.clone().connect()).unwrap();
+    let connection = MockConnection::new();
+
+    for wallet in wrapped {
+        let transactions = (0..2)
+            .map(|_| {
+                TransactionBuilder::new()
+                    .fee_payer(mock.keypair().pubkey())
+                    .add_instruction(build_memo(b"batch", &[]))
+                    .build_legacy()
+                    .unwrap()
+            })
+            .collect();
+        let signatures =
+            block_on(wallet.send_all_transactions(transactions, &connection, None)).unwrap();
+        assert_eq!(signatures.len(), 2, "{}", wallet.name());
+        assert_eq!(
+            mock.requests().last(),
+            Some(&MockRequest::SignAllTransactions(2))
+        );
+    }
+    assert!(!mock.requests().contains(&MockRequest::SignTransaction));
+    assert_eq!(connection.sent().len(), 10);
+}
+
+#[test]
+fn sending_in_turn_stops_at_the_first_failure() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let connection = MockConnection::new();
+    let transactions = || {
+        (0..3)
+            .map(|_| {
+                TransactionBuilder::new()
+                    .fee_payer(wallet.keypair().pubkey())
+                    .add_instruction(build_memo(b"in turn", &[]))
+                    .build_legacy()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    wallet.set_scenario([MockStep::approve(), MockStep::user_rejected()]);
+    let signatures =
+        block_on(send_all_in_turn(&wallet, transactions(), &connection, None)).unwrap();
+    assert!(signatures[0].is_some());
+    assert_eq!(signatures[1..], [None, None]);
+    assert_eq!(connection.sent().len(), 1);
+
+    // nothing was sent
+    wallet.set_scenario([MockStep::user_rejected()]);
+    assert!(matches!(
+        block_on(send_all_in_turn(&wallet, transactions(), &connection, None)),
+        Err(WalletError::WalletUserRejected(_))
+    ));
+}
@@ -0,0 +1,43 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use futures::executor::block_on;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use solana_system_interface::instruction as system_instruction;
+use wallet_adapter_base::{BaseWalletAdapter, BlockingSigner};
+use wallet_adapter_mock::{MockRequest, MockWalletAdapter};
+
+#[test]
+fn blocking_signer_signs_transactions_and_messages() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let public_key = wallet.keypair().pubkey();
+
+    let transaction_only = BlockingSigner::new(wallet.clone());
+    assert!(transaction_only.try_sign_message(b"hello").is_err());
+
+    let signer = BlockingSigner::with_message_signing(wallet);
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &public_key,
+            &Pubkey::new_unique(),
+            1,
+        )],
+        Some(&public_key),
+        &[&signer as &dyn Signer],
+        Hash::new_unique(),
+    );
+    transaction.verify().unwrap();
+
+    let signature = signer.try_sign_message(b"hello").unwrap();
+    assert!(signature.verify(public_key.as_ref(), b"hello"));
+    assert_eq!(
+        signer.adapter().requests()[1..],
+        [
+            MockRequest::SignTransaction,
+            MockRequest::SignMessage(b"hello".to_vec())
+        ]
+    );
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures::executor::block_on;
+use wallet_adapter_base::{BaseWalletAdapter, EagerConnectWalletAdapter};
+use wallet_adapter_common::storage::SettingsStorage;
+use wallet_adapter_mock::{MockBehavior, MockWalletAdapter};
+
+#[derive(Debug, Clone, Default)]
+struct MemorySettings(Arc<Mutex<HashMap<String, String>>>);
+
+impl SettingsStorage for MemorySettings {
+    fn get_setting(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        self.0.lock().unwrap().insert(key.into(), value.into());
+        Ok(())
+    }
+
+    fn remove_setting(&self, key: &str) -> anyhow::Result<()> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[test]
+fn auto_connects_only_wallets_connected_before() {
+    let settings = MemorySettings::default();
+    let eager = |settings: &MemorySettings| {
+        EagerConnectWalletAdapter::new(Box::new(MockWalletAdapter::new()), settings.clone())
+    };
+
+    let mut wallet = eager(&settings);
+    block_on(wallet.auto_connect()).unwrap();
+    assert!(!wallet.connected());
+
+    block_on(wallet.connect()).unwrap();
+    let mut reloaded = eager(&settings);
+    assert!(reloaded.was_connected());
+    block_on(reloaded.auto_connect()).unwrap();
+    assert!(reloaded.connected());
+
+    block_on(reloaded.disconnect()).unwrap();
+    let mut reloaded = eager(&settings);
+    block_on(reloaded.auto_connect()).unwrap();
+    assert!(!reloaded.connected());
+}
+
+#[test]
+fn rejected_connects_are_not_remembered() {
+    let settings = MemorySettings::default();
+    let mock = MockWalletAdapter::new();
+    let mut wallet = EagerConnectWalletAdapter::new(Box::new(mock.clone()), settings.clone());
+    block_on(wallet.connect()).unwrap();
+    assert!(wallet.was_connected());
+
+    block_on(mock.disconnect()).unwrap();
+    mock.set_connect_behavior(MockBehavior::user_rejected());
+    assert!(block_on(wallet.connect()).is_err());
+    assert!(!wallet.was_connected());
+
+    let mut reloaded = EagerConnectWalletAdapter::new(Box::new(mock.clone()), settings);
+    block_on(reloaded.auto_connect()).unwrap();
+    assert_eq!(mock.requests().len(), 3);
+}
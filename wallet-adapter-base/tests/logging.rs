@@ -0,0 +1,30 @@
+use futures::executor::block_on;
+use wallet_adapter_base::{
+    build_memo, BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+    LoggingWalletAdapter, TransactionBuilder,
+};
+use wallet_adapter_mock::{MockBehavior, MockWalletAdapter};
+
+#[test]
+fn logs_calls_with_their_results() {
+    let mock = MockWalletAdapter::new();
+    let mut wallet = LoggingWalletAdapter::new(Box::new(mock.clone()));
+    block_on(wallet.connect()).unwrap();
+    let public_key = wallet.public_key().unwrap();
+    block_on(wallet.sign_message(b"hi")).unwrap();
+    mock.set_sign_transaction_behavior(MockBehavior::user_rejected());
+
+    let transaction = TransactionBuilder::new()
+        .fee_payer(public_key)
+        .add_instruction(build_memo(b"log", &[]))
+        .build_legacy()
+        .unwrap();
+    assert!(block_on(wallet.sign_transaction(transaction)).is_err());
+
+    let records = wallet.log().records();
+    let methods: Vec<_> = records.iter().map(|record| record.method).collect();
+    assert_eq!(methods, vec!["connect", "sign_message", "sign_transaction"]);
+    assert_eq!(records[0].result, Ok(public_key.to_string()));
+    assert_eq!(records[2].payload.len(), 1);
+    assert!(records[2].result.is_err());
+}
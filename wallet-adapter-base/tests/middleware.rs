@@ -0,0 +1,81 @@
+use std::sync::{Arc, Mutex};
+
+use futures::executor::block_on;
+use wallet_adapter_base::{
+    build_memo, BaseSignerWalletAdapter, BaseWalletAdapter, MiddlewareWalletAdapter,
+    TransactionBuilder, TransactionOrVersionedTransaction, WalletError, WalletMiddleware,
+};
+use wallet_adapter_common::types::SendTransactionOptions;
+use wallet_adapter_mock::{MockConnection, MockRequest, MockWalletAdapter};
+
+/// Records the hooks it runs and refuses to send anything
+#[derive(Clone, Default)]
+struct Audit {
+    hooks: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl WalletMiddleware for Audit {
+    async fn before_sign(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        _transaction: &mut TransactionOrVersionedTransaction,
+    ) -> wallet_adapter_base::Result<()> {
+        self.hooks.lock().unwrap().push("before_sign");
+        Ok(())
+    }
+
+    async fn after_sign(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        _transaction: &TransactionOrVersionedTransaction,
+        result: &wallet_adapter_base::Result<TransactionOrVersionedTransaction>,
+    ) {
+        assert!(result.is_ok());
+        self.hooks.lock().unwrap().push("after_sign");
+    }
+
+    async fn before_send(
+        &self,
+        _wallet: &dyn BaseWalletAdapter,
+        _transaction: &mut TransactionOrVersionedTransaction,
+        _options: &mut Option<SendTransactionOptions>,
+    ) -> wallet_adapter_base::Result<()> {
+        self.hooks.lock().unwrap().push("before_send");
+        Err(WalletError::WalletSendTransactionError(
+            "blocked".to_string(),
+        ))
+    }
+}
+
+#[test]
+fn middleware_runs_around_the_wallet() {
+    let audit = Audit::default();
+    let mut wallet =
+        MiddlewareWalletAdapter::new(Box::new(MockWalletAdapter::new())).with(audit.clone());
+    block_on(wallet.connect()).unwrap();
+
+    let transaction = TransactionBuilder::new()
+        .fee_payer(wallet.public_key().unwrap())
+        .add_instruction(build_memo(b"hooks", &[]))
+        .build_legacy()
+        .unwrap();
+    block_on(wallet.sign_transaction(transaction.clone())).unwrap();
+    assert!(block_on(BaseWalletAdapter::send_transaction(
+        &wallet,
+        transaction,
+        &MockConnection::new(),
+        None,
+    ))
+    .is_err());
+
+    assert_eq!(
+        *audit.hooks.lock().unwrap(),
+        vec!["before_sign", "after_sign", "before_send"]
+    );
+    assert_eq!(
+        wallet.inner().requests(),
+        vec![MockRequest::Connect, MockRequest::SignTransaction]
+    );
+}
@@ -0,0 +1,35 @@
+use futures::executor::block_on;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseWalletAdapter, MultiWallet, WalletError,
+};
+use wallet_adapter_mock::MockWalletAdapter;
+
+#[test]
+fn multi_wallet_routes_to_the_selected_account() {
+    let mut first = MockWalletAdapter::new();
+    let mut second = MockWalletAdapter::new();
+    block_on(first.connect()).unwrap();
+    block_on(second.connect()).unwrap();
+    let (first_key, second_key) = (first.public_key().unwrap(), second.public_key().unwrap());
+
+    let mut wallets: MultiWallet<dyn BaseMessageSignerWalletAdapter> = MultiWallet::new();
+    wallets.add(Box::new(first));
+    wallets.add(Box::new(second.clone()));
+    assert_eq!(wallets.accounts().len(), 2);
+    assert_eq!(wallets.selected(), Some(first_key));
+
+    wallets.select(second_key).unwrap();
+    let signature = block_on(wallets.sign_message(b"route")).unwrap();
+    assert!(Signature::try_from(signature.as_slice())
+        .unwrap()
+        .verify(second_key.as_ref(), b"route"));
+
+    block_on(second.disconnect()).unwrap();
+    assert_eq!(wallets.selected(), None);
+    assert!(matches!(
+        wallets.select(Pubkey::new_unique()),
+        Err(WalletError::WalletNotConnected)
+    ));
+}
@@ -0,0 +1,62 @@
+use futures::executor::block_on;
+use solana_sdk::pubkey::Pubkey;
+use solana_system_interface::instruction as system_instruction;
+use solana_system_interface::program as system_program;
+use wallet_adapter_base::{BaseWalletAdapter, TransactionBuilder, TransactionPolicy, WalletError};
+use wallet_adapter_common::types::{SendOptions, SendTransactionOptions};
+use wallet_adapter_mock::{MockConnection, MockWalletAdapter};
+
+#[test]
+fn failed_sends_give_back_their_reservation() {
+    let policy = TransactionPolicy::new().max_lamports_per_day(150);
+    let mut wallet = policy.clone().wrap(Box::new(MockWalletAdapter::new()));
+    block_on(wallet.connect()).unwrap();
+    let owner = wallet.public_key().unwrap();
+    let transfer = TransactionBuilder::new()
+        .fee_payer(owner)
+        .add_instruction(system_instruction::transfer(
+            &owner,
+            &Pubkey::new_unique(),
+            100,
+        ))
+        .build_legacy()
+        .unwrap();
+
+    let connection = MockConnection::new();
+    connection.expire_next(1);
+    assert!(block_on(wallet.send_transaction(transfer.clone(), &connection, None)).is_err());
+    assert_eq!(policy.spent_today(), 0);
+
+    block_on(wallet.send_transaction(transfer.clone(), &connection, None)).unwrap();
+    assert_eq!(policy.spent_today(), 100);
+    assert!(matches!(
+        block_on(wallet.send_transaction(transfer, &connection, None)),
+        Err(WalletError::WalletPolicyViolation(_))
+    ));
+    assert_eq!(policy.spent_today(), 100);
+}
+
+#[test]
+fn checks_the_memo_added_by_the_options() {
+    let policy = TransactionPolicy::new().allow_programs([system_program::ID]);
+    let mut wallet = policy.wrap(Box::new(MockWalletAdapter::new()));
+    block_on(wallet.connect()).unwrap();
+    let owner = wallet.public_key().unwrap();
+    let transfer = TransactionBuilder::new()
+        .fee_payer(owner)
+        .add_instruction(system_instruction::transfer(
+            &owner,
+            &Pubkey::new_unique(),
+            1,
+        ))
+        .build_legacy()
+        .unwrap();
+
+    let options = SendTransactionOptions::from(SendOptions::builder().memo("order 7").build());
+    let connection = MockConnection::new();
+    assert!(matches!(
+        block_on(wallet.send_transaction(transfer, &connection, Some(options))),
+        Err(WalletError::WalletPolicyViolation(_))
+    ));
+    assert!(connection.sent().is_empty());
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use futures::executor::block_on;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseWalletAdapter, SessionTimeoutWalletAdapter,
+    WalletAdapterEvent, WalletError,
+};
+use wallet_adapter_mock::{MockRequest, MockWalletAdapter};
+
+#[test]
+fn expired_sessions_are_disconnected() {
+    let mut wallet = SessionTimeoutWalletAdapter::new(
+        Box::new(MockWalletAdapter::new()),
+        Duration::from_secs(60),
+    );
+    block_on(wallet.connect()).unwrap();
+    block_on(wallet.sign_message(b"active")).unwrap();
+    assert!(!wallet.remaining().is_zero());
+
+    let mut idle =
+        SessionTimeoutWalletAdapter::new(Box::new(MockWalletAdapter::new()), Duration::ZERO);
+    block_on(idle.connect()).unwrap();
+    let events = idle.event_emitter();
+    while events.try_recv().is_some() {}
+
+    assert!(matches!(
+        block_on(idle.sign_message(b"idle")),
+        Err(WalletError::WalletDisconnected)
+    ));
+    assert!(!idle.connected());
+    assert!(matches!(
+        events.try_recv(),
+        Some(WalletAdapterEvent::SessionExpired)
+    ));
+    assert_eq!(
+        idle.inner().requests(),
+        vec![MockRequest::Connect, MockRequest::Disconnect]
+    );
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use base64::prelude::*;
+use futures::executor::block_on;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use wallet_adapter_base::{
+    build_memo, BaseSignerWalletAdapter, BaseWalletAdapter, TransactionBuilder,
+    TransactionOrVersionedTransaction,
+};
+use wallet_adapter_common::relay::FeePayerService;
+use wallet_adapter_common::types::SendTransactionOptions;
+use wallet_adapter_mock::{MockConnection, MockWalletAdapter};
+
+/// Co-signs as fee payer and checks the transaction instead of submitting it
+struct Sponsor(Keypair);
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl FeePayerService for Sponsor {
+    async fn fee_payer(&self) -> anyhow::Result<Pubkey> {
+        Ok(self.0.pubkey())
+    }
+
+    async fn sign_and_send(&self, transaction: &Transaction) -> anyhow::Result<Signature> {
+        anyhow::ensure!(transaction.message.account_keys[0] == self.0.pubkey());
+        let mut transaction = transaction.clone();
+        transaction.try_partial_sign(&[&self.0], transaction.message.recent_blockhash)?;
+        transaction.verify()?;
+        Ok(transaction.signatures[0])
+    }
+}
+
+#[test]
+fn sponsored_transactions_are_submitted_by_the_fee_payer() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let public_key = wallet.public_key().unwrap();
+
+    let transaction = TransactionBuilder::new()
+        .fee_payer(public_key)
+        .add_instruction(build_memo(b"gasless", &[&public_key]))
+        .build_legacy()
+        .unwrap();
+    let options = SendTransactionOptions::default().sponsor(Arc::new(Sponsor(Keypair::new())));
+    let connection = MockConnection::new();
+
+    let signature = block_on(BaseWalletAdapter::send_transaction(
+        &wallet,
+        transaction,
+        &connection,
+        Some(options),
+    ))
+    .unwrap();
+    assert_ne!(signature, Signature::default());
+    assert!(connection.sent().is_empty());
+}
+
+#[test]
+fn co_signs_partially_signed_transactions() {
+    let mut wallet = MockWalletAdapter::new();
+    block_on(wallet.connect()).unwrap();
+    let escrow = Keypair::new();
+
+    let mut transaction = TransactionBuilder::new()
+        .fee_payer(wallet.keypair().pubkey())
+        .add_instruction(Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new_readonly(escrow.pubkey(), true)],
+        ))
+        .recent_blockhash(Hash::new_unique())
+        .build_v0()
+        .unwrap();
+    transaction
+        .partial_sign(&[Box::new(escrow.insecure_clone()) as Box<dyn Signer>])
+        .unwrap();
+    let exported = transaction.serialize_partial().unwrap();
+    assert_eq!(
+        TransactionOrVersionedTransaction::deserialize_partial(&exported)
+            .unwrap()
+            .missing_signers(),
+        vec![wallet.keypair().pubkey()]
+    );
+
+    let signed = block_on(wallet.sign_partial_transaction(&exported)).unwrap();
+    let signed = TransactionOrVersionedTransaction::deserialize_partial(&signed).unwrap();
+    assert!(signed.missing_signers().is_empty());
+    assert!(
+        block_on(wallet.sign_partial_transaction(&signed.serialize_partial().unwrap())).is_err()
+    );
+
+    let TransactionOrVersionedTransaction::VersionedTransaction(mut forged) = transaction else {
+        panic!("expected versioned transaction");
+    };
+    forged.signatures[1] = Signature::new_unique();
+    let forged = TransactionOrVersionedTransaction::VersionedTransaction(forged);
+    let forged = BASE64_STANDARD.encode(forged.serialize().unwrap());
+    assert!(TransactionOrVersionedTransaction::deserialize_partial(&forged).is_err());
+}
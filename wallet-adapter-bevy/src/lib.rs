@@ -101,7 +101,7 @@ fn on_wallet_event_system(mut ev_writer: EventWriter<WalletEvent>, wallet: Res<W
         info!("on_wallet_event_system: {:?}", ev);
 
         match ev {
-            WalletAdapterEvent::Connect(addr) => {
+            WalletAdapterEvent::Connect(addr) | WalletAdapterEvent::AccountChanged(addr) => {
                 ev_writer.send(WalletEvent::Connected(addr.to_string()));
             }
             WalletAdapterEvent::Disconnect => {
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::signature::{read_keypair_file, write_keypair_file, Keypair};
+use wallet_adapter_common::storage::KeypairStorage;
+
+/// A keypair file in the format of `solana-keygen`, eg. `~/.config/solana/id.json`
+#[derive(Debug)]
+pub struct KeypairFile {
+    path: PathBuf,
+}
+
+impl KeypairFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The default keypair of the solana cli
+    pub fn default_path() -> Result<PathBuf> {
+        let home = std::env::var_os("HOME").context("HOME is not set")?;
+        Ok(PathBuf::from(home).join(".config/solana/id.json"))
+    }
+}
+
+impl KeypairStorage for KeypairFile {
+    fn get_keypair(&self) -> Result<Option<Keypair>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let keypair = read_keypair_file(&self.path)
+            .map_err(|err| anyhow!("could not read {}: {err}", self.path.display()))?;
+        Ok(Some(keypair))
+    }
+
+    fn set_keypair(&self, keypair: Keypair) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        write_keypair_file(&keypair, &self.path)
+            .map_err(|err| anyhow!("could not write {}: {err}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    #[test]
+    fn creates_and_reads_back_the_keypair() {
+        let dir = std::env::temp_dir().join(format!("keypair-file-{}", std::process::id()));
+        let file = KeypairFile::new(dir.join("solana/id.json"));
+        assert!(file.get_keypair().unwrap().is_none());
+
+        let keypair = Keypair::new();
+        file.set_keypair(keypair.insecure_clone()).unwrap();
+        assert_eq!(
+            file.get_keypair().unwrap().unwrap().pubkey(),
+            keypair.pubkey()
+        );
+
+        std::fs::write(dir.join("solana/id.json"), "not a keypair").unwrap();
+        assert!(file.get_keypair().is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+//! Smoke-test wallet adapters and connections from the terminal, eg.
+//!
+//! ```sh
+//! wallet-adapter --wallet keypair-file airdrop 1
+//! wallet-adapter --wallet browser --url testnet sign-message hello
+//! ```
+
+mod keypair_file;
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::native_token::sol_str_to_lamports;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_base::BaseMessageSignerWalletAdapter;
+use wallet_adapter_browser_bridge::BrowserBridgeWallet;
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::offchain_message::verify_message;
+use wallet_adapter_unsafe_burner::UnsafeBurnerWallet;
+use wallet_adapter_unsafe_persistent::UnsafePersistentWallet;
+use wallet_adapter_x86::connection::NativeConnection;
+use wallet_adapter_x86::storage::X86Storage;
+
+use crate::keypair_file::KeypairFile;
+
+const CONFIRM_TIMEOUT_MS: u32 = 60_000;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Smoke-test wallet adapters and connections from the terminal"
+)]
+struct Cli {
+    /// The adapter to connect
+    #[arg(long, short, value_enum, default_value_t = Wallet::KeypairFile)]
+    wallet: Wallet,
+
+    /// Keypair of `--wallet keypair-file`, created if missing [default: ~/.config/solana/id.json]
+    #[arg(long, short)]
+    keypair: Option<PathBuf>,
+
+    /// RPC url or one of devnet, testnet, mainnet
+    #[arg(long, short, default_value = "devnet")]
+    url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Wallet {
+    /// A solana cli keypair file
+    KeypairFile,
+    /// UnsafePersistentWallet, keeps its key in the config dir
+    Persistent,
+    /// UnsafeBurnerWallet, a new key every run
+    Burner,
+    /// A browser extension wallet, signing remotely through the browser bridge
+    Browser,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the public key of the connected wallet
+    Pubkey,
+    /// Request an airdrop of SOL to the connected wallet
+    Airdrop {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        lamports: u64,
+    },
+    /// Transfer SOL from the connected wallet
+    Transfer {
+        to: Pubkey,
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        lamports: u64,
+    },
+    /// Sign a message and verify the signature
+    SignMessage { message: String },
+}
+
+/// Lamports of a SOL amount like `0.5`
+fn parse_sol(sol: &str) -> Result<u64, String> {
+    sol_str_to_lamports(sol).ok_or_else(|| format!("invalid SOL amount {sol}"))
+}
+
+fn connection(url: &str) -> NativeConnection {
+    match url {
+        "devnet" => NativeConnection::devnet(),
+        "testnet" => NativeConnection::testnet(),
+        "mainnet" => NativeConnection::mainnet(),
+        url => NativeConnection::new(url.to_string()),
+    }
+}
+
+async fn confirm(connection: &dyn Connection, signature: &Signature) -> Result<()> {
+    println!("signature: {signature}");
+    connection
+        .confirm_transaction(signature, CommitmentLevel::Confirmed, CONFIRM_TIMEOUT_MS)
+        .await?;
+    println!("confirmed");
+    Ok(())
+}
+
+async fn run(
+    mut adapter: impl BaseMessageSignerWalletAdapter,
+    connection: &dyn Connection,
+    command: Command,
+) -> Result<()> {
+    let _events = adapter
+        .event_emitter()
+        .on(|event| eprintln!("event: {event:?}"));
+
+    adapter.connect().await?;
+    let public_key = adapter
+        .public_key()
+        .ok_or_else(|| anyhow!("{} connected without a public key", adapter.name()))?;
+
+    match command {
+        Command::Pubkey => println!("{public_key}"),
+        Command::Airdrop { lamports } => {
+            let signature = connection.request_airdrop(&public_key, lamports).await?;
+            confirm(connection, &signature).await?;
+        }
+        Command::Transfer { to, lamports } => {
+            let signature = adapter.transfer_sol(to, lamports, connection, None).await?;
+            confirm(connection, &signature).await?;
+        }
+        Command::SignMessage { message } => {
+            let signature = adapter.sign_message(message.as_bytes()).await?;
+            let signature = Signature::try_from(signature.as_slice())
+                .map_err(|_| anyhow!("invalid signature length {}", signature.len()))?;
+            println!("signature: {signature}");
+
+            if !verify_message(&public_key, message.as_bytes(), &signature) {
+                return Err(anyhow!("signature does not verify against {public_key}"));
+            }
+            println!("verified");
+        }
+    }
+
+    adapter.disconnect().await?;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let connection = connection(&cli.url);
+
+    match cli.wallet {
+        Wallet::KeypairFile => {
+            let path = match cli.keypair {
+                Some(path) => path,
+                None => KeypairFile::default_path()?,
+            };
+            let adapter = UnsafePersistentWallet::new(KeypairFile::new(path))?;
+            run(adapter, &connection, cli.command).await
+        }
+        Wallet::Persistent => {
+            let adapter = UnsafePersistentWallet::new(X86Storage::new("wallet-adapter-cli")?)?;
+            run(adapter, &connection, cli.command).await
+        }
+        Wallet::Burner => run(UnsafeBurnerWallet::new(), &connection, cli.command).await,
+        Wallet::Browser => {
+            let adapter = BrowserBridgeWallet::new()?;
+            eprintln!("approve the request at {}", adapter.url());
+            run(adapter, &connection, cli.command).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+    use wallet_adapter_mock::MockConnection;
+
+    use super::*;
+
+    #[test]
+    fn parses_the_commands() {
+        Cli::command().debug_assert();
+
+        let cli =
+            Cli::try_parse_from(["wallet-adapter", "-w", "burner", "airdrop", "1.5"]).unwrap();
+        assert!(matches!(cli.wallet, Wallet::Burner));
+        assert!(matches!(
+            cli.command,
+            Command::Airdrop {
+                lamports: 1_500_000_000
+            }
+        ));
+        assert!(Cli::try_parse_from(["wallet-adapter", "airdrop", "lots"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn signs_and_transfers_with_the_adapter() {
+        let connection = MockConnection::new();
+        let command = Command::SignMessage {
+            message: "hello".to_string(),
+        };
+        run(UnsafeBurnerWallet::new(), &connection, command)
+            .await
+            .unwrap();
+
+        // the mock has no cluster to confirm on, but the transfer is sent
+        let command = Command::Transfer {
+            to: Pubkey::new_unique(),
+            lamports: 1,
+        };
+        let err = run(UnsafeBurnerWallet::new(), &connection, command)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no cluster"), "{err}");
+        assert_eq!(connection.sent().len(), 1);
+    }
+}
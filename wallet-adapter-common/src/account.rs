@@ -0,0 +1,187 @@
+//! Accounts of `getAccountInfo` with the `jsonParsed` encoding, decoded by the RPC node for
+//! the programs it knows.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::token::TokenProgramAccount;
+
+/// An account with the data the RPC node parsed, see [`ParsedAccountData`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedAccount {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data: ParsedAccountData,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedAccountData {
+    /// An account of the SPL Token or Token-2022 program
+    Token(TokenProgramAccount),
+    Nonce(NonceAccount),
+    /// Another program the node parses accounts of, eg. `stake` or `vote`
+    Other {
+        program: String,
+        parsed: serde_json::Value,
+    },
+    /// The node has no parser for the owner program and returned the raw data
+    Binary(Vec<u8>),
+}
+
+/// A durable nonce account of the system program
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "info", rename_all = "camelCase")]
+pub enum NonceAccount {
+    Uninitialized,
+    Initialized(NonceAccountInfo),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceAccountInfo {
+    pub authority: String,
+    /// The durable nonce, used as the blockhash of transactions advancing it
+    pub blockhash: String,
+    pub fee_calculator: FeeCalculator,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeCalculator {
+    /// Decimal string
+    pub lamports_per_signature: String,
+}
+
+/// An account of `getAccountInfo` with the `jsonParsed` encoding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcParsedAccount {
+    pub lamports: u64,
+    pub owner: String,
+    pub data: RpcParsedAccountData,
+    pub executable: bool,
+    pub rent_epoch: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RpcParsedAccountData {
+    Parsed {
+        program: String,
+        parsed: serde_json::Value,
+    },
+    /// The data and its encoding
+    Binary(String, String),
+}
+
+impl TryFrom<RpcParsedAccount> for ParsedAccount {
+    type Error = anyhow::Error;
+
+    fn try_from(account: RpcParsedAccount) -> Result<Self> {
+        let data = match account.data {
+            RpcParsedAccountData::Parsed { program, parsed } => match program.as_str() {
+                "spl-token" | "spl-token-2022" => ParsedAccountData::Token(
+                    serde_json::from_value(parsed)
+                        .with_context(|| format!("invalid {program} account"))?,
+                ),
+                "nonce" => ParsedAccountData::Nonce(
+                    serde_json::from_value(parsed).context("invalid nonce account")?,
+                ),
+                _ => ParsedAccountData::Other { program, parsed },
+            },
+            RpcParsedAccountData::Binary(data, encoding) => {
+                anyhow::ensure!(
+                    encoding == "base64",
+                    "unsupported account encoding {encoding}"
+                );
+                ParsedAccountData::Binary(
+                    base64::engine::general_purpose::STANDARD
+                        .decode(data)
+                        .context("invalid base64 account data")?,
+                )
+            }
+        };
+
+        Ok(Self {
+            lamports: account.lamports,
+            owner: account
+                .owner
+                .parse()
+                .with_context(|| format!("invalid account owner {}", account.owner))?,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::token::TOKEN_2022_PROGRAM_ID;
+
+    #[test]
+    fn decodes_mints_nonces_and_raw_accounts() {
+        let account = |data: serde_json::Value| {
+            let account: RpcParsedAccount = serde_json::from_value(json!({
+                "lamports": 1_461_600,
+                "owner": TOKEN_2022_PROGRAM_ID.to_string(),
+                "data": data,
+                "executable": false,
+                "rentEpoch": u64::MAX,
+                "space": 82
+            }))
+            .unwrap();
+            ParsedAccount::try_from(account).unwrap().data
+        };
+
+        let mint = account(json!({
+            "program": "spl-token-2022",
+            "parsed": {
+                "type": "mint",
+                "info": {
+                    "decimals": 6,
+                    "freezeAuthority": null,
+                    "isInitialized": true,
+                    "mintAuthority": TOKEN_2022_PROGRAM_ID.to_string(),
+                    "supply": "1000",
+                    "extensions": [{ "extension": "transferFeeConfig", "state": {} }]
+                }
+            },
+            "space": 82
+        }));
+        let ParsedAccountData::Token(TokenProgramAccount::Mint(mint)) = mint else {
+            panic!("not a mint: {mint:?}");
+        };
+        assert_eq!((mint.decimals, mint.supply.as_str()), (6, "1000"));
+        assert_eq!(mint.extensions[0].extension, "transferFeeConfig");
+
+        let nonce = account(json!({
+            "program": "nonce",
+            "parsed": {
+                "type": "initialized",
+                "info": {
+                    "authority": TOKEN_2022_PROGRAM_ID.to_string(),
+                    "blockhash": "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+                    "feeCalculator": { "lamportsPerSignature": "5000" }
+                }
+            },
+            "space": 80
+        }));
+        assert!(matches!(
+            nonce,
+            ParsedAccountData::Nonce(NonceAccount::Initialized(_))
+        ));
+
+        assert_eq!(
+            account(json!(["AQID", "base64"])),
+            ParsedAccountData::Binary(vec![1, 2, 3])
+        );
+    }
+}
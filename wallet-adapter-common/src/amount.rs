@@ -0,0 +1,207 @@
+//! Exact amounts in base units and their decimal representation, instead of `f64` math on
+//! `amount / 1e9` and the rounding errors that come with it.
+
+use std::fmt;
+
+use anyhow::{ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const SOL_DECIMALS: u8 = 9;
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// An amount in base units of a mint with `decimals`, eg. lamports with 9 decimals.
+///
+/// `Display` writes every significant decimal, or exactly as many as the precision asks for,
+/// rounded half up: `format!("{:.2}", UiAmount::new(1_005, 3))` is `1.01`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct UiAmount {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl UiAmount {
+    pub fn new(amount: u64, decimals: u8) -> Self {
+        Self { amount, decimals }
+    }
+
+    /// Parse a decimal like `1` or `0.25`, failing instead of rounding when it has more
+    /// than `decimals` decimals
+    pub fn parse(amount: &str, decimals: u8) -> Result<Self> {
+        validate_decimal(amount)?;
+
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        ensure!(
+            fraction.len() <= usize::from(decimals),
+            "{amount} has more than {decimals} decimals"
+        );
+
+        let scale = 10u64
+            .checked_pow(decimals.into())
+            .context("too many decimals")?;
+        let fraction = format!("{fraction:0<width$}", width = usize::from(decimals));
+        let amount = whole
+            .parse::<u64>()
+            .ok()
+            .and_then(|whole| whole.checked_mul(scale))
+            .and_then(|whole| whole.checked_add(fraction.parse().unwrap_or(0)))
+            .with_context(|| format!("{amount} is too large"))?;
+
+        Ok(Self { amount, decimals })
+    }
+
+    /// `None` on overflow or when the decimals differ
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        if self.decimals != other.decimals {
+            return None;
+        }
+        Some(Self::new(
+            self.amount.checked_add(other.amount)?,
+            self.decimals,
+        ))
+    }
+
+    /// `None` on underflow or when the decimals differ
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        if self.decimals != other.decimals {
+            return None;
+        }
+        Some(Self::new(
+            self.amount.checked_sub(other.amount)?,
+            self.decimals,
+        ))
+    }
+
+    /// `self * numerator / denominator` rounded down, eg. a fee of 25 basis points is
+    /// `amount.mul_div(25, 10_000)`
+    pub fn mul_div(self, numerator: u64, denominator: u64) -> Option<Self> {
+        let amount = u128::from(self.amount)
+            .checked_mul(numerator.into())?
+            .checked_div(denominator.into())?;
+        Some(Self::new(amount.try_into().ok()?, self.decimals))
+    }
+
+    /// The amount in whole units, only for display or charts, not for math
+    pub fn to_f64(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals.into())
+    }
+}
+
+impl fmt::Display for UiAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = usize::from(self.decimals);
+        let (amount, decimals) = match f.precision() {
+            Some(precision) if precision < decimals => {
+                let divisor = u32::try_from(decimals - precision)
+                    .ok()
+                    .and_then(|exponent| 10u128.checked_pow(exponent));
+                let rounded = match divisor {
+                    Some(divisor) => (u128::from(self.amount) + divisor / 2) / divisor,
+                    None => 0,
+                };
+                (rounded, precision)
+            }
+            _ => (u128::from(self.amount), decimals),
+        };
+
+        let digits = format!("{amount:0>width$}", width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let fraction = match f.precision() {
+            Some(precision) => format!("{fraction:0<precision$}"),
+            None => fraction.trim_end_matches('0').to_string(),
+        };
+
+        if fraction.is_empty() {
+            f.write_str(whole)
+        } else {
+            write!(f, "{whole}.{fraction}")
+        }
+    }
+}
+
+/// An amount of SOL in lamports, displayed in SOL
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Lamports(pub u64);
+
+impl Lamports {
+    /// Parse an amount of SOL like `0.5`
+    pub fn from_sol(sol: &str) -> Result<Self> {
+        Ok(Self(UiAmount::parse(sol, SOL_DECIMALS)?.amount))
+    }
+
+    pub fn sol(&self) -> UiAmount {
+        UiAmount::new(self.0, SOL_DECIMALS)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
+impl From<u64> for Lamports {
+    fn from(lamports: u64) -> Self {
+        Self(lamports)
+    }
+}
+
+impl From<Lamports> for u64 {
+    fn from(lamports: Lamports) -> Self {
+        lamports.0
+    }
+}
+
+impl fmt::Display for Lamports {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.sol(), f)?;
+        f.write_str(" SOL")
+    }
+}
+
+/// A non-negative decimal without exponent, eg. `1`, `0.5` but not `.5` or `1e3`
+pub(crate) fn validate_decimal(amount: &str) -> Result<()> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, "0"));
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+    ensure!(
+        is_digits(whole) && is_digits(fraction),
+        "invalid amount {amount}"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_computes_and_displays_exactly() {
+        let price = UiAmount::parse("0.1", 6).unwrap();
+        let total = price
+            .checked_add(UiAmount::parse("0.2", 6).unwrap())
+            .unwrap();
+        assert_eq!(total, UiAmount::new(300_000, 6));
+        assert_eq!(total.to_string(), "0.3");
+        assert_eq!(total.mul_div(25, 10_000).unwrap().amount, 750);
+        assert!(UiAmount::parse("0.0000001", 6).is_err());
+        assert!(UiAmount::parse("1e3", 6).is_err());
+        assert!(total.checked_add(UiAmount::new(1, 9)).is_none());
+
+        assert_eq!(format!("{:.2}", UiAmount::new(1_005, 3)), "1.01");
+        assert_eq!(format!("{:.4}", UiAmount::new(5, 0)), "5.0000");
+        assert_eq!(format!("{:.0}", UiAmount::new(2_500, 3)), "3");
+
+        let lamports = Lamports::from_sol("1.5").unwrap();
+        assert_eq!(lamports, Lamports(1_500_000_000));
+        assert_eq!(Lamports(1).to_string(), "0.000000001 SOL");
+        assert_eq!(format!("{:.3}", lamports), "1.500 SOL");
+    }
+}
@@ -1,7 +1,19 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
+use base64::Engine;
 use serde::{Deserialize, Serialize};
-use solana_sdk::{commitment_config::CommitmentLevel, hash::Hash, signature::Signature};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentLevel, hash::Hash, pubkey::Pubkey,
+    signature::Signature,
+};
 
+use crate::account::ParsedAccount;
+use crate::explorer::Cluster;
+use crate::history::{
+    ActivityEntry, ParsedTransactionWithMeta, SignatureInfo, SignaturesForAddressOptions,
+};
+use crate::maybe_send::{MaybeSend, MaybeSync};
+use crate::metrics::{ConnectionMetrics, EndpointHealth};
+use crate::token::{KeyedTokenAccount, TokenBalance};
 use crate::types::SendTransactionOptions;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -24,6 +36,159 @@ pub struct GetLatestBlockhash {
     pub value: Value,
 }
 
+/// A blockhash with the last block height transactions using it can land in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatestBlockhash {
+    pub blockhash: Hash,
+    pub last_valid_block_height: u64,
+    /// The slot the RPC node read the blockhash at
+    pub slot: u64,
+}
+
+impl LatestBlockhash {
+    /// Whether transactions using the blockhash can no longer land once the chain is at `block_height`
+    pub fn is_expired(&self, block_height: u64) -> bool {
+        block_height > self.last_valid_block_height
+    }
+}
+
+impl TryFrom<GetLatestBlockhash> for LatestBlockhash {
+    type Error = anyhow::Error;
+
+    fn try_from(response: GetLatestBlockhash) -> Result<Self> {
+        Ok(Self {
+            blockhash: response
+                .value
+                .blockhash
+                .parse()
+                .with_context(|| format!("invalid blockhash {}", response.value.blockhash))?,
+            last_valid_block_height: response.value.last_valid_block_height.try_into()?,
+            slot: response.context.slot.try_into()?,
+        })
+    }
+}
+
+/// `{ context, value }` result of RPC methods reporting the slot they were evaluated at
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithContext<T> {
+    pub context: Context,
+    pub value: T,
+}
+
+/// An account of `getAccountInfo` with the `base64` encoding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccount {
+    pub lamports: u64,
+    pub owner: String,
+    /// The data and its encoding
+    pub data: (String, String),
+    pub executable: bool,
+    pub rent_epoch: u64,
+}
+
+impl TryFrom<RpcAccount> for Account {
+    type Error = anyhow::Error;
+
+    fn try_from(account: RpcAccount) -> Result<Self> {
+        let (data, encoding) = account.data;
+        anyhow::ensure!(
+            encoding == "base64",
+            "unsupported account encoding {encoding}"
+        );
+
+        Ok(Self {
+            lamports: account.lamports,
+            data: base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .context("invalid base64 account data")?,
+            owner: account
+                .owner
+                .parse()
+                .with_context(|| format!("invalid account owner {}", account.owner))?,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfirmationStatus {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl From<CommitmentLevel> for ConfirmationStatus {
+    fn from(commitment: CommitmentLevel) -> Self {
+        match commitment {
+            CommitmentLevel::Processed => ConfirmationStatus::Processed,
+            CommitmentLevel::Confirmed => ConfirmationStatus::Confirmed,
+            CommitmentLevel::Finalized => ConfirmationStatus::Finalized,
+        }
+    }
+}
+
+/// An entry of `getSignatureStatuses`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureStatus {
+    pub slot: u64,
+    pub confirmations: Option<u64>,
+    /// The transaction error, `None` if it succeeded
+    pub err: Option<serde_json::Value>,
+    pub confirmation_status: Option<ConfirmationStatus>,
+}
+
+impl SignatureStatus {
+    /// Whether the transaction reached `commitment`
+    pub fn satisfies(&self, commitment: CommitmentLevel) -> bool {
+        self.confirmation_status
+            .is_some_and(|status| status >= commitment.into())
+    }
+}
+
+/// Where the cluster is in the current epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochInfo {
+    pub absolute_slot: u64,
+    pub block_height: u64,
+    pub epoch: u64,
+    /// Slot of `absolute_slot` within the epoch
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    pub transaction_count: Option<u64>,
+}
+
+impl EpochInfo {
+    /// Slots until the next epoch, eg. for an "epoch ends in" countdown
+    pub fn slots_remaining(&self) -> u64 {
+        self.slots_in_epoch.saturating_sub(self.slot_index)
+    }
+}
+
+/// Version of the node software behind an endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RpcVersion {
+    pub solana_core: String,
+    pub feature_set: Option<u32>,
+}
+
+/// Whether an endpoint answers and is healthy, see [`Connection::probe`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcProbe {
+    /// `Degraded` when the node answers but is unhealthy, eg. behind the cluster,
+    /// `Down` when it doesn't answer
+    pub health: EndpointHealth,
+    pub version: Option<RpcVersion>,
+    /// Why it isn't healthy
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RpcResponse<T, U> {
     pub jsonrpc: String,
@@ -32,6 +197,36 @@ pub struct RpcResponse<T, U> {
     pub id: u64,
 }
 
+/// The error object of a JSON-RPC response, [`rpc_call`](crate::transport::rpc_call) fails with it
+/// so callers can downcast and inspect it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl RpcError {
+    /// Whether the node refused a transaction because it doesn't know its blockhash,
+    /// eg. after it expired
+    pub fn is_blockhash_not_found(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data.get("err"))
+            .and_then(serde_json::Value::as_str)
+            == Some("BlockhashNotFound")
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RpcRequest<T> {
     pub jsonrpc: String,
@@ -50,17 +245,155 @@ impl<T> RpcRequest<T> {
         }
     }
 }
-#[async_trait::async_trait(?Send)]
-pub trait Connection {
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait Connection: MaybeSend + MaybeSync {
     async fn get_recent_blockhash(
         &self,
         commitment: Option<CommitmentLevel>,
         min_context_slots: Option<u32>,
-    ) -> Result<Hash>;
+    ) -> Result<Hash> {
+        Ok(self
+            .get_latest_blockhash_with_context(commitment, min_context_slots)
+            .await?
+            .blockhash)
+    }
+
+    /// The latest blockhash with its expiry, to tell when a transaction can no longer land
+    async fn get_latest_blockhash_with_context(
+        &self,
+        commitment: Option<CommitmentLevel>,
+        min_context_slots: Option<u32>,
+    ) -> Result<LatestBlockhash>;
 
     async fn send_raw_transaction(
         &self,
         raw_transaction: Vec<u8>,
         options: Option<&SendTransactionOptions>,
     ) -> Result<Signature>;
+
+    /// Ask the cluster's faucet for `lamports`, only available on devnet/testnet/localnet
+    async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<Signature>;
+
+    /// Statuses of `signatures` in the same order, `None` for signatures the cluster doesn't know
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>>;
+
+    /// Poll the status of `signature` until it reaches `commitment`,
+    /// fails if the transaction failed or isn't confirmed within `timeout_ms`
+    async fn confirm_transaction(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentLevel,
+        timeout_ms: u32,
+    ) -> Result<()>;
+
+    /// The SPL Token and Token-2022 accounts of `owner`, parsed by the RPC node
+    async fn get_token_accounts(&self, owner: &Pubkey) -> Result<Vec<KeyedTokenAccount>>;
+
+    /// Balances of the SPL Token and Token-2022 accounts of `owner`, one entry per mint
+    async fn get_token_balances(&self, owner: &Pubkey) -> Result<Vec<TokenBalance>> {
+        TokenBalance::from_accounts(&self.get_token_accounts(owner).await?)
+    }
+
+    /// The account at `pubkey`, `None` if it doesn't exist
+    async fn get_account_info(&self, pubkey: &Pubkey) -> Result<Option<Account>>;
+
+    /// The account at `pubkey` with the data parsed by the RPC node, `None` if it doesn't exist
+    async fn get_parsed_account_info(&self, pubkey: &Pubkey) -> Result<Option<ParsedAccount>>;
+
+    /// Signatures of the transactions involving `address`, newest first
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        options: Option<&SignaturesForAddressOptions>,
+    ) -> Result<Vec<SignatureInfo>>;
+
+    /// The transaction of `signature` parsed by the RPC node, `None` if the node doesn't have it
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ParsedTransactionWithMeta>>;
+
+    /// The latest `limit` transactions of `address` summarized for an activity feed,
+    /// newest first
+    async fn recent_activity(&self, address: &Pubkey, limit: usize) -> Result<Vec<ActivityEntry>> {
+        let options = SignaturesForAddressOptions {
+            limit: Some(limit),
+            ..Default::default()
+        };
+        let signatures = self
+            .get_signatures_for_address(address, Some(&options))
+            .await?;
+
+        let transactions = futures::future::try_join_all(signatures.iter().map(|info| async {
+            let signature: Signature = info
+                .signature
+                .parse()
+                .with_context(|| format!("invalid signature {}", info.signature))?;
+            self.get_transaction(&signature).await
+        }))
+        .await?;
+
+        signatures
+            .iter()
+            .zip(&transactions)
+            .map(|(info, transaction)| ActivityEntry::new(address, info, transaction.as_ref()))
+            .collect()
+    }
+
+    /// Lamports an account of `data_len` bytes needs to be exempt from rent
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64>;
+
+    async fn get_slot(&self) -> Result<u64>;
+
+    /// The current block height, what [`LatestBlockhash::last_valid_block_height`] is
+    /// compared to
+    async fn get_block_height(&self) -> Result<u64>;
+
+    async fn get_epoch_info(&self) -> Result<EpochInfo>;
+
+    /// Succeeds when the node reports itself healthy, fails when it is behind the cluster or
+    /// can't be reached
+    async fn get_health(&self) -> Result<()>;
+
+    async fn get_version(&self) -> Result<RpcVersion>;
+
+    /// The hash of the first block of the cluster, which tells the clusters apart
+    async fn get_genesis_hash(&self) -> Result<Hash>;
+
+    /// The public cluster the node belongs to, `None` for others, eg. a test validator
+    async fn cluster(&self) -> Result<Option<Cluster>> {
+        Ok(Cluster::from_genesis_hash(&self.get_genesis_hash().await?))
+    }
+
+    /// Check the endpoint before eg. sending, to tell an RPC outage from a wallet problem
+    async fn probe(&self) -> RpcProbe {
+        let version = self.get_version().await;
+        let health = self.get_health().await;
+
+        let (health, error) = match (&version, health) {
+            (_, Ok(())) => (EndpointHealth::Healthy, None),
+            (Ok(_), Err(err)) => (EndpointHealth::Degraded, Some(err.to_string())),
+            (Err(_), Err(err)) => (EndpointHealth::Down, Some(err.to_string())),
+        };
+        RpcProbe {
+            health,
+            version: version.ok(),
+            error,
+        }
+    }
+
+    /// The health of the endpoint, `None` for connections not tracking it
+    fn metrics(&self) -> Option<ConnectionMetrics> {
+        None
+    }
+
+    /// The highest slot the node evaluated requests at, to pass as the minimum context slot
+    /// of later ones. `None` for connections not tracking it.
+    fn context_slot(&self) -> Option<u64> {
+        None
+    }
 }
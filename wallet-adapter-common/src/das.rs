@@ -0,0 +1,149 @@
+//! The Digital Asset Standard (DAS) RPC methods for NFTs and compressed NFTs.
+//!
+//! Only RPC providers indexing digital assets serve these methods, the public
+//! cluster endpoints reply with a method not found error.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::maybe_send::{MaybeSend, MaybeSync};
+
+/// Largest page `getAssetsByOwner` returns
+pub const DAS_MAX_PAGE_LIMIT: u32 = 1000;
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait DasConnection: MaybeSend + MaybeSync {
+    async fn get_asset(&self, id: &Pubkey) -> Result<Asset>;
+
+    /// The 1-based `page` of the assets owned by `owner`, at most `limit` ([`DAS_MAX_PAGE_LIMIT`]) per page
+    async fn get_assets_by_owner(&self, owner: &Pubkey, page: u32, limit: u32)
+        -> Result<AssetList>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetList {
+    pub total: u32,
+    pub limit: u32,
+    pub page: Option<u32>,
+    pub items: Vec<Asset>,
+}
+
+/// The parts of a DAS asset wallet UIs display, DAS names its fields in snake_case
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Asset {
+    pub id: String,
+    /// eg. `V1_NFT`, `ProgrammableNFT` or `FungibleToken`
+    pub interface: String,
+    #[serde(default)]
+    pub content: Option<AssetContent>,
+    #[serde(default)]
+    pub compression: Option<AssetCompression>,
+    pub ownership: AssetOwnership,
+    #[serde(default)]
+    pub burnt: bool,
+}
+
+impl Asset {
+    pub fn name(&self) -> Option<&str> {
+        self.content.as_ref()?.metadata.name.as_deref()
+    }
+
+    pub fn image(&self) -> Option<&str> {
+        self.content.as_ref()?.links.as_ref()?.image.as_deref()
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compression
+            .as_ref()
+            .is_some_and(|compression| compression.compressed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetContent {
+    #[serde(default)]
+    pub json_uri: String,
+    #[serde(default)]
+    pub metadata: AssetMetadata,
+    #[serde(default)]
+    pub links: Option<AssetLinks>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetMetadata {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetLinks {
+    pub image: Option<String>,
+    pub external_url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetCompression {
+    pub compressed: bool,
+    /// The merkle tree holding a compressed asset
+    #[serde(default)]
+    pub tree: String,
+    #[serde(default)]
+    pub leaf_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetOwnership {
+    pub owner: String,
+    #[serde(default)]
+    pub frozen: bool,
+    pub delegate: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parses_a_compressed_nft() {
+        let asset: Asset = serde_json::from_value(json!({
+            "interface": "V1_NFT",
+            "id": "JEGruwYE13mhX2wi2MGrPmeLiVyZtbBptmVy9vG3pXRC",
+            "content": {
+                "$schema": "https://schema.metaplex.com/nft1.0.json",
+                "json_uri": "https://example.com/nft.json",
+                "files": [],
+                "metadata": { "name": "Cube #1", "symbol": "CUBE" },
+                "links": { "image": "https://example.com/nft.png" }
+            },
+            "compression": {
+                "eligible": false,
+                "compressed": true,
+                "data_hash": "",
+                "creator_hash": "",
+                "asset_hash": "",
+                "tree": "5dGhwbhVuvVZJQYqAfBS4qNMrqfzLV3xeSjjKHtjb6X4",
+                "seq": 12,
+                "leaf_id": 11
+            },
+            "ownership": {
+                "frozen": false,
+                "delegated": false,
+                "delegate": null,
+                "ownership_model": "single",
+                "owner": "3F21SJs4FMpsakrxmd8GjgfQZG6BN6MVsvXcm5Yc6Jcf"
+            },
+            "burnt": false
+        }))
+        .unwrap();
+
+        assert_eq!(asset.name(), Some("Cube #1"));
+        assert_eq!(asset.image(), Some("https://example.com/nft.png"));
+        assert!(asset.is_compressed());
+        assert_eq!(asset.compression.unwrap().leaf_id, 11);
+    }
+}
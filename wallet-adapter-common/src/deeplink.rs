@@ -0,0 +1,462 @@
+//! Mobile wallet deeplinks: the dApp opens a universal link of the wallet app, which redirects
+//! back to the `redirect_link` with its answer encrypted in the query parameters.
+//!
+//! A [`DeeplinkSession`] builds the links and keeps the pending requests, hand every redirect the
+//! app receives to [`DeeplinkSession::handle_redirect`] to resume the request waiting for it.
+//! The session has to outlive the round trip, so this suits native mobile apps (or webviews)
+//! receiving the redirect through an app link rather than pages reloaded by it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use futures::channel::oneshot;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::bs58;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use url::Url;
+
+use crate::nacl_box::{self, SalsaBox};
+
+/// Added to the redirect link to match redirects with their requests
+const REQUEST_ID_PARAM: &str = "wallet_adapter_request";
+
+/// The deeplink flavor of a wallet app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeeplinkWallet {
+    /// The methods are appended to it, eg. `connect`
+    pub base_url: &'static str,
+    /// The redirect parameter carrying the wallet's encryption public key
+    pub encryption_public_key_param: &'static str,
+}
+
+pub const PHANTOM_DEEPLINK: DeeplinkWallet = DeeplinkWallet {
+    base_url: "https://phantom.app/ul/v1/",
+    encryption_public_key_param: "phantom_encryption_public_key",
+};
+
+pub const SOLFLARE_DEEPLINK: DeeplinkWallet = DeeplinkWallet {
+    base_url: "https://solflare.com/ul/v1/",
+    encryption_public_key_param: "solflare_encryption_public_key",
+};
+
+/// `errorCode` and `errorMessage` of a redirect, eg. 4001 when the user rejected the request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeeplinkError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl fmt::Display for DeeplinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for DeeplinkError {}
+
+/// The query parameters a wallet redirects back with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeeplinkRedirect {
+    pub request_id: Option<u64>,
+    pub encryption_public_key: Option<[u8; 32]>,
+    pub nonce: Option<[u8; nacl_box::NONCE_LEN]>,
+    /// The encrypted answer
+    pub data: Option<Vec<u8>>,
+    pub error: Option<DeeplinkError>,
+}
+
+impl DeeplinkRedirect {
+    pub fn parse(url: &str, wallet: &DeeplinkWallet) -> Result<Self> {
+        let url = Url::parse(url).with_context(|| format!("invalid redirect {url}"))?;
+
+        let mut redirect = Self::default();
+        let mut error_code = None;
+        let mut error_message = String::new();
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                REQUEST_ID_PARAM => redirect.request_id = Some(value.parse()?),
+                "nonce" => redirect.nonce = Some(decode_array(&value, "nonce")?),
+                "data" => redirect.data = Some(decode(&value, "data")?),
+                "errorCode" => error_code = Some(value.parse()?),
+                "errorMessage" => error_message = value.into_owned(),
+                key if key == wallet.encryption_public_key_param => {
+                    redirect.encryption_public_key = Some(decode_array(&value, key)?)
+                }
+                _ => {}
+            }
+        }
+        redirect.error = error_code.map(|code| DeeplinkError {
+            code,
+            message: error_message,
+        });
+
+        Ok(redirect)
+    }
+}
+
+fn decode(value: &str, name: &str) -> Result<Vec<u8>> {
+    bs58::decode(value)
+        .into_vec()
+        .map_err(|err| anyhow!("{name} is not base58: {err}"))
+}
+
+fn decode_array<const N: usize>(value: &str, name: &str) -> Result<[u8; N]> {
+    decode(value, name)?
+        .try_into()
+        .map_err(|_| anyhow!("{name} is not {N} bytes long"))
+}
+
+type PendingRequest = oneshot::Sender<Result<Option<Value>>>;
+
+#[derive(Default)]
+struct SessionState {
+    shared_box: Option<Arc<SalsaBox>>,
+    session: Option<String>,
+    public_key: Option<Pubkey>,
+    next_request_id: u64,
+    pending: HashMap<u64, PendingRequest>,
+}
+
+/// The encryption keys, wallet session and pending requests of a deeplink connection
+pub struct DeeplinkSession {
+    wallet: DeeplinkWallet,
+    app_url: String,
+    redirect_link: String,
+    cluster: String,
+    secret_key: [u8; 32],
+    state: Mutex<SessionState>,
+}
+
+impl fmt::Debug for DeeplinkSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeeplinkSession")
+            .field("wallet", &self.wallet)
+            .field("app_url", &self.app_url)
+            .field("redirect_link", &self.redirect_link)
+            .field("cluster", &self.cluster)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeeplinkSession {
+    /// `app_url` is shown by the wallet and used for its metadata,
+    /// `redirect_link` is where the wallet sends the user back to, eg. `myapp://wallet`
+    pub fn new(wallet: DeeplinkWallet, app_url: &str, redirect_link: &str) -> Self {
+        Self {
+            wallet,
+            app_url: app_url.to_string(),
+            redirect_link: redirect_link.to_string(),
+            cluster: "mainnet-beta".to_string(),
+            secret_key: rand::random(),
+            state: Mutex::new(SessionState::default()),
+        }
+    }
+
+    /// One of `mainnet-beta` (the default), `testnet` or `devnet`
+    pub fn with_cluster(mut self, cluster: &str) -> Self {
+        self.cluster = cluster.to_string();
+        self
+    }
+
+    /// The connected account
+    pub fn public_key(&self) -> Option<Pubkey> {
+        self.state.lock().ok()?.public_key
+    }
+
+    /// Resume the request `url` answers, returns false if it doesn't answer a pending request
+    pub fn handle_redirect(&self, url: &str) -> Result<bool> {
+        let redirect = DeeplinkRedirect::parse(url, &self.wallet)?;
+        let Some(request_id) = redirect.request_id else {
+            return Ok(false);
+        };
+
+        let mut state = self.state.lock().map_err(|err| anyhow!("{err:?}"))?;
+        let Some(pending) = state.pending.remove(&request_id) else {
+            return Ok(false);
+        };
+
+        let answer = Self::decrypt(&mut state, &self.secret_key, redirect);
+        // the request was dropped if sending fails, nothing is waiting for the answer then
+        let _ = pending.send(answer);
+        Ok(true)
+    }
+
+    fn decrypt(
+        state: &mut SessionState,
+        secret_key: &[u8; 32],
+        redirect: DeeplinkRedirect,
+    ) -> Result<Option<Value>> {
+        if let Some(error) = redirect.error {
+            return Err(error.into());
+        }
+        if let Some(public_key) = redirect.encryption_public_key {
+            state.shared_box = Some(Arc::new(nacl_box::shared_box(secret_key, &public_key)));
+        }
+
+        let (Some(data), Some(nonce)) = (redirect.data, redirect.nonce) else {
+            return Ok(None);
+        };
+        let shared_box = state
+            .shared_box
+            .as_ref()
+            .context("the wallet answered before connecting")?;
+        let data = nacl_box::open(shared_box, &nonce, &data)?;
+        Ok(Some(serde_json::from_slice(&data)?))
+    }
+
+    /// Open the wallet's connect link with `open` and wait for the approval
+    pub async fn connect(&self, open: impl FnOnce(String)) -> Result<Pubkey> {
+        #[derive(Deserialize)]
+        struct Connected {
+            public_key: String,
+            session: String,
+        }
+
+        let params = [
+            ("app_url", self.app_url.clone()),
+            ("cluster", self.cluster.clone()),
+        ];
+        let answer = self.request("connect", &params, None, open).await?;
+        let connected: Connected =
+            serde_json::from_value(answer.context("the wallet didn't send the account")?)?;
+        let public_key: Pubkey = connected.public_key.parse()?;
+
+        let mut state = self.state.lock().map_err(|err| anyhow!("{err:?}"))?;
+        state.public_key = Some(public_key);
+        state.session = Some(connected.session);
+        Ok(public_key)
+    }
+
+    pub async fn disconnect(&self, open: impl FnOnce(String)) -> Result<()> {
+        let payload = json!({ "session": self.session()? });
+        self.request("disconnect", &[], Some(payload), open).await?;
+
+        let mut state = self.state.lock().map_err(|err| anyhow!("{err:?}"))?;
+        state.public_key = None;
+        state.session = None;
+        Ok(())
+    }
+
+    /// Sign a serialized transaction, returns the signed one
+    pub async fn sign_transaction(
+        &self,
+        transaction: &[u8],
+        open: impl FnOnce(String),
+    ) -> Result<Vec<u8>> {
+        let payload = json!({
+            "transaction": bs58::encode(transaction).into_string(),
+            "session": self.session()?,
+        });
+        let answer = self
+            .request("signTransaction", &[], Some(payload), open)
+            .await?;
+
+        decode(&answer_field(answer, "transaction")?, "transaction")
+    }
+
+    /// Have the wallet sign and send a serialized transaction
+    pub async fn sign_and_send_transaction(
+        &self,
+        transaction: &[u8],
+        open: impl FnOnce(String),
+    ) -> Result<Signature> {
+        let payload = json!({
+            "transaction": bs58::encode(transaction).into_string(),
+            "session": self.session()?,
+        });
+        let answer = self
+            .request("signAndSendTransaction", &[], Some(payload), open)
+            .await?;
+
+        Ok(answer_field(answer, "signature")?.parse()?)
+    }
+
+    pub async fn sign_message(
+        &self,
+        message: &[u8],
+        open: impl FnOnce(String),
+    ) -> Result<Signature> {
+        let display = if std::str::from_utf8(message).is_ok() {
+            "utf8"
+        } else {
+            "hex"
+        };
+        let payload = json!({
+            "message": bs58::encode(message).into_string(),
+            "session": self.session()?,
+            "display": display,
+        });
+        let answer = self
+            .request("signMessage", &[], Some(payload), open)
+            .await?;
+
+        Ok(answer_field(answer, "signature")?.parse()?)
+    }
+
+    fn session(&self) -> Result<String> {
+        self.state
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .session
+            .clone()
+            .context("not connected")
+    }
+
+    /// Open the link of `method` and wait for the redirect answering it
+    async fn request(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+        payload: Option<Value>,
+        open: impl FnOnce(String),
+    ) -> Result<Option<Value>> {
+        let (sender, receiver) = oneshot::channel();
+        let (request_id, shared_box) = {
+            let mut state = self.state.lock().map_err(|err| anyhow!("{err:?}"))?;
+            let request_id = state.next_request_id;
+            state.next_request_id += 1;
+            state.pending.insert(request_id, sender);
+            (request_id, state.shared_box.clone())
+        };
+
+        let mut redirect_link = Url::parse(&self.redirect_link)
+            .with_context(|| format!("invalid redirect link {}", self.redirect_link))?;
+        redirect_link
+            .query_pairs_mut()
+            .append_pair(REQUEST_ID_PARAM, &request_id.to_string());
+
+        let mut url = Url::parse(&format!("{}{method}", self.wallet.base_url))?;
+        {
+            let mut query = url.query_pairs_mut();
+            let public_key = nacl_box::public_key(&self.secret_key);
+            query.append_pair(
+                "dapp_encryption_public_key",
+                &bs58::encode(public_key).into_string(),
+            );
+            query.append_pair("redirect_link", redirect_link.as_str());
+            for (key, value) in params {
+                query.append_pair(key, value);
+            }
+
+            if let Some(payload) = payload {
+                let shared_box = shared_box.as_ref().context("not connected")?;
+                let nonce: [u8; nacl_box::NONCE_LEN] = rand::random();
+                let payload = nacl_box::seal(
+                    shared_box,
+                    &nonce,
+                    serde_json::to_string(&payload)?.as_bytes(),
+                )?;
+                query.append_pair("nonce", &bs58::encode(nonce).into_string());
+                query.append_pair("payload", &bs58::encode(payload).into_string());
+            }
+        }
+
+        open(url.to_string());
+        receiver
+            .await
+            .map_err(|_| anyhow!("the {method} request was dropped"))?
+    }
+}
+
+fn answer_field(answer: Option<Value>, field: &str) -> Result<String> {
+    answer
+        .as_ref()
+        .and_then(|answer| answer.get(field))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .with_context(|| format!("the wallet didn't send the {field}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use futures::executor::block_on;
+
+    use super::*;
+
+    /// Answer the request in `url` like the wallet app with `secret_key` would
+    fn wallet_redirect(url: &str, secret_key: &[u8; 32], data: &Value) -> String {
+        let url = Url::parse(url).unwrap();
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+                .unwrap()
+        };
+
+        let dapp_public_key: [u8; 32] =
+            decode_array(&param("dapp_encryption_public_key"), "key").unwrap();
+        let shared_box = nacl_box::shared_box(secret_key, &dapp_public_key);
+        let nonce = [7; nacl_box::NONCE_LEN];
+        let data = nacl_box::seal(&shared_box, &nonce, data.to_string().as_bytes()).unwrap();
+
+        let mut redirect = Url::parse(&param("redirect_link")).unwrap();
+        redirect
+            .query_pairs_mut()
+            .append_pair(
+                "phantom_encryption_public_key",
+                &bs58::encode(nacl_box::public_key(secret_key)).into_string(),
+            )
+            .append_pair("nonce", &bs58::encode(nonce).into_string())
+            .append_pair("data", &bs58::encode(data).into_string());
+        redirect.to_string()
+    }
+
+    #[test]
+    fn resumes_connect_with_the_decrypted_account() {
+        let session =
+            DeeplinkSession::new(PHANTOM_DEEPLINK, "https://example.com", "myapp://wallet")
+                .with_cluster("devnet");
+        let account = Pubkey::new_unique();
+        let opened = RefCell::new(String::new());
+
+        let (public_key, resumed) = block_on(async {
+            futures::join!(session.connect(|url| *opened.borrow_mut() = url), async {
+                let redirect = wallet_redirect(
+                    &opened.borrow(),
+                    &[9; 32],
+                    &json!({ "public_key": account.to_string(), "session": "abc" }),
+                );
+                session.handle_redirect(&redirect).unwrap()
+            })
+        });
+
+        assert!(resumed);
+        assert_eq!(public_key.unwrap(), account);
+        assert_eq!(session.public_key(), Some(account));
+        assert!(opened
+            .borrow()
+            .starts_with("https://phantom.app/ul/v1/connect?"));
+        assert!(opened.borrow().contains("cluster=devnet"));
+    }
+
+    #[test]
+    fn rejections_fail_the_request() {
+        let session =
+            DeeplinkSession::new(SOLFLARE_DEEPLINK, "https://example.com", "myapp://wallet");
+        let opened = RefCell::new(String::new());
+
+        let (result, _) = block_on(async {
+            futures::join!(session.connect(|url| *opened.borrow_mut() = url), async {
+                let url = Url::parse(&opened.borrow()).unwrap();
+                let (_, redirect_link) = url
+                    .query_pairs()
+                    .find(|(key, _)| key == "redirect_link")
+                    .unwrap();
+                session
+                    .handle_redirect(&format!(
+                        "{redirect_link}&errorCode=4001&errorMessage=User%20rejected"
+                    ))
+                    .unwrap()
+            })
+        });
+
+        let err = result.unwrap_err();
+        assert_eq!(err.downcast_ref::<DeeplinkError>().unwrap().code, 4001);
+    }
+}
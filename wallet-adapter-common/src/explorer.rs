@@ -0,0 +1,212 @@
+//! Links to block explorers for signatures, accounts and transactions that weren't sent yet,
+//! with the cluster in the query the way each explorer expects it.
+
+use base64::prelude::*;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use url::Url;
+
+use crate::standard::SolanaChain;
+
+pub const LOCALNET_RPC_URL: &str = "http://localhost:8899";
+
+pub const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+pub const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+pub const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Cluster {
+    #[default]
+    MainnetBeta,
+    Devnet,
+    Testnet,
+    /// A test validator at [`LOCALNET_RPC_URL`]
+    Localnet,
+    /// Another cluster, by its RPC URL
+    Custom(String),
+}
+
+impl Cluster {
+    /// The public cluster whose first block is `genesis_hash`, `None` for others,
+    /// eg. a test validator
+    pub fn from_genesis_hash(genesis_hash: &Hash) -> Option<Self> {
+        match genesis_hash.to_string().as_str() {
+            MAINNET_GENESIS_HASH => Some(Cluster::MainnetBeta),
+            DEVNET_GENESIS_HASH => Some(Cluster::Devnet),
+            TESTNET_GENESIS_HASH => Some(Cluster::Testnet),
+            _ => None,
+        }
+    }
+}
+
+impl From<SolanaChain> for Cluster {
+    fn from(chain: SolanaChain) -> Self {
+        match chain {
+            SolanaChain::Mainnet => Cluster::MainnetBeta,
+            SolanaChain::Devnet => Cluster::Devnet,
+            SolanaChain::Testnet => Cluster::Testnet,
+            SolanaChain::Localnet => Cluster::Localnet,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Explorer {
+    /// explorer.solana.com
+    #[default]
+    SolanaExplorer,
+    /// solscan.io
+    Solscan,
+    /// xray.helius.xyz, only knows mainnet and devnet
+    Xray,
+}
+
+impl Explorer {
+    /// `None` when the explorer doesn't know `cluster`
+    pub fn transaction_url(&self, signature: &Signature, cluster: &Cluster) -> Option<String> {
+        self.url(&format!("tx/{signature}"), cluster)
+            .map(String::from)
+    }
+
+    /// `None` when the explorer doesn't know `cluster`
+    pub fn address_url(&self, address: &Pubkey, cluster: &Cluster) -> Option<String> {
+        let path = match self {
+            Explorer::SolanaExplorer => format!("address/{address}"),
+            Explorer::Solscan | Explorer::Xray => format!("account/{address}"),
+        };
+        self.url(&path, cluster).map(String::from)
+    }
+
+    /// The inspector of a transaction, signed or not, eg. to see why its simulation failed.
+    /// Legacy transactions convert with `.into()`. `None` for explorers without an inspector,
+    /// only the Solana Explorer has one.
+    pub fn inspector_url(
+        &self,
+        transaction: &VersionedTransaction,
+        cluster: &Cluster,
+    ) -> Option<String> {
+        if *self != Explorer::SolanaExplorer {
+            return None;
+        }
+
+        let signatures: Vec<String> = transaction
+            .signatures
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let mut url = self.url("tx/inspector", cluster)?;
+        url.query_pairs_mut()
+            .append_pair(
+                "message",
+                &BASE64_STANDARD.encode(transaction.message.serialize()),
+            )
+            .append_pair(
+                "signatures",
+                &serde_json::to_string(&signatures).unwrap_or_default(),
+            );
+        Some(url.into())
+    }
+
+    fn url(&self, path: &str, cluster: &Cluster) -> Option<Url> {
+        let base = match self {
+            Explorer::SolanaExplorer => "https://explorer.solana.com",
+            Explorer::Solscan => "https://solscan.io",
+            Explorer::Xray => "https://xray.helius.xyz",
+        };
+        let mut url = Url::parse(&format!("{base}/{path}")).ok()?;
+
+        match (self, cluster) {
+            (_, Cluster::MainnetBeta) => {}
+            (Explorer::Xray, Cluster::Devnet) => {
+                url.query_pairs_mut().append_pair("network", "devnet");
+            }
+            (Explorer::Xray, _) => return None,
+            (_, Cluster::Devnet) => {
+                url.query_pairs_mut().append_pair("cluster", "devnet");
+            }
+            (_, Cluster::Testnet) => {
+                url.query_pairs_mut().append_pair("cluster", "testnet");
+            }
+            (_, Cluster::Localnet) => {
+                url.query_pairs_mut()
+                    .append_pair("cluster", "custom")
+                    .append_pair("customUrl", LOCALNET_RPC_URL);
+            }
+            (_, Cluster::Custom(rpc_url)) => {
+                url.query_pairs_mut()
+                    .append_pair("cluster", "custom")
+                    .append_pair("customUrl", rpc_url);
+            }
+        }
+
+        Some(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::message::{Message, VersionedMessage};
+
+    use super::*;
+
+    #[test]
+    fn links_with_the_query_of_each_explorer() {
+        let signature = Signature::default();
+        let address = Pubkey::default();
+
+        assert_eq!(
+            Explorer::SolanaExplorer
+                .transaction_url(&signature, &Cluster::MainnetBeta)
+                .unwrap(),
+            format!("https://explorer.solana.com/tx/{signature}")
+        );
+        assert_eq!(
+            Explorer::Solscan
+                .address_url(&address, &SolanaChain::Devnet.into())
+                .unwrap(),
+            format!("https://solscan.io/account/{address}?cluster=devnet")
+        );
+        assert_eq!(
+            Explorer::Xray
+                .transaction_url(&signature, &Cluster::Devnet)
+                .unwrap(),
+            format!("https://xray.helius.xyz/tx/{signature}?network=devnet")
+        );
+        assert!(Explorer::Xray
+            .transaction_url(&signature, &Cluster::Testnet)
+            .is_none());
+        assert_eq!(
+            Explorer::SolanaExplorer
+                .address_url(&address, &Cluster::Localnet)
+                .unwrap(),
+            format!(
+                "https://explorer.solana.com/address/{address}\
+                 ?cluster=custom&customUrl=http%3A%2F%2Flocalhost%3A8899"
+            )
+        );
+
+        let transaction = VersionedTransaction {
+            signatures: vec![signature],
+            message: VersionedMessage::Legacy(Message::new(&[], Some(&address))),
+        };
+        let inspector = Url::parse(
+            &Explorer::SolanaExplorer
+                .inspector_url(&transaction, &Cluster::Devnet)
+                .unwrap(),
+        )
+        .unwrap();
+        let query: Vec<_> = inspector.query_pairs().into_owned().collect();
+        assert_eq!(inspector.path(), "/tx/inspector");
+        assert_eq!(query[0], ("cluster".to_string(), "devnet".to_string()));
+        assert_eq!(
+            BASE64_STANDARD.decode(&query[1].1).unwrap(),
+            transaction.message.serialize()
+        );
+        assert_eq!(query[2].1, format!("[\"{signature}\"]"));
+        assert!(Explorer::Solscan
+            .inspector_url(&transaction, &Cluster::Devnet)
+            .is_none());
+    }
+}
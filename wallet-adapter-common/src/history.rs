@@ -0,0 +1,330 @@
+//! Past transactions of an address, eg. for the activity feed of a wallet UI.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::compute_budget;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use crate::amount::{Lamports, UiAmount};
+use crate::connection::ConfirmationStatus;
+use crate::token::TokenAmount;
+
+/// The history methods don't support `processed`, it becomes `confirmed`
+pub fn history_commitment(commitment: CommitmentLevel) -> CommitmentLevel {
+    match commitment {
+        CommitmentLevel::Processed => CommitmentLevel::Confirmed,
+        commitment => commitment,
+    }
+}
+
+/// Paging of `getSignaturesForAddress`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SignaturesForAddressOptions {
+    /// At most 1000, the default
+    pub limit: Option<usize>,
+    /// Only signatures older than this one
+    pub before: Option<Signature>,
+    /// Only signatures newer than this one
+    pub until: Option<Signature>,
+    /// Only answer from a node that has reached this slot
+    pub min_context_slot: Option<u64>,
+}
+
+impl SignaturesForAddressOptions {
+    /// The config object of the request
+    pub fn to_config(&self, commitment: CommitmentLevel) -> serde_json::Value {
+        let mut config = json!({ "commitment": history_commitment(commitment) });
+        if let Some(limit) = self.limit {
+            config["limit"] = json!(limit);
+        }
+        if let Some(before) = self.before {
+            config["before"] = json!(before.to_string());
+        }
+        if let Some(until) = self.until {
+            config["until"] = json!(until.to_string());
+        }
+        if let Some(slot) = self.min_context_slot {
+            config["minContextSlot"] = json!(slot);
+        }
+        config
+    }
+}
+
+/// An entry of `getSignaturesForAddress`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    /// The transaction error, `None` if it succeeded
+    pub err: Option<serde_json::Value>,
+    pub memo: Option<String>,
+    /// Unix timestamp
+    pub block_time: Option<i64>,
+    pub confirmation_status: Option<ConfirmationStatus>,
+}
+
+/// A transaction of `getTransaction` with the `jsonParsed` encoding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedTransactionWithMeta {
+    pub slot: u64,
+    /// Unix timestamp
+    pub block_time: Option<i64>,
+    pub meta: Option<TransactionMeta>,
+    pub transaction: ParsedTransaction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionMeta {
+    pub err: Option<serde_json::Value>,
+    pub fee: u64,
+    /// Lamports of the account keys in the same order, before the transaction
+    pub pre_balances: Vec<u64>,
+    pub post_balances: Vec<u64>,
+    #[serde(default)]
+    pub pre_token_balances: Vec<TransactionTokenBalance>,
+    #[serde(default)]
+    pub post_token_balances: Vec<TransactionTokenBalance>,
+    #[serde(default)]
+    pub log_messages: Option<Vec<String>>,
+}
+
+/// The balance of a token account the transaction touched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTokenBalance {
+    pub account_index: usize,
+    pub mint: String,
+    pub owner: Option<String>,
+    pub ui_token_amount: TokenAmount,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedTransaction {
+    pub signatures: Vec<String>,
+    pub message: ParsedMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedMessage {
+    pub account_keys: Vec<ParsedAccountKey>,
+    pub instructions: Vec<ParsedInstruction>,
+    pub recent_blockhash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedAccountKey {
+    pub pubkey: String,
+    pub signer: bool,
+    pub writable: bool,
+}
+
+/// An instruction parsed by the RPC node, or its raw `accounts` and `data` for programs the
+/// node has no parser for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedInstruction {
+    pub program_id: String,
+    /// The name of the program, eg. `system` or `spl-token`
+    #[serde(default)]
+    pub program: Option<String>,
+    #[serde(default)]
+    pub parsed: Option<serde_json::Value>,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// Base58
+    #[serde(default)]
+    pub data: Option<String>,
+}
+
+/// A transaction in the activity feed of an address, see
+/// [`Connection::recent_activity`](crate::connection::Connection::recent_activity)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityEntry {
+    pub signature: Signature,
+    pub slot: u64,
+    pub time: Option<DateTime<Utc>>,
+    pub status: ActivityStatus,
+    /// What the transaction did for the address, eg. `Sent 0.5 SOL`
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActivityStatus {
+    Success,
+    /// With the error of the transaction
+    Failed(String),
+}
+
+impl ActivityEntry {
+    /// Summarize the transaction of `info` for `owner`, from the signature info only when
+    /// the node doesn't have the transaction
+    pub fn new(
+        owner: &Pubkey,
+        info: &SignatureInfo,
+        transaction: Option<&ParsedTransactionWithMeta>,
+    ) -> Result<Self> {
+        let status = match &info.err {
+            None => ActivityStatus::Success,
+            Some(err) => ActivityStatus::Failed(err.to_string()),
+        };
+        let summary = match transaction {
+            Some(transaction) => summarize(owner, transaction),
+            None => info.memo.clone().unwrap_or_else(|| "Unknown".to_string()),
+        };
+
+        Ok(Self {
+            signature: info
+                .signature
+                .parse()
+                .with_context(|| format!("invalid signature {}", info.signature))?,
+            slot: info.slot,
+            time: info
+                .block_time
+                .and_then(|time| DateTime::from_timestamp(time, 0)),
+            status,
+            summary,
+        })
+    }
+}
+
+/// The token or SOL balance change of `owner`, or the programs the transaction called
+fn summarize(owner: &Pubkey, transaction: &ParsedTransactionWithMeta) -> String {
+    let owner = owner.to_string();
+    let message = &transaction.transaction.message;
+
+    if let Some(meta) = &transaction.meta {
+        let token_balance = |balances: &[TransactionTokenBalance], mint: &str| -> i128 {
+            balances
+                .iter()
+                .filter(|balance| balance.mint == mint && balance.owner.as_ref() == Some(&owner))
+                .filter_map(|balance| balance.ui_token_amount.amount.parse::<i128>().ok())
+                .sum()
+        };
+        for balance in meta
+            .post_token_balances
+            .iter()
+            .chain(&meta.pre_token_balances)
+            .filter(|balance| balance.owner.as_ref() == Some(&owner))
+        {
+            let change = token_balance(&meta.post_token_balances, &balance.mint)
+                - token_balance(&meta.pre_token_balances, &balance.mint);
+            if change != 0 {
+                let amount = UiAmount::new(
+                    change.unsigned_abs().try_into().unwrap_or(u64::MAX),
+                    balance.ui_token_amount.decimals,
+                );
+                let direction = if change > 0 { "Received" } else { "Sent" };
+                return format!("{direction} {amount} of {}", balance.mint);
+            }
+        }
+
+        if let Some(index) = message
+            .account_keys
+            .iter()
+            .position(|key| key.pubkey == owner)
+        {
+            let pre = i128::from(meta.pre_balances.get(index).copied().unwrap_or_default());
+            let post = i128::from(meta.post_balances.get(index).copied().unwrap_or_default());
+            // the fee payer comes first, leave its fee out
+            let fee = if index == 0 { i128::from(meta.fee) } else { 0 };
+            let change = post - pre + fee;
+            if change != 0 {
+                let amount = Lamports(change.unsigned_abs().try_into().unwrap_or(u64::MAX));
+                let direction = if change > 0 { "Received" } else { "Sent" };
+                return format!("{direction} {amount}");
+            }
+        }
+    }
+
+    let compute_budget = compute_budget::id().to_string();
+    let mut programs: Vec<&str> = Vec::new();
+    for instruction in &message.instructions {
+        let program = instruction
+            .program
+            .as_deref()
+            .unwrap_or(&instruction.program_id);
+        if instruction.program_id != compute_budget && !programs.contains(&program) {
+            programs.push(program);
+        }
+    }
+    format!("Called {}", programs.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_transfers_for_the_owner() {
+        let sender = Pubkey::new_unique();
+        let receiver = Pubkey::new_unique();
+        let transaction: ParsedTransactionWithMeta = serde_json::from_value(json!({
+            "slot": 42,
+            "blockTime": 1_700_000_000,
+            "meta": {
+                "err": null,
+                "fee": 5_000,
+                "preBalances": [2_000_000_000u64, 0, 1],
+                "postBalances": [1_499_995_000u64, 500_000_000, 1],
+                "preTokenBalances": [],
+                "postTokenBalances": [],
+                "logMessages": []
+            },
+            "transaction": {
+                "signatures": [Signature::default().to_string()],
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": sender.to_string(), "signer": true, "writable": true, "source": "transaction" },
+                        { "pubkey": receiver.to_string(), "signer": false, "writable": true, "source": "transaction" },
+                        { "pubkey": "11111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" }
+                    ],
+                    "instructions": [{
+                        "program": "system",
+                        "programId": "11111111111111111111111111111111",
+                        "parsed": {
+                            "type": "transfer",
+                            "info": {
+                                "source": sender.to_string(),
+                                "destination": receiver.to_string(),
+                                "lamports": 500_000_000
+                            }
+                        },
+                        "stackHeight": null
+                    }],
+                    "recentBlockhash": "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi"
+                }
+            },
+            "version": "legacy"
+        }))
+        .unwrap();
+        let info = SignatureInfo {
+            signature: Signature::default().to_string(),
+            slot: 42,
+            err: None,
+            memo: None,
+            block_time: Some(1_700_000_000),
+            confirmation_status: Some(ConfirmationStatus::Finalized),
+        };
+
+        let sent = ActivityEntry::new(&sender, &info, Some(&transaction)).unwrap();
+        assert_eq!(sent.summary, "Sent 0.5 SOL");
+        assert_eq!(sent.status, ActivityStatus::Success);
+        assert_eq!(sent.time.unwrap().timestamp(), 1_700_000_000);
+
+        let received = ActivityEntry::new(&receiver, &info, Some(&transaction)).unwrap();
+        assert_eq!(received.summary, "Received 0.5 SOL");
+
+        let bystander = ActivityEntry::new(&Pubkey::new_unique(), &info, Some(&transaction));
+        assert_eq!(bystander.unwrap().summary, "Called system");
+    }
+}
@@ -0,0 +1,171 @@
+//! Bundles of up to [`MAX_BUNDLE_LEN`] transactions landing atomically and in order through a
+//! [Jito](https://docs.jito.wtf) block engine, which also keeps them away from sandwiching bots.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use base64::prelude::*;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_system_interface::instruction as system_instruction;
+
+use crate::connection::{ConfirmationStatus, WithContext};
+use crate::transport::{rpc_call, RpcTransport};
+
+pub const MAINNET_BLOCK_ENGINE: &str = "https://mainnet.block-engine.jito.wtf";
+
+/// The block engine rejects bundles with more transactions
+pub const MAX_BUNDLE_LEN: usize = 5;
+
+/// An entry of `getBundleStatuses`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleStatus {
+    pub bundle_id: String,
+    /// Signatures of the bundle's transactions, in order
+    pub transactions: Vec<String>,
+    pub slot: u64,
+    pub confirmation_status: Option<ConfirmationStatus>,
+    /// `{ "Ok": null }` when the bundle landed
+    pub err: serde_json::Value,
+}
+
+/// The bundle API of a block engine, eg. from `RpcConnection::block_engine` to share the
+/// connection's transport
+#[derive(Clone)]
+pub struct JitoBlockEngine {
+    url: String,
+    transport: Arc<dyn RpcTransport>,
+}
+
+impl JitoBlockEngine {
+    /// The block engine at `url`, eg. [`MAINNET_BLOCK_ENGINE`], reached through `transport`
+    pub fn new(url: impl ToString, transport: impl RpcTransport + 'static) -> Self {
+        Self {
+            url: format!("{}/api/v1/bundles", url.to_string().trim_end_matches('/')),
+            transport: Arc::new(transport),
+        }
+    }
+
+    /// Submit the signed, serialized `transactions` as one bundle and return its id
+    pub async fn send_bundle(&self, transactions: &[Vec<u8>]) -> Result<String> {
+        if transactions.is_empty() || transactions.len() > MAX_BUNDLE_LEN {
+            bail!(
+                "a bundle holds 1 to {MAX_BUNDLE_LEN} transactions, got {}",
+                transactions.len()
+            );
+        }
+
+        let transactions: Vec<String> = transactions
+            .iter()
+            .map(|tx| BASE64_STANDARD.encode(tx))
+            .collect();
+        rpc_call(
+            self.transport.as_ref(),
+            &self.url,
+            "sendBundle",
+            json!([transactions, { "encoding": "base64" }]),
+        )
+        .await
+    }
+
+    /// Statuses of `bundle_ids` in the same order, `None` for bundles that haven't landed
+    pub async fn get_bundle_statuses(
+        &self,
+        bundle_ids: &[String],
+    ) -> Result<Vec<Option<BundleStatus>>> {
+        let statuses: WithContext<Vec<Option<BundleStatus>>> = rpc_call(
+            self.transport.as_ref(),
+            &self.url,
+            "getBundleStatuses",
+            json!([bundle_ids]),
+        )
+        .await?;
+
+        Ok(statuses.value)
+    }
+
+    /// Accounts the block engine takes tips on, a bundle must pay one of them
+    pub async fn get_tip_accounts(&self) -> Result<Vec<Pubkey>> {
+        let accounts: Vec<String> = rpc_call(
+            self.transport.as_ref(),
+            &self.url,
+            "getTipAccounts",
+            json!([]),
+        )
+        .await?;
+
+        accounts
+            .iter()
+            .map(|account| Pubkey::from_str(account).context("invalid tip account"))
+            .collect()
+    }
+
+    /// A transfer of `lamports` from `payer` to a random tip account, spreading the tips
+    /// across the accounts avoids write lock contention between bundles
+    pub async fn tip_instruction(&self, payer: &Pubkey, lamports: u64) -> Result<Instruction> {
+        let accounts = self.get_tip_accounts().await?;
+        let tip_account = accounts
+            .choose(&mut rand::thread_rng())
+            .context("the block engine has no tip accounts")?;
+
+        Ok(system_instruction::transfer(payer, tip_account, lamports))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Answers every call with `result` and records the requests
+    struct FakeBlockEngine {
+        result: serde_json::Value,
+        requests: Mutex<Vec<serde_json::Value>>,
+    }
+
+    #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+    impl RpcTransport for FakeBlockEngine {
+        async fn post_json(&self, _url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push(serde_json::from_slice(&body)?);
+            Ok(serde_json::to_vec(
+                &json!({ "jsonrpc": "2.0", "result": self.result, "id": 1 }),
+            )?)
+        }
+
+        async fn get(&self, _url: &str) -> Result<Vec<u8>> {
+            bail!("unused")
+        }
+    }
+
+    #[test]
+    fn sends_bundles_base64_encoded() {
+        let fake = Arc::new(FakeBlockEngine {
+            result: json!("bundle-id"),
+            requests: Mutex::new(vec![]),
+        });
+        let block_engine = JitoBlockEngine::new(MAINNET_BLOCK_ENGINE, fake.clone());
+
+        let bundle_id =
+            futures::executor::block_on(block_engine.send_bundle(&[vec![1, 2, 3]])).unwrap();
+        assert_eq!(bundle_id, "bundle-id");
+
+        let requests = fake.requests.lock().unwrap();
+        assert_eq!(requests[0]["method"], "sendBundle");
+        assert_eq!(
+            requests[0]["params"],
+            json!([["AQID"], { "encoding": "base64" }])
+        );
+
+        let too_many = vec![vec![0]; MAX_BUNDLE_LEN + 1];
+        assert!(futures::executor::block_on(block_engine.send_bundle(&too_many)).is_err());
+    }
+}
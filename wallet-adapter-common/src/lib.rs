@@ -1,3 +1,25 @@
+pub mod account;
+pub mod amount;
 pub mod connection;
+pub mod das;
+pub mod deeplink;
+pub mod explorer;
+pub mod history;
+pub mod jito;
+pub mod maybe_send;
+pub mod metrics;
+pub mod nacl_box;
+pub mod offchain_message;
+pub mod pubsub;
+#[cfg(feature = "qrcode")]
+pub mod qrcode;
+pub mod relay;
+pub mod rpc_connection;
+pub mod runtime;
+pub mod siws;
+pub mod solana_pay;
+pub mod standard;
 pub mod storage;
+pub mod token;
+pub mod transport;
 pub mod types;
@@ -0,0 +1,31 @@
+//! Thread-safety bounds that only apply off wasm.
+//!
+//! Native executors like tokio need `Send` futures to spawn them, so on native targets adapters
+//! and connections are `Send + Sync` and their async methods return `Send` futures.
+//! In the browser everything runs on one thread and JS handles are neither, so the bounds vanish.
+//!
+//! Traits and impls pick the matching `async_trait` flavour with
+//! ```ignore
+//! #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+//! #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSend for T {}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSync: Sync {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSync {}
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSync for T {}
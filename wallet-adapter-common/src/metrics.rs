@@ -0,0 +1,200 @@
+//! Health of the RPC endpoint behind a connection, eg. for an RPC status indicator or to pick
+//! the best of several endpoints.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::channel::mpsc;
+
+use crate::transport::RpcTransport;
+
+/// Failed requests in a row after which the endpoint is [`EndpointHealth::Down`]
+const DOWN_AFTER_ERRORS: u32 = 3;
+/// Average latency above which the endpoint is [`EndpointHealth::Degraded`]
+const SLOW_LATENCY_MS: f64 = 1_000.0;
+/// Weight of the latest request in the average latency
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Stream of [`MetricsRecorder::health_changes`]
+pub type HealthChanges = mpsc::UnboundedReceiver<EndpointHealth>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EndpointHealth {
+    #[default]
+    Healthy,
+    /// Slow, or the last request failed
+    Degraded,
+    /// Several requests in a row failed
+    Down,
+}
+
+/// Requests of a connection to its endpoint. Failures are requests the endpoint didn't answer
+/// or answered with an HTTP error, JSON-RPC errors like a failed simulation don't count.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConnectionMetrics {
+    pub requests: u64,
+    pub errors: u64,
+    pub consecutive_errors: u32,
+    /// Moving average favoring recent requests, `None` before the first answer
+    pub average_latency_ms: Option<f64>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<DateTime<Utc>>,
+}
+
+impl ConnectionMetrics {
+    /// Share of failed requests since the connection was created
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.requests as f64
+    }
+
+    pub fn health(&self) -> EndpointHealth {
+        if self.consecutive_errors >= DOWN_AFTER_ERRORS {
+            EndpointHealth::Down
+        } else if self.consecutive_errors > 0
+            || self
+                .average_latency_ms
+                .is_some_and(|latency| latency > SLOW_LATENCY_MS)
+        {
+            EndpointHealth::Degraded
+        } else {
+            EndpointHealth::Healthy
+        }
+    }
+}
+
+/// Collects the [`ConnectionMetrics`] of a connection, clones share them
+#[derive(Debug, Clone, Default)]
+pub struct MetricsRecorder {
+    metrics: Arc<Mutex<ConnectionMetrics>>,
+    listeners: Arc<Mutex<Vec<mpsc::UnboundedSender<EndpointHealth>>>>,
+}
+
+impl MetricsRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn metrics(&self) -> ConnectionMetrics {
+        self.metrics
+            .lock()
+            .map(|metrics| metrics.clone())
+            .unwrap_or_default()
+    }
+
+    /// The new health each time it changes
+    pub fn health_changes(&self) -> HealthChanges {
+        let (tx, rx) = mpsc::unbounded();
+        if let Ok(mut listeners) = self.listeners.lock() {
+            listeners.push(tx);
+        }
+        rx
+    }
+
+    /// Record a request that took `latency_ms`
+    pub fn record(&self, latency_ms: u64, success: bool) {
+        let Ok(mut metrics) = self.metrics.lock() else {
+            return;
+        };
+        let health = metrics.health();
+
+        metrics.requests += 1;
+        let now = Utc::now();
+        if success {
+            metrics.consecutive_errors = 0;
+            metrics.last_success = Some(now);
+            let latency = latency_ms as f64;
+            metrics.average_latency_ms = Some(match metrics.average_latency_ms {
+                Some(average) => average + LATENCY_SMOOTHING * (latency - average),
+                None => latency,
+            });
+        } else {
+            metrics.errors += 1;
+            metrics.consecutive_errors = metrics.consecutive_errors.saturating_add(1);
+            metrics.last_error = Some(now);
+        }
+
+        let new_health = metrics.health();
+        drop(metrics);
+        if new_health != health {
+            if let Ok(mut listeners) = self.listeners.lock() {
+                listeners.retain(|listener| listener.unbounded_send(new_health).is_ok());
+            }
+        }
+    }
+}
+
+/// Records the requests sent through `inner` in a [`MetricsRecorder`]
+#[derive(Debug, Clone)]
+pub struct MeteredTransport<T> {
+    inner: T,
+    metrics: MetricsRecorder,
+}
+
+impl<T> MeteredTransport<T> {
+    pub fn new(inner: T, metrics: MetricsRecorder) -> Self {
+        Self { inner, metrics }
+    }
+
+    async fn measure(
+        &self,
+        request: impl std::future::Future<Output = Result<Vec<u8>>>,
+    ) -> Result<Vec<u8>> {
+        let start = Utc::now();
+        let result = request.await;
+        let latency = (Utc::now() - start).num_milliseconds().max(0) as u64;
+        self.metrics.record(latency, result.is_ok());
+        result
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<T: RpcTransport> RpcTransport for MeteredTransport<T> {
+    async fn post_json(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+        self.measure(self.inner.post_json(url, body)).await
+    }
+
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        self.measure(self.inner.get(url)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_errors_latency_and_health_changes() {
+        let recorder = MetricsRecorder::new();
+        let mut changes = recorder.health_changes();
+
+        recorder.record(100, true);
+        recorder.record(200, true);
+        assert_eq!(recorder.metrics().average_latency_ms, Some(120.0));
+        assert_eq!(recorder.metrics().health(), EndpointHealth::Healthy);
+
+        for _ in 0..DOWN_AFTER_ERRORS {
+            recorder.record(0, false);
+        }
+        recorder.record(50, true);
+
+        let metrics = recorder.metrics();
+        assert_eq!((metrics.requests, metrics.errors), (6, 3));
+        assert_eq!(metrics.error_rate(), 0.5);
+        assert!(metrics.last_success >= metrics.last_error);
+
+        let changes: Vec<_> = std::iter::from_fn(|| changes.try_recv().ok()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                EndpointHealth::Degraded,
+                EndpointHealth::Down,
+                EndpointHealth::Healthy
+            ]
+        );
+    }
+}
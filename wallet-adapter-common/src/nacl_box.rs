@@ -0,0 +1,89 @@
+//! The NaCl `box` (X25519, XSalsa20 and Poly1305) mobile wallets encrypt deeplink payloads with,
+//! compatible with tweetnacl's `box.before`, `box.after` and `box.open.after`.
+
+use anyhow::{anyhow, Result};
+use crypto_box::aead::Aead;
+pub use crypto_box::SalsaBox;
+use crypto_box::{PublicKey, SecretKey};
+
+pub const NONCE_LEN: usize = 24;
+
+pub fn public_key(secret_key: &[u8; 32]) -> [u8; 32] {
+    SecretKey::from_bytes(*secret_key).public_key().to_bytes()
+}
+
+/// The box `secret_key` shares with the owner of `public_key`
+pub fn shared_box(secret_key: &[u8; 32], public_key: &[u8; 32]) -> SalsaBox {
+    SalsaBox::new(
+        &PublicKey::from_bytes(*public_key),
+        &SecretKey::from_bytes(*secret_key),
+    )
+}
+
+/// Encrypt and authenticate `plaintext`, the result is the tag followed by the ciphertext
+pub fn seal(shared_box: &SalsaBox, nonce: &[u8; NONCE_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
+    shared_box
+        .encrypt(&(*nonce).into(), plaintext)
+        .map_err(|_| anyhow!("could not encrypt the payload"))
+}
+
+pub fn open(shared_box: &SalsaBox, nonce: &[u8; NONCE_LEN], sealed: &[u8]) -> Result<Vec<u8>> {
+    shared_box
+        .decrypt(&(*nonce).into(), sealed)
+        .map_err(|_| anyhow!("could not decrypt the payload"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(hex: &str) -> [u8; 32] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    /// NaCl's `tests/box.c`, `tests/box2.c` and their expected output
+    #[test]
+    fn matches_nacl() {
+        let alice = key("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = key("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let nonce: [u8; NONCE_LEN] =
+            hex::decode("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let message = hex::decode(
+            "be075fc53c81f2d5cf141316ebeb0c7b5228c52a4c62cbd44b66849b64244ffce5ecbaaf33bd751a\
+             1ac728d45e6c61296cdc3c01233561f41db66cce314adb310e3be8250c46f06dceea3a7fa1348057\
+             e2f6556ad6b1318a024a838f21af1fde048977eb48f59ffd4924ca1c60902e52f0a089bc76897040\
+             e082f937763848645e0705",
+        )
+        .unwrap();
+        let sealed = hex::decode(
+            "f3ffc7703f9400e52a7dfb4b3d3305d98e993b9f48681273c29650ba32fc76ce48332ea7164d96a4\
+             476fb8c531a1186ac0dfc17c98dce87b4da7f011ec48c97271d2c20f9b928fe2270d6fb863d51738\
+             b48eeee314a7cc8ab932164548e526ae90224368517acfeabd6bb3732bc0e9da99832b61ca01b6de\
+             56244a9e88d5f9b37973f622a43d14a6599b1f654cb45a74e355a5",
+        )
+        .unwrap();
+
+        let alice_public_key = public_key(&alice);
+        let bob_public_key = public_key(&bob);
+        assert_eq!(
+            hex::encode(alice_public_key),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        assert_eq!(
+            hex::encode(bob_public_key),
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        );
+
+        let alice_box = shared_box(&alice, &bob_public_key);
+        assert_eq!(seal(&alice_box, &nonce, &message).unwrap(), sealed);
+
+        let bob_box = shared_box(&bob, &alice_public_key);
+        assert_eq!(open(&bob_box, &nonce, &sealed).unwrap(), message);
+        let mut tampered = sealed;
+        tampered[20] ^= 1;
+        assert!(open(&bob_box, &nonce, &tampered).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Solana off-chain messages, the format Ledger and Phantom sign text messages in.
+//!
+//! The signed bytes are the signing domain, the version, the [`MessageFormat`], the
+//! little-endian length and the message itself. The domain starts with `0xff`, which can't
+//! start a transaction message, so a signed off-chain message can't be replayed on chain.
+
+pub use solana_offchain_message::{MessageFormat, OffchainMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+
+pub const SIGNING_DOMAIN: &[u8] = OffchainMessage::SIGNING_DOMAIN;
+
+/// Wrap `message` in a version 0 off-chain message of the most restricted format it fits,
+/// fails for binary messages and messages over 65515 bytes
+pub fn new_message(message: &[u8]) -> anyhow::Result<OffchainMessage> {
+    OffchainMessage::new(0, message)
+        .map_err(|err| anyhow::anyhow!("Invalid off-chain message: {err}"))
+}
+
+/// The bytes to sign for `message` as an off-chain message, `message` itself when it
+/// already is one
+pub fn serialize_message(message: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if message.starts_with(SIGNING_DOMAIN) {
+        return Ok(message.to_vec());
+    }
+
+    new_message(message)?
+        .serialize()
+        .map_err(|err| anyhow::anyhow!("Invalid off-chain message: {err}"))
+}
+
+/// Sign `message` as an off-chain message, see [`serialize_message`]
+pub fn sign_message(signer: &dyn Signer, message: &[u8]) -> anyhow::Result<Signature> {
+    Ok(signer.sign_message(&serialize_message(message)?))
+}
+
+/// Whether `signature` signs `message` either directly or wrapped in an off-chain message,
+/// covering wallets signing raw bytes as well as Ledger-safe ones
+pub fn verify_message(public_key: &Pubkey, message: &[u8], signature: &Signature) -> bool {
+    signature.verify(public_key.as_ref(), message)
+        || new_message(message).is_ok_and(|offchain_message| {
+            offchain_message
+                .verify(public_key, signature)
+                .unwrap_or(false)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+
+    #[test]
+    fn round_trips_and_verifies_both_signature_kinds() {
+        let message = new_message(b"Hello, world!").unwrap();
+        assert_eq!(message.get_format(), MessageFormat::RestrictedAscii);
+        let serialized = message.serialize().unwrap();
+        assert_eq!(&serialized[..16], SIGNING_DOMAIN);
+        assert_eq!(
+            &serialized[16..],
+            b"\x00\x00\x0d\x00Hello, world!".as_slice()
+        );
+        assert_eq!(OffchainMessage::deserialize(&serialized).unwrap(), message);
+        assert_eq!(
+            new_message("Grüße".as_bytes()).unwrap().get_format(),
+            MessageFormat::LimitedUtf8
+        );
+        assert!(new_message(&[0xff, 0xfe]).is_err());
+
+        let keypair = Keypair::new();
+        let wrapped = sign_message(&keypair, b"Hello, world!").unwrap();
+        let raw = keypair.sign_message(b"Hello, world!");
+        assert_ne!(wrapped, raw);
+        assert_eq!(sign_message(&keypair, &serialized).unwrap(), wrapped);
+        assert!(sign_message(&keypair, &[0xff, 0xfe]).is_err());
+        assert!(verify_message(
+            &keypair.pubkey(),
+            b"Hello, world!",
+            &wrapped
+        ));
+        assert!(verify_message(&keypair.pubkey(), b"Hello, world!", &raw));
+        assert!(!verify_message(
+            &keypair.pubkey(),
+            b"Hello, world?",
+            &wrapped
+        ));
+    }
+}
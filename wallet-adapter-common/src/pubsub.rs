@@ -0,0 +1,299 @@
+//! Subscriptions to accounts, signatures, slots and transactions, pushed by the node instead of
+//! polled. Backends reconnect on their own and resubscribe, reporting it as [`PubSubStatus`].
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use futures::channel::mpsc;
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use crate::connection::{RpcAccount, WithContext};
+use crate::maybe_send::{MaybeSend, MaybeSync};
+
+/// Stream of the notifications of one subscription, dropping it unsubscribes
+pub type Notifications = mpsc::UnboundedReceiver<Notification>;
+
+/// Stream of [`PubSub::status_changes`]
+pub type PubSubStatusChanges = mpsc::UnboundedReceiver<PubSubStatus>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Subscription {
+    /// Every change of the account at `pubkey`
+    Account {
+        pubkey: Pubkey,
+        commitment: CommitmentLevel,
+    },
+    /// The transaction of `signature` reaching `commitment`, notified once
+    Signature {
+        signature: Signature,
+        commitment: CommitmentLevel,
+    },
+    /// Every slot the node processes
+    Slot,
+    /// Every transaction mentioning `account`
+    Transactions {
+        account: Pubkey,
+        commitment: CommitmentLevel,
+    },
+}
+
+impl Subscription {
+    /// The JSON-RPC websocket method and params subscribing to this
+    pub fn subscribe_request(&self) -> (&'static str, serde_json::Value) {
+        match self {
+            Subscription::Account { pubkey, commitment } => (
+                "accountSubscribe",
+                json!([pubkey.to_string(), { "encoding": "base64", "commitment": commitment }]),
+            ),
+            Subscription::Signature {
+                signature,
+                commitment,
+            } => (
+                "signatureSubscribe",
+                json!([signature.to_string(), { "commitment": commitment }]),
+            ),
+            Subscription::Slot => ("slotSubscribe", json!([])),
+            Subscription::Transactions {
+                account,
+                commitment,
+            } => (
+                "logsSubscribe",
+                json!([{ "mentions": [account.to_string()] }, { "commitment": commitment }]),
+            ),
+        }
+    }
+
+    /// The JSON-RPC websocket method ending this subscription
+    pub fn unsubscribe_method(&self) -> &'static str {
+        match self {
+            Subscription::Account { .. } => "accountUnsubscribe",
+            Subscription::Signature { .. } => "signatureUnsubscribe",
+            Subscription::Slot => "slotUnsubscribe",
+            Subscription::Transactions { .. } => "logsUnsubscribe",
+        }
+    }
+
+    /// The commitment the notifications wait for, `None` for slots
+    pub fn commitment(&self) -> Option<CommitmentLevel> {
+        match self {
+            Subscription::Account { commitment, .. }
+            | Subscription::Signature { commitment, .. }
+            | Subscription::Transactions { commitment, .. } => Some(*commitment),
+            Subscription::Slot => None,
+        }
+    }
+
+    /// Whether the node ends the subscription after its first notification
+    pub fn is_one_shot(&self) -> bool {
+        matches!(self, Subscription::Signature { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    Account {
+        slot: u64,
+        account: Account,
+    },
+    Signature {
+        slot: u64,
+        /// The transaction error, `None` if it succeeded
+        err: Option<serde_json::Value>,
+    },
+    Slot {
+        slot: u64,
+        parent: u64,
+        root: u64,
+    },
+    Transaction {
+        slot: u64,
+        signature: Signature,
+        /// The transaction error, `None` if it succeeded
+        err: Option<serde_json::Value>,
+    },
+}
+
+#[derive(Deserialize)]
+struct SignatureResult {
+    err: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct SlotResult {
+    slot: u64,
+    parent: u64,
+    root: u64,
+}
+
+#[derive(Deserialize)]
+struct LogsResult {
+    signature: String,
+    err: Option<serde_json::Value>,
+}
+
+fn context_slot(slot: i64) -> u64 {
+    slot.try_into().unwrap_or_default()
+}
+
+impl Notification {
+    /// The notification of a JSON-RPC websocket `method`, eg. `accountNotification`,
+    /// from the `result` of its params
+    pub fn from_websocket(method: &str, result: serde_json::Value) -> Result<Self> {
+        Ok(match method {
+            "accountNotification" => {
+                let account: WithContext<RpcAccount> = serde_json::from_value(result)?;
+                Notification::Account {
+                    slot: context_slot(account.context.slot),
+                    account: account.value.try_into()?,
+                }
+            }
+            "signatureNotification" => {
+                let status: WithContext<SignatureResult> = serde_json::from_value(result)?;
+                Notification::Signature {
+                    slot: context_slot(status.context.slot),
+                    err: status.value.err,
+                }
+            }
+            "slotNotification" => {
+                let slot: SlotResult = serde_json::from_value(result)?;
+                Notification::Slot {
+                    slot: slot.slot,
+                    parent: slot.parent,
+                    root: slot.root,
+                }
+            }
+            "logsNotification" => {
+                let logs: WithContext<LogsResult> = serde_json::from_value(result)?;
+                Notification::Transaction {
+                    slot: context_slot(logs.context.slot),
+                    signature: logs.value.signature.parse()?,
+                    err: logs.value.err,
+                }
+            }
+            method => bail!("unexpected notification {method}"),
+        })
+    }
+}
+
+/// The state of the connection under the subscriptions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PubSubStatus {
+    /// The connection dropped or could not be established, notifications pause until the
+    /// backend reconnects in `retry_in_ms`
+    Degraded { error: String, retry_in_ms: u64 },
+    /// The connection is back and the subscriptions were renewed, notifications sent while it
+    /// was down are lost
+    Reconnected,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait PubSub: MaybeSend + MaybeSync {
+    /// The notifications of `subscription` until the stream is dropped,
+    /// renewed after reconnecting
+    async fn subscribe(&self, subscription: Subscription) -> Result<Notifications>;
+
+    /// The new status each time it changes, eg. to pause a game while the node is unreachable
+    fn status_changes(&self) -> PubSubStatusChanges;
+}
+
+/// Delays between reconnection attempts, doubling from `initial` up to `max`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Backoff {
+    /// The delay after another failed attempt following `delay`
+    pub fn next(&self, delay: Duration) -> Duration {
+        delay.saturating_mul(2).min(self.max)
+    }
+}
+
+/// The listeners of [`PubSub::status_changes`], clones share them
+#[derive(Debug, Clone, Default)]
+pub struct StatusListeners {
+    listeners: Arc<Mutex<Vec<mpsc::UnboundedSender<PubSubStatus>>>>,
+}
+
+impl StatusListeners {
+    pub fn listen(&self) -> PubSubStatusChanges {
+        let (tx, rx) = mpsc::unbounded();
+        if let Ok(mut listeners) = self.listeners.lock() {
+            listeners.push(tx);
+        }
+        rx
+    }
+
+    pub fn emit(&self, status: PubSubStatus) {
+        if let Ok(mut listeners) = self.listeners.lock() {
+            listeners.retain(|listener| listener.unbounded_send(status.clone()).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_websocket_notifications() {
+        let account = Notification::from_websocket(
+            "accountNotification",
+            json!({
+                "context": { "slot": 5199307 },
+                "value": {
+                    "data": ["AQID", "base64"],
+                    "executable": false,
+                    "lamports": 33594,
+                    "owner": "11111111111111111111111111111111",
+                    "rentEpoch": 18446744073709551615u64,
+                    "space": 3
+                }
+            }),
+        )
+        .unwrap();
+        let Notification::Account { slot, account } = account else {
+            panic!("expected an account notification");
+        };
+        assert_eq!((slot, account.lamports), (5199307, 33594));
+        assert_eq!(account.data, vec![1, 2, 3]);
+
+        let logs = Notification::from_websocket(
+            "logsNotification",
+            json!({
+                "context": { "slot": 5208469 },
+                "value": {
+                    "signature": Signature::default().to_string(),
+                    "err": { "InstructionError": [0, "Custom"] },
+                    "logs": []
+                }
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            logs,
+            Notification::Transaction {
+                slot: 5208469,
+                signature: Signature::default(),
+                err: Some(json!({ "InstructionError": [0, "Custom"] })),
+            }
+        );
+    }
+}
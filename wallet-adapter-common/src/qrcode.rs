@@ -0,0 +1,134 @@
+//! QR codes for Solana Pay URLs, WalletConnect URIs and Mobile Wallet Adapter association URIs.
+//!
+//! [`QrCode::to_rgba8`] fits image textures such as Bevy's, [`QrCode::to_png_data_url`] and
+//! [`QrCode::to_svg`] fit the `src` of an `<img>` tag and inline markup respectively.
+
+use ::qrcode::{Color, EcLevel};
+use anyhow::{Context, Result};
+use base64::Engine;
+
+/// Light modules around the code, scanners need 4
+pub const QUIET_ZONE: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+    width: u32,
+    /// Row by row, true for dark modules
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode `data` with medium error correction, which survives a logo over the center
+    pub fn new(data: &str) -> Result<Self> {
+        let code = ::qrcode::QrCode::with_error_correction_level(data, EcLevel::M)
+            .with_context(|| format!("can't encode {} bytes in a QR code", data.len()))?;
+
+        Ok(Self {
+            width: code.width() as u32,
+            modules: code
+                .to_colors()
+                .into_iter()
+                .map(|color| color == Color::Dark)
+                .collect(),
+        })
+    }
+
+    /// Modules per side, without the quiet zone
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn is_dark(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.width && self.modules[(y * self.width + x) as usize]
+    }
+
+    /// Pixels per side of the images rendered with `scale` pixels per module
+    pub fn image_size(&self, scale: u32) -> u32 {
+        (self.width + 2 * QUIET_ZONE) * scale
+    }
+
+    /// Grayscale pixels with the quiet zone, row by row, [`Self::image_size`] per side
+    pub fn to_luma8(&self, scale: u32) -> Vec<u8> {
+        let size = self.image_size(scale);
+        let mut pixels = Vec::with_capacity((size * size) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let dark = self.is_dark(
+                    (x / scale).wrapping_sub(QUIET_ZONE),
+                    (y / scale).wrapping_sub(QUIET_ZONE),
+                );
+                pixels.push(if dark { 0 } else { 255 });
+            }
+        }
+        pixels
+    }
+
+    /// Like [`Self::to_luma8`] with opaque RGBA pixels
+    pub fn to_rgba8(&self, scale: u32) -> Vec<u8> {
+        self.to_luma8(scale)
+            .into_iter()
+            .flat_map(|luma| [luma, luma, luma, 255])
+            .collect()
+    }
+
+    pub fn to_png(&self, scale: u32) -> Result<Vec<u8>> {
+        let size = self.image_size(scale);
+        let mut png = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut png, size, size);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_luma8(scale))?;
+        writer.finish()?;
+
+        Ok(png)
+    }
+
+    pub fn to_png_data_url(&self, scale: u32) -> Result<String> {
+        let png = base64::engine::general_purpose::STANDARD.encode(self.to_png(scale)?);
+        Ok(format!("data:image/png;base64,{png}"))
+    }
+
+    /// A scalable image with one unit per module, size it with CSS
+    pub fn to_svg(&self) -> String {
+        let size = self.width + 2 * QUIET_ZONE;
+        let mut path = String::new();
+        for y in 0..self.width {
+            for x in 0..self.width {
+                if self.is_dark(x, y) {
+                    path.push_str(&format!("M{} {}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE));
+                }
+            }
+        }
+
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {size} {size}" shape-rendering="crispEdges"><rect width="{size}" height="{size}" fill="#fff"/><path d="{path}" fill="#000"/></svg>"##
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_the_same_modules_in_every_format() {
+        let code =
+            QrCode::new("solana:mvines9iiHiQTysrwkJjGf2gb9Ex9jXJX8ns3qwf2kN?amount=1").unwrap();
+        let size = code.image_size(2);
+        let pixels = code.to_luma8(2);
+
+        assert_eq!(pixels.len(), (size * size) as usize);
+        // the top left finder pattern starts right after the quiet zone
+        assert_eq!(pixels[0], 255);
+        assert!(code.is_dark(0, 0));
+        assert_eq!(pixels[(QUIET_ZONE * 2 * size + QUIET_ZONE * 2) as usize], 0);
+
+        assert_eq!(code.to_rgba8(2).len(), pixels.len() * 4);
+        assert!(code.to_png(2).unwrap().starts_with(b"\x89PNG"));
+        assert!(code
+            .to_svg()
+            .contains(&format!("M{QUIET_ZONE} {QUIET_ZONE}h1")));
+    }
+}
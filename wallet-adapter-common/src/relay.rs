@@ -0,0 +1,149 @@
+//! Gasless transactions: a relayer pays the fees, the wallet only signs for its own accounts.
+//!
+//! Set a [`FeePayerService`] with [`SendTransactionOptions::sponsor`](crate::types::SendTransactionOptions::sponsor)
+//! and the signer wallets hand the partially signed transaction to it instead of the RPC node.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+
+use crate::maybe_send::{MaybeSend, MaybeSync};
+use crate::transport::RpcTransport;
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait FeePayerService: MaybeSend + MaybeSync {
+    /// The account paying the fees, it becomes the fee payer of sponsored transactions
+    async fn fee_payer(&self) -> Result<Pubkey>;
+
+    /// Add the fee payer's signature to `transaction`, which the user already signed,
+    /// submit it and return its signature
+    async fn sign_and_send(&self, transaction: &Transaction) -> Result<Signature>;
+}
+
+/// An [Octane](https://github.com/solana-labs/octane) relayer, or any relayer speaking its API
+pub struct OctaneRelay {
+    url: String,
+    transport: Arc<dyn RpcTransport>,
+    endpoint: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OctaneConfig {
+    fee_payer: String,
+}
+
+#[derive(Deserialize)]
+struct OctaneResponse {
+    status: String,
+    signature: Option<String>,
+    message: Option<String>,
+}
+
+impl OctaneRelay {
+    /// The relayer at `url`, eg. `https://octane.example.com`, reached through `transport`
+    pub fn new(url: impl ToString, transport: impl RpcTransport + 'static) -> Self {
+        Self {
+            url: url.to_string().trim_end_matches('/').to_string(),
+            transport: Arc::new(transport),
+            endpoint: "/api/transfer".to_string(),
+        }
+    }
+
+    /// Path the transactions are posted to, `/api/transfer` by default
+    pub fn endpoint(mut self, endpoint: impl ToString) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl FeePayerService for OctaneRelay {
+    async fn fee_payer(&self) -> Result<Pubkey> {
+        let config: OctaneConfig =
+            serde_json::from_slice(&self.transport.get(&format!("{}/api", self.url)).await?)
+                .context("invalid relayer config")?;
+
+        Pubkey::from_str(&config.fee_payer).context("invalid relayer fee payer")
+    }
+
+    async fn sign_and_send(&self, transaction: &Transaction) -> Result<Signature> {
+        let transaction = solana_sdk::bs58::encode(bincode::serialize(transaction)?).into_string();
+        let body = serde_json::to_vec(&serde_json::json!({ "transaction": transaction }))?;
+        let resp: OctaneResponse = serde_json::from_slice(
+            &self
+                .transport
+                .post_json(&format!("{}{}", self.url, self.endpoint), body)
+                .await?,
+        )
+        .context("invalid relayer response")?;
+
+        if resp.status != "ok" {
+            bail!(
+                "relayer refused the transaction: {}",
+                resp.message.unwrap_or(resp.status)
+            );
+        }
+
+        Signature::from_str(&resp.signature.context("relayer returned no signature")?)
+            .context("invalid relayer signature")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeRelayer {
+        posted: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+    impl RpcTransport for FakeRelayer {
+        async fn post_json(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+            self.posted
+                .lock()
+                .unwrap()
+                .push((url.to_string(), serde_json::from_slice(&body)?));
+            Ok(serde_json::to_vec(
+                &json!({ "status": "ok", "signature": Signature::default().to_string() }),
+            )?)
+        }
+
+        async fn get(&self, _url: &str) -> Result<Vec<u8>> {
+            Ok(serde_json::to_vec(
+                &json!({ "feePayer": Pubkey::default().to_string() }),
+            )?)
+        }
+    }
+
+    #[test]
+    fn talks_to_the_octane_api() {
+        let relayer = Arc::new(FakeRelayer::default());
+        let relay = OctaneRelay::new("https://octane.example/", relayer.clone());
+
+        let fee_payer = futures::executor::block_on(relay.fee_payer()).unwrap();
+        assert_eq!(fee_payer, Pubkey::default());
+
+        let signature =
+            futures::executor::block_on(relay.sign_and_send(&Transaction::default())).unwrap();
+        assert_eq!(signature, Signature::default());
+
+        let posted = relayer.posted.lock().unwrap();
+        assert_eq!(posted[0].0, "https://octane.example/api/transfer");
+        assert!(posted[0].1["transaction"].is_string());
+    }
+}
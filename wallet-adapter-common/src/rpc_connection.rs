@@ -0,0 +1,988 @@
+//! The JSON-RPC [`Connection`] of every target, generic over the HTTP client it sends its
+//! requests through, see [`RpcTransport`].
+
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{commitment_config::CommitmentLevel, signature::Signature};
+use url::Url;
+
+use crate::account::{ParsedAccount, RpcParsedAccount};
+use crate::connection::{
+    Connection, EpochInfo, GetLatestBlockhash, LatestBlockhash, RpcAccount, RpcVersion,
+    SignatureStatus, WithContext,
+};
+use crate::das::{Asset, AssetList, DasConnection};
+use crate::explorer::Cluster;
+use crate::history::{
+    history_commitment, ParsedTransactionWithMeta, SignatureInfo, SignaturesForAddressOptions,
+};
+use crate::jito::JitoBlockEngine;
+use crate::metrics::{ConnectionMetrics, HealthChanges, MeteredTransport, MetricsRecorder};
+use crate::runtime;
+use crate::solana_pay::{
+    SolanaPayClient, TransactionRequestBody, TransactionRequestMetadata, TransactionRequestResponse,
+};
+use crate::token::{KeyedTokenAccount, TOKEN_PROGRAM_IDS};
+use crate::transport::{rpc_call, RequestTimeouts, RpcTransport};
+use crate::types::SendTransactionOptions;
+
+const CONFIRM_POLL_INTERVAL_MS: u32 = 1000;
+const BLOCKHASH_REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+/// Blockhashes expire after about a minute, leave the wallet the other half to sign
+const PREFETCHED_BLOCKHASH_MAX_AGE: Duration = Duration::from_secs(30);
+
+struct PrefetchedBlockhash {
+    blockhash: LatestBlockhash,
+    commitment: CommitmentLevel,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Clones share the transport, eg. the pooled connections of a `reqwest::Client`,
+/// and the prefetched blockhash
+pub struct RpcConnection<T> {
+    url: String,
+    transport: Arc<T>,
+    metrics: MetricsRecorder,
+    prefetched: Arc<Mutex<Option<PrefetchedBlockhash>>>,
+    commitment: CommitmentLevel,
+    timeouts: RequestTimeouts,
+    min_context_slot: Option<u64>,
+    context_slot: Arc<AtomicU64>,
+}
+
+impl<T> Clone for RpcConnection<T> {
+    fn clone(&self) -> Self {
+        Self {
+            url: self.url.clone(),
+            transport: self.transport.clone(),
+            metrics: self.metrics.clone(),
+            prefetched: self.prefetched.clone(),
+            commitment: self.commitment,
+            timeouts: self.timeouts,
+            min_context_slot: self.min_context_slot,
+            context_slot: self.context_slot.clone(),
+        }
+    }
+}
+
+impl<T: RpcTransport + Default + 'static> RpcConnection<T> {
+    pub fn new(url: String) -> Self {
+        Self::with_transport(url, T::default())
+    }
+
+    pub fn devnet() -> Self {
+        Self::new("https://api.devnet.solana.com".to_string())
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new("https://api.mainnet-beta.solana.com".to_string())
+    }
+
+    pub fn testnet() -> Self {
+        Self::new("https://api.testnet.solana.com".to_string())
+    }
+}
+
+impl<T: RpcTransport + 'static> RpcConnection<T> {
+    /// Send the requests through `transport`, eg. a `reqwest::Client` shared with the rest
+    /// of the app or another HTTP client, see [`RpcTransport`]
+    pub fn with_transport(url: String, transport: T) -> Self {
+        Self {
+            url,
+            transport: Arc::new(transport),
+            metrics: MetricsRecorder::new(),
+            prefetched: Arc::new(Mutex::new(None)),
+            commitment: CommitmentLevel::Finalized,
+            timeouts: RequestTimeouts::default(),
+            min_context_slot: None,
+            context_slot: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Commitment of blockhashes, preflight checks and queries not asking for one,
+    /// `Finalized` by default. `Confirmed` sends noticeably faster.
+    pub fn with_commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn commitment(&self) -> CommitmentLevel {
+        self.commitment
+    }
+
+    /// Fail requests taking longer than `timeout`, by default and with `None` they wait forever.
+    /// Natively the timeouts need the time driver of the tokio runtime.
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeouts.request = timeout;
+        self
+    }
+
+    /// Fail the requests made after `timeout` from now, eg. to bound a whole
+    /// `send_transaction` on a clone of the connection:
+    /// `wallet.send_transaction(tx, &connection.clone().with_deadline(timeout), None)`
+    pub fn with_deadline(mut self, timeout: Duration) -> Self {
+        self.timeouts = self.timeouts.deadline_after(timeout);
+        self
+    }
+
+    /// Only let nodes that have reached `slot` answer, eg. on a clone of the connection after
+    /// sending, to read the writes of the transaction:
+    /// `connection.clone().with_min_context_slot(connection.context_slot().unwrap_or_default())`
+    pub fn with_min_context_slot(mut self, slot: u64) -> Self {
+        self.min_context_slot = Some(slot);
+        self
+    }
+
+    pub fn min_context_slot(&self) -> Option<u64> {
+        self.min_context_slot
+    }
+
+    /// The highest slot a node evaluated the requests of this connection and its clones at
+    pub fn context_slot(&self) -> Option<u64> {
+        match self.context_slot.load(Ordering::Relaxed) {
+            0 => None,
+            slot => Some(slot),
+        }
+    }
+
+    /// The health of the endpoint, see [`ConnectionMetrics`]
+    pub fn metrics(&self) -> ConnectionMetrics {
+        self.metrics.metrics()
+    }
+
+    /// The new health of the endpoint each time it changes, eg. to show a warning
+    pub fn health_changes(&self) -> HealthChanges {
+        self.metrics.health_changes()
+    }
+
+    /// Keep a `commitment` blockhash warm in the background so sending skips its round trip.
+    ///
+    /// The refresh task runs until the connection and all its clones are dropped,
+    /// natively call this from within a tokio runtime.
+    pub fn with_blockhash_prefetch(self, commitment: CommitmentLevel) -> Self {
+        let fetcher = Self {
+            prefetched: Arc::new(Mutex::new(None)),
+            ..self.clone()
+        };
+        let prefetched = Arc::downgrade(&self.prefetched);
+
+        runtime::spawn(async move {
+            loop {
+                let result = fetcher.fetch_blockhash(commitment, None).await;
+                let Some(prefetched) = prefetched.upgrade() else {
+                    break;
+                };
+                match result {
+                    Ok(blockhash) => {
+                        if let Ok(mut prefetched) = prefetched.lock() {
+                            *prefetched = Some(PrefetchedBlockhash {
+                                blockhash,
+                                commitment,
+                                fetched_at: Utc::now(),
+                            });
+                        }
+                    }
+                    Err(err) => tracing::warn!("could not prefetch a blockhash: {err}"),
+                }
+                drop(prefetched);
+
+                runtime::sleep(BLOCKHASH_REFRESH_INTERVAL).await;
+            }
+        });
+
+        self
+    }
+
+    /// The prefetched blockhash if it is still fresh and read at `min_context_slot` or later
+    fn prefetched_blockhash(
+        &self,
+        commitment: CommitmentLevel,
+        min_context_slot: Option<u64>,
+    ) -> Option<LatestBlockhash> {
+        let prefetched = self.prefetched.lock().ok()?;
+        prefetched
+            .as_ref()
+            .filter(|prefetched| {
+                prefetched.commitment == commitment
+                    && prefetched.blockhash.slot >= min_context_slot.unwrap_or_default()
+                    && (Utc::now() - prefetched.fetched_at)
+                        .to_std()
+                        .is_ok_and(|age| age < PREFETCHED_BLOCKHASH_MAX_AGE)
+            })
+            .map(|prefetched| prefetched.blockhash)
+    }
+
+    async fn request<R: DeserializeOwned + Debug>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<R> {
+        let transport = MeteredTransport::new(self.transport.clone(), self.metrics.clone());
+        let request = rpc_call(&transport, &self.url, method, params);
+        let Some(timeout) = self.timeouts.next(method)? else {
+            return request.await;
+        };
+
+        match runtime::timeout(timeout, request).await {
+            Some(result) => result,
+            None => {
+                self.metrics.record(timeout.as_millis() as u64, false);
+                bail!("{method} timed out after {timeout:?}")
+            }
+        }
+    }
+
+    /// `config` with the `minContextSlot` of the connection, unless it asks for a later one
+    fn query_config(&self, mut config: serde_json::Value) -> serde_json::Value {
+        let min_context_slot = config["minContextSlot"].as_u64().max(self.min_context_slot);
+        if let Some(slot) = min_context_slot {
+            config["minContextSlot"] = json!(slot);
+        }
+        config
+    }
+
+    /// The value of a `{ context, value }` result, remembering the slot of its context
+    fn observe<V>(&self, response: WithContext<V>) -> V {
+        if let Ok(slot) = u64::try_from(response.context.slot) {
+            self.context_slot.fetch_max(slot, Ordering::Relaxed);
+        }
+        response.value
+    }
+
+    async fn fetch_blockhash(
+        &self,
+        commitment: CommitmentLevel,
+        min_context_slot: Option<u64>,
+    ) -> Result<LatestBlockhash> {
+        let config = self.query_config(json!({
+            "commitment": commitment,
+            "minContextSlot": min_context_slot
+        }));
+        let response: GetLatestBlockhash =
+            self.request("getLatestBlockhash", json!([config])).await?;
+
+        let blockhash: LatestBlockhash = response.try_into()?;
+        self.context_slot
+            .fetch_max(blockhash.slot, Ordering::Relaxed);
+        Ok(blockhash)
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The Jito block engine at `url` for bundles, sharing this connection's transport
+    pub fn block_engine(&self, url: impl ToString) -> JitoBlockEngine {
+        JitoBlockEngine::new(url, self.transport.clone())
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        program_id: &str,
+    ) -> Result<Vec<KeyedTokenAccount>> {
+        let config = self.query_config(json!({
+            "encoding": "jsonParsed",
+            "commitment": self.commitment
+        }));
+        let accounts: WithContext<Vec<KeyedTokenAccount>> = self
+            .request(
+                "getTokenAccountsByOwner",
+                json!([owner.to_string(), { "programId": program_id }, config]),
+            )
+            .await?;
+
+        Ok(self.observe(accounts))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<T: RpcTransport + 'static> Connection for RpcConnection<T> {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_latest_blockhash_with_context(
+        &self,
+        commitment: Option<CommitmentLevel>,
+        min_context_slots: Option<u32>,
+    ) -> Result<LatestBlockhash> {
+        let commitment = commitment.unwrap_or(self.commitment);
+        let min_context_slot = min_context_slots.map(u64::from).max(self.min_context_slot);
+        if let Some(blockhash) = self.prefetched_blockhash(commitment, min_context_slot) {
+            return Ok(blockhash);
+        }
+
+        self.fetch_blockhash(commitment, min_context_slot).await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn send_raw_transaction(
+        &self,
+        raw_transaction: Vec<u8>,
+        options: Option<&SendTransactionOptions>,
+    ) -> Result<Signature> {
+        tracing::debug!("||| send_raw_transaction |||");
+
+        let encoding = options
+            .and_then(|options| options.send_options.encoding)
+            .unwrap_or_default();
+        let encoded_tx = encoding.encode(&raw_transaction);
+
+        let req_options = self.query_config(match options {
+            Some(options) => json!({
+                "skipPreflight": options.send_options.skip_preflight,
+                "preflightCommitment": options
+                    .send_options
+                    .preflight_commitment
+                    .unwrap_or(self.commitment),
+                "maxRetries": options.send_options.max_retries,
+                "minContextSlot": options.send_options.min_context_slots,
+                "encoding": encoding
+            }),
+            None => json!({
+                "preflightCommitment": self.commitment,
+                "encoding": encoding
+            }),
+        });
+
+        let signature: String = self
+            .request("sendTransaction", json!([encoded_tx, req_options]))
+            .await?;
+
+        Ok(Signature::from_str(&signature)?)
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<Signature> {
+        if self.cluster().await? == Some(Cluster::MainnetBeta) {
+            bail!("refusing to request an airdrop on mainnet");
+        }
+
+        let signature: String = self
+            .request("requestAirdrop", json!([pubkey.to_string(), lamports]))
+            .await?;
+
+        Ok(Signature::from_str(&signature)?)
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>> {
+        let signatures: Vec<String> = signatures.iter().map(|s| s.to_string()).collect();
+        let statuses: WithContext<Vec<Option<SignatureStatus>>> = self
+            .request("getSignatureStatuses", json!([signatures]))
+            .await?;
+
+        Ok(self.observe(statuses))
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn confirm_transaction(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentLevel,
+        timeout_ms: u32,
+    ) -> Result<()> {
+        for _ in 0..timeout_ms.div_ceil(CONFIRM_POLL_INTERVAL_MS) {
+            let status = self
+                .get_signature_statuses(std::slice::from_ref(signature))
+                .await?
+                .pop()
+                .flatten();
+
+            if let Some(status) = status {
+                if let Some(err) = status.err {
+                    bail!("transaction {signature} failed: {err}");
+                }
+                if status.satisfies(commitment) {
+                    return Ok(());
+                }
+            }
+
+            runtime::sleep(Duration::from_millis(CONFIRM_POLL_INTERVAL_MS.into())).await;
+        }
+
+        bail!("transaction {signature} was not confirmed within {timeout_ms}ms")
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_token_accounts(&self, owner: &Pubkey) -> Result<Vec<KeyedTokenAccount>> {
+        let mut accounts = Vec::new();
+        for program_id in TOKEN_PROGRAM_IDS {
+            accounts.extend(self.get_token_accounts_by_owner(owner, program_id).await?);
+        }
+
+        Ok(accounts)
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_account_info(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        let account: WithContext<Option<RpcAccount>> = self
+            .request(
+                "getAccountInfo",
+                json!([
+                    pubkey.to_string(),
+                    self.query_config(
+                        json!({ "encoding": "base64", "commitment": self.commitment })
+                    )
+                ]),
+            )
+            .await?;
+
+        self.observe(account).map(Account::try_from).transpose()
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_parsed_account_info(&self, pubkey: &Pubkey) -> Result<Option<ParsedAccount>> {
+        let account: WithContext<Option<RpcParsedAccount>> = self
+            .request(
+                "getAccountInfo",
+                json!([
+                    pubkey.to_string(),
+                    self.query_config(
+                        json!({ "encoding": "jsonParsed", "commitment": self.commitment })
+                    )
+                ]),
+            )
+            .await?;
+
+        self.observe(account)
+            .map(ParsedAccount::try_from)
+            .transpose()
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        options: Option<&SignaturesForAddressOptions>,
+    ) -> Result<Vec<SignatureInfo>> {
+        let config = self.query_config(
+            options
+                .cloned()
+                .unwrap_or_default()
+                .to_config(self.commitment),
+        );
+        self.request(
+            "getSignaturesForAddress",
+            json!([address.to_string(), config]),
+        )
+        .await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ParsedTransactionWithMeta>> {
+        self.request(
+            "getTransaction",
+            json!([
+                signature.to_string(),
+                {
+                    "encoding": "jsonParsed",
+                    "commitment": history_commitment(self.commitment),
+                    "maxSupportedTransactionVersion": 0
+                }
+            ]),
+        )
+        .await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.request(
+            "getMinimumBalanceForRentExemption",
+            json!([data_len, { "commitment": self.commitment }]),
+        )
+        .await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_slot(&self) -> Result<u64> {
+        let config = self.query_config(json!({ "commitment": self.commitment }));
+        self.request("getSlot", json!([config])).await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_block_height(&self) -> Result<u64> {
+        let config = self.query_config(json!({ "commitment": self.commitment }));
+        self.request("getBlockHeight", json!([config])).await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_epoch_info(&self) -> Result<EpochInfo> {
+        let config = self.query_config(json!({ "commitment": self.commitment }));
+        self.request("getEpochInfo", json!([config])).await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_health(&self) -> Result<()> {
+        let health: String = self.request("getHealth", json!([])).await?;
+        if health != "ok" {
+            bail!("node at {} is {health}", self.url);
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_version(&self) -> Result<RpcVersion> {
+        self.request("getVersion", json!([])).await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_genesis_hash(&self) -> Result<Hash> {
+        let genesis_hash: String = self.request("getGenesisHash", json!([])).await?;
+        Ok(Hash::from_str(&genesis_hash)?)
+    }
+
+    fn metrics(&self) -> Option<ConnectionMetrics> {
+        Some(self.metrics.metrics())
+    }
+
+    fn context_slot(&self) -> Option<u64> {
+        RpcConnection::context_slot(self)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<T: RpcTransport + 'static> DasConnection for RpcConnection<T> {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_asset(&self, id: &Pubkey) -> Result<Asset> {
+        self.request("getAsset", json!({ "id": id.to_string() }))
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.url))
+    )]
+    async fn get_assets_by_owner(
+        &self,
+        owner: &Pubkey,
+        page: u32,
+        limit: u32,
+    ) -> Result<AssetList> {
+        self.request(
+            "getAssetsByOwner",
+            json!({ "ownerAddress": owner.to_string(), "page": page, "limit": limit }),
+        )
+        .await
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<T: RpcTransport + 'static> SolanaPayClient for RpcConnection<T> {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(link = %link))
+    )]
+    async fn get_transaction_request(&self, link: &Url) -> Result<TransactionRequestMetadata> {
+        Ok(serde_json::from_slice(
+            &self.transport.get(link.as_str()).await?,
+        )?)
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(link = %link))
+    )]
+    async fn post_transaction_request(
+        &self,
+        link: &Url,
+        account: &Pubkey,
+    ) -> Result<TransactionRequestResponse> {
+        let body = serde_json::to_vec(&TransactionRequestBody {
+            account: account.to_string(),
+        })?;
+
+        Ok(serde_json::from_slice(
+            &self.transport.post_json(link.as_str(), body).await?,
+        )?)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::metrics::EndpointHealth;
+    use crate::types::{SendOptions, TransactionEncoding};
+
+    use super::*;
+
+    /// A node answering `getVersion`, and every other request with `answer`, or failing
+    /// every request without one
+    struct FakeNode {
+        answer: Option<serde_json::Value>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcTransport for FakeNode {
+        async fn post_json(&self, _url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+            let Some(answer) = &self.answer else {
+                bail!("connection refused");
+            };
+            let request: serde_json::Value = serde_json::from_slice(&body)?;
+            let response = match request["method"].as_str() {
+                Some("getVersion") => json!({
+                    "jsonrpc": "2.0",
+                    "result": { "solana-core": "2.1.0", "feature-set": 1 },
+                    "id": 1
+                }),
+                _ => answer.clone(),
+            };
+            Ok(serde_json::to_vec(&response)?)
+        }
+
+        async fn get(&self, _url: &str) -> Result<Vec<u8>> {
+            bail!("unused")
+        }
+    }
+
+    /// A node at slot 200 remembering the params of the last request and counting them
+    #[derive(Default)]
+    struct RecordingNode {
+        params: Arc<Mutex<serde_json::Value>>,
+        requests: Arc<AtomicU64>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcTransport for RecordingNode {
+        async fn post_json(&self, _url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+            let request: serde_json::Value = serde_json::from_slice(&body)?;
+            *self.params.lock().unwrap() = request["params"].clone();
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let result = match request["method"].as_str() {
+                Some("getLatestBlockhash") => json!({
+                    "context": { "slot": 200 },
+                    "value": {
+                        "blockhash": "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+                        "lastValidBlockHeight": 350
+                    }
+                }),
+                Some("sendTransaction") => json!(Signature::default().to_string()),
+                _ => json!(200),
+            };
+            Ok(serde_json::to_vec(
+                &json!({ "jsonrpc": "2.0", "result": result, "id": 1 }),
+            )?)
+        }
+
+        async fn get(&self, _url: &str) -> Result<Vec<u8>> {
+            bail!("unused")
+        }
+    }
+
+    /// A node that never answers
+    struct HungNode;
+
+    #[async_trait::async_trait]
+    impl RpcTransport for HungNode {
+        async fn post_json(&self, _url: &str, _body: Vec<u8>) -> Result<Vec<u8>> {
+            std::future::pending().await
+        }
+
+        async fn get(&self, _url: &str) -> Result<Vec<u8>> {
+            std::future::pending().await
+        }
+    }
+
+    #[test]
+    fn probe_tells_unhealthy_nodes_from_unreachable_ones() {
+        let probe = |answer| {
+            let connection =
+                RpcConnection::with_transport("http://rpc".to_string(), FakeNode { answer });
+            tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .unwrap()
+                .block_on(connection.probe())
+        };
+
+        let healthy = probe(Some(json!({ "jsonrpc": "2.0", "result": "ok", "id": 1 })));
+        assert_eq!(healthy.health, EndpointHealth::Healthy);
+        assert_eq!(healthy.version.unwrap().solana_core, "2.1.0");
+
+        let behind = probe(Some(json!({
+            "jsonrpc": "2.0",
+            "error": { "code": -32005, "message": "Node is behind by 42 slots" },
+            "id": 1
+        })));
+        assert_eq!(behind.health, EndpointHealth::Degraded);
+        assert!(behind.error.unwrap().contains("behind by 42 slots"));
+
+        let down = probe(None);
+        assert_eq!(down.health, EndpointHealth::Down);
+        assert!(down.version.is_none());
+    }
+
+    #[test]
+    fn reads_the_epoch_progress() {
+        let answer = json!({
+            "jsonrpc": "2.0",
+            "result": {
+                "absoluteSlot": 166_598,
+                "blockHeight": 166_500,
+                "epoch": 27,
+                "slotIndex": 2_790,
+                "slotsInEpoch": 8_192,
+                "transactionCount": 22_661_093
+            },
+            "id": 1
+        });
+        let connection = RpcConnection::with_transport(
+            "http://rpc".to_string(),
+            FakeNode {
+                answer: Some(answer),
+            },
+        );
+        let epoch_info = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(connection.get_epoch_info())
+            .unwrap();
+
+        assert_eq!(epoch_info.epoch, 27);
+        assert_eq!(epoch_info.slots_remaining(), 5_402);
+    }
+
+    #[test]
+    fn refuses_airdrops_on_mainnet() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let connection = |genesis_hash: &str| {
+            RpcConnection::with_transport(
+                "http://rpc".to_string(),
+                FakeNode {
+                    answer: Some(json!({ "jsonrpc": "2.0", "result": genesis_hash, "id": 1 })),
+                },
+            )
+        };
+
+        let devnet = connection(crate::explorer::DEVNET_GENESIS_HASH);
+        assert_eq!(
+            runtime.block_on(devnet.cluster()).unwrap(),
+            Some(Cluster::Devnet)
+        );
+
+        let mainnet = connection(crate::explorer::MAINNET_GENESIS_HASH);
+        let err = runtime
+            .block_on(mainnet.request_airdrop(&Pubkey::new_unique(), 1))
+            .unwrap_err();
+        assert!(err.to_string().contains("on mainnet"));
+    }
+
+    #[test]
+    fn hung_requests_time_out() {
+        let connection = RpcConnection::with_transport("http://rpc".to_string(), HungNode)
+            .with_request_timeout(Some(Duration::from_millis(10)));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        let err = runtime.block_on(connection.get_health()).unwrap_err();
+        assert!(err.to_string().contains("getHealth timed out"));
+        assert_eq!(connection.metrics().errors, 1);
+
+        let expired = connection.with_deadline(Duration::ZERO);
+        let err = runtime.block_on(expired.get_version()).unwrap_err();
+        assert!(err.to_string().contains("deadline exceeded"));
+    }
+
+    #[test]
+    fn sends_transactions_in_the_configured_encoding() {
+        let node = RecordingNode::default();
+        let params = node.params.clone();
+        let connection = RpcConnection::with_transport("http://rpc".to_string(), node);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let raw_transaction = vec![1, 2, 3, 255];
+
+        runtime
+            .block_on(connection.send_raw_transaction(raw_transaction.clone(), None))
+            .unwrap();
+        assert_eq!(params.lock().unwrap()[0], "AQID/w==");
+        assert_eq!(params.lock().unwrap()[1]["encoding"], "base64");
+
+        let options = SendOptions::builder()
+            .encoding(TransactionEncoding::Base58)
+            .build_transaction_options();
+        runtime
+            .block_on(connection.send_raw_transaction(raw_transaction.clone(), Some(&options)))
+            .unwrap();
+        assert_eq!(
+            params.lock().unwrap()[0],
+            solana_sdk::bs58::encode(&raw_transaction).into_string()
+        );
+        assert_eq!(params.lock().unwrap()[1]["encoding"], "base58");
+    }
+
+    #[test]
+    fn passes_the_min_context_slot() {
+        let node = RecordingNode::default();
+        let params = node.params.clone();
+        let connection = RpcConnection::with_transport("http://rpc".to_string(), node)
+            .with_min_context_slot(100);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        let blockhash = runtime
+            .block_on(connection.get_latest_blockhash_with_context(None, Some(150)))
+            .unwrap();
+        assert_eq!(params.lock().unwrap()[0]["minContextSlot"], 150);
+        assert_eq!(blockhash.slot, 200);
+        assert_eq!(blockhash.last_valid_block_height, 350);
+        assert_eq!(connection.context_slot(), Some(200));
+
+        runtime.block_on(connection.get_slot()).unwrap();
+        assert_eq!(params.lock().unwrap()[0]["minContextSlot"], 100);
+    }
+
+    #[test]
+    fn applies_the_default_commitment() {
+        let node = RecordingNode::default();
+        let params = node.params.clone();
+        let connection = RpcConnection::with_transport("http://rpc".to_string(), node)
+            .with_commitment(CommitmentLevel::Confirmed);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime
+            .block_on(connection.get_latest_blockhash_with_context(None, None))
+            .unwrap();
+        assert_eq!(params.lock().unwrap()[0]["commitment"], "confirmed");
+
+        runtime
+            .block_on(connection.send_raw_transaction(vec![1], None))
+            .unwrap();
+        assert_eq!(
+            params.lock().unwrap()[1]["preflightCommitment"],
+            "confirmed"
+        );
+
+        runtime.block_on(connection.get_slot()).unwrap();
+        assert_eq!(params.lock().unwrap()[0]["commitment"], "confirmed");
+
+        // asking for another commitment overrides it
+        runtime
+            .block_on(
+                connection
+                    .get_latest_blockhash_with_context(Some(CommitmentLevel::Finalized), None),
+            )
+            .unwrap();
+        assert_eq!(params.lock().unwrap()[0]["commitment"], "finalized");
+    }
+
+    #[test]
+    fn sends_with_the_prefetched_blockhash() {
+        let node = RecordingNode::default();
+        let requests = node.requests.clone();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let connection = RpcConnection::with_transport("http://rpc".to_string(), node)
+                .with_blockhash_prefetch(CommitmentLevel::Confirmed);
+            runtime::sleep(Duration::from_millis(10)).await;
+            assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+            let blockhash = connection
+                .clone()
+                .get_latest_blockhash_with_context(Some(CommitmentLevel::Confirmed), None)
+                .await
+                .unwrap();
+            assert_eq!(blockhash.slot, 200);
+            assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+            // another commitment, or a later slot than the prefetched one, asks the node
+            connection
+                .get_latest_blockhash_with_context(Some(CommitmentLevel::Finalized), None)
+                .await
+                .unwrap();
+            connection
+                .get_latest_blockhash_with_context(Some(CommitmentLevel::Confirmed), Some(300))
+                .await
+                .unwrap();
+            assert_eq!(requests.load(Ordering::SeqCst), 3);
+        });
+    }
+}
@@ -0,0 +1,55 @@
+//! Timers and background tasks of the connections: tokio on native targets and the JS event
+//! loop on wasm32, where tokio has no driver.
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::maybe_send::MaybeSend;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: Duration) {
+    let millis = duration.as_millis().try_into().unwrap_or(i32::MAX);
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        set_timeout(&resolve, millis);
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+extern "C" {
+    /// The global `setTimeout`, pages and Web Workers both have one
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> wasm_bindgen::JsValue;
+}
+
+/// The output of `future`, or `None` if it takes longer than `duration`
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn timeout<T>(duration: Duration, future: impl Future<Output = T>) -> Option<T> {
+    tokio::time::timeout(duration, future).await.ok()
+}
+
+/// The output of `future`, or `None` if it takes longer than `duration`.
+/// A `fetch` keeps running in the background, the caller just stops waiting for it.
+#[cfg(target_arch = "wasm32")]
+pub async fn timeout<T>(duration: Duration, future: impl Future<Output = T>) -> Option<T> {
+    use futures::future::{select, Either};
+
+    match select(Box::pin(future), Box::pin(sleep(duration))).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+/// Run `future` in the background, natively on the current tokio runtime
+pub fn spawn(future: impl Future<Output = ()> + MaybeSend + 'static) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(future);
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(future);
+}
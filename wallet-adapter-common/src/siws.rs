@@ -0,0 +1,395 @@
+//! Sign In With Solana (SIWS) messages and their server-side verification.
+//!
+//! The message format follows `createSignInMessageText` from `@solana/wallet-standard-util`,
+//! so messages signed by Phantom/Solflare/Backpack verify against the same input here.
+
+use std::fmt;
+
+use anyhow::{bail, ensure, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::offchain_message::verify_message;
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Solana account:";
+
+/// Sign-in request sent to the wallet, every field is optional and filled by the wallet if omitted
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct SignInInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Vec<String>>,
+}
+
+/// What the wallet returns after signing in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct SignInOutput {
+    /// The 32 bytes of the public key
+    #[cfg_attr(feature = "tsify", tsify(type = "number[]"))]
+    pub address: Pubkey,
+    pub signed_message: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// The parsed text of a SIWS message
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SignInMessage {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: Option<String>,
+    pub version: Option<String>,
+    pub chain_id: Option<String>,
+    pub nonce: Option<String>,
+    pub issued_at: Option<String>,
+    pub expiration_time: Option<String>,
+    pub not_before: Option<String>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SignInMessage {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.split('\n').peekable();
+
+        let header = lines.next().context("empty message")?;
+        let domain = header
+            .strip_suffix(HEADER_SUFFIX)
+            .context("invalid message header")?;
+        let address = lines.next().context("missing address")?;
+
+        let mut message = Self {
+            domain: domain.to_string(),
+            address: address.to_string(),
+            ..Default::default()
+        };
+
+        // statement and fields are each preceded by an empty line
+        while let Some(line) = lines.next() {
+            if !line.is_empty() {
+                bail!("unexpected line: {line}");
+            }
+
+            let Some(&next) = lines.peek() else {
+                bail!("unexpected end of message");
+            };
+
+            if !is_field(next) {
+                message.statement = lines.next().map(str::to_string);
+                continue;
+            }
+
+            while let Some(line) = lines.next() {
+                let (key, value) = line.split_once(": ").unwrap_or((line, ""));
+                let value = Some(value.to_string());
+                match key {
+                    "URI" => message.uri = value,
+                    "Version" => message.version = value,
+                    "Chain ID" => message.chain_id = value,
+                    "Nonce" => message.nonce = value,
+                    "Issued At" => message.issued_at = value,
+                    "Expiration Time" => message.expiration_time = value,
+                    "Not Before" => message.not_before = value,
+                    "Request ID" => message.request_id = value,
+                    "Resources:" => {
+                        while let Some(resource) = lines.next_if(|l| l.starts_with("- ")) {
+                            message.resources.push(resource[2..].to_string());
+                        }
+                    }
+                    _ => bail!("unknown field: {line}"),
+                }
+            }
+        }
+
+        Ok(message)
+    }
+
+    /// Check that every field requested in `input` made it into the signed message unchanged
+    pub fn matches_input(&self, input: &SignInInput) -> Result<()> {
+        fn check(name: &str, expected: &Option<String>, actual: &Option<String>) -> Result<()> {
+            if expected.is_some() {
+                ensure!(
+                    expected == actual,
+                    "{name} does not match the sign-in input"
+                );
+            }
+            Ok(())
+        }
+
+        if let Some(domain) = &input.domain {
+            ensure!(
+                domain == &self.domain,
+                "domain does not match the sign-in input"
+            );
+        }
+        if let Some(address) = &input.address {
+            ensure!(
+                address == &self.address,
+                "address does not match the sign-in input"
+            );
+        }
+        check("statement", &input.statement, &self.statement)?;
+        check("uri", &input.uri, &self.uri)?;
+        check("version", &input.version, &self.version)?;
+        check("chain id", &input.chain_id, &self.chain_id)?;
+        check("nonce", &input.nonce, &self.nonce)?;
+        check("issued at", &input.issued_at, &self.issued_at)?;
+        check(
+            "expiration time",
+            &input.expiration_time,
+            &self.expiration_time,
+        )?;
+        check("not before", &input.not_before, &self.not_before)?;
+        check("request id", &input.request_id, &self.request_id)?;
+        if let Some(resources) = &input.resources {
+            ensure!(
+                resources == &self.resources,
+                "resources do not match the sign-in input"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn is_field(line: &str) -> bool {
+    [
+        "URI: ",
+        "Version: ",
+        "Chain ID: ",
+        "Nonce: ",
+        "Issued At: ",
+        "Expiration Time: ",
+        "Not Before: ",
+        "Request ID: ",
+    ]
+    .iter()
+    .any(|prefix| line.starts_with(prefix))
+        || line == "Resources:"
+}
+
+impl fmt::Display for SignInMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{HEADER_SUFFIX}\n{}", self.domain, self.address)?;
+
+        if let Some(statement) = &self.statement {
+            write!(f, "\n\n{statement}")?;
+        }
+
+        let mut fields = vec![];
+        let optional = [
+            ("URI", &self.uri),
+            ("Version", &self.version),
+            ("Chain ID", &self.chain_id),
+            ("Nonce", &self.nonce),
+            ("Issued At", &self.issued_at),
+            ("Expiration Time", &self.expiration_time),
+            ("Not Before", &self.not_before),
+            ("Request ID", &self.request_id),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                fields.push(format!("{key}: {value}"));
+            }
+        }
+        if !self.resources.is_empty() {
+            fields.push("Resources:".to_string());
+            for resource in &self.resources {
+                fields.push(format!("- {resource}"));
+            }
+        }
+
+        if !fields.is_empty() {
+            write!(f, "\n\n{}", fields.join("\n"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Server-side expectations checked on top of the sign-in input
+#[derive(Debug, Clone)]
+pub struct SignInVerifyOptions {
+    /// The domain the backend is served from, the message must be bound to it
+    pub expected_domain: Option<String>,
+    /// The nonce issued to this client, prevents replaying old sign-ins
+    pub expected_nonce: Option<String>,
+    /// How old `Issued At` may be
+    pub max_age: Duration,
+    /// Tolerated clock difference between the client and the server
+    pub clock_skew: Duration,
+    pub now: DateTime<Utc>,
+}
+
+impl Default for SignInVerifyOptions {
+    fn default() -> Self {
+        Self {
+            expected_domain: None,
+            expected_nonce: None,
+            max_age: Duration::minutes(10),
+            clock_skew: Duration::minutes(1),
+            now: Utc::now(),
+        }
+    }
+}
+
+fn parse_time(name: &str, value: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(value)
+        .with_context(|| format!("invalid {name}: {value}"))?
+        .with_timezone(&Utc))
+}
+
+/// Verify the output of a sign-in against the input the backend handed out.
+/// Returns the parsed message on success.
+pub fn verify_sign_in(
+    input: &SignInInput,
+    output: &SignInOutput,
+    options: &SignInVerifyOptions,
+) -> Result<SignInMessage> {
+    let text = std::str::from_utf8(&output.signed_message).context("message is not utf8")?;
+    let message = SignInMessage::parse(text)?;
+
+    ensure!(
+        message.address == output.address.to_string(),
+        "message address does not match the signing account"
+    );
+    message.matches_input(input)?;
+
+    if let Some(domain) = &options.expected_domain {
+        ensure!(
+            &message.domain == domain,
+            "unexpected domain {}",
+            message.domain
+        );
+    }
+    if let Some(nonce) = &options.expected_nonce {
+        ensure!(message.nonce.as_ref() == Some(nonce), "unexpected nonce");
+    }
+
+    let now = options.now;
+    if let Some(issued_at) = &message.issued_at {
+        let issued_at = parse_time("issued at", issued_at)?;
+        ensure!(
+            issued_at <= now + options.clock_skew,
+            "message is issued in the future"
+        );
+        ensure!(issued_at + options.max_age >= now, "message is too old");
+    }
+    if let Some(expiration_time) = &message.expiration_time {
+        let expiration_time = parse_time("expiration time", expiration_time)?;
+        ensure!(
+            expiration_time + options.clock_skew > now,
+            "message has expired"
+        );
+    }
+    if let Some(not_before) = &message.not_before {
+        let not_before = parse_time("not before", not_before)?;
+        ensure!(
+            not_before <= now + options.clock_skew,
+            "message is not valid yet"
+        );
+    }
+
+    let signature = Signature::try_from(output.signature.as_slice())
+        .map_err(|_| anyhow::anyhow!("invalid signature length"))?;
+    ensure!(
+        verify_message(&output.address, &output.signed_message, &signature),
+        "invalid signature"
+    );
+
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    fn message(address: &Pubkey) -> SignInMessage {
+        SignInMessage {
+            domain: "example.com".to_string(),
+            address: address.to_string(),
+            statement: Some("Sign in to example".to_string()),
+            uri: Some("https://example.com".to_string()),
+            version: Some("1".to_string()),
+            chain_id: Some("mainnet".to_string()),
+            nonce: Some("abc123".to_string()),
+            issued_at: Some("2024-01-01T00:00:00Z".to_string()),
+            resources: vec!["https://example.com/terms".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn message_text_roundtrips() {
+        let message = message(&Pubkey::new_unique());
+        assert_eq!(SignInMessage::parse(&message.to_string()).unwrap(), message);
+
+        let minimal = SignInMessage {
+            domain: "example.com".to_string(),
+            address: Pubkey::new_unique().to_string(),
+            ..Default::default()
+        };
+        assert_eq!(SignInMessage::parse(&minimal.to_string()).unwrap(), minimal);
+    }
+
+    #[test]
+    fn verify_sign_in_checks_signature_and_time_window() {
+        let keypair = Keypair::new();
+        let signed_message = message(&keypair.pubkey()).to_string().into_bytes();
+        let output = SignInOutput {
+            address: keypair.pubkey(),
+            signature: keypair.sign_message(&signed_message).as_ref().to_vec(),
+            signed_message,
+        };
+        let input = SignInInput {
+            domain: Some("example.com".to_string()),
+            nonce: Some("abc123".to_string()),
+            ..Default::default()
+        };
+        let options = SignInVerifyOptions {
+            now: parse_time("now", "2024-01-01T00:05:00Z").unwrap(),
+            ..Default::default()
+        };
+
+        assert!(verify_sign_in(&input, &output, &options).is_ok());
+
+        let late = SignInVerifyOptions {
+            now: parse_time("now", "2024-01-02T00:00:00Z").unwrap(),
+            ..Default::default()
+        };
+        assert!(verify_sign_in(&input, &output, &late).is_err());
+
+        let mut tampered = output.clone();
+        tampered.signature[0] ^= 1;
+        assert!(verify_sign_in(&input, &tampered, &options).is_err());
+    }
+}
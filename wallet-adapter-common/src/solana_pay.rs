@@ -0,0 +1,263 @@
+//! Solana Pay links, see <https://docs.solanapay.com/spec>.
+//!
+//! A transfer request link holds the payment itself: the recipient, amount and token.
+//! A transaction request link points the wallet to a merchant server: the wallet fetches its
+//! label and icon with a GET, then POSTs the account paying and receives the transaction to sign.
+
+use anyhow::{bail, ensure, Context, Result};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use url::Url;
+
+use crate::amount::{validate_decimal, UiAmount};
+use crate::maybe_send::{MaybeSend, MaybeSync};
+
+pub const SOLANA_PAY_SCHEME: &str = "solana";
+
+/// Everything but the unreserved characters of RFC 3986
+const QUERY_VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// A `solana:` URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolanaPayRequest {
+    Transfer(TransferRequest),
+    /// The HTTPS link of the transaction request
+    Transaction(Url),
+}
+
+impl SolanaPayRequest {
+    pub fn parse(url: &str) -> Result<Self> {
+        let is_transaction_request = url
+            .strip_prefix(SOLANA_PAY_SCHEME)
+            .and_then(|link| link.strip_prefix(':'))
+            .is_some_and(|link| link.starts_with("https"));
+
+        Ok(if is_transaction_request {
+            Self::Transaction(parse_transaction_request_url(url)?)
+        } else {
+            Self::Transfer(TransferRequest::parse(url)?)
+        })
+    }
+}
+
+/// A payment of `amount` SOL, or `amount` of the `spl_token` mint, to `recipient`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferRequest {
+    /// The wallet paid, not its token account
+    pub recipient: Pubkey,
+    /// A decimal amount in SOL or tokens, eg. `0.5`, the wallet asks the user when it is missing
+    pub amount: Option<String>,
+    pub spl_token: Option<Pubkey>,
+    /// Added to the transfer instruction as read-only keys, so the merchant can find the payment
+    pub references: Vec<Pubkey>,
+    /// Where the request is from, eg. the merchant
+    pub label: Option<String>,
+    /// What the request is for, eg. the item bought
+    pub message: Option<String>,
+    /// Added to the transaction in a memo instruction, public on chain
+    pub memo: Option<String>,
+}
+
+impl TransferRequest {
+    pub fn new(recipient: Pubkey) -> Self {
+        Self {
+            recipient,
+            amount: None,
+            spl_token: None,
+            references: Vec::new(),
+            label: None,
+            message: None,
+            memo: None,
+        }
+    }
+
+    pub fn parse(url: &str) -> Result<Self> {
+        let url = Url::parse(url).with_context(|| format!("invalid transfer request {url}"))?;
+        ensure!(
+            url.scheme() == SOLANA_PAY_SCHEME,
+            "{url} is not a solana: URL"
+        );
+
+        let recipient = url
+            .path()
+            .parse()
+            .with_context(|| format!("invalid recipient {}", url.path()))?;
+        let mut request = Self::new(recipient);
+
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "amount" => {
+                    validate_decimal(&value)?;
+                    request.amount = Some(value.into_owned());
+                }
+                "spl-token" => request.spl_token = Some(value.parse()?),
+                "reference" => request.references.push(value.parse()?),
+                "label" => request.label = Some(value.into_owned()),
+                "message" => request.message = Some(value.into_owned()),
+                "memo" => request.memo = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+
+        Ok(request)
+    }
+
+    /// The `solana:` URL to display, eg. in a QR code
+    pub fn to_url(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(amount) = &self.amount {
+            params.push(("amount", amount.clone()));
+        }
+        if let Some(spl_token) = &self.spl_token {
+            params.push(("spl-token", spl_token.to_string()));
+        }
+        for reference in &self.references {
+            params.push(("reference", reference.to_string()));
+        }
+        for (key, value) in [
+            ("label", &self.label),
+            ("message", &self.message),
+            ("memo", &self.memo),
+        ] {
+            if let Some(value) = value {
+                params.push((key, value.clone()));
+            }
+        }
+
+        let mut url = format!("{SOLANA_PAY_SCHEME}:{}", self.recipient);
+        for (i, (key, value)) in params.iter().enumerate() {
+            let separator = if i == 0 { '?' } else { '&' };
+            url.push_str(&format!(
+                "{separator}{key}={}",
+                utf8_percent_encode(value, QUERY_VALUE)
+            ));
+        }
+        url
+    }
+
+    /// `amount` in base units of a mint with `decimals`, 9 for SOL
+    pub fn base_units(&self, decimals: u8) -> Result<u64> {
+        let amount = self
+            .amount
+            .as_deref()
+            .context("the transfer request has no amount")?;
+        Ok(UiAmount::parse(amount, decimals)?.amount)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait SolanaPayClient: MaybeSend + MaybeSync {
+    /// The GET request, the merchant's label and icon to show before asking for the transaction
+    async fn get_transaction_request(&self, link: &Url) -> Result<TransactionRequestMetadata>;
+
+    /// The POST request, the transaction the merchant built for `account`
+    async fn post_transaction_request(
+        &self,
+        link: &Url,
+        account: &Pubkey,
+    ) -> Result<TransactionRequestResponse>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionRequestMetadata {
+    pub label: String,
+    /// An SVG, PNG or WebP image
+    pub icon: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionRequestBody {
+    pub account: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionRequestResponse {
+    /// A base64 serialized transaction
+    pub transaction: String,
+    /// Describes the transaction to the user
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// The HTTPS link of a `solana:` transaction request URL, decoding it when it was URL-encoded
+pub fn parse_transaction_request_url(url: &str) -> Result<Url> {
+    let link = url
+        .strip_prefix(SOLANA_PAY_SCHEME)
+        .and_then(|link| link.strip_prefix(':'))
+        .with_context(|| format!("{url} is not a solana: URL"))?;
+
+    let link = if link.contains("://") {
+        link.to_string()
+    } else {
+        percent_decode_str(link).decode_utf8()?.into_owned()
+    };
+
+    let link = Url::parse(&link).with_context(|| format!("invalid transaction request {link}"))?;
+    if link.scheme() != "https" {
+        bail!("transaction requests have to be HTTPS, got {link}");
+    }
+    Ok(link)
+}
+
+/// The `solana:` URL of a transaction request to `link`
+pub fn transaction_request_url(link: &Url) -> Result<String> {
+    ensure!(
+        link.scheme() == "https",
+        "transaction requests have to be HTTPS, got {link}"
+    );
+
+    // links with a query have to be encoded so wallets don't take it for the solana: one
+    let link = if link.query().is_some() {
+        url::form_urlencoded::byte_serialize(link.as_str().as_bytes()).collect()
+    } else {
+        link.to_string()
+    };
+    Ok(format!("{SOLANA_PAY_SCHEME}:{link}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_requests_round_trip() {
+        let url = "solana:mvines9iiHiQTysrwkJjGf2gb9Ex9jXJX8ns3qwf2kN?amount=0.01&spl-token=EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v&reference=82ZJ7nbGpixjeDCmEhUcmwXYfvurzAgGdtSMuHnUgyny&label=Michael&message=Thanks%20for%20all%20the%20fish&memo=OrderId12345";
+        let SolanaPayRequest::Transfer(request) = SolanaPayRequest::parse(url).unwrap() else {
+            panic!("expected a transfer request");
+        };
+
+        assert_eq!(request.amount.as_deref(), Some("0.01"));
+        assert_eq!(request.references.len(), 1);
+        assert_eq!(request.message.as_deref(), Some("Thanks for all the fish"));
+        assert_eq!(request.base_units(6).unwrap(), 10_000);
+        assert!(request.base_units(1).is_err());
+        assert_eq!(request.to_url(), url);
+
+        assert!(TransferRequest::parse(
+            "solana:mvines9iiHiQTysrwkJjGf2gb9Ex9jXJX8ns3qwf2kN?amount=.5"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn transaction_request_links_round_trip() {
+        let url = "solana:https%3A%2F%2Fexample.com%2Fsolana-pay%3Forder%3D12345";
+        let link = parse_transaction_request_url(url).unwrap();
+        assert_eq!(link.as_str(), "https://example.com/solana-pay?order=12345");
+        assert_eq!(transaction_request_url(&link).unwrap(), url);
+
+        let link = parse_transaction_request_url("solana:https://example.com/solana-pay").unwrap();
+        assert_eq!(
+            transaction_request_url(&link).unwrap(),
+            "solana:https://example.com/solana-pay"
+        );
+
+        assert!(parse_transaction_request_url("solana:http://example.com").is_err());
+    }
+}
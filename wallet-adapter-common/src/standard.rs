@@ -0,0 +1,218 @@
+//! Types from the Wallet Standard (https://github.com/wallet-standard/wallet-standard)
+//! and its Solana extension, serialized to the same identifiers the JS packages use.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SolanaChain {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+}
+
+impl SolanaChain {
+    pub const ALL: [SolanaChain; 4] = [
+        SolanaChain::Mainnet,
+        SolanaChain::Devnet,
+        SolanaChain::Testnet,
+        SolanaChain::Localnet,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SolanaChain::Mainnet => "solana:mainnet",
+            SolanaChain::Devnet => "solana:devnet",
+            SolanaChain::Testnet => "solana:testnet",
+            SolanaChain::Localnet => "solana:localnet",
+        }
+    }
+}
+
+impl fmt::Display for SolanaChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SolanaChain {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.into_iter().find(|chain| chain.as_str() == s) {
+            Some(chain) => Ok(chain),
+            None => bail!("unknown solana chain {s}"),
+        }
+    }
+}
+
+impl TryFrom<String> for SolanaChain {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<SolanaChain> for String {
+    fn from(chain: SolanaChain) -> Self {
+        chain.to_string()
+    }
+}
+
+/// Feature identifiers a wallet advertises, unknown ones are kept in `Other`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum WalletFeature {
+    StandardConnect,
+    StandardDisconnect,
+    StandardEvents,
+    SolanaSignTransaction,
+    SolanaSignAndSendTransaction,
+    SolanaSignMessage,
+    SolanaSignIn,
+    Other(String),
+}
+
+impl WalletFeature {
+    pub fn as_str(&self) -> &str {
+        match self {
+            WalletFeature::StandardConnect => "standard:connect",
+            WalletFeature::StandardDisconnect => "standard:disconnect",
+            WalletFeature::StandardEvents => "standard:events",
+            WalletFeature::SolanaSignTransaction => "solana:signTransaction",
+            WalletFeature::SolanaSignAndSendTransaction => "solana:signAndSendTransaction",
+            WalletFeature::SolanaSignMessage => "solana:signMessage",
+            WalletFeature::SolanaSignIn => "solana:signIn",
+            WalletFeature::Other(feature) => feature,
+        }
+    }
+}
+
+impl fmt::Display for WalletFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for WalletFeature {
+    fn from(value: &str) -> Self {
+        match value {
+            "standard:connect" => WalletFeature::StandardConnect,
+            "standard:disconnect" => WalletFeature::StandardDisconnect,
+            "standard:events" => WalletFeature::StandardEvents,
+            "solana:signTransaction" => WalletFeature::SolanaSignTransaction,
+            "solana:signAndSendTransaction" => WalletFeature::SolanaSignAndSendTransaction,
+            "solana:signMessage" => WalletFeature::SolanaSignMessage,
+            "solana:signIn" => WalletFeature::SolanaSignIn,
+            other => WalletFeature::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for WalletFeature {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<WalletFeature> for String {
+    fn from(feature: WalletFeature) -> Self {
+        feature.to_string()
+    }
+}
+
+/// An account exposed by a wallet, mirrors `WalletAccount` of `@wallet-standard/base`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletAccount {
+    /// Base58 encoded address
+    pub address: String,
+    pub public_key: Vec<u8>,
+    pub chains: Vec<SolanaChain>,
+    pub features: Vec<WalletFeature>,
+    pub label: Option<String>,
+    pub icon: Option<String>,
+}
+
+impl WalletAccount {
+    pub fn new(public_key: Pubkey, chains: Vec<SolanaChain>, features: Vec<WalletFeature>) -> Self {
+        Self {
+            address: public_key.to_string(),
+            public_key: public_key.to_bytes().to_vec(),
+            chains,
+            features,
+            label: None,
+            icon: None,
+        }
+    }
+
+    pub fn pubkey(&self) -> anyhow::Result<Pubkey> {
+        Ok(Pubkey::try_from(self.public_key.as_slice())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uses_the_identifiers_of_the_js_packages() {
+        for chain in SolanaChain::ALL {
+            assert_eq!(chain.as_str().parse::<SolanaChain>().unwrap(), chain);
+        }
+        assert!("solana:unknown".parse::<SolanaChain>().is_err());
+
+        assert_eq!(
+            WalletFeature::from("solana:signIn"),
+            WalletFeature::SolanaSignIn
+        );
+        assert_eq!(
+            WalletFeature::from("phantom:experimental"),
+            WalletFeature::Other("phantom:experimental".to_string())
+        );
+        assert_eq!(
+            WalletFeature::Other("phantom:experimental".to_string()).to_string(),
+            "phantom:experimental"
+        );
+    }
+
+    #[test]
+    fn serializes_accounts_like_wallet_standard() {
+        let public_key = Pubkey::new_unique();
+        let account = WalletAccount::new(
+            public_key,
+            vec![SolanaChain::Devnet],
+            vec![WalletFeature::SolanaSignMessage, "custom:feature".into()],
+        );
+
+        let json = serde_json::to_value(&account).unwrap();
+        assert_eq!(json["address"], public_key.to_string());
+        assert_eq!(json["publicKey"].as_array().unwrap().len(), 32);
+        assert_eq!(json["chains"], serde_json::json!(["solana:devnet"]));
+        assert_eq!(
+            json["features"],
+            serde_json::json!(["solana:signMessage", "custom:feature"])
+        );
+
+        let read: WalletAccount = serde_json::from_value(json).unwrap();
+        assert_eq!(read, account);
+        assert_eq!(read.pubkey().unwrap(), public_key);
+
+        let unknown_chain = serde_json::json!({
+            "address": public_key.to_string(),
+            "publicKey": public_key.to_bytes(),
+            "chains": ["solana:unknown"],
+            "features": [],
+            "label": null,
+            "icon": null,
+        });
+        assert!(serde_json::from_value::<WalletAccount>(unknown_chain).is_err());
+    }
+}
@@ -5,3 +5,10 @@ pub trait KeypairStorage: std::fmt::Debug + Sync + Send {
     fn get_keypair(&self) -> Result<Option<Keypair>>;
     fn set_keypair(&self, keypair: Keypair) -> Result<()>;
 }
+
+/// Small string settings that outlive the session, eg. which wallets were connected
+pub trait SettingsStorage: std::fmt::Debug + Sync + Send {
+    fn get_setting(&self, key: &str) -> Result<Option<String>>;
+    fn set_setting(&self, key: &str, value: &str) -> Result<()>;
+    fn remove_setting(&self, key: &str) -> Result<()>;
+}
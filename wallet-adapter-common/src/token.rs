@@ -0,0 +1,446 @@
+use anyhow::{bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::amount::UiAmount;
+
+/// The SPL Token and Token-2022 programs, whose accounts make up a token portfolio
+pub const TOKEN_PROGRAM_IDS: [&str; 2] = [
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAs6EPFLC1PHnBqCXEpPxuEb",
+];
+
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("TokenzQdBNbLqP5VEhdkAs6EPFLC1PHnBqCXEpPxuEb");
+
+/// The size of the mint state both token programs start mint accounts with
+const MINT_LEN: usize = 82;
+/// Token-2022 pads mints with extensions to the size of a token account, then writes the
+/// account type and the extensions
+const ACCOUNT_TYPE_OFFSET: usize = 165;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const TRANSFER_FEE_CONFIG_LEN: usize = 108;
+
+/// The Token-2022 extensions a mint can have, see `spl_token_2022::extension::ExtensionType`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MintExtension {
+    TransferFeeConfig,
+    MintCloseAuthority,
+    ConfidentialTransferMint,
+    DefaultAccountState,
+    NonTransferable,
+    InterestBearingConfig,
+    PermanentDelegate,
+    TransferHook,
+    ConfidentialTransferFeeConfig,
+    MetadataPointer,
+    TokenMetadata,
+    GroupPointer,
+    TokenGroup,
+    GroupMemberPointer,
+    TokenGroupMember,
+    Other(u16),
+}
+
+impl From<u16> for MintExtension {
+    fn from(extension_type: u16) -> Self {
+        match extension_type {
+            1 => Self::TransferFeeConfig,
+            3 => Self::MintCloseAuthority,
+            4 => Self::ConfidentialTransferMint,
+            6 => Self::DefaultAccountState,
+            9 => Self::NonTransferable,
+            10 => Self::InterestBearingConfig,
+            12 => Self::PermanentDelegate,
+            14 => Self::TransferHook,
+            16 => Self::ConfidentialTransferFeeConfig,
+            18 => Self::MetadataPointer,
+            19 => Self::TokenMetadata,
+            20 => Self::GroupPointer,
+            21 => Self::TokenGroup,
+            22 => Self::GroupMemberPointer,
+            23 => Self::TokenGroupMember,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// The fee Token-2022 withholds from transfers of a mint, from `epoch` on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TransferFee {
+    pub epoch: u64,
+    /// In base units of the mint
+    pub maximum_fee: u64,
+    pub transfer_fee_basis_points: u16,
+}
+
+impl TransferFee {
+    /// The fee on transferring `amount` base units, rounded up like the token program does
+    pub fn fee(&self, amount: u64) -> u64 {
+        let fee = (u128::from(amount) * u128::from(self.transfer_fee_basis_points))
+            .div_ceil(10_000)
+            .min(self.maximum_fee.into());
+        // at most `maximum_fee`
+        fee as u64
+    }
+
+    fn unpack(data: &[u8]) -> Self {
+        Self {
+            epoch: read_u64(data, 0),
+            maximum_fee: read_u64(data, 8),
+            transfer_fee_basis_points: u16::from_le_bytes([data[16], data[17]]),
+        }
+    }
+}
+
+/// The `TransferFeeConfig` extension: the `newer` fee replaces the `older` one at its epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TransferFeeConfig {
+    /// Fees withheld in the mint, waiting for the withdraw authority
+    pub withheld_amount: u64,
+    pub older_transfer_fee: TransferFee,
+    pub newer_transfer_fee: TransferFee,
+}
+
+impl TransferFeeConfig {
+    /// The fee of transfers in `epoch`
+    pub fn epoch_fee(&self, epoch: u64) -> &TransferFee {
+        if epoch >= self.newer_transfer_fee.epoch {
+            &self.newer_transfer_fee
+        } else {
+            &self.older_transfer_fee
+        }
+    }
+
+    fn unpack(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() >= TRANSFER_FEE_CONFIG_LEN,
+            "invalid transfer fee config"
+        );
+        // skips the config and withdraw authorities
+        Ok(Self {
+            withheld_amount: read_u64(data, 64),
+            older_transfer_fee: TransferFee::unpack(&data[72..90]),
+            newer_transfer_fee: TransferFee::unpack(&data[90..108]),
+        })
+    }
+}
+
+/// A mint account of the SPL Token or Token-2022 program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mint {
+    /// The program owning the mint, which is also the program of its token accounts
+    pub program_id: Pubkey,
+    pub supply: u64,
+    pub decimals: u8,
+    /// Always empty for SPL Token mints
+    pub extensions: Vec<MintExtension>,
+    pub transfer_fee_config: Option<TransferFeeConfig>,
+}
+
+impl Mint {
+    /// Decode the `data` of a mint account owned by `program_id`
+    pub fn unpack(program_id: &Pubkey, data: &[u8]) -> Result<Self> {
+        ensure!(
+            *program_id == TOKEN_PROGRAM_ID || *program_id == TOKEN_2022_PROGRAM_ID,
+            "{program_id} is not a token program"
+        );
+        ensure!(data.len() >= MINT_LEN, "the account is not a mint");
+        ensure!(data[45] == 1, "the mint is not initialized");
+
+        let mut mint = Self {
+            program_id: *program_id,
+            supply: read_u64(data, 36),
+            decimals: data[44],
+            extensions: Vec::new(),
+            transfer_fee_config: None,
+        };
+        if data.len() == MINT_LEN {
+            return Ok(mint);
+        }
+
+        ensure!(
+            *program_id == TOKEN_2022_PROGRAM_ID
+                && data.get(ACCOUNT_TYPE_OFFSET) == Some(&ACCOUNT_TYPE_MINT),
+            "the account is not a mint"
+        );
+        let mut tlv = &data[ACCOUNT_TYPE_OFFSET + 1..];
+        while tlv.len() >= 4 {
+            let extension_type = u16::from_le_bytes([tlv[0], tlv[1]]);
+            let len = usize::from(u16::from_le_bytes([tlv[2], tlv[3]]));
+            // the rest of the account is zeroed once the extensions end
+            if extension_type == 0 {
+                break;
+            }
+            let Some(value) = tlv.get(4..4 + len) else {
+                bail!("the extensions of the mint are truncated");
+            };
+
+            let extension = MintExtension::from(extension_type);
+            if extension == MintExtension::TransferFeeConfig {
+                mint.transfer_fee_config = Some(TransferFeeConfig::unpack(value)?);
+            }
+            mint.extensions.push(extension);
+            tlv = &tlv[4 + len..];
+        }
+        Ok(mint)
+    }
+
+    pub fn is_token_2022(&self) -> bool {
+        self.program_id == TOKEN_2022_PROGRAM_ID
+    }
+
+    pub fn has_extension(&self, extension: MintExtension) -> bool {
+        self.extensions.contains(&extension)
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// The balance of one mint, summed over all token accounts of the owner
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenBalance {
+    pub mint: Pubkey,
+    /// In base units of the mint
+    pub amount: u64,
+    /// `amount` divided by `10^decimals`, for display
+    pub ui_amount: f64,
+    pub decimals: u8,
+}
+
+impl TokenBalance {
+    /// Sum the `getTokenAccountsByOwner` accounts per mint, keeping the order mints first appear in
+    pub fn from_accounts(accounts: &[KeyedTokenAccount]) -> Result<Vec<TokenBalance>> {
+        let mut balances: Vec<TokenBalance> = Vec::new();
+        for account in accounts {
+            let info = &account.account.data.parsed.info;
+            let mint: Pubkey = info
+                .mint
+                .parse()
+                .with_context(|| format!("invalid mint {}", info.mint))?;
+            let amount: u64 = info
+                .token_amount
+                .amount
+                .parse()
+                .with_context(|| format!("invalid amount of {}", account.pubkey))?;
+            let decimals = info.token_amount.decimals;
+
+            match balances.iter_mut().find(|balance| balance.mint == mint) {
+                Some(balance) => balance.amount = balance.amount.saturating_add(amount),
+                None => balances.push(TokenBalance {
+                    mint,
+                    amount,
+                    ui_amount: 0.0,
+                    decimals,
+                }),
+            }
+        }
+
+        for balance in &mut balances {
+            balance.ui_amount = UiAmount::new(balance.amount, balance.decimals).to_f64();
+        }
+        Ok(balances)
+    }
+}
+
+/// An entry of `getTokenAccountsByOwner` with the `jsonParsed` encoding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyedTokenAccount {
+    pub pubkey: String,
+    pub account: ParsedTokenAccount,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedTokenAccount {
+    pub data: ParsedTokenAccountData,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedTokenAccountData {
+    pub parsed: ParsedTokenAccountInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedTokenAccountInfo {
+    pub info: TokenAccountInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAccountInfo {
+    pub mint: String,
+    pub owner: String,
+    pub token_amount: TokenAmount,
+    #[serde(default)]
+    pub state: TokenAccountState,
+    /// Whether the account holds wrapped SOL
+    #[serde(default)]
+    pub is_native: bool,
+    #[serde(default)]
+    pub delegate: Option<String>,
+    #[serde(default)]
+    pub delegated_amount: Option<TokenAmount>,
+    #[serde(default)]
+    pub close_authority: Option<String>,
+    /// Token-2022 extensions of the account
+    #[serde(default)]
+    pub extensions: Vec<ParsedExtension>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TokenAccountState {
+    Uninitialized,
+    #[default]
+    Initialized,
+    Frozen,
+}
+
+/// A mint of `getAccountInfo` with the `jsonParsed` encoding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintInfo {
+    pub mint_authority: Option<String>,
+    /// The raw supply as a decimal string
+    pub supply: String,
+    pub decimals: u8,
+    pub is_initialized: bool,
+    pub freeze_authority: Option<String>,
+    /// Token-2022 extensions of the mint
+    #[serde(default)]
+    pub extensions: Vec<ParsedExtension>,
+}
+
+/// A Token-2022 extension as the RPC node parses it, eg. `transferFeeConfig`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedExtension {
+    pub extension: String,
+    #[serde(default)]
+    pub state: serde_json::Value,
+}
+
+/// An account of the SPL Token or Token-2022 program with the `jsonParsed` encoding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "info", rename_all = "camelCase")]
+pub enum TokenProgramAccount {
+    Account(TokenAccountInfo),
+    Mint(MintInfo),
+    Multisig(serde_json::Value),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAmount {
+    /// The raw amount as a decimal string
+    pub amount: String,
+    pub decimals: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn sums_the_accounts_of_a_mint() {
+        let mint = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let account = |mint: &Pubkey, amount: &str, decimals: u8| {
+            json!({
+                "pubkey": Pubkey::new_unique().to_string(),
+                "account": {
+                    "data": {
+                        "parsed": {
+                            "info": {
+                                "mint": mint.to_string(),
+                                "owner": Pubkey::new_unique().to_string(),
+                                "tokenAmount": { "amount": amount, "decimals": decimals, "uiAmount": 0.0 }
+                            },
+                            "type": "account"
+                        },
+                        "program": "spl-token"
+                    },
+                    "lamports": 2039280
+                }
+            })
+        };
+        let accounts: Vec<KeyedTokenAccount> = serde_json::from_value(json!([
+            account(&mint, "1500000", 6),
+            account(&other, "7", 0),
+            account(&mint, "500000", 6),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            TokenBalance::from_accounts(&accounts).unwrap(),
+            vec![
+                TokenBalance {
+                    mint,
+                    amount: 2_000_000,
+                    ui_amount: 2.0,
+                    decimals: 6
+                },
+                TokenBalance {
+                    mint: other,
+                    amount: 7,
+                    ui_amount: 7.0,
+                    decimals: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unpacks_token_2022_mints_with_a_transfer_fee() {
+        let mut data = vec![0; ACCOUNT_TYPE_OFFSET];
+        data[36..44].copy_from_slice(&1_000_000u64.to_le_bytes());
+        data[44] = 6;
+        data[45] = 1;
+        data.push(ACCOUNT_TYPE_MINT);
+
+        let mut config = vec![0; TRANSFER_FEE_CONFIG_LEN];
+        config[80..88].copy_from_slice(&u64::MAX.to_le_bytes());
+        config[88..90].copy_from_slice(&50u16.to_le_bytes());
+        config[90..98].copy_from_slice(&10u64.to_le_bytes());
+        config[98..106].copy_from_slice(&5_000u64.to_le_bytes());
+        config[106..108].copy_from_slice(&100u16.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&(TRANSFER_FEE_CONFIG_LEN as u16).to_le_bytes());
+        data.extend_from_slice(&config);
+        data.extend_from_slice(&18u16.to_le_bytes());
+        data.extend_from_slice(&64u16.to_le_bytes());
+        data.extend_from_slice(&[0; 64]);
+
+        let mint = Mint::unpack(&TOKEN_2022_PROGRAM_ID, &data).unwrap();
+        assert!(mint.is_token_2022());
+        assert_eq!((mint.supply, mint.decimals), (1_000_000, 6));
+        assert_eq!(
+            mint.extensions,
+            vec![
+                MintExtension::TransferFeeConfig,
+                MintExtension::MetadataPointer
+            ]
+        );
+
+        let config = mint.transfer_fee_config.unwrap();
+        assert_eq!(config.epoch_fee(9).fee(1_000_001), 5_001);
+        assert_eq!(config.epoch_fee(10).fee(1_000), 10);
+        assert_eq!(config.epoch_fee(10).fee(10_000_000), 5_000);
+
+        assert!(Mint::unpack(&TOKEN_PROGRAM_ID, &data).is_err());
+        let classic = Mint::unpack(&TOKEN_PROGRAM_ID, &data[..MINT_LEN]).unwrap();
+        assert!(classic.extensions.is_empty() && classic.transfer_fee_config.is_none());
+    }
+}
@@ -0,0 +1,226 @@
+//! The HTTP client under the connections, implement [`RpcTransport`] to send their requests
+//! through gloo-net, ureq, hyper or an instrumented client instead of the default one.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::connection::{RpcError, RpcRequest, RpcResponse};
+use crate::maybe_send::{MaybeSend, MaybeSync};
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait RpcTransport: MaybeSend + MaybeSync {
+    /// POST the JSON `body` to `url` and return the response body, failing on a non-2xx status
+    async fn post_json(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>>;
+
+    /// GET `url` and return the response body, failing on a non-2xx status
+    async fn get(&self, url: &str) -> Result<Vec<u8>>;
+}
+
+/// Lets clients share one transport, eg. a connection's
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<T: RpcTransport + ?Sized> RpcTransport for Arc<T> {
+    async fn post_json(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+        self.as_ref().post_json(url, body).await
+    }
+
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        self.as_ref().get(url).await
+    }
+}
+
+/// Bounds on the requests of a connection, so a hung node can't stall its callers.
+/// There are none by default, natively they need the time driver of the tokio runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RequestTimeouts {
+    /// Longest a single request may take, `None` waits forever
+    pub request: Option<Duration>,
+    /// When the operation the requests belong to must be done, eg. sending a transaction
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+impl RequestTimeouts {
+    /// Set the deadline `timeout` from now, none if that is too far out to represent
+    pub fn deadline_after(mut self, timeout: Duration) -> Self {
+        self.deadline = chrono::Duration::from_std(timeout)
+            .ok()
+            .and_then(|timeout| Utc::now().checked_add_signed(timeout));
+        self
+    }
+
+    /// How long the next request may take, failing once the deadline passed
+    pub fn next(&self, method: &str) -> Result<Option<Duration>> {
+        let remaining = match self.deadline {
+            Some(deadline) => match (deadline - Utc::now()).to_std() {
+                Ok(remaining) if !remaining.is_zero() => Some(remaining),
+                _ => bail!("deadline exceeded before {method}"),
+            },
+            None => None,
+        };
+
+        Ok(match (self.request, remaining) {
+            (Some(request), Some(remaining)) => Some(request.min(remaining)),
+            (request, remaining) => request.or(remaining),
+        })
+    }
+}
+
+/// Send the JSON-RPC `method` to `url` and return its result, or its error as one
+pub async fn rpc_call<T: DeserializeOwned + Debug>(
+    transport: &dyn RpcTransport,
+    url: &str,
+    method: &str,
+    params: impl Serialize,
+) -> Result<T> {
+    let body = serde_json::to_vec(&RpcRequest::new(method, params))?;
+    let resp: RpcResponse<T, RpcError> =
+        serde_json::from_slice(&transport.post_json(url, body).await?)
+            .with_context(|| format!("invalid {method} response"))?;
+
+    tracing::debug!(?resp, "rpc response");
+
+    if let Some(err) = resp.error {
+        return Err(err.into());
+    }
+
+    resp.result.context("no result")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use serde_json::json;
+
+    use super::*;
+
+    struct FixedResponse(serde_json::Value);
+
+    #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+    impl RpcTransport for FixedResponse {
+        async fn post_json(&self, _url: &str, _body: Vec<u8>) -> Result<Vec<u8>> {
+            Ok(serde_json::to_vec(&self.0)?)
+        }
+
+        async fn get(&self, _url: &str) -> Result<Vec<u8>> {
+            bail!("unused")
+        }
+    }
+
+    #[test]
+    fn far_deadlines_are_no_deadline() {
+        assert_eq!(
+            RequestTimeouts::default(),
+            RequestTimeouts {
+                request: None,
+                deadline: None
+            }
+        );
+
+        // too long for chrono, and past the last representable date
+        for timeout in [Duration::MAX, Duration::from_secs(1 << 50)] {
+            let timeouts = RequestTimeouts::default().deadline_after(timeout);
+            assert_eq!(timeouts.deadline, None);
+            assert_eq!(timeouts.next("getSlot").unwrap(), None);
+        }
+
+        let timeouts = RequestTimeouts::default().deadline_after(Duration::from_secs(60));
+        let remaining = timeouts.next("getSlot").unwrap().unwrap();
+        assert!(remaining <= Duration::from_secs(60));
+    }
+
+    #[test]
+    fn rpc_errors_fail_the_call() {
+        let ok = FixedResponse(json!({ "jsonrpc": "2.0", "result": 42, "id": 1 }));
+        let result: u64 =
+            futures::executor::block_on(rpc_call(&ok, "http://rpc", "getSlot", json!([]))).unwrap();
+        assert_eq!(result, 42);
+
+        let err = FixedResponse(json!({
+            "jsonrpc": "2.0",
+            "error": { "code": -32601, "message": "Method not found" },
+            "id": 1
+        }));
+        let result =
+            futures::executor::block_on(rpc_call::<u64>(&err, "http://rpc", "getSlot", json!([])));
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Method not found"));
+        assert_eq!(err.downcast_ref::<RpcError>().unwrap().code, -32601);
+    }
+
+    thread_local! {
+        static FORMATTED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// A result counting how often it is formatted
+    struct Formatted;
+
+    impl<'de> serde::Deserialize<'de> for Formatted {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            serde::de::IgnoredAny::deserialize(deserializer)?;
+            Ok(Self)
+        }
+    }
+
+    impl Debug for Formatted {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            FORMATTED.with(|formatted| formatted.set(formatted.get() + 1));
+            f.write_str("Formatted")
+        }
+    }
+
+    /// Formats the fields of the events at level `0` or more severe
+    struct MaxLevel(tracing::Level);
+
+    impl tracing::Subscriber for MaxLevel {
+        fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+            *metadata.level() <= self.0
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            event.record(&mut |_: &tracing::field::Field, value: &dyn Debug| {
+                let _ = format!("{value:?}");
+            });
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[test]
+    fn responses_are_only_formatted_for_debug_logs() {
+        let node = FixedResponse(json!({ "jsonrpc": "2.0", "result": 42, "id": 1 }));
+        let call = || {
+            futures::executor::block_on(rpc_call::<Formatted>(
+                &node,
+                "http://rpc",
+                "getSlot",
+                json!([]),
+            ))
+            .unwrap();
+        };
+
+        tracing::subscriber::with_default(MaxLevel(tracing::Level::INFO), call);
+        assert_eq!(FORMATTED.with(Cell::get), 0);
+
+        tracing::subscriber::with_default(MaxLevel(tracing::Level::DEBUG), call);
+        assert_eq!(FORMATTED.with(Cell::get), 1);
+    }
+}
@@ -1,24 +1,280 @@
+use std::sync::Arc;
+
+use base64::prelude::*;
 use serde::{Deserialize, Serialize};
-use solana_sdk::{commitment_config::CommitmentLevel, signer::Signer};
+use solana_sdk::{commitment_config::CommitmentLevel, pubkey::Pubkey, signer::Signer};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+use crate::relay::FeePayerService;
+
+/// `CommitmentLevel` as serde writes it, it has no `Tsify` impl
+#[cfg(feature = "tsify")]
+#[wasm_bindgen::prelude::wasm_bindgen(typescript_custom_section)]
+const COMMITMENT: &str = r#"export type Commitment = "processed" | "confirmed" | "finalized";"#;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct SendOptions {
     /** disable transaction verification step */
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub skip_preflight: Option<bool>,
     /** preflight commitment level */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "tsify", tsify(type = "Commitment"))]
     pub preflight_commitment: Option<CommitmentLevel>,
     /** Maximum number of times for the RPC node to retry sending the transaction to the leader. */
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_retries: Option<u32>,
     /** The minimum slot that the request can be evaluated at */
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_context_slots: Option<u32>,
+    /** Memo appended to outgoing transactions, eg. an order id used for reconciliation */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /** Wire encoding of the transaction in `sendTransaction`, base64 when unset */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<TransactionEncoding>,
+    /** Account paying the fees, the wallet when unset and the transaction has no fee payer */
+    #[serde(
+        default,
+        with = "base58_pubkey",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[cfg_attr(feature = "tsify", tsify(type = "string"))]
+    pub fee_payer: Option<Pubkey>,
+}
+
+/// `Option<Pubkey>` as a base58 string instead of its bytes
+mod base58_pubkey {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(
+        pubkey: &Option<Pubkey>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        pubkey
+            .map(|pubkey| pubkey.to_string())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Pubkey>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|pubkey| pubkey.parse().map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+/// Encoding of a serialized transaction in RPC requests.
+/// Base58 is deprecated by the RPC but still the only one some older/self-hosted nodes accept.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub enum TransactionEncoding {
+    Base58,
+    #[default]
+    Base64,
+}
+
+impl TransactionEncoding {
+    pub fn encode(&self, raw_transaction: &[u8]) -> String {
+        match self {
+            TransactionEncoding::Base58 => solana_sdk::bs58::encode(raw_transaction).into_string(),
+            TransactionEncoding::Base64 => BASE64_STANDARD.encode(raw_transaction),
+        }
+    }
+}
+
+impl SendOptions {
+    pub fn builder() -> SendOptionsBuilder {
+        SendOptionsBuilder::default()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SendOptionsBuilder {
+    options: SendOptions,
+}
+
+impl SendOptionsBuilder {
+    pub fn skip_preflight(mut self, skip_preflight: bool) -> Self {
+        self.options.skip_preflight = Some(skip_preflight);
+        self
+    }
+
+    pub fn preflight_commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.options.preflight_commitment = Some(commitment);
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.options.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn min_context_slots(mut self, min_context_slots: u32) -> Self {
+        self.options.min_context_slots = Some(min_context_slots);
+        self
+    }
+
+    pub fn encoding(mut self, encoding: TransactionEncoding) -> Self {
+        self.options.encoding = Some(encoding);
+        self
+    }
+
+    pub fn memo(mut self, memo: impl Into<String>) -> Self {
+        self.options.memo = Some(memo.into());
+        self
+    }
+
+    /// Have `fee_payer` pay the fees, it has to sign the transaction as well
+    pub fn fee_payer(mut self, fee_payer: Pubkey) -> Self {
+        self.options.fee_payer = Some(fee_payer);
+        self
+    }
+
+    pub fn build(self) -> SendOptions {
+        self.options
+    }
+
+    /// Finish as [`SendTransactionOptions`] without extra signers
+    pub fn build_transaction_options(self) -> SendTransactionOptions {
+        self.options.into()
+    }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// Signer handed across `await`s, `Send + Sync` off wasm like the rest of the async API
+#[cfg(not(target_arch = "wasm32"))]
+pub type DynSigner = dyn Signer + Send + Sync;
+#[cfg(target_arch = "wasm32")]
+pub type DynSigner = dyn Signer;
+
+#[derive(Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SendTransactionOptions {
     #[serde(skip)]
-    pub signers: Vec<Box<dyn Signer>>,
+    pub signers: Vec<Box<DynSigner>>,
+    /// Relayer paying the fees instead of the wallet, see [`crate::relay`]
+    #[serde(skip)]
+    pub sponsor: Option<Arc<dyn FeePayerService>>,
     #[serde(flatten)]
     pub send_options: SendOptions,
 }
+
+impl SendTransactionOptions {
+    /// Add a signer that partially signs the transaction before the wallet does
+    pub fn signer(mut self, signer: Box<DynSigner>) -> Self {
+        self.signers.push(signer);
+        self
+    }
+
+    /// Have `sponsor` pay the fees and submit the transaction, the wallet only signs it
+    pub fn sponsor(mut self, sponsor: Arc<dyn FeePayerService>) -> Self {
+        self.sponsor = Some(sponsor);
+        self
+    }
+
+    fn signer_pubkeys(&self) -> Vec<Pubkey> {
+        self.signers.iter().map(|signer| signer.pubkey()).collect()
+    }
+}
+
+impl std::fmt::Debug for SendTransactionOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendTransactionOptions")
+            .field("signers", &self.signer_pubkeys())
+            .field("sponsor", &self.sponsor.is_some())
+            .field("send_options", &self.send_options)
+            .finish()
+    }
+}
+
+impl PartialEq for SendTransactionOptions {
+    fn eq(&self, other: &Self) -> bool {
+        let same_sponsor = match (&self.sponsor, &other.sponsor) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        self.signer_pubkeys() == other.signer_pubkeys()
+            && same_sponsor
+            && self.send_options == other.send_options
+    }
+}
+
+impl From<SendOptions> for SendTransactionOptions {
+    fn from(send_options: SendOptions) -> Self {
+        Self {
+            signers: vec![],
+            sponsor: None,
+            send_options,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+
+    #[test]
+    fn builds_send_options() {
+        let fee_payer = Pubkey::new_unique();
+        let options = SendOptions::builder()
+            .skip_preflight(true)
+            .preflight_commitment(CommitmentLevel::Confirmed)
+            .max_retries(3)
+            .min_context_slots(42)
+            .memo("order-1")
+            .fee_payer(fee_payer)
+            .build();
+
+        assert_eq!(
+            options,
+            SendOptions {
+                skip_preflight: Some(true),
+                preflight_commitment: Some(CommitmentLevel::Confirmed),
+                max_retries: Some(3),
+                min_context_slots: Some(42),
+                memo: Some("order-1".to_string()),
+                encoding: None,
+                fee_payer: Some(fee_payer),
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&options).unwrap(),
+            serde_json::json!({
+                "skipPreflight": true,
+                "preflightCommitment": "confirmed",
+                "maxRetries": 3,
+                "minContextSlots": 42,
+                "memo": "order-1",
+                "feePayer": fee_payer.to_string(),
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(SendOptions::default()).unwrap(),
+            serde_json::json!({})
+        );
+    }
+
+    #[test]
+    fn builds_transaction_options() {
+        let signer = Keypair::new();
+        let options = SendOptions::builder()
+            .max_retries(1)
+            .build_transaction_options()
+            .signer(Box::new(signer.insecure_clone()));
+
+        assert_eq!(options.send_options.max_retries, Some(1));
+        assert_eq!(options.signer_pubkeys(), vec![signer.pubkey()]);
+        assert!(options.sponsor.is_none());
+        assert_eq!(
+            SendTransactionOptions::default(),
+            SendOptions::default().into()
+        );
+    }
+}
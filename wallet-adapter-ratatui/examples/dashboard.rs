@@ -0,0 +1,118 @@
+//! A terminal wallet dashboard on devnet:
+//!
+//! ```sh
+//! cargo run -p wallet-adapter-ratatui --example dashboard
+//! ```
+//!
+//! Up/Down pick a wallet, `c` connects, `d` disconnects, `r` refreshes the balance,
+//! `s` opens the send form (Tab switches fields, Enter sends, Esc closes), `q` quits.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::widgets::Block;
+use ratatui::{DefaultTerminal, Frame};
+use wallet_adapter_ratatui::{
+    Balance, ConnectStatus, SendForm, SendFormView, WalletList, WalletStore,
+};
+use wallet_adapter_unsafe_burner::UnsafeBurnerWallet;
+use wallet_adapter_unsafe_persistent::UnsafePersistentWallet;
+use wallet_adapter_x86::connection::NativeConnection;
+use wallet_adapter_x86::storage::X86Storage;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let store = WalletStore::new(vec![
+        Box::new(UnsafePersistentWallet::new(X86Storage::new(
+            "wallet-adapter-ratatui",
+        )?)?),
+        Box::new(UnsafeBurnerWallet::new()),
+    ]);
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, store, NativeConnection::devnet()).await;
+    ratatui::restore();
+    result
+}
+
+async fn run(
+    terminal: &mut DefaultTerminal,
+    mut store: WalletStore,
+    connection: NativeConnection,
+) -> Result<()> {
+    let mut send_form: Option<SendForm> = None;
+
+    loop {
+        terminal.draw(|frame| draw(frame, &store, send_form.as_ref()))?;
+
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        // errors show in the connect status, the results are only for callers that need them
+        if let Some(form) = &mut send_form {
+            if key.code == KeyCode::Esc {
+                send_form = None;
+            } else if form.handle_key(key) {
+                form.submit(&mut store, &connection).await;
+            }
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') => return Ok(()),
+            KeyCode::Up => store.select_previous(),
+            KeyCode::Down => store.select_next(),
+            KeyCode::Char('c') => {
+                let connected = store.connect().await.is_ok();
+                if connected {
+                    let _ = store.refresh_balance(&connection).await;
+                }
+            }
+            KeyCode::Char('d') => {
+                let _ = store.disconnect().await;
+            }
+            KeyCode::Char('r') => {
+                let _ = store.refresh_balance(&connection).await;
+            }
+            KeyCode::Char('s') => send_form = Some(SendForm::default()),
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, store: &WalletStore, send_form: Option<&SendForm>) {
+    let [wallets, status, balance, send] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(4),
+        Constraint::Length(3),
+        Constraint::Length(5),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(
+        WalletList::new(store).block(Block::bordered().title("Wallets")),
+        wallets,
+    );
+    frame.render_widget(
+        ConnectStatus::new(store).block(Block::bordered().title("Status")),
+        status,
+    );
+    frame.render_widget(
+        Balance::new(store).block(Block::bordered().title("Balance")),
+        balance,
+    );
+    if let Some(form) = send_form {
+        frame.render_widget(
+            SendFormView::new(form).block(Block::bordered().title("Send SOL")),
+            send,
+        );
+    }
+}
@@ -0,0 +1,24 @@
+//! Terminal widgets for wallets: the wallet list, the connect status, the balance and a send
+//! form, all drawn from a [`WalletStore`] of native adapters, eg.
+//!
+//! ```no_run
+//! # use ratatui::layout::{Constraint, Layout};
+//! # use ratatui::widgets::Block;
+//! # use wallet_adapter_ratatui::{Balance, ConnectStatus, WalletList, WalletStore};
+//! # fn draw(frame: &mut ratatui::Frame, store: &WalletStore) {
+//! let [list, status, balance] = Layout::vertical([Constraint::Fill(1); 3]).areas(frame.area());
+//! frame.render_widget(WalletList::new(store).block(Block::bordered().title("Wallets")), list);
+//! frame.render_widget(ConnectStatus::new(store), status);
+//! frame.render_widget(Balance::new(store), balance);
+//! # }
+//! ```
+//!
+//! See `examples/dashboard.rs` for the event loop around them.
+
+pub mod send_form;
+pub mod store;
+pub mod widgets;
+
+pub use send_form::{SendField, SendForm};
+pub use store::WalletStore;
+pub use widgets::{Balance, ConnectStatus, SendFormView, WalletList};
@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_common::amount::Lamports;
+use wallet_adapter_common::connection::Connection;
+
+use crate::WalletStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SendField {
+    #[default]
+    Recipient,
+    Amount,
+}
+
+/// The input of a SOL transfer, drawn by [`SendFormView`](crate::SendFormView)
+#[derive(Debug, Clone, Default)]
+pub struct SendForm {
+    recipient: String,
+    amount: String,
+    focus: SendField,
+    outcome: Option<Result<Signature, String>>,
+}
+
+impl SendForm {
+    pub fn recipient(&self) -> &str {
+        &self.recipient
+    }
+
+    /// The amount in SOL as typed, eg. `0.5`
+    pub fn amount(&self) -> &str {
+        &self.amount
+    }
+
+    pub fn focus(&self) -> SendField {
+        self.focus
+    }
+
+    /// The signature of the last transfer, or why it failed
+    pub fn outcome(&self) -> Option<&Result<Signature, String>> {
+        self.outcome.as_ref()
+    }
+
+    /// Edit the focused field, Tab and the arrows move the focus.
+    /// Returns whether the key was Enter, ie. the form should be submitted.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.kind == KeyEventKind::Release {
+            return false;
+        }
+        match key.code {
+            KeyCode::Enter => return true,
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                self.focus = match self.focus {
+                    SendField::Recipient => SendField::Amount,
+                    SendField::Amount => SendField::Recipient,
+                };
+            }
+            KeyCode::Backspace => {
+                self.field_mut().pop();
+            }
+            KeyCode::Char(c) if !c.is_whitespace() => self.field_mut().push(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn field_mut(&mut self) -> &mut String {
+        match self.focus {
+            SendField::Recipient => &mut self.recipient,
+            SendField::Amount => &mut self.amount,
+        }
+    }
+
+    /// The recipient and amount of the transfer
+    pub fn transfer(&self) -> Result<(Pubkey, Lamports)> {
+        let recipient = self
+            .recipient
+            .parse()
+            .with_context(|| format!("invalid recipient {}", self.recipient))?;
+        let lamports = Lamports::from_sol(&self.amount)?;
+        anyhow::ensure!(lamports.0 > 0, "nothing to send");
+        Ok((recipient, lamports))
+    }
+
+    /// Send the transfer from the selected wallet of `store`, clearing the amount once it
+    /// went through
+    pub async fn submit(&mut self, store: &mut WalletStore, connection: &dyn Connection) {
+        let result = match self.transfer() {
+            Ok((recipient, lamports)) => store.send(recipient, lamports, connection).await,
+            Err(err) => Err(err),
+        };
+        if result.is_ok() {
+            self.amount.clear();
+        }
+        self.outcome = Some(result.map_err(|err| format!("{err:#}")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    fn type_text(form: &mut SendForm, text: &str) {
+        for c in text.chars() {
+            form.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn parses_the_typed_transfer() {
+        let recipient = Pubkey::new_unique();
+        let mut form = SendForm::default();
+        type_text(&mut form, &recipient.to_string());
+        form.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        type_text(&mut form, "0.25");
+
+        assert_eq!(form.transfer().unwrap(), (recipient, Lamports(250_000_000)));
+        assert!(form.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+
+        form.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        type_text(&mut form, "0000000001");
+        assert!(form.transfer().is_err());
+    }
+}
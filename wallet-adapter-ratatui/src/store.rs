@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_base::BaseWalletAdapter;
+use wallet_adapter_common::amount::Lamports;
+use wallet_adapter_common::connection::Connection;
+
+/// The wallets a terminal app offers, the selected one and what the widgets show about it.
+///
+/// Failed requests are kept in [`last_error`](Self::last_error) for the status line
+/// as well as returned.
+pub struct WalletStore {
+    wallets: Vec<Box<dyn BaseWalletAdapter>>,
+    selected: usize,
+    balance: Option<Lamports>,
+    last_error: Option<String>,
+}
+
+impl WalletStore {
+    pub fn new(wallets: Vec<Box<dyn BaseWalletAdapter>>) -> Self {
+        Self {
+            wallets,
+            selected: 0,
+            balance: None,
+            last_error: None,
+        }
+    }
+
+    pub fn wallets(&self) -> &[Box<dyn BaseWalletAdapter>] {
+        &self.wallets
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected(&self) -> Option<&dyn BaseWalletAdapter> {
+        self.wallets.get(self.selected).map(|wallet| &**wallet)
+    }
+
+    /// The balance of the selected wallet as of the last [`refresh_balance`](Self::refresh_balance)
+    pub fn balance(&self) -> Option<Lamports> {
+        self.balance
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Select the next wallet, wrapping around. The selection stays on a connected wallet
+    /// until it is disconnected.
+    pub fn select_next(&mut self) {
+        self.select(self.selected + 1);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.select(self.selected + self.wallets.len().saturating_sub(1));
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.wallets.is_empty() || self.selected().is_some_and(|w| w.connected()) {
+            return;
+        }
+        self.selected = index % self.wallets.len();
+        self.balance = None;
+        self.last_error = None;
+    }
+
+    pub async fn connect(&mut self) -> Result<()> {
+        let result = self.selected_mut()?.connect().await.map_err(Into::into);
+        self.record(result)
+    }
+
+    pub async fn disconnect(&mut self) -> Result<()> {
+        let result = self.selected_mut()?.disconnect().await;
+        self.balance = None;
+        self.record(result)
+    }
+
+    pub async fn refresh_balance(&mut self, connection: &dyn Connection) -> Result<Lamports> {
+        let result = self.fetch_balance(connection).await;
+        self.balance = result.as_ref().ok().copied();
+        self.record(result)
+    }
+
+    /// Transfer `lamports` from the selected wallet to `to` and refresh the balance
+    pub async fn send(
+        &mut self,
+        to: Pubkey,
+        lamports: Lamports,
+        connection: &dyn Connection,
+    ) -> Result<Signature> {
+        let result = match self.selected() {
+            Some(wallet) => wallet
+                .transfer_sol(to, lamports.0, connection, None)
+                .await
+                .map_err(Into::into),
+            None => Err(no_wallets()),
+        };
+        let signature = self.record(result)?;
+        // the transfer went through, a failed refresh only shows in the status line
+        let _ = self.refresh_balance(connection).await;
+        Ok(signature)
+    }
+
+    async fn fetch_balance(&self, connection: &dyn Connection) -> Result<Lamports> {
+        let wallet = self.selected().ok_or_else(no_wallets)?;
+        let public_key = wallet
+            .public_key()
+            .with_context(|| format!("{} is not connected", wallet.name()))?;
+        let account = connection.get_account_info(&public_key).await?;
+        Ok(Lamports(account.map_or(0, |account| account.lamports)))
+    }
+
+    fn selected_mut(&mut self) -> Result<&mut Box<dyn BaseWalletAdapter>> {
+        self.wallets.get_mut(self.selected).ok_or_else(no_wallets)
+    }
+
+    /// Keep the error of `result` for the status line, clear it on success
+    fn record<T>(&mut self, result: Result<T>) -> Result<T> {
+        self.last_error = result.as_ref().err().map(|err| format!("{err:#}"));
+        result
+    }
+}
+
+fn no_wallets() -> anyhow::Error {
+    anyhow::anyhow!("no wallets")
+}
@@ -0,0 +1,218 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, StatefulWidget, Widget, Wrap};
+use wallet_adapter_base::WalletReadyState;
+
+use crate::{SendField, SendForm, WalletStore};
+
+/// The wallets of the store with their ready state, the selected one highlighted
+pub struct WalletList<'a> {
+    store: &'a WalletStore,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> WalletList<'a> {
+    pub fn new(store: &'a WalletStore) -> Self {
+        Self { store, block: None }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl Widget for WalletList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let items = self.store.wallets().iter().map(|wallet| {
+            let state = match wallet.ready_state() {
+                _ if wallet.connected() => "connected".green(),
+                WalletReadyState::Installed | WalletReadyState::Loadable => {
+                    wallet.ready_state().to_string().into()
+                }
+                state => state.to_string().dark_gray(),
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(wallet.name()),
+                Span::raw("  "),
+                state,
+            ]))
+        });
+
+        let mut list = List::new(items)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        if let Some(block) = self.block {
+            list = list.block(block);
+        }
+
+        let mut state = ListState::default().with_selected(Some(self.store.selected_index()));
+        StatefulWidget::render(list, area, buf, &mut state);
+    }
+}
+
+/// Whether the selected wallet is connected and to which account, followed by the last error
+pub struct ConnectStatus<'a> {
+    store: &'a WalletStore,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> ConnectStatus<'a> {
+    pub fn new(store: &'a WalletStore) -> Self {
+        Self { store, block: None }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl Widget for ConnectStatus<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let status = match self.store.selected() {
+            Some(wallet) => match wallet.public_key() {
+                Some(public_key) if wallet.connected() => Line::from(vec![
+                    "Connected ".green(),
+                    Span::raw(public_key.to_string()),
+                ]),
+                _ if wallet.connecting() => Line::from("Connecting…".yellow()),
+                _ => Line::from("Disconnected".dark_gray()),
+            },
+            None => Line::from("No wallets".dark_gray()),
+        };
+
+        let mut lines = vec![status];
+        if let Some(error) = self.store.last_error() {
+            lines.push(Line::from(error.to_string().red()));
+        }
+        render_paragraph(Paragraph::new(lines), self.block, area, buf);
+    }
+}
+
+/// The SOL balance of the selected wallet, `—` until it is fetched
+pub struct Balance<'a> {
+    store: &'a WalletStore,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> Balance<'a> {
+    pub fn new(store: &'a WalletStore) -> Self {
+        Self { store, block: None }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl Widget for Balance<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let balance = match self.store.balance() {
+            Some(lamports) => Line::from(format!("{} SOL", lamports.sol()).bold()),
+            None => Line::from("—".dark_gray()),
+        };
+        render_paragraph(Paragraph::new(balance), self.block, area, buf);
+    }
+}
+
+/// The fields of a [`SendForm`], the focused one highlighted, and the outcome of the last send
+pub struct SendFormView<'a> {
+    form: &'a SendForm,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> SendFormView<'a> {
+    pub fn new(form: &'a SendForm) -> Self {
+        Self { form, block: None }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl Widget for SendFormView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let field = |label: &'static str, value: &str, field: SendField| {
+            let style = if self.form.focus() == field {
+                Style::new().fg(Color::Yellow)
+            } else {
+                Style::new()
+            };
+            Line::from(vec![
+                Span::styled(label, style),
+                Span::raw(value.to_string()),
+            ])
+        };
+
+        let mut lines = vec![
+            field("To:     ", self.form.recipient(), SendField::Recipient),
+            field("Amount: ", self.form.amount(), SendField::Amount),
+        ];
+        match self.form.outcome() {
+            Some(Ok(signature)) => lines.push(Line::from(vec![
+                "Sent ".green(),
+                Span::raw(signature.to_string()),
+            ])),
+            Some(Err(error)) => lines.push(Line::from(error.clone().red())),
+            None => {}
+        }
+        render_paragraph(Paragraph::new(lines), self.block, area, buf);
+    }
+}
+
+fn render_paragraph(paragraph: Paragraph, block: Option<Block>, area: Rect, buf: &mut Buffer) {
+    let mut paragraph = paragraph.wrap(Wrap { trim: true });
+    if let Some(block) = block {
+        paragraph = paragraph.block(block);
+    }
+    paragraph.render(area, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::Terminal;
+    use wallet_adapter_base::BaseWalletAdapter;
+    use wallet_adapter_mock::MockWalletAdapter;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn draws_the_connected_wallet() {
+        let wallet = MockWalletAdapter::new();
+        let mut store = WalletStore::new(vec![
+            Box::new(MockWalletAdapter::new()),
+            Box::new(wallet.clone()),
+        ]);
+        store.select_next();
+        store.connect().await.unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 4)).unwrap();
+        terminal
+            .draw(|frame| {
+                let [list, status] =
+                    Layout::vertical([Constraint::Length(2); 2]).areas(frame.area());
+                frame.render_widget(WalletList::new(&store), list);
+                frame.render_widget(ConnectStatus::new(&store), status);
+            })
+            .unwrap();
+
+        let rows: Vec<String> = (0..4)
+            .map(|y| {
+                let buffer = terminal.backend().buffer();
+                (0..60).map(|x| buffer[(x, y)].symbol()).collect()
+            })
+            .collect();
+        assert!(rows[0].starts_with("  MockWallet  Installed"));
+        assert!(rows[1].starts_with("> MockWallet  connected"));
+        let public_key = wallet.public_key().unwrap().to_string();
+        assert!(rows[2].contains(&public_key[..20]));
+    }
+}
@@ -0,0 +1,8 @@
+const COMMANDS: &[&str] = &["set_public_key", "respond"];
+
+fn main() {
+    // permissions are only generated for the tauri app, not the wasm frontend
+    if std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() != Ok("wasm32") {
+        tauri_plugin::Builder::new(COMMANDS).build();
+    }
+}
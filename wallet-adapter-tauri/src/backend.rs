@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use base64::prelude::*;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::TransactionVersion;
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::oneshot;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+    SupportedTransactionVersions, TransactionOrVersionedTransaction, WalletAdapterEvent,
+    WalletAdapterEventEmitter, WalletError, WalletReadyState,
+};
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::types::{DynSigner, SendTransactionOptions};
+
+use crate::protocol::{WalletMethod, WalletRequest, WalletResponse, PLUGIN_NAME, REQUEST_EVENT};
+
+/// Register the plugin, the adapter is then available through [`WalletAdapterExt`]
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new(PLUGIN_NAME)
+        .invoke_handler(tauri::generate_handler![set_public_key, respond])
+        .setup(|app, _api| {
+            app.manage(TauriWalletAdapter::new(app.clone()));
+            Ok(())
+        })
+        .build()
+}
+
+pub trait WalletAdapterExt<R: Runtime> {
+    fn wallet_adapter(&self) -> TauriWalletAdapter<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> WalletAdapterExt<R> for T {
+    fn wallet_adapter(&self) -> TauriWalletAdapter<R> {
+        self.state::<TauriWalletAdapter<R>>().inner().clone()
+    }
+}
+
+#[tauri::command]
+fn set_public_key<R: Runtime>(
+    wallet: State<'_, TauriWalletAdapter<R>>,
+    public_key: Option<String>,
+) -> Result<(), String> {
+    let public_key = public_key
+        .map(|public_key| public_key.parse::<Pubkey>())
+        .transpose()
+        .map_err(|err| err.to_string())?;
+
+    wallet
+        .set_public_key(public_key)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn respond<R: Runtime>(
+    wallet: State<'_, TauriWalletAdapter<R>>,
+    response: WalletResponse,
+) -> Result<(), String> {
+    let reply = wallet
+        .shared
+        .waiting
+        .lock()
+        .map_err(|err| err.to_string())?
+        .remove(&response.id);
+
+    if let Some(reply) = reply {
+        let _ = reply.send(response);
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Shared {
+    public_key: Mutex<Option<Pubkey>>,
+    next_id: AtomicU64,
+    waiting: Mutex<HashMap<u64, oneshot::Sender<WalletResponse>>>,
+}
+
+/// The wallet connected in the webview, seen from the backend
+pub struct TauriWalletAdapter<R: Runtime> {
+    app: AppHandle<R>,
+    shared: Arc<Shared>,
+    event_emitter: WalletAdapterEventEmitter,
+}
+
+impl<R: Runtime> Clone for TauriWalletAdapter<R> {
+    fn clone(&self) -> Self {
+        Self {
+            app: self.app.clone(),
+            shared: self.shared.clone(),
+            event_emitter: self.event_emitter.clone(),
+        }
+    }
+}
+
+impl<R: Runtime> TauriWalletAdapter<R> {
+    fn new(app: AppHandle<R>) -> Self {
+        Self {
+            app,
+            shared: Arc::new(Shared::default()),
+            event_emitter: WalletAdapterEventEmitter::new(),
+        }
+    }
+
+    /// Store the account reported by the webview, emitting `Connect`/`Disconnect` when it changed
+    fn set_public_key(&self, public_key: Option<Pubkey>) -> anyhow::Result<()> {
+        let previous = std::mem::replace(
+            &mut *self
+                .shared
+                .public_key
+                .lock()
+                .map_err(|err| anyhow!("{err:?}"))?,
+            public_key,
+        );
+
+        match public_key {
+            Some(public_key) if previous != Some(public_key) => self
+                .event_emitter
+                .try_emit(WalletAdapterEvent::Connect(public_key)),
+            None if previous.is_some() => {
+                self.event_emitter.try_emit(WalletAdapterEvent::Disconnect)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn request(
+        &self,
+        method: WalletMethod,
+        payload: Option<String>,
+    ) -> wallet_adapter_base::Result<Option<String>> {
+        let id = self.shared.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.shared
+            .waiting
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .insert(id, tx);
+
+        self.app
+            .emit(
+                REQUEST_EVENT,
+                WalletRequest {
+                    id,
+                    method,
+                    payload,
+                },
+            )
+            .map_err(|err| anyhow!("{err}"))?;
+
+        rx.await
+            .map_err(|_| WalletError::WalletDisconnected)?
+            .into_result()
+    }
+
+    async fn request_bytes(
+        &self,
+        method: WalletMethod,
+        payload: &[u8],
+    ) -> wallet_adapter_base::Result<Vec<u8>> {
+        let result = self
+            .request(method, Some(BASE64_STANDARD.encode(payload)))
+            .await?
+            .ok_or_else(|| anyhow!("{method:?} returned nothing"))?;
+
+        Ok(BASE64_STANDARD
+            .decode(result)
+            .map_err(|err| anyhow!("{err}"))?)
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: Runtime> BaseWalletAdapter for TauriWalletAdapter<R> {
+    fn event_emitter(&self) -> WalletAdapterEventEmitter {
+        self.event_emitter.clone()
+    }
+
+    fn name(&self) -> String {
+        "Tauri Webview Wallet".to_string()
+    }
+
+    fn url(&self) -> String {
+        "https://github.com/mucks/wallet-adapter".to_string()
+    }
+
+    fn icon(&self) -> String {
+        String::new()
+    }
+
+    fn ready_state(&self) -> WalletReadyState {
+        WalletReadyState::Loadable
+    }
+
+    fn public_key(&self) -> Option<Pubkey> {
+        *self.shared.public_key.lock().ok()?
+    }
+
+    fn connecting(&self) -> bool {
+        false
+    }
+
+    fn supported_transaction_versions(&self) -> Option<SupportedTransactionVersions> {
+        Some(vec![
+            TransactionVersion::LEGACY,
+            TransactionVersion::Number(0),
+        ])
+    }
+
+    /// Ask the webview to connect its wallet
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
+        let public_key = self
+            .request(WalletMethod::Connect, None)
+            .await?
+            .ok_or_else(|| anyhow!("connect returned no public key"))?
+            .parse::<Pubkey>()
+            .map_err(|err| anyhow!("{err}"))?;
+
+        self.set_public_key(Some(public_key))?;
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn disconnect(&self) -> anyhow::Result<()> {
+        self.request(WalletMethod::Disconnect, None).await?;
+        self.set_public_key(None)
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> wallet_adapter_base::Result<Signature> {
+        self.check_if_transaction_is_supported(&transaction)?;
+
+        let transaction = match transaction {
+            TransactionOrVersionedTransaction::Transaction(tx) => {
+                let send_options = options.as_ref().map(|o| o.send_options.clone());
+                let mut tx = self
+                    .prepare_transaction(tx, connection, send_options.as_ref())
+                    .await?;
+
+                if let Some(options) = options.as_ref().filter(|o| !o.signers.is_empty()) {
+                    let signers: Vec<&dyn Signer> = options
+                        .signers
+                        .iter()
+                        .map(|s| s.as_ref() as &dyn Signer)
+                        .collect();
+                    tx.try_partial_sign(&signers, tx.message.recent_blockhash)
+                        .map_err(|err| anyhow!("{err}"))?;
+                }
+
+                TransactionOrVersionedTransaction::Transaction(tx)
+            }
+            versioned => versioned,
+        };
+
+        let signed = self.sign_transaction(transaction).await?;
+
+        Ok(connection
+            .send_raw_transaction(signed.serialize()?, options.as_ref())
+            .await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: Runtime> BaseSignerWalletAdapter for TauriWalletAdapter<R> {
+    /// The key stays in the webview's wallet
+    fn wallet_signer(&self) -> Option<Box<DynSigner>> {
+        None
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> wallet_adapter_base::Result<TransactionOrVersionedTransaction> {
+        if !self.connected() {
+            return Err(WalletError::WalletNotConnected);
+        }
+
+        let signed = self
+            .request_bytes(WalletMethod::SignTransaction, &transaction.serialize()?)
+            .await?;
+
+        Ok(TransactionOrVersionedTransaction::deserialize(&signed)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: Runtime> BaseMessageSignerWalletAdapter for TauriWalletAdapter<R> {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_message(&self, message: &[u8]) -> wallet_adapter_base::Result<Vec<u8>> {
+        if !self.connected() {
+            return Err(WalletError::WalletNotConnected);
+        }
+
+        self.request_bytes(WalletMethod::SignMessage, message).await
+    }
+}
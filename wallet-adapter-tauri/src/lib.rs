@@ -0,0 +1,22 @@
+//! Use the wallet of a Tauri webview from the Rust backend.
+//!
+//! The wasm frontend keeps using its browser adapters (Phantom, Solflare, ...) and runs
+//! [`bridge`] next to them. The app registers the plugin with [`init`] and gets a
+//! [`TauriWalletAdapter`] implementing the usual adapter traits, whose requests are
+//! forwarded to the webview over Tauri's IPC.
+//!
+//! The webview needs `app.withGlobalTauri` and the `wallet-adapter:default` permission.
+
+mod protocol;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backend;
+#[cfg(target_arch = "wasm32")]
+mod webview;
+
+pub use protocol::{WalletMethod, WalletRequest, WalletResponse, PLUGIN_NAME, REQUEST_EVENT};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use backend::{init, TauriWalletAdapter, WalletAdapterExt};
+#[cfg(target_arch = "wasm32")]
+pub use webview::{bridge, WebviewBridge};
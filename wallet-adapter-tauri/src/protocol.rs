@@ -0,0 +1,71 @@
+//! Messages exchanged between the backend and the webview
+
+use serde::{Deserialize, Serialize};
+use wallet_adapter_base::WalletError;
+
+pub const PLUGIN_NAME: &str = "wallet-adapter";
+/// Emitted by the backend to the webview with a [`WalletRequest`]
+pub const REQUEST_EVENT: &str = "wallet-adapter://request";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WalletMethod {
+    Connect,
+    Disconnect,
+    /// `payload` is the base64 encoded wire transaction
+    SignTransaction,
+    /// `payload` is the base64 encoded message
+    SignMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletRequest {
+    pub id: u64,
+    pub method: WalletMethod,
+    pub payload: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletResponseError {
+    pub code: u32,
+    pub message: String,
+}
+
+/// Answer of the webview, `result` is the base58 public key for `connect`
+/// and base64 bytes for the signing methods
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletResponse {
+    pub id: u64,
+    pub result: Option<String>,
+    pub error: Option<WalletResponseError>,
+}
+
+impl WalletResponse {
+    pub fn new(id: u64, result: Result<Option<String>, WalletError>) -> Self {
+        match result {
+            Ok(result) => Self {
+                id,
+                result,
+                error: None,
+            },
+            Err(err) => Self {
+                id,
+                result: None,
+                error: Some(WalletResponseError {
+                    code: err.code(),
+                    message: err.message(),
+                }),
+            },
+        }
+    }
+
+    pub fn into_result(self) -> Result<Option<String>, WalletError> {
+        match self.error {
+            Some(err) => Err(WalletError::from_code(err.code, err.message)),
+            None => Ok(self.result),
+        }
+    }
+}
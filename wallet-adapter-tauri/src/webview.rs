@@ -0,0 +1,149 @@
+use anyhow::{anyhow, Context};
+use base64::prelude::*;
+use serde::Serialize;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, EventSubscription, TransactionOrVersionedTransaction,
+    WalletAdapterEvent, WalletError,
+};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::protocol::{WalletMethod, WalletRequest, WalletResponse, PLUGIN_NAME, REQUEST_EVENT};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["__TAURI__", "event"], catch)]
+    async fn listen(event: &str, handler: &js_sys::Function) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SetPublicKeyArgs {
+    public_key: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RespondArgs {
+    response: WalletResponse,
+}
+
+async fn invoke_command<T: Serialize>(name: &str, args: &T) -> anyhow::Result<()> {
+    let args = serde_wasm_bindgen::to_value(args).map_err(|err| anyhow!("{err}"))?;
+    invoke(&format!("plugin:{PLUGIN_NAME}|{name}"), args)
+        .await
+        .map_err(|err| anyhow!("{name} failed: {err:?}"))?;
+    Ok(())
+}
+
+fn report_public_key(public_key: Option<String>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(err) = invoke_command("set_public_key", &SetPublicKeyArgs { public_key }).await {
+            tracing::warn!("{err:?}");
+        }
+    });
+}
+
+/// Forwards the backend's requests to the adapter until dropped
+pub struct WebviewBridge {
+    _subscription: EventSubscription,
+    _handler: Closure<dyn FnMut(JsValue)>,
+    unlisten: js_sys::Function,
+}
+
+impl Drop for WebviewBridge {
+    fn drop(&mut self) {
+        let _ = self.unlisten.call0(&JsValue::NULL);
+    }
+}
+
+/// Serve the backend's `TauriWalletAdapter` with `adapter`,
+/// and keep the backend informed about the connected account
+pub async fn bridge<A>(adapter: A) -> anyhow::Result<WebviewBridge>
+where
+    A: BaseMessageSignerWalletAdapter + Clone + 'static,
+{
+    report_public_key(
+        adapter
+            .public_key()
+            .map(|public_key| public_key.to_string()),
+    );
+
+    let subscription = adapter.event_emitter().on(|event| match event {
+        WalletAdapterEvent::Connect(public_key)
+        | WalletAdapterEvent::AccountChanged(public_key) => {
+            report_public_key(Some(public_key.to_string()))
+        }
+        WalletAdapterEvent::Disconnect => report_public_key(None),
+        _ => {}
+    });
+
+    let handler = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+        let adapter = adapter.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let payload = js_sys::Reflect::get(&event, &JsValue::from_str("payload"))
+                .unwrap_or(JsValue::UNDEFINED);
+            let request: WalletRequest = match serde_wasm_bindgen::from_value(payload) {
+                Ok(request) => request,
+                Err(err) => {
+                    tracing::warn!("invalid wallet request: {err}");
+                    return;
+                }
+            };
+
+            let response = WalletResponse::new(request.id, handle(adapter, &request).await);
+            if let Err(err) = invoke_command("respond", &RespondArgs { response }).await {
+                tracing::warn!("{err:?}");
+            }
+        });
+    });
+
+    let unlisten = listen(REQUEST_EVENT, handler.as_ref().unchecked_ref())
+        .await
+        .map_err(|err| anyhow!("could not listen to {REQUEST_EVENT}: {err:?}"))?
+        .dyn_into::<js_sys::Function>()
+        .map_err(|_| anyhow!("listen returned no unlisten function"))?;
+
+    Ok(WebviewBridge {
+        _subscription: subscription,
+        _handler: handler,
+        unlisten,
+    })
+}
+
+fn payload(request: &WalletRequest) -> anyhow::Result<Vec<u8>> {
+    let payload = request
+        .payload
+        .as_deref()
+        .context("request has no payload")?;
+    Ok(BASE64_STANDARD.decode(payload)?)
+}
+
+async fn handle<A: BaseMessageSignerWalletAdapter>(
+    mut adapter: A,
+    request: &WalletRequest,
+) -> Result<Option<String>, WalletError> {
+    match request.method {
+        WalletMethod::Connect => {
+            adapter.connect().await?;
+            Ok(adapter
+                .public_key()
+                .map(|public_key| public_key.to_string()))
+        }
+        WalletMethod::Disconnect => {
+            adapter.disconnect().await?;
+            Ok(None)
+        }
+        WalletMethod::SignTransaction => {
+            let transaction = TransactionOrVersionedTransaction::deserialize(&payload(request)?)?;
+            let signed = adapter.sign_transaction(transaction).await?;
+            Ok(Some(BASE64_STANDARD.encode(signed.serialize()?)))
+        }
+        WalletMethod::SignMessage => {
+            let signature = adapter.sign_message(&payload(request)?).await?;
+            Ok(Some(BASE64_STANDARD.encode(signature)))
+        }
+    }
+}
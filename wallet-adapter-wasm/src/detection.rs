@@ -0,0 +1,139 @@
+use anyhow::{anyhow, Result};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// How [`GenericWasmWalletAdapter`](crate::generic_wallet::GenericWasmWalletAdapter) looks for
+/// the injected provider of a wallet.
+///
+/// The provider is checked every `interval_ms` until it is found or `max_attempts` checks failed.
+/// `events` and `observe_dom` trigger an extra check as soon as something happens on the page,
+/// so a longer interval can be used without detecting the wallet late.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletDetection {
+    pub interval_ms: i32,
+    pub max_attempts: u32,
+    /// Window events that trigger a check, eg. `phantom#initialized`
+    pub events: Vec<String>,
+    /// Check whenever nodes are added to the document, extensions inject their script tags there
+    pub observe_dom: bool,
+}
+
+impl Default for WalletDetection {
+    fn default() -> Self {
+        Self {
+            interval_ms: 1000,
+            max_attempts: 60,
+            events: vec![],
+            observe_dom: false,
+        }
+    }
+}
+
+impl WalletDetection {
+    /// Rely on page events and DOM changes, polling only as a slow fallback
+    pub fn event_driven(events: Vec<String>) -> Self {
+        Self {
+            interval_ms: 5000,
+            max_attempts: 12,
+            events,
+            observe_dom: true,
+        }
+    }
+
+    pub fn interval_ms(mut self, interval_ms: i32) -> Self {
+        self.interval_ms = interval_ms;
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+/// Event listeners and DOM observer calling `check` until dropped
+pub(crate) struct DetectionListeners {
+    events: Vec<String>,
+    closure: Closure<dyn FnMut()>,
+    observer: Option<web_sys::MutationObserver>,
+}
+
+impl DetectionListeners {
+    pub(crate) fn new(detection: &WalletDetection, check: impl FnMut() + 'static) -> Result<Self> {
+        let window = web_sys::window().ok_or_else(|| anyhow!("no window"))?;
+        let closure = Closure::wrap(Box::new(check) as Box<dyn FnMut()>);
+        let f: &js_sys::Function = closure.as_ref().unchecked_ref();
+
+        for event in &detection.events {
+            window
+                .add_event_listener_with_callback(event, f)
+                .map_err(|e| anyhow!("{:?}", e))?;
+        }
+
+        let observer = match window.document().and_then(|d| d.document_element()) {
+            Some(root) if detection.observe_dom => {
+                let observer = web_sys::MutationObserver::new(f).map_err(|e| anyhow!("{:?}", e))?;
+                let init = web_sys::MutationObserverInit::new();
+                init.set_child_list(true);
+                init.set_subtree(true);
+                observer
+                    .observe_with_options(&root, &init)
+                    .map_err(|e| anyhow!("{:?}", e))?;
+                Some(observer)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            events: detection.events.clone(),
+            closure,
+            observer,
+        })
+    }
+}
+
+impl Drop for DetectionListeners {
+    fn drop(&mut self) {
+        if let Some(observer) = &self.observer {
+            observer.disconnect();
+        }
+
+        if let Some(window) = web_sys::window() {
+            let f: &js_sys::Function = self.closure.as_ref().unchecked_ref();
+            for event in &self.events {
+                let _ = window.remove_event_listener_with_callback(event, f);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::testing::dispatch_window_event;
+
+    #[wasm_bindgen_test]
+    fn checks_on_the_events_until_dropped() {
+        let checks = Rc::new(Cell::new(0));
+        let counter = checks.clone();
+        let detection = WalletDetection {
+            events: vec!["detectionTest#initialized".to_string()],
+            ..WalletDetection::default()
+        };
+        let listeners =
+            DetectionListeners::new(&detection, move || counter.set(counter.get() + 1)).unwrap();
+
+        dispatch_window_event("detectionTest#initialized").unwrap();
+        dispatch_window_event("otherWallet#initialized").unwrap();
+        assert_eq!(checks.get(), 1);
+
+        drop(listeners);
+        dispatch_window_event("detectionTest#initialized").unwrap();
+        assert_eq!(checks.get(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::anyhow;
+use wallet_adapter_base::WalletError;
+use wasm_bindgen::JsValue;
+
+use crate::util::reflect_get;
+
+const USER_REJECTED: i64 = 4001;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Convert an error thrown by an injected provider into a typed [`WalletError`].
+///
+/// Providers throw objects with a numeric `code` and a `message` (eg. `PhantomError`),
+/// anything else ends up in `WalletError::Anyhow`.
+pub fn provider_error(err: impl Into<JsValue>) -> WalletError {
+    let err: JsValue = err.into();
+
+    let code = reflect_get(&err, &JsValue::from_str("code"))
+        .ok()
+        .and_then(|code| code.as_f64())
+        .map(|code| code as i64);
+    let message = reflect_get(&err, &JsValue::from_str("message"))
+        .ok()
+        .and_then(|message| message.as_string())
+        .unwrap_or_else(|| format!("{err:?}"));
+
+    wallet_error(code, message)
+}
+
+/// The [`WalletError`] of an error `code` in the style of the injected providers
+pub fn wallet_error(code: Option<i64>, message: String) -> WalletError {
+    match code {
+        Some(USER_REJECTED) => WalletError::WalletUserRejected(message),
+        Some(INTERNAL_ERROR) => WalletError::WalletInternalError(message),
+        Some(code) => WalletError::WalletProviderError((code, message)),
+        None => WalletError::Anyhow(anyhow!(message)),
+    }
+}
+
+/// The provider error code of `error`, the inverse of [`wallet_error`]
+pub fn provider_error_code(error: &WalletError) -> Option<i64> {
+    match error {
+        WalletError::WalletUserRejected(_) => Some(USER_REJECTED),
+        WalletError::WalletInternalError(_) => Some(INTERNAL_ERROR),
+        WalletError::WalletProviderError((code, _)) => Some(*code),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_provider_codes_to_typed_errors() {
+        let rejected = wallet_error(Some(4001), "User rejected the request.".to_string());
+        assert!(
+            matches!(&rejected, WalletError::WalletUserRejected(message) if message == "User rejected the request.")
+        );
+        assert_eq!(provider_error_code(&rejected), Some(4001));
+
+        let internal = wallet_error(Some(-32603), "Internal JSON-RPC error.".to_string());
+        assert!(matches!(internal, WalletError::WalletInternalError(_)));
+        assert_eq!(provider_error_code(&internal), Some(-32603));
+
+        let unknown = wallet_error(Some(4100), "Unauthorized".to_string());
+        assert!(matches!(
+            &unknown,
+            WalletError::WalletProviderError((4100, message)) if message == "Unauthorized"
+        ));
+        assert_eq!(provider_error_code(&unknown), Some(4100));
+    }
+
+    #[test]
+    fn errors_without_a_code_keep_their_message() {
+        let error = wallet_error(None, "provider exploded".to_string());
+        assert!(
+            matches!(&error, WalletError::Anyhow(err) if err.to_string() == "provider exploded")
+        );
+        assert_eq!(provider_error_code(&error), None);
+
+        let error = wallet_error(Some(4001), String::new());
+        assert!(matches!(&error, WalletError::WalletUserRejected(message) if message.is_empty()));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    fn reads_provider_error_objects() {
+        let error = js_sys::Object::new();
+        crate::util::reflect_set(&error, "code", &4001.into()).unwrap();
+        // without a message the whole object is kept for debugging
+        assert!(matches!(
+            provider_error(error.clone()),
+            WalletError::WalletUserRejected(message) if !message.is_empty()
+        ));
+
+        crate::util::reflect_set(&error, "message", &"Rejected".into()).unwrap();
+        assert!(matches!(
+            provider_error(error),
+            WalletError::WalletUserRejected(message) if message == "Rejected"
+        ));
+        assert!(matches!(
+            provider_error(js_sys::Error::new("boom")),
+            WalletError::Anyhow(err) if err.to_string() == "boom"
+        ));
+    }
+}
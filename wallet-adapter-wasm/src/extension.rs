@@ -0,0 +1,275 @@
+//! [`MessagingWallet`] over `chrome.runtime` ports, for dApps that are browser extensions
+//! themselves and don't get providers injected into their pages.
+//!
+//! ```ignore
+//! const MY_WALLET: MessagingWalletInfo = MessagingWalletInfo {
+//!     name: "My Wallet",
+//!     url: "https://mywallet.app",
+//!     icon: "data:image/svg+xml;base64,...",
+//! };
+//!
+//! let wallet = MessagingWallet::new(MY_WALLET, ExtensionTransport::new("<extension id>"));
+//! let adapter = GenericWasmWalletAdapter::new(wallet)?;
+//! ```
+//!
+//! The wallet extension has to list the dApp in its `externally_connectable` manifest key
+//! and answer on `chrome.runtime.onConnectExternal` ports.
+
+use anyhow::{anyhow, Context, Result};
+use js_sys::{Function, Object};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::messaging::{MessageChannel, MessageTransport};
+use crate::util::{reflect_get, reflect_set};
+
+/// Name of the ports, so the wallet can tell them from its other connections
+pub const PORT_NAME: &str = "wallet-adapter";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionTransport {
+    extension_id: String,
+}
+
+impl ExtensionTransport {
+    pub fn new(extension_id: impl Into<String>) -> Self {
+        Self {
+            extension_id: extension_id.into(),
+        }
+    }
+
+    pub fn extension_id(&self) -> &str {
+        &self.extension_id
+    }
+
+    /// `chrome.runtime`, `browser.runtime` on Firefox
+    fn runtime() -> Result<JsValue> {
+        let global = js_sys::global();
+        for namespace in ["chrome", "browser"] {
+            let namespace = reflect_get(&global, &JsValue::from_str(namespace))?;
+            if namespace.is_undefined() || namespace.is_null() {
+                continue;
+            }
+            let runtime = reflect_get(&namespace, &JsValue::from_str("runtime"))?;
+            if reflect_get(&runtime, &JsValue::from_str("connect")).is_ok_and(|f| f.is_function()) {
+                return Ok(runtime);
+            }
+        }
+        Err(anyhow!("not running in a browser extension"))
+    }
+
+    /// Why the port disconnected, from `runtime.lastError`
+    fn last_error() -> String {
+        Self::runtime()
+            .and_then(|runtime| reflect_get(&runtime, &JsValue::from_str("lastError")))
+            .and_then(|error| reflect_get(&error, &JsValue::from_str("message")))
+            .ok()
+            .and_then(|message| message.as_string())
+            .unwrap_or_else(|| "port disconnected".to_string())
+    }
+}
+
+/// `event.addListener(closure)` for the `onMessage` and `onDisconnect` events of a port
+fn add_listener(port: &JsValue, event: &str, closure: &Closure<dyn FnMut(JsValue)>) -> Result<()> {
+    let event = reflect_get(port, &JsValue::from_str(event))?;
+    let add_listener: Function = reflect_get(&event, &JsValue::from_str("addListener"))?
+        .dyn_into()
+        .map_err(|_| anyhow!("port event without addListener"))?;
+    add_listener
+        .call1(&event, closure.as_ref())
+        .map_err(|e| anyhow!("{e:?}"))?;
+    Ok(())
+}
+
+impl MessageTransport for ExtensionTransport {
+    fn is_available(&self) -> bool {
+        Self::runtime().is_ok()
+    }
+
+    fn open(&self, channel: &MessageChannel) -> Result<()> {
+        let runtime = Self::runtime()?;
+        let connect: Function = reflect_get(&runtime, &JsValue::from_str("connect"))?
+            .dyn_into()
+            .map_err(|_| anyhow!("runtime.connect is not a function"))?;
+
+        let options = Object::new();
+        reflect_set(&options, "name", &PORT_NAME.into())?;
+        let port = connect
+            .call2(&runtime, &self.extension_id.as_str().into(), &options)
+            .map_err(|e| anyhow!("could not connect to {}: {e:?}", self.extension_id))?;
+        if port.is_undefined() || port.is_null() {
+            return Err(anyhow!("no port to {}", self.extension_id));
+        }
+
+        let on_message = {
+            let channel = channel.clone();
+            Closure::wrap(Box::new(move |message: JsValue| channel.handle(&message))
+                as Box<dyn FnMut(JsValue)>)
+        };
+        let on_disconnect = {
+            let channel = channel.clone();
+            Closure::wrap(Box::new(move |_port: JsValue| {
+                let reason = Self::last_error();
+                tracing::info!("wallet extension port disconnected: {reason}");
+                channel.close(&reason);
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        add_listener(&port, "onMessage", &on_message)?;
+        add_listener(&port, "onDisconnect", &on_disconnect)?;
+
+        channel.keep_closure(on_message);
+        channel.keep_closure(on_disconnect);
+        channel.set_target(port);
+        Ok(())
+    }
+
+    fn post(&self, target: &JsValue, message: &JsValue) -> Result<()> {
+        let post_message: Function = reflect_get(target, &JsValue::from_str("postMessage"))?
+            .dyn_into()
+            .map_err(|_| anyhow!("port without postMessage"))?;
+        post_message
+            .call1(target, message)
+            .map_err(|e| anyhow!("{e:?}"))
+            .context("port disconnected")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js_sys::Array;
+    use serde_json::Value;
+    use solana_sdk::pubkey::Pubkey;
+    use wallet_adapter_base::WalletError;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::generic_wallet::{ConnectOptions, GenericWasmWallet};
+    use crate::messaging::{MessagingWallet, MessagingWalletInfo};
+    use crate::util::sleep_ms;
+
+    const INFO: MessagingWalletInfo = MessagingWalletInfo {
+        name: "Extension",
+        url: "https://example.com",
+        icon: "",
+    };
+
+    #[wasm_bindgen(inline_js = "
+        export function install_runtime() {
+            const runtime = { connects: [], posted: [] };
+            globalThis.chrome = {
+                runtime: {
+                    connect(extensionId, { name }) {
+                        const listeners = { onMessage: [], onDisconnect: [] };
+                        const port = {
+                            onMessage: { addListener: (listener) => listeners.onMessage.push(listener) },
+                            onDisconnect: { addListener: (listener) => listeners.onDisconnect.push(listener) },
+                            postMessage: (message) => runtime.posted.push(message),
+                        };
+                        runtime.connects.push([extensionId, name]);
+                        runtime.reply = (message) => listeners.onMessage.forEach((listener) => listener(message, port));
+                        runtime.disconnect = (message) => {
+                            globalThis.chrome.runtime.lastError = { message };
+                            listeners.onDisconnect.forEach((listener) => listener(port));
+                            delete globalThis.chrome.runtime.lastError;
+                        };
+                        return port;
+                    },
+                },
+            };
+            return runtime;
+        }
+
+        export function uninstall_runtime() {
+            delete globalThis.chrome;
+        }
+    ")]
+    extern "C" {
+        /// A `chrome.runtime` whose ports record the requests and answer what the test says
+        type FakeRuntime;
+
+        fn install_runtime() -> FakeRuntime;
+        fn uninstall_runtime();
+
+        #[wasm_bindgen(method, getter)]
+        fn connects(this: &FakeRuntime) -> Array;
+        #[wasm_bindgen(method, getter)]
+        fn posted(this: &FakeRuntime) -> Array;
+        #[wasm_bindgen(method)]
+        fn reply(this: &FakeRuntime, message: JsValue);
+        #[wasm_bindgen(method)]
+        fn disconnect(this: &FakeRuntime, message: &str);
+    }
+
+    /// The id and method of the last request posted to the port
+    fn last_request(runtime: &FakeRuntime) -> (u64, String, Value) {
+        let json = js_sys::JSON::stringify(&runtime.posted().pop())
+            .unwrap()
+            .as_string()
+            .unwrap();
+        let request: crate::messaging::MessageRequest = serde_json::from_str(&json).unwrap();
+        (request.id, request.method, request.params)
+    }
+
+    fn to_js(value: Value) -> JsValue {
+        crate::messaging::to_js(&value).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    async fn talks_to_the_wallet_over_a_port() {
+        let wallet = MessagingWallet::new(INFO, ExtensionTransport::new("wallet-extension-id"));
+        assert!(!wallet.is_correct_wallet());
+
+        let runtime = install_runtime();
+        assert!(wallet.is_correct_wallet());
+        let public_key = Pubkey::new_unique();
+
+        let (connected, _) = futures::join!(wallet.connect(ConnectOptions::default()), async {
+            sleep_ms(0).await;
+            let (id, method, params) = last_request(&runtime);
+            assert_eq!(method, "connect");
+            assert_eq!(params["onlyIfTrusted"], false);
+            runtime.reply(to_js(
+                serde_json::json!({ "id": id, "result": { "publicKey": public_key.to_string() } }),
+            ));
+        });
+        connected.unwrap();
+        assert_eq!(wallet.public_key().unwrap(), public_key);
+        let connects = runtime.connects();
+        assert_eq!(connects.length(), 1);
+        let port = Array::from(&connects.get(0));
+        assert_eq!(
+            port.get(0).as_string().as_deref(),
+            Some("wallet-extension-id")
+        );
+        assert_eq!(port.get(1).as_string().as_deref(), Some(PORT_NAME));
+
+        let (rejected, _) = futures::join!(wallet.sign_message(b"hello"), async {
+            sleep_ms(0).await;
+            let (id, method, _) = last_request(&runtime);
+            assert_eq!(method, "signMessage");
+            runtime.reply(to_js(serde_json::json!({
+                "id": id,
+                "error": { "code": 4001, "message": "User rejected the request." },
+            })));
+        });
+        assert!(matches!(
+            WalletError::from(rejected.unwrap_err()),
+            WalletError::WalletUserRejected(_)
+        ));
+
+        // the wallet closing the port fails the pending request and ends the session
+        let (closed, _) = futures::join!(wallet.sign_message(b"hello"), async {
+            sleep_ms(0).await;
+            runtime.disconnect("The wallet was locked");
+        });
+        assert!(matches!(
+            WalletError::from(closed.unwrap_err()),
+            WalletError::WalletDisconnection((_, reason)) if reason == "The wallet was locked"
+        ));
+        assert!(!wallet.is_connected());
+
+        uninstall_runtime();
+    }
+}
@@ -1,16 +1,21 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use solana_sdk::{pubkey::Pubkey, transaction::TransactionVersion};
 use wallet_adapter_base::{
-    BaseWalletAdapter, SupportedTransactionVersions, TransactionOrVersionedTransaction,
-    WalletAdapterEvent, WalletAdapterEventEmitter, WalletError, WalletReadyState,
+    check_versioned_send_options, send_all_in_turn, BaseWalletAdapter,
+    SupportedTransactionVersions, TransactionOrVersionedTransaction, WalletAdapterEvent,
+    WalletAdapterEventEmitter, WalletError, WalletOperation, WalletReadyState,
 };
 use wallet_adapter_common::connection::Connection;
-use wallet_adapter_common::types::SendTransactionOptions;
+use wallet_adapter_common::standard::WalletFeature;
+use wallet_adapter_common::types::{SendOptions, SendTransactionOptions};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::{prelude::Closure, JsCast};
 
+use crate::detection::{DetectionListeners, WalletDetection};
+
 mod wallet_binding {
     use super::*;
 
@@ -26,22 +31,50 @@ mod wallet_binding {
     }
 }
 
+/// Options passed to the provider's `connect()`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectOptions {
+    /// Only connect if the user already trusts the app, without showing the approval popup
+    pub only_if_trusted: bool,
+}
+
+impl ConnectOptions {
+    pub fn to_js(&self) -> Result<JsValue> {
+        let options = js_sys::Object::new();
+        crate::util::reflect_set(&options, "onlyIfTrusted", &self.only_if_trusted.into())?;
+        Ok(options.into())
+    }
+}
+
 #[async_trait::async_trait(?Send)]
 pub trait GenericWasmWallet: Sync + Send + std::fmt::Debug + Clone {
     fn is_correct_wallet(&self) -> bool;
     fn is_connected(&self) -> bool;
-    async fn connect(&self) -> Result<()>;
-    fn disconnect(&self) -> Result<()>;
+    async fn connect(&self, options: ConnectOptions) -> Result<()>;
+    async fn disconnect(&self) -> Result<()>;
     async fn sign_and_send_transaction(
         &self,
         transaction: TransactionOrVersionedTransaction,
     ) -> Result<solana_sdk::signature::Signature>;
+    /// Whether [`Self::sign_and_send_all_transactions`] asks for a single approval
+    fn can_sign_and_send_all(&self) -> bool {
+        false
+    }
+    /// The signature of each transaction, `None` for the ones the wallet didn't send
+    async fn sign_and_send_all_transactions(
+        &self,
+        _transactions: Vec<TransactionOrVersionedTransaction>,
+    ) -> Result<Vec<Option<solana_sdk::signature::Signature>>> {
+        anyhow::bail!("{} can't send several transactions at once", self.name())
+    }
     fn on(&self, event: &str, cb: js_sys::Function) -> Result<()>;
     fn off(&self, event: &str, cb: js_sys::Function) -> Result<()>;
     fn public_key(&self) -> Result<Pubkey>;
     fn name(&self) -> String;
     fn url(&self) -> String;
     fn icon(&self) -> String;
+    /// Whether connecting should open the current page in the wallet's in-app browser,
+    /// see [`is_ios_and_redirectable`](crate::util::is_ios_and_redirectable)
     fn is_ios_redirectable(&self) -> Result<bool> {
         Ok(false)
     }
@@ -50,52 +83,68 @@ pub trait GenericWasmWallet: Sync + Send + std::fmt::Debug + Clone {
     }
 }
 
+type SharedClosure<F> = Arc<Mutex<Option<Closure<F>>>>;
+
 #[derive(Debug, Clone)]
 pub struct GenericWasmWalletAdapter<T: GenericWasmWallet + 'static> {
     connecting: Arc<Mutex<bool>>,
+    disconnecting: Arc<Mutex<bool>>,
     wallet: Arc<T>,
     public_key: Arc<Mutex<Option<Pubkey>>>,
     wallet_ready_state: Arc<Mutex<WalletReadyState>>,
-    account_changed_closure: Arc<Mutex<Option<Closure<dyn FnMut(wallet_binding::Pubkey)>>>>,
+    account_changed_closure: SharedClosure<dyn FnMut(JsValue)>,
     disconnected_closure: Arc<Mutex<Option<Closure<dyn FnMut()>>>>,
+    connect_closure: SharedClosure<dyn FnMut(JsValue)>,
+    /// Listeners of [`BaseWalletAdapter::subscribe_provider_event`] by event name
+    provider_event_closures: Arc<Mutex<HashMap<String, Closure<dyn FnMut(JsValue)>>>>,
     event_emitter: WalletAdapterEventEmitter,
 }
 
 impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
     pub fn new(wallet: T) -> Result<Self> {
+        Self::new_with_detection(wallet, WalletDetection::default())
+    }
+
+    pub fn new_with_detection(wallet: T, detection: WalletDetection) -> Result<Self> {
         let adapter = Self {
             event_emitter: WalletAdapterEventEmitter::new(),
             connecting: Arc::new(Mutex::new(false)),
+            disconnecting: Arc::new(Mutex::new(false)),
             wallet: Arc::new(wallet),
             public_key: Arc::new(Mutex::new(None)),
             wallet_ready_state: Arc::new(Mutex::new(WalletReadyState::NotDetected)),
             account_changed_closure: Arc::new(Mutex::new(None)),
             disconnected_closure: Arc::new(Mutex::new(None)),
+            connect_closure: Arc::new(Mutex::new(None)),
+            provider_event_closures: Arc::new(Mutex::new(HashMap::new())),
         };
 
+        // lets UIs list the wallet right away and move it up once it is detected
+        adapter.emit_from_callback(WalletAdapterEvent::ReadyStateChange(adapter.ready_state()));
+
         if adapter.ready_state() != WalletReadyState::Unsupported {
             if adapter.wallet.is_ios_redirectable()? {
-                *adapter.wallet_ready_state.lock().unwrap() = WalletReadyState::Loadable;
-                // js lib emits event here
-            } else {
+                adapter.set_ready_state(WalletReadyState::Loadable);
+                adapter.emit_from_callback(WalletAdapterEvent::ReadyStateChange(
+                    WalletReadyState::Loadable,
+                ));
+            } else if !adapter.detect_wallet() {
                 let self_clone = adapter.clone();
 
-                // TODO: make this waiting loop a shared logic
                 wasm_bindgen_futures::spawn_local(async move {
-                    for _i in 0..60 {
-                        if self_clone.wallet.is_correct_wallet() {
-                            tracing::debug!("wallet detected {}", self_clone.wallet.name());
-                            self_clone.set_ready_state(WalletReadyState::Installed);
-                            self_clone
-                                .event_emitter
-                                .emit(WalletAdapterEvent::ReadyStateChange(
-                                    WalletReadyState::Installed,
-                                ))
-                                .await
-                                .unwrap();
+                    let listener_adapter = self_clone.clone();
+                    // dropped once detection is over
+                    let _listeners = DetectionListeners::new(&detection, move || {
+                        listener_adapter.detect_wallet();
+                    })
+                    .map_err(|err| tracing::error!("wallet detection listeners: {err:?}"))
+                    .ok();
+
+                    for _ in 0..detection.max_attempts {
+                        crate::util::sleep_ms(detection.interval_ms).await;
+                        if self_clone.detect_wallet() {
                             break;
                         }
-                        crate::util::sleep_ms(1000).await;
                     }
                 });
             }
@@ -104,6 +153,31 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
         Ok(adapter)
     }
 
+    /// Mark the wallet as installed once its provider shows up, returns whether it is installed
+    fn detect_wallet(&self) -> bool {
+        if self.ready_state() == WalletReadyState::Installed {
+            return true;
+        }
+
+        if !self.wallet.is_correct_wallet() {
+            return false;
+        }
+
+        tracing::debug!("wallet detected {}", self.wallet.name());
+        self.set_ready_state(WalletReadyState::Installed);
+
+        // sessions can also be approved from the extension, eg. after a silent eager connect
+        if let Err(err) = self.wallet.on("connect", self.connect_event()) {
+            tracing::error!("failed to subscribe to connect event: {err:?}");
+        }
+
+        self.emit_from_callback(WalletAdapterEvent::ReadyStateChange(
+            WalletReadyState::Installed,
+        ));
+
+        true
+    }
+
     fn disconnected(&self) -> js_sys::Function {
         let mut disconnected = self.disconnected_closure.lock().unwrap();
 
@@ -111,9 +185,22 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
             let f: &js_sys::Function = closure.as_ref().unchecked_ref();
             return f.clone();
         } else {
+            let self_clone = self.clone();
             let closure = Closure::wrap(Box::new(move || {
-                // disconnected code here
-                tracing::info!("disconnected");
+                tracing::info!("disconnected from the wallet");
+
+                // disconnects started by the adapter unsubscribe before reaching the wallet
+                if self_clone.public_key().is_none() {
+                    return;
+                }
+
+                if let Err(err) = self_clone.unsubscribe_session_events() {
+                    tracing::error!("failed to unsubscribe from wallet events: {err:?}");
+                }
+
+                self_clone.set_public_key(None);
+                self_clone.set_connecting(false);
+                self_clone.emit_from_callback(WalletAdapterEvent::Disconnect);
             }) as Box<dyn FnMut()>);
             let f: &js_sys::Function = closure.as_ref().unchecked_ref();
 
@@ -124,6 +211,60 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
         }
     }
 
+    fn connect_event(&self) -> js_sys::Function {
+        let mut connect = self.connect_closure.lock().unwrap();
+
+        if let Some(closure) = connect.as_ref() {
+            let f: &js_sys::Function = closure.as_ref().unchecked_ref();
+            f.clone()
+        } else {
+            let self_clone = self.clone();
+            let closure = Closure::wrap(Box::new(move |_public_key: JsValue| {
+                // connects started by the adapter are handled in try_connect
+                if self_clone.connecting() {
+                    return;
+                }
+
+                let public_key = match self_clone.wallet.public_key() {
+                    Ok(public_key) => public_key,
+                    Err(err) => {
+                        tracing::error!("connect event without public key: {err:?}");
+                        return;
+                    }
+                };
+
+                if self_clone.public_key() == Some(public_key) {
+                    return;
+                }
+
+                tracing::info!("connected from the wallet: {public_key}");
+
+                if let Err(err) = self_clone.subscribe_session_events() {
+                    tracing::error!("failed to subscribe to wallet events: {err:?}");
+                }
+
+                self_clone.set_public_key(Some(public_key));
+                self_clone.emit_from_callback(WalletAdapterEvent::Connect(public_key));
+            }) as Box<dyn FnMut(JsValue)>);
+            let f: &js_sys::Function = closure.as_ref().unchecked_ref();
+            let f = f.clone();
+            *connect = Some(closure);
+            f
+        }
+    }
+
+    fn subscribe_session_events(&self) -> Result<()> {
+        self.wallet.on("disconnect", self.disconnected())?;
+        self.wallet.on("accountChanged", self.account_changed())?;
+        Ok(())
+    }
+
+    fn unsubscribe_session_events(&self) -> Result<()> {
+        self.wallet.off("disconnect", self.disconnected())?;
+        self.wallet.off("accountChanged", self.account_changed())?;
+        Ok(())
+    }
+
     fn account_changed(&self) -> js_sys::Function {
         let mut account_changed = self.account_changed_closure.lock().unwrap();
 
@@ -132,22 +273,46 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
             return f.clone();
         } else {
             let self_clone = self.clone();
-            let closure = Closure::wrap(Box::new(move |pubkey: wallet_binding::Pubkey| {
-                // disconnected code here
+            let closure = Closure::wrap(Box::new(move |pubkey: JsValue| {
                 tracing::info!("account changed: {pubkey:?}");
 
-                let public_key: Pubkey = pubkey.to_bytes().try_into().unwrap();
-
-                if self_clone.public_key() == Some(public_key) {
+                // the new account hasn't authorized the app yet, ask for it like the js adapters do
+                if pubkey.is_null() || pubkey.is_undefined() {
+                    let self_clone = self_clone.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        // an account change, not a connect from the wallet
+                        self_clone.set_connecting(true);
+                        let public_key =
+                            match self_clone.wallet.connect(ConnectOptions::default()).await {
+                                Ok(()) => self_clone.wallet.public_key(),
+                                Err(err) => Err(err),
+                            };
+                        self_clone.set_connecting(false);
+                        match public_key {
+                            Ok(public_key) => self_clone.account_changed_to(public_key),
+                            Err(err) => {
+                                tracing::error!(
+                                    "failed to reconnect after account change: {err:?}"
+                                );
+                                self_clone.emit_from_callback(WalletAdapterEvent::Error {
+                                    wallet: self_clone.name(),
+                                    operation: WalletOperation::AccountChange,
+                                    error: err.into(),
+                                });
+                            }
+                        }
+                    });
                     return;
                 }
 
-                self_clone.set_public_key(Some(public_key));
-                self_clone
-                    .event_emitter
-                    .emit_sync(WalletAdapterEvent::Connect(public_key))
-                    .unwrap();
-            }) as Box<dyn FnMut(wallet_binding::Pubkey)>);
+                let pubkey: wallet_binding::Pubkey = pubkey.unchecked_into();
+                match Pubkey::try_from(pubkey.to_bytes()) {
+                    Ok(public_key) => self_clone.account_changed_to(public_key),
+                    Err(bytes) => {
+                        tracing::error!("invalid public key in accountChanged: {bytes:?}")
+                    }
+                }
+            }) as Box<dyn FnMut(JsValue)>);
             let f: &js_sys::Function = closure.as_ref().unchecked_ref();
             let f = f.clone();
             *account_changed = Some(closure);
@@ -155,10 +320,47 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
         }
     }
 
+    /// Emits the provider's `event` with its first argument as JSON, `null` if it has none
+    fn provider_event(&self, event: &str) -> Closure<dyn FnMut(JsValue)> {
+        let self_clone = self.clone();
+        let name = event.to_string();
+        Closure::wrap(Box::new(move |payload: JsValue| {
+            let payload = js_sys::JSON::stringify(&payload)
+                .ok()
+                .and_then(|json| json.as_string())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+            self_clone.emit_from_callback(WalletAdapterEvent::ProviderEvent {
+                name: name.clone(),
+                payload,
+            });
+        }) as Box<dyn FnMut(JsValue)>)
+    }
+
+    fn account_changed_to(&self, public_key: Pubkey) {
+        if self.public_key() == Some(public_key) {
+            return;
+        }
+
+        self.set_public_key(Some(public_key));
+        self.emit_from_callback(WalletAdapterEvent::AccountChanged(public_key));
+    }
+
+    /// Emit from a JS callback, where waiting for room in the channel isn't possible
+    fn emit_from_callback(&self, event: WalletAdapterEvent) {
+        if let Err(err) = self.event_emitter.try_emit(event) {
+            tracing::error!("failed to emit wallet event: {err:?}");
+        }
+    }
+
     fn set_connecting(&self, connecting: bool) {
         *self.connecting.lock().unwrap() = connecting;
     }
 
+    fn set_disconnecting(&self, disconnecting: bool) {
+        *self.disconnecting.lock().unwrap() = disconnecting;
+    }
+
     fn set_public_key(&self, public_key: Option<Pubkey>) {
         *self.public_key.lock().unwrap() = public_key;
     }
@@ -167,7 +369,52 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
         *self.wallet_ready_state.lock().unwrap() = ready_state;
     }
 
-    async fn try_connect(&mut self) -> wallet_adapter_base::Result<()> {
+    /// Add the blockhash and the extra signatures, leaving the wallet's signature to the wallet
+    async fn prepare_for_wallet(
+        &self,
+        mut transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> wallet_adapter_base::Result<TransactionOrVersionedTransaction> {
+        if self.public_key().is_none() {
+            return Err(WalletError::WalletNotConnected);
+        }
+        if options.as_ref().is_some_and(|o| o.sponsor.is_some()) {
+            return Err(WalletError::WalletSendTransactionError(
+                "This wallet sends transactions itself, a sponsor can't submit them".to_string(),
+            ));
+        }
+
+        let send_options = options.as_ref().map(|o| o.send_options.clone());
+
+        match &mut transaction {
+            TransactionOrVersionedTransaction::Transaction(ref mut tx) => {
+                *tx = self
+                    .prepare_transaction(tx.clone(), connection, send_options.as_ref())
+                    .await?;
+
+                if let Some(opt) = options {
+                    if opt.signers.len() > 0 {
+                        tx.partial_sign(&opt.signers, tx.message.recent_blockhash);
+                    }
+                }
+            }
+            TransactionOrVersionedTransaction::VersionedTransaction(_) => {
+                self.check_if_transaction_is_supported(&transaction)?;
+                check_versioned_send_options(send_options.as_ref())?;
+
+                if let Some(opt) = options {
+                    if !opt.signers.is_empty() {
+                        transaction.partial_sign(&opt.signers)?;
+                    }
+                }
+            }
+        }
+
+        Ok(transaction)
+    }
+
+    async fn try_connect(&mut self, options: ConnectOptions) -> wallet_adapter_base::Result<()> {
         tracing::info!("{} connect", self.name());
 
         if self.connected() || self.connecting() {
@@ -175,7 +422,9 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
         }
 
         if self.ready_state() == WalletReadyState::Loadable {
+            // the page reopens in the wallet's browser, where the provider is injected
             self.wallet.set_wallet_url()?;
+            return Ok(());
         }
 
         if self.ready_state() != WalletReadyState::Installed {
@@ -185,7 +434,7 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
         self.set_connecting(true);
 
         if !self.wallet.is_connected() {
-            match self.wallet.connect().await {
+            match self.wallet.connect(options).await {
                 Ok(_) => {}
                 Err(e) => {
                     return Err(e.into());
@@ -195,8 +444,7 @@ impl<T: GenericWasmWallet + 'static> GenericWasmWalletAdapter<T> {
 
         let public_key = self.wallet.public_key()?;
 
-        self.wallet.on("disconnect", self.disconnected())?;
-        self.wallet.on("accountChanged", self.account_changed())?;
+        self.subscribe_session_events()?;
 
         self.set_public_key(Some(public_key));
 
@@ -242,6 +490,10 @@ impl<T: GenericWasmWallet + 'static> BaseWalletAdapter for GenericWasmWalletAdap
         self.connecting.lock().unwrap().clone()
     }
 
+    fn disconnecting(&self) -> bool {
+        *self.disconnecting.lock().unwrap()
+    }
+
     fn supported_transaction_versions(&self) -> Option<SupportedTransactionVersions> {
         Some(vec![
             TransactionVersion::LEGACY,
@@ -249,17 +501,92 @@ impl<T: GenericWasmWallet + 'static> BaseWalletAdapter for GenericWasmWalletAdap
         ])
     }
 
+    fn features(&self) -> Vec<WalletFeature> {
+        vec![
+            WalletFeature::StandardConnect,
+            WalletFeature::StandardDisconnect,
+            WalletFeature::StandardEvents,
+            WalletFeature::SolanaSignTransaction,
+            WalletFeature::SolanaSignAndSendTransaction,
+            WalletFeature::SolanaSignMessage,
+        ]
+    }
+
+    fn subscribe_provider_event(&self, event: &str) -> wallet_adapter_base::Result<()> {
+        if self.ready_state() != WalletReadyState::Installed {
+            return Err(WalletError::WalletNotReady);
+        }
+
+        let mut closures = self.provider_event_closures.lock().unwrap();
+        if closures.contains_key(event) {
+            return Ok(());
+        }
+        let closure = self.provider_event(event);
+        self.wallet.on(
+            event,
+            closure.as_ref().unchecked_ref::<js_sys::Function>().clone(),
+        )?;
+        closures.insert(event.to_string(), closure);
+        Ok(())
+    }
+
+    fn unsubscribe_provider_event(&self, event: &str) -> wallet_adapter_base::Result<()> {
+        let Some(closure) = self.provider_event_closures.lock().unwrap().remove(event) else {
+            return Ok(());
+        };
+        self.wallet.off(
+            event,
+            closure.as_ref().unchecked_ref::<js_sys::Function>().clone(),
+        )?;
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn auto_connect(&mut self) -> wallet_adapter_base::Result<()> {
-        if self.ready_state() == WalletReadyState::Installed {
-            return self.connect().await;
+        if self.ready_state() != WalletReadyState::Installed {
+            return Ok(());
         }
+
+        // reconnect previously trusted users without popping the approval dialog
+        let options = ConnectOptions {
+            only_if_trusted: true,
+        };
+        match self.try_connect(options).await {
+            Ok(()) => {}
+            Err(WalletError::WalletUserRejected(err)) => {
+                tracing::debug!("{} is not trusted yet: {err}", self.name());
+            }
+            Err(err) => {
+                self.event_emitter
+                    .emit(WalletAdapterEvent::Error {
+                        wallet: self.name(),
+                        operation: WalletOperation::AutoConnect,
+                        error: err,
+                    })
+                    .await?
+            }
+        }
+
+        self.set_connecting(false);
+
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
-        if let Err(err) = self.try_connect().await {
+        if let Err(err) = self.try_connect(ConnectOptions::default()).await {
             self.event_emitter
-                .emit(WalletAdapterEvent::Error(err))
+                .emit(WalletAdapterEvent::Error {
+                    wallet: self.name(),
+                    operation: WalletOperation::Connect,
+                    error: err,
+                })
                 .await?
         }
 
@@ -268,15 +595,34 @@ impl<T: GenericWasmWallet + 'static> BaseWalletAdapter for GenericWasmWalletAdap
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn disconnect(&self) -> Result<()> {
-        self.wallet.off("disconnect", self.disconnected())?;
-        self.wallet.off("accountChanged", self.account_changed())?;
+        self.unsubscribe_session_events()?;
 
         self.set_public_key(None);
 
-        if let Err(err) = self.wallet.disconnect() {
+        // listeners of `Disconnecting` already see `disconnecting()`
+        self.set_disconnecting(true);
+        if let Err(err) = self
+            .event_emitter
+            .emit(WalletAdapterEvent::Disconnecting)
+            .await
+        {
+            self.set_disconnecting(false);
+            return Err(err);
+        }
+        let result = self.wallet.disconnect().await;
+        self.set_disconnecting(false);
+        if let Err(err) = result {
             self.event_emitter
-                .emit(WalletAdapterEvent::Error(WalletError::Anyhow(err.into())))
+                .emit(WalletAdapterEvent::Error {
+                    wallet: self.name(),
+                    operation: WalletOperation::Disconnect,
+                    error: err.into(),
+                })
                 .await?;
         }
 
@@ -287,42 +633,211 @@ impl<T: GenericWasmWallet + 'static> BaseWalletAdapter for GenericWasmWalletAdap
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn send_transaction(
         &self,
-        mut transaction: wallet_adapter_base::TransactionOrVersionedTransaction,
+        transaction: wallet_adapter_base::TransactionOrVersionedTransaction,
         connection: &dyn Connection,
         options: Option<SendTransactionOptions>,
     ) -> wallet_adapter_base::Result<solana_sdk::signature::Signature> {
-        if self.public_key().is_none() {
-            return Err(WalletError::WalletNotConnected);
+        let transaction = self
+            .prepare_for_wallet(transaction, connection, options)
+            .await?;
+
+        Ok(self.wallet.sign_and_send_transaction(transaction).await?)
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn send_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+        connection: &dyn Connection,
+        options: Option<SendOptions>,
+    ) -> wallet_adapter_base::Result<Vec<Option<solana_sdk::signature::Signature>>> {
+        if !self.wallet.can_sign_and_send_all() {
+            return send_all_in_turn(self, transactions, connection, options).await;
         }
 
-        let send_options = options.as_ref().map(|o| o.send_options);
+        let mut prepared = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            let options = options.clone().map(SendTransactionOptions::from);
+            prepared.push(
+                self.prepare_for_wallet(transaction, connection, options)
+                    .await?,
+            );
+        }
 
-        match &mut transaction {
-            TransactionOrVersionedTransaction::Transaction(ref mut tx) => {
-                *tx = self
-                    .prepare_transaction(tx.clone(), connection, send_options.as_ref())
-                    .await?;
+        Ok(self.wallet.sign_and_send_all_transactions(prepared).await?)
+    }
+}
 
-                if let Some(opt) = options {
-                    if opt.signers.len() > 0 {
-                        tx.partial_sign(&opt.signers, tx.message.recent_blockhash);
-                    }
-                }
-            }
-            TransactionOrVersionedTransaction::VersionedTransaction(ref mut tx) => {
-                if let Some(opt) = options {
-                    if opt.signers.len() > 0 {
-                        // TODO: implement support for VersionedTransaction
-                        return Err(
-                            anyhow!("Unsupported transaction version: {:?}", tx.version()).into(),
-                        );
-                    }
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::injected::{InjectedWallet, InjectedWalletConfig, RequestStyle};
+    use crate::testing::{dispatch_window_event, FakeProvider};
+
+    /// A wallet at `window[window_key]`, each test uses its own key
+    fn config(window_key: &'static str) -> InjectedWalletConfig {
+        InjectedWalletConfig {
+            name: "Test",
+            url: "https://example.com",
+            icon: "",
+            window_key,
+            detection_flag: "isTest",
+            alternative_detection_flags: &[],
+            request_style: RequestStyle::Method,
+            browse_url: None,
         }
+    }
 
-        Ok(self.wallet.sign_and_send_transaction(transaction).await?)
+    #[wasm_bindgen_test]
+    async fn detects_the_wallet_on_its_event() {
+        let config = config("detectedOnEvent");
+        let detection =
+            WalletDetection::event_driven(vec!["detectedOnEvent#initialized".to_string()])
+                .interval_ms(60_000);
+        let adapter =
+            GenericWasmWalletAdapter::new_with_detection(InjectedWallet::new(config), detection)
+                .unwrap();
+        assert_eq!(adapter.ready_state(), WalletReadyState::NotDetected);
+
+        let _provider = FakeProvider::install(&config, Keypair::new()).unwrap();
+        // let the detection task add its listeners
+        crate::util::sleep_ms(0).await;
+        dispatch_window_event("detectedOnEvent#initialized").unwrap();
+
+        assert_eq!(adapter.ready_state(), WalletReadyState::Installed);
+    }
+
+    #[wasm_bindgen_test]
+    async fn connects_when_the_wallet_does() {
+        let config = config("connectedByWallet");
+        let provider = FakeProvider::install(&config, Keypair::new()).unwrap();
+        let adapter = GenericWasmWalletAdapter::new(InjectedWallet::new(config)).unwrap();
+        let events = adapter.event_emitter();
+        while events.try_recv().is_some() {}
+
+        provider.connect_from_wallet().unwrap();
+
+        assert_eq!(adapter.public_key(), Some(provider.public_key()));
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Connect(public_key)) if public_key == provider.public_key()
+        ));
+
+        // the same account connecting again changes nothing
+        provider.connect_from_wallet().unwrap();
+        assert!(events.try_recv().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn disconnects_when_the_wallet_does() {
+        let config = config("disconnectedByWallet");
+        let provider = FakeProvider::install(&config, Keypair::new()).unwrap();
+        let mut adapter = GenericWasmWalletAdapter::new(InjectedWallet::new(config)).unwrap();
+        adapter.connect().await.unwrap();
+        let events = adapter.event_emitter();
+        while events.try_recv().is_some() {}
+
+        provider.disconnect_from_wallet().unwrap();
+
+        assert!(!adapter.connected());
+        assert!(!adapter.connecting());
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Disconnect)
+        ));
+
+        // the session listeners are gone with the session
+        provider.disconnect_from_wallet().unwrap();
+        assert!(events.try_recv().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn follows_the_account_changes_of_the_wallet() {
+        let config = config("accountChanged");
+        let provider = FakeProvider::install(&config, Keypair::new()).unwrap();
+        let mut adapter = GenericWasmWalletAdapter::new(InjectedWallet::new(config)).unwrap();
+        adapter.connect().await.unwrap();
+        let events = adapter.event_emitter();
+        while events.try_recv().is_some() {}
+
+        let other = Pubkey::new_unique();
+        provider.change_account(Some(&other));
+        assert_eq!(adapter.public_key(), Some(other));
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::AccountChanged(public_key)) if public_key == other
+        ));
+
+        // an account that hasn't authorized the app yet is asked to connect
+        provider.change_account(None);
+        crate::util::sleep_ms(0).await;
+        assert_eq!(adapter.public_key(), Some(provider.public_key()));
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::AccountChanged(public_key)) if public_key == provider.public_key()
+        ));
+        assert!(events.try_recv().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn auto_connects_only_trusted_apps() {
+        let config = config("autoConnect");
+        let provider = FakeProvider::install(&config, Keypair::new()).unwrap();
+        let mut adapter = GenericWasmWalletAdapter::new(InjectedWallet::new(config)).unwrap();
+        let events = adapter.event_emitter();
+        while events.try_recv().is_some() {}
+
+        adapter.auto_connect().await.unwrap();
+        assert!(!adapter.connected());
+        assert!(!adapter.connecting());
+        // not being trusted yet isn't an error
+        assert!(events.try_recv().is_none());
+
+        provider.set_trusted(true);
+        adapter.auto_connect().await.unwrap();
+        assert_eq!(adapter.public_key(), Some(provider.public_key()));
+    }
+
+    #[wasm_bindgen_test]
+    async fn failed_provider_disconnects_are_emitted() {
+        let config = config("failedDisconnect");
+        let provider = FakeProvider::install(&config, Keypair::new()).unwrap();
+        let mut adapter = GenericWasmWalletAdapter::new(InjectedWallet::new(config)).unwrap();
+        adapter.connect().await.unwrap();
+        let events = adapter.event_emitter();
+        while events.try_recv().is_some() {}
+
+        provider.set_rejecting(true);
+        adapter.disconnect().await.unwrap();
+
+        assert!(!adapter.connected());
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Disconnecting)
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Error {
+                operation: WalletOperation::Disconnect,
+                error: WalletError::WalletUserRejected(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Disconnect)
+        ));
     }
 }
@@ -0,0 +1,337 @@
+//! A Rust adapter driven from JavaScript, for hybrid apps with existing JS/TS code.
+//!
+//! ```ts
+//! const handle: WalletAdapterHandle = rustApp.walletHandle();
+//! const id = handle.on("connect", (publicKey) => console.log(publicKey));
+//! await handle.connect();
+//! const signature = await handle.sendTransaction(transaction);
+//! handle.off(id);
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use js_sys::{Function, Uint8Array};
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseWalletAdapter, WalletAdapterEvent, WalletError,
+};
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::types::SendOptions;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::future_to_promise;
+
+use crate::js_transaction::from_js_transaction;
+use crate::util::{on_event_local, wallet_error_to_js, LocalEventSubscription};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &str = r#"
+/** The error promises of `WalletAdapterHandle` reject with and `error` events carry */
+export interface WalletError {
+    code: number;
+    name: string;
+    message: string;
+}
+
+export type WalletReadyState = "Installed" | "NotDetected" | "Loadable" | "Unsupported";
+
+/** The events of `WalletAdapterHandle.on` and their callbacks */
+export interface WalletAdapterEvents {
+    connect: (publicKey: string) => void;
+    accountChanged: (publicKey: string) => void;
+    disconnect: () => void;
+    locked: () => void;
+    unlocked: () => void;
+    sessionExpired: () => void;
+    error: (error: WalletError) => void;
+    readyStateChange: (readyState: WalletReadyState) => void;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "keyof WalletAdapterEvents")]
+    pub type WalletAdapterEventName;
+
+    #[wasm_bindgen(typescript_type = "WalletAdapterEvents[keyof WalletAdapterEvents]")]
+    pub type WalletAdapterEventCallback;
+
+    #[wasm_bindgen(typescript_type = "SendOptions")]
+    pub type JsSendOptions;
+
+    #[wasm_bindgen(typescript_type = "WalletReadyState")]
+    pub type JsWalletReadyState;
+
+    #[wasm_bindgen(typescript_type = "Promise<string>")]
+    pub type StringPromise;
+
+    #[wasm_bindgen(typescript_type = "Promise<void>")]
+    pub type VoidPromise;
+
+    #[wasm_bindgen(typescript_type = "Promise<Uint8Array>")]
+    pub type BytesPromise;
+}
+
+/// The JS event name and argument of `event`, `None` for events JS can't subscribe to
+fn event_to_js(event: &WalletAdapterEvent) -> Option<(&'static str, JsValue)> {
+    match event {
+        WalletAdapterEvent::Connect(public_key) => Some(("connect", public_key.to_string().into())),
+        WalletAdapterEvent::AccountChanged(public_key) => {
+            Some(("accountChanged", public_key.to_string().into()))
+        }
+        WalletAdapterEvent::Disconnect => Some(("disconnect", JsValue::UNDEFINED)),
+        WalletAdapterEvent::Locked => Some(("locked", JsValue::UNDEFINED)),
+        WalletAdapterEvent::Unlocked => Some(("unlocked", JsValue::UNDEFINED)),
+        WalletAdapterEvent::SessionExpired => Some(("sessionExpired", JsValue::UNDEFINED)),
+        WalletAdapterEvent::Error { error, .. } => Some(("error", wallet_error_to_js(error))),
+        WalletAdapterEvent::ReadyStateChange(ready_state) => {
+            Some(("readyStateChange", ready_state.to_string().into()))
+        }
+        _ => None,
+    }
+}
+
+/// Call `callback` with the argument of `event` if it is the event called `name`
+fn call_callback(name: &str, callback: &Function, event: &WalletAdapterEvent) {
+    let Some((event_name, argument)) = event_to_js(event).filter(|(event, _)| *event == name)
+    else {
+        return;
+    };
+    let callback = callback.clone();
+
+    // listeners run inside `emit`, a callback calling back into the handle must not end up there
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(err) = callback.call1(&JsValue::NULL, &argument) {
+            tracing::warn!("{event_name} callback threw: {err:?}");
+        }
+    });
+}
+
+fn reject(error: impl Into<WalletError>) -> JsValue {
+    wallet_error_to_js(&error.into())
+}
+
+/// An adapter exported to JavaScript. Methods return promises rejecting with the
+/// `{ code, name, message }` object of the [`WalletError`].
+#[wasm_bindgen]
+pub struct WalletAdapterHandle {
+    adapter: Box<dyn BaseMessageSignerWalletAdapter>,
+    connection: Rc<dyn Connection>,
+    subscriptions: RefCell<HashMap<u32, LocalEventSubscription>>,
+    next_id: Cell<u32>,
+}
+
+impl WalletAdapterHandle {
+    /// Hand `adapter` to JS, transactions are sent through `connection`
+    pub fn new(
+        adapter: impl BaseMessageSignerWalletAdapter + 'static,
+        connection: impl Connection + 'static,
+    ) -> Self {
+        Self {
+            adapter: Box::new(adapter),
+            connection: Rc::new(connection),
+            subscriptions: RefCell::new(HashMap::new()),
+            next_id: Cell::new(0),
+        }
+    }
+
+    /// A copy of the adapter for a request, adapters share their state between clones
+    fn adapter(&self) -> Box<dyn BaseMessageSignerWalletAdapter> {
+        dyn_clone::clone_box(&*self.adapter)
+    }
+}
+
+#[wasm_bindgen]
+impl WalletAdapterHandle {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.adapter.name()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn url(&self) -> String {
+        self.adapter.url()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn icon(&self) -> String {
+        self.adapter.icon()
+    }
+
+    #[wasm_bindgen(getter, js_name = readyState)]
+    pub fn ready_state(&self) -> JsWalletReadyState {
+        JsValue::from(self.adapter.ready_state().to_string()).unchecked_into()
+    }
+
+    /// The base58 public key, `undefined` while disconnected
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Option<String> {
+        self.adapter.public_key().map(|key| key.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn connected(&self) -> bool {
+        self.adapter.connected()
+    }
+
+    /// Resolves with the base58 public key
+    pub fn connect(&self) -> StringPromise {
+        let mut adapter = self.adapter();
+        future_to_promise(async move {
+            adapter.connect().await.map_err(reject)?;
+            Ok(adapter
+                .public_key()
+                .map(|key| JsValue::from(key.to_string()))
+                .unwrap_or(JsValue::UNDEFINED))
+        })
+        .unchecked_into()
+    }
+
+    pub fn disconnect(&self) -> VoidPromise {
+        let adapter = self.adapter();
+        future_to_promise(async move {
+            adapter.disconnect().await.map_err(reject)?;
+            Ok(JsValue::UNDEFINED)
+        })
+        .unchecked_into()
+    }
+
+    /// Resolves with the signature bytes
+    #[wasm_bindgen(js_name = signMessage)]
+    pub fn sign_message(&self, message: Vec<u8>) -> BytesPromise {
+        let adapter = self.adapter();
+        future_to_promise(async move {
+            let signature = adapter.sign_message(&message).await.map_err(reject)?;
+            Ok(Uint8Array::from(signature.as_slice()).into())
+        })
+        .unchecked_into()
+    }
+
+    /// Send a web3.js `Transaction`/`VersionedTransaction` or its serialized bytes,
+    /// resolves with the base58 signature
+    #[wasm_bindgen(js_name = sendTransaction)]
+    pub fn send_transaction(
+        &self,
+        transaction: JsValue,
+        options: Option<JsSendOptions>,
+    ) -> StringPromise {
+        let adapter = self.adapter();
+        let connection = self.connection.clone();
+        future_to_promise(async move {
+            let transaction = from_js_transaction(&transaction).map_err(reject)?;
+            let options = options
+                .map(|options| serde_wasm_bindgen::from_value::<SendOptions>(options.into()))
+                .transpose()
+                .map_err(|err| reject(anyhow::anyhow!("invalid send options: {err}")))?;
+            let signature = BaseWalletAdapter::send_transaction(
+                &*adapter,
+                transaction,
+                connection.as_ref(),
+                options.map(Into::into),
+            )
+            .await
+            .map_err(reject)?;
+            Ok(signature.to_string().into())
+        })
+        .unchecked_into()
+    }
+
+    /// Call `callback` on `event`: `connect` and `accountChanged` with the base58 public key,
+    /// `disconnect`, `locked`, `unlocked`, `sessionExpired`, `error` with the error object and `readyStateChange`
+    /// with the new ready state. Returns the id to pass to [`off`](Self::off).
+    pub fn on(&self, event: WalletAdapterEventName, callback: WalletAdapterEventCallback) -> u32 {
+        let event = JsValue::from(event).as_string().unwrap_or_default();
+        let callback: Function = callback.unchecked_into();
+        let id = self.next_id.get() + 1;
+        self.next_id.set(id);
+
+        let subscription = on_event_local(&self.adapter.event_emitter(), move |wallet_event| {
+            call_callback(&event, &callback, wallet_event)
+        });
+        self.subscriptions.borrow_mut().insert(id, subscription);
+        id
+    }
+
+    /// Remove the callback registered as `id`
+    pub fn off(&self, id: u32) {
+        self.subscriptions.borrow_mut().remove(&id);
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use js_sys::Promise;
+    use solana_sdk::signature::{Signature, Signer};
+    use wallet_adapter_base::{build_memo, TransactionBuilder};
+    use wallet_adapter_mock::{MockConnection, MockStep, MockWalletAdapter};
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::util::{reflect_get, sleep_ms};
+
+    async fn settle(promise: impl JsCast) -> Result<JsValue, JsValue> {
+        JsFuture::from(promise.unchecked_into::<Promise>()).await
+    }
+
+    #[wasm_bindgen_test]
+    async fn drives_the_adapter_from_js() {
+        let wallet = MockWalletAdapter::new();
+        let public_key = wallet.keypair().pubkey();
+        let connection = MockConnection::new();
+        let handle = WalletAdapterHandle::new(wallet.clone(), connection.clone());
+
+        let connected = Rc::new(RefCell::new(vec![]));
+        let on_connect = {
+            let connected = connected.clone();
+            Closure::<dyn Fn(String)>::new(move |key: String| connected.borrow_mut().push(key))
+        };
+        let id = handle.on(
+            JsValue::from("connect").unchecked_into(),
+            on_connect.as_ref().clone().unchecked_into(),
+        );
+
+        let connected_key = settle(handle.connect()).await.unwrap();
+        assert_eq!(connected_key.as_string(), Some(public_key.to_string()));
+        assert_eq!(handle.public_key(), Some(public_key.to_string()));
+        // callbacks run on their own task
+        sleep_ms(0).await;
+        assert_eq!(*connected.borrow(), vec![public_key.to_string()]);
+
+        let signature = settle(handle.sign_message(b"hello".to_vec()))
+            .await
+            .unwrap();
+        let signature = Signature::try_from(Uint8Array::new(&signature).to_vec()).unwrap();
+        assert!(signature.verify(public_key.as_ref(), b"hello"));
+
+        let transaction = TransactionBuilder::new()
+            .fee_payer(public_key)
+            .add_instruction(build_memo(b"handle", &[]))
+            .build_legacy()
+            .unwrap();
+        let bytes = Uint8Array::from(transaction.serialize().unwrap().as_slice());
+        let sent = settle(handle.send_transaction(bytes.into(), None))
+            .await
+            .unwrap();
+        assert!(sent.as_string().is_some());
+        assert_eq!(connection.sent().len(), 1);
+
+        // rejections carry the error object
+        wallet.set_scenario([MockStep::user_rejected()]);
+        let error = settle(handle.sign_message(b"again".to_vec()))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            reflect_get(&error, &"name".into()).unwrap().as_string(),
+            Some("WALLET_USER_REJECTED".to_string())
+        );
+
+        handle.off(id);
+        settle(handle.disconnect()).await.unwrap();
+        assert!(!handle.connected());
+        settle(handle.connect()).await.unwrap();
+        sleep_ms(0).await;
+        assert_eq!(connected.borrow().len(), 1);
+    }
+}
@@ -0,0 +1,232 @@
+//! [`MessagingWallet`] over `postMessage` to a wallet hosted in an iframe of the page, eg. an
+//! embedded wallet provider.
+//!
+//! ```ignore
+//! let transport = IframeTransport::new("#wallet-frame", "https://embedded.mywallet.app");
+//! let wallet = MessagingWallet::new(MY_WALLET, transport);
+//! let adapter = GenericWasmWalletAdapter::new(wallet)?;
+//! ```
+//!
+//! Only messages from the iframe's window with an allowed origin are handled, and requests
+//! are only posted to the iframe while its `src` has an allowed origin.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use url::Url;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::messaging::{MessageChannel, MessageTransport};
+
+/// Long enough to approve a request in the wallet
+pub const DEFAULT_IFRAME_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IframeTransport {
+    selector: String,
+    allowed_origins: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+impl IframeTransport {
+    /// The wallet in the iframe matching the CSS `selector`, served from `origin`
+    pub fn new(selector: impl Into<String>, origin: impl Into<String>) -> Self {
+        Self {
+            selector: selector.into(),
+            allowed_origins: vec![origin.into()],
+            timeout: Some(DEFAULT_IFRAME_TIMEOUT),
+        }
+    }
+
+    /// Also talk to the wallet at `origin`, eg. a staging deployment
+    pub fn with_allowed_origin(mut self, origin: impl Into<String>) -> Self {
+        self.allowed_origins.push(origin.into());
+        self
+    }
+
+    /// `None` waits for answers as long as it takes
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn allowed_origins(&self) -> &[String] {
+        &self.allowed_origins
+    }
+
+    fn iframe(&self) -> Result<web_sys::HtmlIFrameElement> {
+        web_sys::window()
+            .and_then(|window| window.document())
+            .context("could not get document")?
+            .query_selector(&self.selector)
+            .map_err(|e| anyhow!("{e:?}"))?
+            .with_context(|| format!("no element matches {}", self.selector))?
+            .dyn_into()
+            .map_err(|_| anyhow!("{} is not an iframe", self.selector))
+    }
+
+    /// The origin of the iframe's `src` if it is allowed
+    fn iframe_origin(&self) -> Result<String> {
+        let src = self.iframe()?.src();
+        let origin = Url::parse(&src)
+            .with_context(|| format!("invalid iframe src {src}"))?
+            .origin()
+            .ascii_serialization();
+        if !self.is_allowed(&origin) {
+            return Err(anyhow!("iframe origin {origin} is not allowed"));
+        }
+        Ok(origin)
+    }
+
+    fn is_allowed(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('/') == origin)
+    }
+}
+
+impl MessageTransport for IframeTransport {
+    fn is_available(&self) -> bool {
+        self.iframe_origin().is_ok()
+    }
+
+    fn open(&self, channel: &MessageChannel) -> Result<()> {
+        let window = web_sys::window().context("could not get window")?;
+        let wallet_window = self
+            .iframe()?
+            .content_window()
+            .context("iframe has no window yet")?;
+
+        let on_message = {
+            let channel = channel.clone();
+            let transport = self.clone();
+            let wallet_window: JsValue = wallet_window.clone().into();
+            Closure::wrap(Box::new(move |event: JsValue| {
+                let event: web_sys::MessageEvent = event.unchecked_into();
+                let from_wallet = event
+                    .source()
+                    .is_some_and(|source| JsValue::from(source) == wallet_window);
+                if !from_wallet {
+                    return;
+                }
+                if !transport.is_allowed(&event.origin()) {
+                    tracing::warn!("ignoring wallet message from {}", event.origin());
+                    return;
+                }
+                channel.handle(&event.data());
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        window
+            .add_event_listener_with_callback("message", on_message.as_ref().unchecked_ref())
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        channel.keep_closure(on_message);
+        channel.set_target(wallet_window.into());
+        Ok(())
+    }
+
+    fn post(&self, target: &JsValue, message: &JsValue) -> Result<()> {
+        let wallet_window: &web_sys::Window = target.unchecked_ref();
+        wallet_window
+            .post_message(message, &self.iframe_origin()?)
+            .map_err(|e| anyhow!("{e:?}"))
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use solana_sdk::pubkey::Pubkey;
+    use wallet_adapter_base::WalletError;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::generic_wallet::{ConnectOptions, GenericWasmWallet};
+    use crate::messaging::{to_js, MessagingWallet, MessagingWalletInfo};
+    use crate::util::sleep_ms;
+
+    const INFO: MessagingWalletInfo = MessagingWalletInfo {
+        name: "Embedded",
+        url: "https://example.com",
+        icon: "",
+    };
+
+    #[wasm_bindgen(inline_js = "
+        export function page_origin() {
+            return location.origin;
+        }
+
+        export function wallet_frame(id, publicKey) {
+            const frame = document.createElement('iframe');
+            frame.id = id;
+            frame.src = `${location.origin}/${id}`;
+            frame.srcdoc = `<script>
+                addEventListener('message', ({ data, origin }) => {
+                    const answer = (reply) => parent.postMessage({ id: data.id, ...reply }, origin);
+                    if (data.method === 'connect') {
+                        answer({ result: { publicKey: '${publicKey}' } });
+                    } else if (data.method === 'signMessage') {
+                        answer({ error: { code: 4001, message: 'User rejected the request.' } });
+                    }
+                });
+            </script>`;
+            const loaded = new Promise((resolve) => frame.addEventListener('load', resolve));
+            document.body.appendChild(frame);
+            return loaded;
+        }
+
+        export function post_to_page(message) {
+            window.postMessage(message, '*');
+        }
+    ")]
+    extern "C" {
+        fn page_origin() -> String;
+        /// Add an iframe wallet connecting `public_key`, rejecting messages and leaving
+        /// other requests unanswered
+        fn wallet_frame(id: &str, public_key: &str) -> js_sys::Promise;
+        fn post_to_page(message: JsValue);
+    }
+
+    #[wasm_bindgen_test]
+    async fn talks_to_the_wallet_in_the_iframe() {
+        let public_key = Pubkey::new_unique();
+        JsFuture::from(wallet_frame("embedded-wallet", &public_key.to_string()))
+            .await
+            .unwrap();
+
+        let elsewhere = IframeTransport::new("#embedded-wallet", "https://wallet.example");
+        assert!(!MessagingWallet::new(INFO, elsewhere).is_correct_wallet());
+
+        let transport = IframeTransport::new("#embedded-wallet", "https://wallet.example")
+            .with_allowed_origin(page_origin())
+            .with_timeout(Some(Duration::from_millis(200)));
+        let wallet = MessagingWallet::new(INFO, transport);
+        assert!(wallet.is_correct_wallet());
+
+        wallet.connect(ConnectOptions::default()).await.unwrap();
+        assert_eq!(wallet.public_key().unwrap(), public_key);
+
+        let rejected = wallet.sign_message(b"hello").await.unwrap_err();
+        assert!(matches!(
+            WalletError::from(rejected),
+            WalletError::WalletUserRejected(_)
+        ));
+
+        // answers from outside the iframe are ignored, the request times out
+        let (spoofed, _) = futures::join!(wallet.request("signTransaction", Value::Null), async {
+            sleep_ms(0).await;
+            // the third request of the channel
+            post_to_page(to_js(&json!({ "id": 3, "result": { "transaction": "" } })).unwrap());
+        });
+        assert!(WalletError::from(spoofed.unwrap_err())
+            .message()
+            .contains("timed out"));
+    }
+}
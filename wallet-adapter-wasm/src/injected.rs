@@ -0,0 +1,354 @@
+//! Config-driven [`GenericWasmWallet`] for wallets injecting a provider object into `window`.
+//!
+//! ```ignore
+//! pub const MY_WALLET: InjectedWalletConfig = InjectedWalletConfig {
+//!     name: "My Wallet",
+//!     url: "https://mywallet.app",
+//!     icon: "data:image/svg+xml;base64,...",
+//!     window_key: "mywallet",
+//!     detection_flag: "isMyWallet",
+//!     alternative_detection_flags: &[],
+//!     request_style: RequestStyle::Request { param: "message" },
+//!     browse_url: None,
+//! };
+//!
+//! let adapter = GenericWasmWalletAdapter::new(InjectedWallet::new(MY_WALLET))?;
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use js_sys::{Array, Function, Object};
+use solana_sdk::bs58;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_base::{TransactionOrVersionedTransaction, WalletError};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::error::provider_error;
+use crate::generic_wallet::{ConnectOptions, GenericWasmWallet};
+use crate::js_transaction::to_js_transaction;
+use crate::util::{is_ios_and_redirectable, reflect_get, reflect_set, resolve_maybe_promise};
+
+/// How the provider expects `signAndSendTransaction` to be called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestStyle {
+    /// `provider.request({ method: "signAndSendTransaction", params: { [param]: base58 } })`
+    Request { param: &'static str },
+    /// `provider.signAndSendTransaction(transaction)` with a web3.js shaped transaction
+    Method,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InjectedWalletConfig {
+    pub name: &'static str,
+    pub url: &'static str,
+    pub icon: &'static str,
+    /// Property of `window` holding the provider, eg. `solana` for `window.solana`
+    pub window_key: &'static str,
+    /// Property of the provider that is `true` for this wallet, eg. `isPhantom`
+    pub detection_flag: &'static str,
+    /// Flags of other variants of the wallet sharing the provider's shape,
+    /// eg. `isSolflareSnap` for Solflare running as a MetaMask Snap
+    pub alternative_detection_flags: &'static [&'static str],
+    pub request_style: RequestStyle,
+    /// Universal link opening a page in the wallet's in-app browser on iOS,
+    /// the encoded page URL and `?ref=<origin>` get appended
+    pub browse_url: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InjectedWallet {
+    config: InjectedWalletConfig,
+}
+
+impl InjectedWallet {
+    pub fn new(config: InjectedWalletConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &InjectedWalletConfig {
+        &self.config
+    }
+
+    /// The provider object, failing with `WalletNotReady` until the wallet injected it
+    pub fn provider(&self) -> Result<JsValue> {
+        let window = web_sys::window().context("could not get window")?;
+        let provider = reflect_get(&window, &JsValue::from_str(self.config.window_key))?;
+        if provider.is_undefined() || provider.is_null() {
+            return Err(WalletError::WalletNotReady.into());
+        }
+        Ok(provider)
+    }
+
+    /// Call `provider[method](...args)` and await the result if it is a promise
+    pub async fn call(&self, method: &str, args: &[JsValue]) -> Result<JsValue> {
+        let result = self.call_sync(method, args)?;
+        Ok(resolve_maybe_promise(result)
+            .await
+            .map_err(provider_error)?)
+    }
+
+    fn call_sync(&self, method: &str, args: &[JsValue]) -> Result<JsValue> {
+        let provider = self.provider()?;
+        let function: Function = reflect_get(&provider, &JsValue::from_str(method))?
+            .dyn_into()
+            .map_err(|_| anyhow!("{} has no method {method}", self.config.name))?;
+
+        let args = args.iter().collect::<Array>();
+        Ok(function.apply(&provider, &args).map_err(provider_error)?)
+    }
+
+    fn set_browse_url(&self, browse_url: &str) -> Result<()> {
+        let location = web_sys::window()
+            .context("could not get window")?
+            .location();
+        let url = location.href().map_err(|e| anyhow!("{:?}", e))?;
+        let origin = location.origin().map_err(|e| anyhow!("{:?}", e))?;
+
+        let href = format!(
+            "{browse_url}{}?ref={}",
+            String::from(js_sys::encode_uri_component(&url)),
+            String::from(js_sys::encode_uri_component(&origin)),
+        );
+        location.set_href(&href).map_err(|e| anyhow!("{:?}", e))
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl GenericWasmWallet for InjectedWallet {
+    fn name(&self) -> String {
+        self.config.name.into()
+    }
+
+    fn url(&self) -> String {
+        self.config.url.into()
+    }
+
+    fn icon(&self) -> String {
+        self.config.icon.into()
+    }
+
+    fn is_correct_wallet(&self) -> bool {
+        let Ok(provider) = self.provider() else {
+            return false;
+        };
+
+        std::iter::once(self.config.detection_flag)
+            .chain(self.config.alternative_detection_flags.iter().copied())
+            .any(|flag| {
+                reflect_get(&provider, &JsValue::from_str(flag))
+                    .is_ok_and(|flag| flag.as_bool().unwrap_or(false))
+            })
+    }
+
+    fn is_connected(&self) -> bool {
+        self.provider()
+            .and_then(|provider| reflect_get(&provider, &JsValue::from_str("isConnected")))
+            .map(|connected| connected.as_bool().unwrap_or(false))
+            .unwrap_or(false)
+    }
+
+    async fn connect(&self, options: ConnectOptions) -> Result<()> {
+        tracing::debug!("{} wallet connect", self.config.name);
+
+        let result = self.call("connect", &[options.to_js()?]).await?;
+
+        tracing::debug!("{:?}", result);
+
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<()> {
+        self.call("disconnect", &[]).await?;
+        Ok(())
+    }
+
+    fn on(&self, event: &str, cb: js_sys::Function) -> Result<()> {
+        self.call_sync("on", &[event.into(), cb.into()])?;
+        Ok(())
+    }
+
+    fn off(&self, event: &str, cb: js_sys::Function) -> Result<()> {
+        self.call_sync("off", &[event.into(), cb.into()])?;
+        Ok(())
+    }
+
+    fn public_key(&self) -> Result<Pubkey> {
+        let public_key = reflect_get(&self.provider()?, &JsValue::from_str("publicKey"))?;
+        if public_key.is_null() || public_key.is_undefined() {
+            return Err(anyhow!("{} has no public key", self.config.name));
+        }
+
+        let to_bytes: Function = reflect_get(&public_key, &JsValue::from_str("toBytes"))?
+            .dyn_into()
+            .map_err(|_| anyhow!("invalid public key"))?;
+        let bytes = to_bytes
+            .call0(&public_key)
+            .map_err(|e| anyhow!("{:?}", e))?;
+
+        js_sys::Uint8Array::new(&bytes)
+            .to_vec()
+            .try_into()
+            .map_err(|e| anyhow!("{e:?}"))
+    }
+
+    async fn sign_and_send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> Result<Signature> {
+        let response = match self.config.request_style {
+            RequestStyle::Request { param } => {
+                let tx_bs58 = bs58::encode(transaction.serialize()?).into_string();
+                tracing::debug!("tx_bs58: {}", tx_bs58);
+
+                let params = Object::new();
+                reflect_set(&params, param, &tx_bs58.into())?;
+                let request = Object::new();
+                reflect_set(&request, "method", &"signAndSendTransaction".into())?;
+                reflect_set(&request, "params", &params)?;
+
+                self.call("request", &[request.into()]).await?
+            }
+            RequestStyle::Method => {
+                let tx = to_js_transaction(&transaction)?;
+                self.call("signAndSendTransaction", &[tx, JsValue::NULL])
+                    .await?
+            }
+        };
+
+        // either the signature itself or `{ signature, publicKey }`
+        let signature = match response.as_string() {
+            Some(signature) => signature,
+            None => reflect_get(&response, &JsValue::from_str("signature"))?
+                .as_string()
+                .context("signature not found")?,
+        };
+
+        tracing::debug!("result: {}", signature);
+
+        Ok(signature.parse()?)
+    }
+
+    fn can_sign_and_send_all(&self) -> bool {
+        self.provider()
+            .and_then(|provider| {
+                reflect_get(&provider, &JsValue::from_str("signAndSendAllTransactions"))
+            })
+            .is_ok_and(|method| method.is_function())
+    }
+
+    /// `provider.signAndSendAllTransactions(transactions)` with web3.js shaped transactions
+    async fn sign_and_send_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+    ) -> Result<Vec<Option<Signature>>> {
+        let js_transactions = transactions
+            .iter()
+            .map(to_js_transaction)
+            .collect::<Result<Array>>()?;
+        let response = self
+            .call(
+                "signAndSendAllTransactions",
+                &[js_transactions.into(), JsValue::NULL],
+            )
+            .await?;
+
+        // either the signatures themselves or `{ signatures, publicKey }`
+        let signatures = if Array::is_array(&response) {
+            response
+        } else {
+            reflect_get(&response, &JsValue::from_str("signatures"))?
+        };
+        let signatures: Array = signatures
+            .dyn_into()
+            .map_err(|_| anyhow!("signatures not found"))?;
+
+        tracing::debug!("result: {:?}", signatures);
+
+        // wallets return null for the transactions they couldn't send
+        signatures
+            .iter()
+            .map(|signature| signature.as_string().map(|s| s.parse()).transpose())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(Into::into)
+    }
+
+    fn is_ios_redirectable(&self) -> Result<bool> {
+        Ok(self.config.browse_url.is_some()
+            && !self.is_correct_wallet()
+            && is_ios_and_redirectable())
+    }
+
+    fn set_wallet_url(&self) -> Result<()> {
+        match self.config.browse_url {
+            Some(browse_url) => self.set_browse_url(browse_url),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::generic_wallet::GenericWasmWalletAdapter;
+    use crate::testing::{self, FakeProvider};
+
+    const WALLET: InjectedWalletConfig = InjectedWalletConfig {
+        name: "Injected",
+        url: "https://example.com",
+        icon: "",
+        window_key: "injectedTest",
+        detection_flag: "isInjected",
+        alternative_detection_flags: &["isInjectedSnap"],
+        request_style: RequestStyle::Method,
+        browse_url: None,
+    };
+
+    #[wasm_bindgen_test]
+    async fn sends_in_both_request_styles() {
+        for request_style in [
+            RequestStyle::Method,
+            RequestStyle::Request { param: "message" },
+        ] {
+            let config = InjectedWalletConfig {
+                request_style,
+                ..WALLET
+            };
+            let provider = FakeProvider::install(&config, Keypair::new()).unwrap();
+            let adapter = GenericWasmWalletAdapter::new(InjectedWallet::new(config)).unwrap();
+
+            testing::assert_connect_and_send(Box::new(adapter), &provider).await;
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn detects_the_wallet_by_its_flags() {
+        let wallet = InjectedWallet::new(WALLET);
+        assert!(!wallet.is_correct_wallet());
+
+        let other = FakeProvider::install(
+            &InjectedWalletConfig {
+                detection_flag: "isOther",
+                ..WALLET
+            },
+            Keypair::new(),
+        )
+        .unwrap();
+        assert!(!wallet.is_correct_wallet());
+        drop(other);
+
+        let _snap = FakeProvider::install(
+            &InjectedWalletConfig {
+                detection_flag: "isInjectedSnap",
+                ..WALLET
+            },
+            Keypair::new(),
+        )
+        .unwrap();
+        assert!(wallet.is_correct_wallet());
+        assert!(!wallet.is_connected());
+        assert!(wallet.public_key().is_err());
+    }
+}
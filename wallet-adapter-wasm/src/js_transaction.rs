@@ -0,0 +1,231 @@
+//! Conversion between solana-sdk transactions and the web3.js shaped objects injected wallets expect.
+//!
+//! The objects only carry what wallets read: `serialize()`/`serializeMessage()`, signatures,
+//! fee payer, blockhash and instructions for legacy transactions, and `version`, `message` and
+//! signatures for versioned ones. Methods return copies of byte arrays captured at creation,
+//! so there is no Rust closure to keep alive.
+
+use anyhow::{anyhow, Result};
+use js_sys::{Array, Function, Object, Uint8Array};
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+use wallet_adapter_base::TransactionOrVersionedTransaction;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::util::{reflect_get, reflect_set};
+
+#[wasm_bindgen(inline_js = "
+    export function web3_public_key(bytes, base58) {
+        return {
+            toBytes() { return bytes.slice(); },
+            toBuffer() { return bytes.slice(); },
+            toBase58() { return base58; },
+            toString() { return base58; },
+            toJSON() { return base58; },
+            equals(other) { return other != null && other.toBase58() === base58; },
+        };
+    }
+
+    export function with_method(target, name, value) {
+        target[name] = function() {
+            return value instanceof Uint8Array ? value.slice() : value;
+        };
+        return target;
+    }
+")]
+extern "C" {
+    fn web3_public_key(bytes: Uint8Array, base58: &str) -> JsValue;
+    fn with_method(target: Object, name: &str, value: JsValue) -> Object;
+}
+
+pub fn to_js_public_key(pubkey: &Pubkey) -> JsValue {
+    web3_public_key(Uint8Array::from(pubkey.as_ref()), &pubkey.to_string())
+}
+
+fn to_js_signature(signature: &Signature) -> JsValue {
+    if signature == &Signature::default() {
+        JsValue::NULL
+    } else {
+        Uint8Array::from(signature.as_ref()).into()
+    }
+}
+
+fn bytes(bytes: &[u8]) -> JsValue {
+    Uint8Array::from(bytes).into()
+}
+
+fn legacy_to_js(tx: &Transaction) -> Result<JsValue> {
+    let message = &tx.message;
+    let object = Object::new();
+
+    let signatures = Array::new();
+    for (signature, pubkey) in tx.signatures.iter().zip(message.account_keys.iter()) {
+        let entry = Object::new();
+        reflect_set(&entry, "publicKey", &to_js_public_key(pubkey))?;
+        reflect_set(&entry, "signature", &to_js_signature(signature))?;
+        signatures.push(&entry);
+    }
+
+    let instructions = Array::new();
+    for ix in &message.instructions {
+        let keys = Array::new();
+        for &index in &ix.accounts {
+            let index = index as usize;
+            let key = Object::new();
+            reflect_set(
+                &key,
+                "pubkey",
+                &to_js_public_key(&message.account_keys[index]),
+            )?;
+            reflect_set(&key, "isSigner", &message.is_signer(index).into())?;
+            reflect_set(
+                &key,
+                "isWritable",
+                &message.is_maybe_writable(index, None).into(),
+            )?;
+            keys.push(&key);
+        }
+
+        let instruction = Object::new();
+        let program_id = &message.account_keys[ix.program_id_index as usize];
+        reflect_set(&instruction, "programId", &to_js_public_key(program_id))?;
+        reflect_set(&instruction, "keys", &keys)?;
+        reflect_set(&instruction, "data", &bytes(&ix.data))?;
+        instructions.push(&instruction);
+    }
+
+    if let Some(fee_payer) = message.account_keys.first() {
+        reflect_set(&object, "feePayer", &to_js_public_key(fee_payer))?;
+    }
+    reflect_set(
+        &object,
+        "recentBlockhash",
+        &message.recent_blockhash.to_string().into(),
+    )?;
+    reflect_set(&object, "signatures", &signatures)?;
+    reflect_set(&object, "instructions", &instructions)?;
+
+    let object = with_method(object, "serialize", bytes(&bincode::serialize(tx)?));
+    let object = with_method(object, "serializeMessage", bytes(&message.serialize()));
+
+    Ok(object.into())
+}
+
+fn versioned_to_js(tx: &VersionedTransaction) -> Result<JsValue> {
+    let message = Object::new();
+    let static_account_keys = Array::new();
+    for pubkey in tx.message.static_account_keys() {
+        static_account_keys.push(&to_js_public_key(pubkey));
+    }
+    reflect_set(&message, "staticAccountKeys", &static_account_keys)?;
+    reflect_set(
+        &message,
+        "recentBlockhash",
+        &tx.message.recent_blockhash().to_string().into(),
+    )?;
+    let message = with_method(message, "serialize", bytes(&tx.message.serialize()));
+
+    let signatures = Array::new();
+    for signature in &tx.signatures {
+        signatures.push(&bytes(signature.as_ref()));
+    }
+
+    let version = match tx.message {
+        VersionedMessage::Legacy(_) => JsValue::from_str("legacy"),
+        VersionedMessage::V0(_) => JsValue::from(0),
+    };
+
+    let object = Object::new();
+    reflect_set(&object, "version", &version)?;
+    reflect_set(&object, "message", &message)?;
+    reflect_set(&object, "signatures", &signatures)?;
+    let object = with_method(object, "serialize", bytes(&bincode::serialize(tx)?));
+
+    Ok(object.into())
+}
+
+/// Build the web3.js shaped object of a transaction
+pub fn to_js_transaction(transaction: &TransactionOrVersionedTransaction) -> Result<JsValue> {
+    match transaction {
+        TransactionOrVersionedTransaction::Transaction(tx) => legacy_to_js(tx),
+        TransactionOrVersionedTransaction::VersionedTransaction(tx) => versioned_to_js(tx),
+    }
+}
+
+/// Read a transaction returned by a wallet, either a web3.js object or its serialized bytes
+pub fn from_js_transaction(value: &JsValue) -> Result<TransactionOrVersionedTransaction> {
+    let serialized = if value.is_instance_of::<Uint8Array>() {
+        value.clone()
+    } else {
+        let serialize: Function = reflect_get(value, &JsValue::from_str("serialize"))?
+            .dyn_into()
+            .map_err(|_| anyhow!("transaction has no serialize method"))?;
+
+        let config = Object::new();
+        reflect_set(&config, "requireAllSignatures", &JsValue::FALSE)?;
+        reflect_set(&config, "verifySignatures", &JsValue::FALSE)?;
+
+        serialize
+            .call1(value, &config)
+            .map_err(|err| anyhow!("failed to serialize transaction: {err:?}"))?
+    };
+
+    TransactionOrVersionedTransaction::deserialize(&Uint8Array::new(&serialized).to_vec())
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use solana_sdk::signature::{Keypair, Signer};
+    use wallet_adapter_base::{build_memo, TransactionBuilder};
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::testing;
+
+    #[wasm_bindgen_test]
+    fn golden_vectors_match_web3_js() {
+        testing::assert_js_conformance();
+    }
+
+    #[wasm_bindgen_test]
+    fn missing_signatures_are_null() {
+        let payer = Keypair::new();
+        let cosigner = Pubkey::new_unique();
+        let TransactionOrVersionedTransaction::Transaction(mut tx) = TransactionBuilder::new()
+            .fee_payer(payer.pubkey())
+            .add_instruction(build_memo(b"web3", &[&cosigner]))
+            .build_legacy()
+            .unwrap()
+        else {
+            panic!("expected a legacy transaction");
+        };
+        tx.partial_sign(&[&payer], tx.message.recent_blockhash);
+        let transaction = TransactionOrVersionedTransaction::Transaction(tx);
+
+        let object = to_js_transaction(&transaction).unwrap();
+        let signatures: Array = reflect_get(&object, &"signatures".into())
+            .unwrap()
+            .unchecked_into();
+        let signature = |index| reflect_get(&signatures.get(index), &"signature".into()).unwrap();
+
+        assert!(signature(0).is_instance_of::<Uint8Array>());
+        assert!(signature(1).is_null());
+        assert_eq!(from_js_transaction(&object).unwrap(), transaction);
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_serialized_bytes() {
+        let transaction = TransactionBuilder::new()
+            .fee_payer(Pubkey::new_unique())
+            .add_instruction(build_memo(b"web3", &[]))
+            .build_legacy()
+            .unwrap();
+        let serialized = bytes(&transaction.serialize().unwrap());
+
+        assert_eq!(from_js_transaction(&serialized).unwrap(), transaction);
+        assert!(from_js_transaction(&Object::new().into()).is_err());
+    }
+}
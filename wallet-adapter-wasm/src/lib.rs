@@ -1,4 +1,31 @@
+//! Browser wallets built on `window` APIs, the wallets and transports only exist on wasm32.
+
+#[cfg(target_arch = "wasm32")]
 pub mod connection;
+#[cfg(target_arch = "wasm32")]
+pub mod detection;
+pub mod error;
+#[cfg(target_arch = "wasm32")]
+pub mod extension;
+#[cfg(target_arch = "wasm32")]
 pub mod generic_wallet;
+pub mod handle;
+#[cfg(target_arch = "wasm32")]
+pub mod iframe;
+#[cfg(target_arch = "wasm32")]
+pub mod injected;
+pub mod js_transaction;
+#[cfg(target_arch = "wasm32")]
+pub mod messaging;
+pub mod standard_wallet;
 pub mod storage;
+#[cfg(all(any(test, feature = "testing"), target_arch = "wasm32"))]
+pub mod testing;
+#[cfg(target_arch = "wasm32")]
+pub mod transport;
 pub mod util;
+#[cfg(target_arch = "wasm32")]
+pub mod worker;
+
+#[cfg(all(test, target_arch = "wasm32"))]
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
@@ -0,0 +1,482 @@
+//! [`GenericWasmWallet`] for wallets reached through messages instead of a provider injected
+//! into the page, eg. another browser extension or a wallet in an iframe.
+//!
+//! Requests are `{ id, method, params }`, the wallet answers with `{ id, result }` or
+//! `{ id, error: { code, message } }`. Events are pushed as `{ event, data }`, `connect` and
+//! `accountChanged` with the base58 public key as data. Transactions, messages and signatures
+//! are base58 encoded:
+//!
+//! | method                   | params                      | result                     |
+//! |--------------------------|-----------------------------|----------------------------|
+//! | `connect`                | `{ onlyIfTrusted }`         | `{ publicKey }`            |
+//! | `disconnect`             | `null`                      | anything                   |
+//! | `signTransaction`        | `{ transaction }`           | `{ transaction }`          |
+//! | `signMessage`            | `{ message }`               | `{ signature }`            |
+//! | `signAndSendTransaction` | `{ transaction }`           | `{ signature }`            |
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use futures::channel::oneshot;
+use futures::future::Either;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::bs58;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_base::{TransactionOrVersionedTransaction, WalletError};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsValue;
+
+use crate::error::wallet_error;
+use crate::generic_wallet::{ConnectOptions, GenericWasmWallet};
+use crate::util::{reflect_set, sleep_ms};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageRequest {
+    /// Matches the response to the request
+    pub id: u64,
+    pub method: String,
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageError {
+    /// Provider error code, eg. 4001 when the user rejected the request
+    pub code: Option<i64>,
+    pub message: String,
+}
+
+impl From<MessageError> for WalletError {
+    fn from(error: MessageError) -> Self {
+        wallet_error(error.code, error.message)
+    }
+}
+
+/// A message of the wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WalletMessage {
+    Response {
+        id: u64,
+        #[serde(default)]
+        result: Value,
+        #[serde(default)]
+        error: Option<MessageError>,
+    },
+    Event {
+        event: String,
+        #[serde(default)]
+        data: Value,
+    },
+}
+
+/// How requests reach the wallet and its messages come back
+pub trait MessageTransport: Send + Sync + std::fmt::Debug + Clone + 'static {
+    /// Whether the wallet can be reached, eg. the extension is installed
+    fn is_available(&self) -> bool;
+
+    /// Pass the wallet's messages to [`MessageChannel::handle`] and set the
+    /// [`MessageChannel::target`] requests are posted to. Called before a request while the
+    /// channel has no target.
+    fn open(&self, channel: &MessageChannel) -> Result<()>;
+
+    /// Send `message` to the `target` set by [`Self::open`]
+    fn post(&self, target: &JsValue, message: &JsValue) -> Result<()>;
+
+    /// How long to wait for an answer, `None` for as long as the transport is open
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+}
+
+type PendingResponse = oneshot::Sender<std::result::Result<Value, WalletError>>;
+
+/// The JS side of a channel, kept in a thread local since JS values aren't `Send`
+#[derive(Default)]
+struct ChannelState {
+    next_request: u64,
+    pending: HashMap<u64, PendingResponse>,
+    listeners: HashMap<String, Vec<js_sys::Function>>,
+    public_key: Option<Pubkey>,
+    target: Option<JsValue>,
+    closures: Vec<Closure<dyn FnMut(JsValue)>>,
+}
+
+thread_local! {
+    static CHANNELS: RefCell<HashMap<u64, ChannelState>> = RefCell::new(HashMap::new());
+}
+
+static NEXT_CHANNEL: AtomicU64 = AtomicU64::new(0);
+
+/// Pending requests, event listeners and the session of a messaging wallet, shared by clones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageChannel {
+    id: u64,
+}
+
+impl MessageChannel {
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_CHANNEL.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut ChannelState) -> R) -> R {
+        CHANNELS.with(|channels| f(channels.borrow_mut().entry(self.id).or_default()))
+    }
+
+    /// Where requests are posted to, `None` until the transport opened the channel
+    pub fn target(&self) -> Option<JsValue> {
+        self.with_state(|state| state.target.clone())
+    }
+
+    pub fn set_target(&self, target: JsValue) {
+        self.with_state(|state| state.target = Some(target));
+    }
+
+    /// Keep a callback of the target alive until the channel is closed
+    pub fn keep_closure(&self, closure: Closure<dyn FnMut(JsValue)>) {
+        self.with_state(|state| state.closures.push(closure));
+    }
+
+    /// The account of the session, from the `connect` answer and the wallet's events
+    pub fn public_key(&self) -> Option<Pubkey> {
+        self.with_state(|state| state.public_key)
+    }
+
+    pub fn set_public_key(&self, public_key: Option<Pubkey>) {
+        self.with_state(|state| state.public_key = public_key);
+    }
+
+    pub fn on(&self, event: &str, listener: js_sys::Function) {
+        self.with_state(|state| {
+            state
+                .listeners
+                .entry(event.to_string())
+                .or_default()
+                .push(listener)
+        });
+    }
+
+    pub fn off(&self, event: &str, listener: &js_sys::Function) {
+        self.with_state(|state| {
+            if let Some(listeners) = state.listeners.get_mut(event) {
+                listeners.retain(|registered| registered != listener);
+            }
+        });
+    }
+
+    /// Post `method` through `post` and wait for the wallet's answer, at most `timeout`
+    pub async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Option<Duration>,
+        post: impl FnOnce(&JsValue) -> Result<()>,
+    ) -> wallet_adapter_base::Result<Value> {
+        let (sender, receiver) = oneshot::channel();
+        let id = self.with_state(|state| {
+            state.next_request += 1;
+            state.pending.insert(state.next_request, sender);
+            state.next_request
+        });
+
+        let request = MessageRequest {
+            id,
+            method: method.to_string(),
+            params,
+        };
+        if let Err(err) = to_js(&request).and_then(|request| post(&request)) {
+            self.with_state(|state| state.pending.remove(&id));
+            return Err(err.into());
+        }
+
+        let response = match timeout {
+            None => receiver.await,
+            Some(timeout) => {
+                let sleep = sleep_ms(timeout.as_millis().try_into().unwrap_or(i32::MAX));
+                let first = futures::future::select(receiver, Box::pin(sleep)).await;
+                match first {
+                    Either::Left((response, _)) => response,
+                    Either::Right(_) => {
+                        // a late answer finds no request and gets dropped
+                        self.with_state(|state| state.pending.remove(&id));
+                        return Err(WalletError::Anyhow(anyhow!(
+                            "{method} timed out after {timeout:?}"
+                        )));
+                    }
+                }
+            }
+        };
+        response.map_err(|_| WalletError::Anyhow(anyhow!("{method} was cancelled")))?
+    }
+
+    /// Answer the pending request or notify the listeners of an event, other messages are
+    /// ignored
+    pub fn handle(&self, message: &JsValue) {
+        let message = js_sys::JSON::stringify(message)
+            .ok()
+            .and_then(|json| json.as_string())
+            .and_then(|json| serde_json::from_str::<WalletMessage>(&json).ok());
+
+        match message {
+            Some(WalletMessage::Response { id, result, error }) => {
+                let Some(pending) = self.with_state(|state| state.pending.remove(&id)) else {
+                    tracing::debug!("response to unknown request {id}");
+                    return;
+                };
+                let _ = pending.send(match error {
+                    Some(error) => Err(error.into()),
+                    None => Ok(result),
+                });
+            }
+            Some(WalletMessage::Event { event, data }) => self.emit(&event, data),
+            None => tracing::debug!("ignoring message that isn't from the wallet"),
+        }
+    }
+
+    /// Fail the pending requests and end the session, eg. once the port disconnected.
+    /// The transport opens the channel again on the next request.
+    pub fn close(&self, reason: &str) {
+        let (pending, closures, connected) = self.with_state(|state| {
+            state.target = None;
+            (
+                std::mem::take(&mut state.pending),
+                std::mem::take(&mut state.closures),
+                state.public_key.is_some(),
+            )
+        });
+
+        for (_, pending) in pending {
+            let _ = pending.send(Err(WalletError::WalletDisconnection((
+                "channel closed".to_string(),
+                reason.to_string(),
+            ))));
+        }
+        if connected {
+            self.emit("disconnect", Value::Null);
+        }
+
+        // closing usually runs inside one of these callbacks, which can't be dropped while
+        // they run
+        wasm_bindgen_futures::spawn_local(async move { drop(closures) });
+    }
+
+    fn emit(&self, event: &str, data: Value) {
+        let argument = match event {
+            "connect" | "accountChanged" => {
+                let public_key = data.as_str().and_then(|key| key.parse::<Pubkey>().ok());
+                if event == "accountChanged" || public_key.is_some() {
+                    self.set_public_key(public_key);
+                }
+                public_key.map_or(JsValue::NULL, |key| js_public_key(&key))
+            }
+            "disconnect" => {
+                self.set_public_key(None);
+                JsValue::NULL
+            }
+            _ => to_js(&data).unwrap_or(JsValue::NULL),
+        };
+
+        let listeners = self.with_state(|state| state.listeners.get(event).cloned());
+        for listener in listeners.unwrap_or_default() {
+            if let Err(err) = listener.call1(&JsValue::NULL, &argument) {
+                tracing::error!("{event} listener failed: {err:?}");
+            }
+        }
+    }
+}
+
+impl Default for MessageChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessagingWalletInfo {
+    pub name: &'static str,
+    pub url: &'static str,
+    pub icon: &'static str,
+}
+
+/// A wallet speaking the protocol of this module over `T`.
+///
+/// [`GenericWasmWalletAdapter`](crate::generic_wallet::GenericWasmWalletAdapter) only sends
+/// transactions, keep a clone of the wallet to [`Self::sign_transaction`] or
+/// [`Self::sign_message`], clones share the session.
+#[derive(Debug, Clone)]
+pub struct MessagingWallet<T: MessageTransport> {
+    info: MessagingWalletInfo,
+    transport: T,
+    channel: MessageChannel,
+}
+
+impl<T: MessageTransport> MessagingWallet<T> {
+    pub fn new(info: MessagingWalletInfo, transport: T) -> Self {
+        Self {
+            info,
+            transport,
+            channel: MessageChannel::new(),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn channel(&self) -> &MessageChannel {
+        &self.channel
+    }
+
+    /// Send `method` to the wallet, opening the transport first if needed
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        if self.channel.target().is_none() {
+            self.transport.open(&self.channel)?;
+        }
+        let target = self
+            .channel
+            .target()
+            .with_context(|| format!("{} transport did not open", self.info.name))?;
+
+        Ok(self
+            .channel
+            .request(method, params, self.transport.timeout(), |message| {
+                self.transport.post(&target, message)
+            })
+            .await?)
+    }
+
+    pub async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> Result<TransactionOrVersionedTransaction> {
+        let params = json!({ "transaction": bs58::encode(transaction.serialize()?).into_string() });
+        let result = self.request("signTransaction", params).await?;
+
+        let signed = result["transaction"]
+            .as_str()
+            .context("signed transaction not found")?;
+        let bytes = bs58::decode(signed)
+            .into_vec()
+            .map_err(|err| anyhow!("signed transaction is not base58: {err}"))?;
+        TransactionOrVersionedTransaction::deserialize(&bytes)
+    }
+
+    pub async fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let params = json!({ "message": bs58::encode(message).into_string() });
+        let result = self.request("signMessage", params).await?;
+
+        let signature = result["signature"]
+            .as_str()
+            .context("signature not found")?;
+        bs58::decode(signature)
+            .into_vec()
+            .map_err(|err| anyhow!("signature is not base58: {err}"))
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<T: MessageTransport> GenericWasmWallet for MessagingWallet<T> {
+    fn name(&self) -> String {
+        self.info.name.into()
+    }
+
+    fn url(&self) -> String {
+        self.info.url.into()
+    }
+
+    fn icon(&self) -> String {
+        self.info.icon.into()
+    }
+
+    fn is_correct_wallet(&self) -> bool {
+        self.transport.is_available()
+    }
+
+    fn is_connected(&self) -> bool {
+        self.channel.public_key().is_some()
+    }
+
+    async fn connect(&self, options: ConnectOptions) -> Result<()> {
+        tracing::debug!("{} wallet connect", self.info.name);
+
+        let result = self
+            .request(
+                "connect",
+                json!({ "onlyIfTrusted": options.only_if_trusted }),
+            )
+            .await?;
+        let public_key = result["publicKey"]
+            .as_str()
+            .context("public key not found")?
+            .parse()?;
+        self.channel.set_public_key(Some(public_key));
+
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<()> {
+        self.channel.set_public_key(None);
+        self.request("disconnect", Value::Null).await?;
+        Ok(())
+    }
+
+    fn on(&self, event: &str, cb: js_sys::Function) -> Result<()> {
+        self.channel.on(event, cb);
+        Ok(())
+    }
+
+    fn off(&self, event: &str, cb: js_sys::Function) -> Result<()> {
+        self.channel.off(event, &cb);
+        Ok(())
+    }
+
+    fn public_key(&self) -> Result<Pubkey> {
+        self.channel
+            .public_key()
+            .ok_or_else(|| anyhow!("{} has no public key", self.info.name))
+    }
+
+    async fn sign_and_send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> Result<Signature> {
+        let params = json!({ "transaction": bs58::encode(transaction.serialize()?).into_string() });
+        let result = self.request("signAndSendTransaction", params).await?;
+
+        // either the signature itself or `{ signature }`
+        let signature = match result.as_str() {
+            Some(signature) => signature,
+            None => result["signature"]
+                .as_str()
+                .context("signature not found")?,
+        };
+
+        tracing::debug!("result: {}", signature);
+
+        Ok(signature.parse()?)
+    }
+}
+
+/// `value` as a plain JS object, the way `postMessage` clones it
+pub fn to_js(value: &impl Serialize) -> Result<JsValue> {
+    js_sys::JSON::parse(&serde_json::to_string(value)?).map_err(|e| anyhow!("{e:?}"))
+}
+
+/// A public key with `toBytes()` like the injected providers pass to their listeners
+fn js_public_key(public_key: &Pubkey) -> JsValue {
+    let object = js_sys::Object::new();
+    let bytes = js_sys::Uint8Array::from(public_key.as_ref());
+    let _ = reflect_set(&object, "bytes", &bytes);
+    let _ = reflect_set(
+        &object,
+        "toBytes",
+        &js_sys::Function::new_no_args("return this.bytes"),
+    );
+    object.into()
+}
@@ -0,0 +1,435 @@
+//! The wallet side of the Wallet Standard: announce a Rust wallet on `window` so ordinary
+//! JS dApps, and E2E tests driving them, list and use it like an injected wallet.
+//!
+//! The wallet implements `standard:connect`, `standard:disconnect`, `standard:events`,
+//! `solana:signAndSendTransaction`, `solana:signTransaction` and `solana:signMessage`.
+
+use std::rc::Rc;
+
+use js_sys::{Array, Function, Object, Uint8Array};
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::pubkey::Pubkey;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseWalletAdapter, TransactionOrVersionedTransaction,
+    WalletAdapterEvent,
+};
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::standard::SolanaChain;
+use wallet_adapter_common::types::SendOptions;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::future_to_promise;
+
+use crate::util::{
+    on_event_local, reflect_get, reflect_set, wallet_error_to_js, LocalEventSubscription,
+};
+
+const FEATURES: [&str; 6] = [
+    "standard:connect",
+    "standard:disconnect",
+    "standard:events",
+    "solana:signAndSendTransaction",
+    "solana:signTransaction",
+    "solana:signMessage",
+];
+
+#[wasm_bindgen(inline_js = "
+    export function standard_wallet(name, icon, chains, accounts, connect, disconnect, send, sign, signMessage) {
+        const listeners = new Set();
+        const all = async (inputs, answer) => {
+            const outputs = [];
+            for (const input of inputs) {
+                outputs.push(await answer(input));
+            }
+            return outputs;
+        };
+
+        const wallet = {
+            version: '1.0.0',
+            name,
+            icon,
+            chains,
+            get accounts() {
+                return accounts();
+            },
+            features: {
+                'standard:connect': {
+                    version: '1.0.0',
+                    connect: async () => {
+                        await connect();
+                        return { accounts: accounts() };
+                    },
+                },
+                'standard:disconnect': { version: '1.0.0', disconnect },
+                'standard:events': {
+                    version: '1.0.0',
+                    on: (event, listener) => {
+                        if (event !== 'change') {
+                            return () => {};
+                        }
+                        listeners.add(listener);
+                        return () => listeners.delete(listener);
+                    },
+                },
+                'solana:signAndSendTransaction': {
+                    version: '1.0.0',
+                    supportedTransactionVersions: ['legacy', 0],
+                    signAndSendTransaction: (...inputs) => all(inputs, async (input) => ({
+                        signature: await send(input.transaction, input.options ?? {}),
+                    })),
+                },
+                'solana:signTransaction': {
+                    version: '1.0.0',
+                    supportedTransactionVersions: ['legacy', 0],
+                    signTransaction: (...inputs) => all(inputs, async (input) => ({
+                        signedTransaction: await sign(input.transaction),
+                    })),
+                },
+                'solana:signMessage': {
+                    version: '1.0.0',
+                    signMessage: (...inputs) => all(inputs, async (input) => ({
+                        signedMessage: input.message,
+                        signature: await signMessage(input.message),
+                    })),
+                },
+            },
+        };
+
+        const change = () => {
+            for (const listener of listeners) {
+                listener({ accounts: accounts() });
+            }
+        };
+        return { wallet, change };
+    }
+
+    export function register_wallet(wallet) {
+        const unregisters = [];
+        const callback = ({ register }) => unregisters.push(register(wallet));
+        try {
+            window.dispatchEvent(new CustomEvent('wallet-standard:register-wallet', { detail: callback }));
+        } catch (error) {
+            console.error('wallet-standard:register-wallet event could not be dispatched', error);
+        }
+        const listener = ({ detail: api }) => callback(api);
+        window.addEventListener('wallet-standard:app-ready', listener);
+
+        return () => {
+            window.removeEventListener('wallet-standard:app-ready', listener);
+            unregisters.forEach((unregister) => unregister());
+        };
+    }
+")]
+extern "C" {
+    #[allow(clippy::too_many_arguments)]
+    fn standard_wallet(
+        name: &str,
+        icon: &str,
+        chains: Array,
+        accounts: JsValue,
+        connect: JsValue,
+        disconnect: JsValue,
+        send: JsValue,
+        sign: JsValue,
+        sign_message: JsValue,
+    ) -> Object;
+
+    fn register_wallet(wallet: &JsValue) -> Function;
+}
+
+/// `SolanaSignAndSendTransactionOptions`, which names `minContextSlot` unlike [`SendOptions`]
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StandardSendOptions {
+    skip_preflight: Option<bool>,
+    preflight_commitment: Option<CommitmentLevel>,
+    max_retries: Option<u32>,
+    min_context_slot: Option<u32>,
+}
+
+impl From<StandardSendOptions> for SendOptions {
+    fn from(options: StandardSendOptions) -> Self {
+        SendOptions {
+            skip_preflight: options.skip_preflight,
+            preflight_commitment: options.preflight_commitment,
+            max_retries: options.max_retries,
+            min_context_slots: options.min_context_slot,
+            ..Default::default()
+        }
+    }
+}
+
+fn account_to_js(public_key: &Pubkey, chain: SolanaChain) -> anyhow::Result<JsValue> {
+    let account = Object::new();
+    reflect_set(&account, "address", &public_key.to_string().into())?;
+    reflect_set(
+        &account,
+        "publicKey",
+        &Uint8Array::from(public_key.as_ref()).into(),
+    )?;
+    reflect_set(
+        &account,
+        "chains",
+        &Array::of1(&chain.as_str().into()).into(),
+    )?;
+    let features: Array = FEATURES.iter().map(|f| JsValue::from_str(f)).collect();
+    reflect_set(&account, "features", &features.into())?;
+    Ok(account.into())
+}
+
+fn reject(error: impl Into<wallet_adapter_base::WalletError>) -> JsValue {
+    wallet_error_to_js(&error.into())
+}
+
+/// Keeps a wallet registered with [`register`], unregisters it when dropped
+#[must_use = "the wallet is unregistered when this is dropped"]
+pub struct RegisteredWallet {
+    unregister: Function,
+    _subscription: LocalEventSubscription,
+}
+
+impl RegisteredWallet {
+    /// Keep the wallet registered for as long as the page lives
+    pub fn forget(self) {
+        std::mem::forget(self)
+    }
+}
+
+impl Drop for RegisteredWallet {
+    fn drop(&mut self) {
+        let _ = self.unregister.call0(&JsValue::NULL);
+    }
+}
+
+/// Register `adapter` as a Wallet Standard wallet on `chain`, sending transactions through
+/// `connection`. dApps loaded before and after this call both see it.
+pub fn register<A>(
+    adapter: A,
+    connection: impl Connection + 'static,
+    chain: SolanaChain,
+) -> anyhow::Result<RegisteredWallet>
+where
+    A: BaseMessageSignerWalletAdapter + Clone + 'static,
+{
+    let connection: Rc<dyn Connection> = Rc::new(connection);
+
+    let accounts = {
+        let adapter = adapter.clone();
+        Closure::<dyn Fn() -> JsValue>::new(move || {
+            let accounts = Array::new();
+            if let Some(account) = adapter
+                .public_key()
+                .and_then(|key| account_to_js(&key, chain).ok())
+            {
+                accounts.push(&account);
+            }
+            accounts.into()
+        })
+    };
+
+    let connect = {
+        let adapter = adapter.clone();
+        Closure::<dyn Fn() -> js_sys::Promise>::new(move || {
+            let mut adapter = adapter.clone();
+            future_to_promise(async move {
+                if !adapter.connected() {
+                    adapter.connect().await.map_err(reject)?;
+                }
+                Ok(JsValue::UNDEFINED)
+            })
+        })
+    };
+
+    let disconnect = {
+        let adapter = adapter.clone();
+        Closure::<dyn Fn() -> js_sys::Promise>::new(move || {
+            let adapter = adapter.clone();
+            future_to_promise(async move {
+                adapter.disconnect().await.map_err(reject)?;
+                Ok(JsValue::UNDEFINED)
+            })
+        })
+    };
+
+    let send = {
+        let adapter = adapter.clone();
+        Closure::<dyn Fn(Vec<u8>, JsValue) -> js_sys::Promise>::new(
+            move |transaction: Vec<u8>, options: JsValue| {
+                let adapter = adapter.clone();
+                let connection = connection.clone();
+                future_to_promise(async move {
+                    let transaction = TransactionOrVersionedTransaction::deserialize(&transaction)
+                        .map_err(reject)?;
+                    let options: StandardSendOptions = serde_wasm_bindgen::from_value(options)
+                        .map_err(|err| reject(anyhow::anyhow!("invalid send options: {err}")))?;
+                    let signature = BaseWalletAdapter::send_transaction(
+                        &adapter,
+                        transaction,
+                        connection.as_ref(),
+                        Some(SendOptions::from(options).into()),
+                    )
+                    .await
+                    .map_err(reject)?;
+                    Ok(Uint8Array::from(signature.as_ref()).into())
+                })
+            },
+        )
+    };
+
+    let sign = {
+        let adapter = adapter.clone();
+        Closure::<dyn Fn(Vec<u8>) -> js_sys::Promise>::new(move |transaction: Vec<u8>| {
+            let adapter = adapter.clone();
+            future_to_promise(async move {
+                let transaction =
+                    TransactionOrVersionedTransaction::deserialize(&transaction).map_err(reject)?;
+                let signed = adapter
+                    .sign_transaction(transaction)
+                    .await
+                    .map_err(reject)?
+                    .serialize()
+                    .map_err(reject)?;
+                Ok(Uint8Array::from(signed.as_slice()).into())
+            })
+        })
+    };
+
+    let sign_message = {
+        let adapter = adapter.clone();
+        Closure::<dyn Fn(Vec<u8>) -> js_sys::Promise>::new(move |message: Vec<u8>| {
+            let adapter = adapter.clone();
+            future_to_promise(async move {
+                let signature = adapter.sign_message(&message).await.map_err(reject)?;
+                Ok(Uint8Array::from(signature.as_slice()).into())
+            })
+        })
+    };
+
+    // the wallet object keeps the closures for as long as the page may call it
+    let standard = standard_wallet(
+        &adapter.name(),
+        &adapter.icon(),
+        Array::of1(&chain.as_str().into()),
+        accounts.into_js_value(),
+        connect.into_js_value(),
+        disconnect.into_js_value(),
+        send.into_js_value(),
+        sign.into_js_value(),
+        sign_message.into_js_value(),
+    );
+    let wallet = reflect_get(&standard, &"wallet".into())?;
+    let change: Function = reflect_get(&standard, &"change".into())?.unchecked_into();
+
+    let subscription = on_event_local(&adapter.event_emitter(), move |event| {
+        if matches!(
+            event,
+            WalletAdapterEvent::Connect(_)
+                | WalletAdapterEvent::AccountChanged(_)
+                | WalletAdapterEvent::Disconnect
+        ) {
+            // listeners run inside `emit`, JS reacting to the change may call back into the wallet
+            let change = change.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(err) = change.call0(&JsValue::NULL) {
+                    tracing::warn!("wallet standard change listener threw: {err:?}");
+                }
+            });
+        }
+    });
+
+    Ok(RegisteredWallet {
+        unregister: register_wallet(&wallet),
+        _subscription: subscription,
+    })
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use solana_sdk::signature::{Signature, Signer};
+    use wallet_adapter_mock::{MockConnection, MockWalletAdapter};
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[wasm_bindgen(inline_js = "
+        const wallets = [];
+        const api = {
+            register: (wallet) => {
+                wallets.push(wallet);
+                return () => wallets.splice(wallets.indexOf(wallet), 1);
+            },
+        };
+
+        export function listen_for_wallets() {
+            window.addEventListener('wallet-standard:register-wallet', ({ detail: callback }) => callback(api));
+        }
+
+        export function app_ready() {
+            window.dispatchEvent(new CustomEvent('wallet-standard:app-ready', { detail: api }));
+        }
+
+        export function registered() {
+            return wallets;
+        }
+
+        export async function connect(wallet) {
+            const { accounts } = await wallet.features['standard:connect'].connect();
+            return accounts[0].address;
+        }
+
+        export async function sign_message(wallet, message) {
+            const [{ signature }] = await wallet.features['solana:signMessage'].signMessage({ message });
+            return signature;
+        }
+    ")]
+    extern "C" {
+        fn listen_for_wallets();
+        fn app_ready();
+        fn registered() -> Array;
+        fn connect(wallet: &JsValue) -> js_sys::Promise;
+        fn sign_message(wallet: &JsValue, message: Uint8Array) -> js_sys::Promise;
+    }
+
+    #[wasm_bindgen_test]
+    async fn dapps_see_and_use_the_wallet() {
+        let wallet = MockWalletAdapter::new();
+        let public_key = wallet.keypair().pubkey();
+
+        // a dApp loaded later picks the wallet up when it is ready
+        let early = register(wallet.clone(), MockConnection::new(), SolanaChain::Devnet).unwrap();
+        assert_eq!(registered().length(), 0);
+        app_ready();
+        assert_eq!(registered().length(), 1);
+
+        // a dApp already listening is told right away
+        listen_for_wallets();
+        let late = register(wallet.clone(), MockConnection::new(), SolanaChain::Devnet).unwrap();
+        assert_eq!(registered().length(), 2);
+
+        let standard = registered().get(0);
+        assert_eq!(
+            reflect_get(&standard, &"name".into()).unwrap().as_string(),
+            Some(wallet.name())
+        );
+        let features = reflect_get(&standard, &"features".into()).unwrap();
+        for feature in FEATURES {
+            assert!(reflect_get(&features, &feature.into()).unwrap().is_object());
+        }
+
+        let address = JsFuture::from(connect(&standard)).await.unwrap();
+        assert_eq!(address.as_string(), Some(public_key.to_string()));
+        assert!(wallet.connected());
+
+        let signature = JsFuture::from(sign_message(&standard, Uint8Array::from(&b"hello"[..])))
+            .await
+            .unwrap();
+        let signature = Signature::try_from(Uint8Array::new(&signature).to_vec()).unwrap();
+        assert!(signature.verify(public_key.as_ref(), b"hello"));
+
+        drop(early);
+        drop(late);
+        assert_eq!(registered().length(), 0);
+    }
+}
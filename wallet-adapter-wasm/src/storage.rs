@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Context, Result};
 use solana_sdk::signature::Keypair;
-use wallet_adapter_common::storage::KeypairStorage;
+use wallet_adapter_common::storage::{KeypairStorage, SettingsStorage};
 use web_sys::Storage;
 
 #[derive(Debug)]
@@ -45,7 +45,7 @@ impl KeypairStorage for WasmStorage {
             .get_item("keypair")
             .map_err(|err| anyhow!("{err:?}"))?;
         match item {
-            Some(item) => Ok(Some(Keypair::from_bytes(&hex::decode(item)?)?)),
+            Some(item) => Ok(Some(Keypair::try_from(&hex::decode(item)?[..])?)),
             None => Ok(None),
         }
     }
@@ -58,3 +58,23 @@ impl KeypairStorage for WasmStorage {
         Ok(())
     }
 }
+
+impl SettingsStorage for WasmStorage {
+    fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        self.storage()?
+            .get_item(key)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.storage()?
+            .set_item(key, value)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    fn remove_setting(&self, key: &str) -> Result<()> {
+        self.storage()?
+            .remove_item(key)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+}
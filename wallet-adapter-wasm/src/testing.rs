@@ -0,0 +1,610 @@
+//! Fake injected providers to test wallet adapters with `wasm-bindgen-test` in a headless browser.
+//!
+//! ```ignore
+//! let provider = FakeProvider::install(&PHANTOM, Keypair::new())?;
+//! let adapter = GenericWasmWalletAdapter::new(InjectedWallet::new(PHANTOM))?;
+//! testing::assert_connect_and_send(Box::new(adapter), &provider).await;
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use js_sys::{Array, Function, Object, Promise, Uint8Array};
+use solana_sdk::account::Account;
+use solana_sdk::bs58;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_system_interface::instruction as system_instruction;
+use wallet_adapter_base::conformance::GOLDEN_VECTORS;
+use wallet_adapter_base::{
+    BaseWalletAdapter, TransactionBuilder, TransactionOrVersionedTransaction,
+};
+use wallet_adapter_common::account::ParsedAccount;
+use wallet_adapter_common::connection::{
+    Connection, EpochInfo, LatestBlockhash, RpcVersion, SignatureStatus,
+};
+use wallet_adapter_common::history::{
+    ParsedTransactionWithMeta, SignatureInfo, SignaturesForAddressOptions,
+};
+use wallet_adapter_common::token::KeyedTokenAccount;
+use wallet_adapter_common::types::SendTransactionOptions;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::injected::InjectedWalletConfig;
+use crate::js_transaction::{from_js_transaction, to_js_public_key, to_js_transaction};
+use crate::util::{reflect_get, reflect_set};
+
+const USER_REJECTED: i32 = 4001;
+
+type Method = Closure<dyn FnMut(JsValue, JsValue) -> JsValue>;
+type Handler = Box<dyn FnMut(&Inner, JsValue, JsValue) -> JsValue>;
+
+#[derive(Default)]
+struct State {
+    rejecting: bool,
+    trusted: bool,
+    listeners: Vec<(String, Function)>,
+    sent: Vec<TransactionOrVersionedTransaction>,
+    batches: usize,
+    /// Index of the transaction of each batch answered with `null`
+    unsent: Option<usize>,
+    signed_messages: Vec<Vec<u8>>,
+}
+
+struct Inner {
+    keypair: Keypair,
+    provider: Object,
+    state: RefCell<State>,
+}
+
+/// The error of a user closing the popup
+fn rejected() -> JsValue {
+    let error = Object::new();
+    let _ = reflect_set(&error, "code", &USER_REJECTED.into());
+    let _ = reflect_set(&error, "message", &"User rejected the request.".into());
+    error.into()
+}
+
+impl Inner {
+    fn check_approved(&self) -> std::result::Result<(), JsValue> {
+        if !self.state.borrow().rejecting {
+            return Ok(());
+        }
+
+        Err(rejected())
+    }
+
+    fn emit(&self, event: &str, argument: &JsValue) {
+        let listeners: Vec<Function> = self
+            .state
+            .borrow()
+            .listeners
+            .iter()
+            .filter(|(name, _)| name == event)
+            .map(|(_, listener)| listener.clone())
+            .collect();
+
+        for listener in listeners {
+            let _ = listener.call1(&JsValue::NULL, argument);
+        }
+    }
+
+    /// `connect({ onlyIfTrusted: true })` fails like the wallets do until the app is trusted
+    fn check_trusted(&self, options: &JsValue) -> std::result::Result<(), JsValue> {
+        let only_if_trusted = reflect_get(options, &JsValue::from_str("onlyIfTrusted"))
+            .ok()
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        if !only_if_trusted || self.state.borrow().trusted {
+            return Ok(());
+        }
+
+        Err(rejected())
+    }
+
+    fn connect(&self) -> Result<JsValue> {
+        let public_key = to_js_public_key(&self.keypair.pubkey());
+        reflect_set(&self.provider, "isConnected", &JsValue::TRUE)?;
+        reflect_set(&self.provider, "publicKey", &public_key)?;
+        self.emit("connect", &public_key);
+
+        let result = Object::new();
+        reflect_set(&result, "publicKey", &public_key)?;
+        Ok(result.into())
+    }
+
+    fn disconnect(&self) -> Result<JsValue> {
+        reflect_set(&self.provider, "isConnected", &JsValue::FALSE)?;
+        reflect_set(&self.provider, "publicKey", &JsValue::NULL)?;
+        self.emit("disconnect", &JsValue::UNDEFINED);
+        Ok(JsValue::UNDEFINED)
+    }
+
+    fn sign(
+        &self,
+        mut transaction: TransactionOrVersionedTransaction,
+    ) -> Result<TransactionOrVersionedTransaction> {
+        let signer: Box<dyn Signer> = Box::new(self.keypair.insecure_clone());
+        transaction.partial_sign(&[signer])?;
+        Ok(transaction)
+    }
+
+    /// Sign and "send", the transaction is only recorded
+    fn sign_and_send(&self, transaction: TransactionOrVersionedTransaction) -> Result<JsValue> {
+        let transaction = self.sign(transaction)?;
+        let signature = match &transaction {
+            TransactionOrVersionedTransaction::Transaction(tx) => tx.signatures[0],
+            TransactionOrVersionedTransaction::VersionedTransaction(tx) => tx.signatures[0],
+        };
+        self.state.borrow_mut().sent.push(transaction);
+
+        let result = Object::new();
+        reflect_set(&result, "signature", &signature.to_string().into())?;
+        reflect_set(
+            &result,
+            "publicKey",
+            &to_js_public_key(&self.keypair.pubkey()),
+        )?;
+        Ok(result.into())
+    }
+
+    /// `signAndSendAllTransactions(transactions)`, answering `{ signatures, publicKey }`
+    fn sign_and_send_all(&self, transactions: &JsValue) -> Result<JsValue> {
+        let signatures = Array::new();
+        let unsent = self.state.borrow().unsent;
+        for (index, transaction) in Array::from(transactions).iter().enumerate() {
+            if unsent == Some(index) {
+                signatures.push(&JsValue::NULL);
+                continue;
+            }
+            let result = self.sign_and_send(from_js_transaction(&transaction)?)?;
+            signatures.push(&reflect_get(&result, &JsValue::from_str("signature"))?);
+        }
+        self.state.borrow_mut().batches += 1;
+
+        let result = Object::new();
+        reflect_set(&result, "signatures", &signatures)?;
+        reflect_set(
+            &result,
+            "publicKey",
+            &to_js_public_key(&self.keypair.pubkey()),
+        )?;
+        Ok(result.into())
+    }
+
+    /// `request({ method: "signAndSendTransaction", params: { [param]: base58 } })`
+    fn request(&self, request: &JsValue) -> Result<JsValue> {
+        let method = reflect_get(request, &JsValue::from_str("method"))?.as_string();
+        if method.as_deref() != Some("signAndSendTransaction") {
+            bail!("unsupported request {method:?}");
+        }
+
+        let params = reflect_get(request, &JsValue::from_str("params"))?;
+        let encoded = Object::values(params.unchecked_ref())
+            .iter()
+            .find_map(|value: JsValue| value.as_string())
+            .context("request has no transaction")?;
+        let bytes = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|err| anyhow!("{err}"))?;
+
+        self.sign_and_send(TransactionOrVersionedTransaction::deserialize(&bytes)?)
+    }
+
+    fn sign_transaction(&self, transaction: &JsValue) -> Result<JsValue> {
+        let signed = self.sign(from_js_transaction(transaction)?)?;
+        Ok(Uint8Array::from(signed.serialize()?.as_slice()).into())
+    }
+
+    fn sign_message(&self, message: &JsValue) -> Result<JsValue> {
+        let message = Uint8Array::new(message).to_vec();
+        let signature = self.keypair.sign_message(&message);
+        self.state.borrow_mut().signed_messages.push(message);
+
+        let result = Object::new();
+        reflect_set(
+            &result,
+            "signature",
+            &Uint8Array::from(signature.as_ref()).into(),
+        )?;
+        reflect_set(
+            &result,
+            "publicKey",
+            &to_js_public_key(&self.keypair.pubkey()),
+        )?;
+        Ok(result.into())
+    }
+}
+
+fn to_promise(result: std::result::Result<JsValue, JsValue>) -> JsValue {
+    match result {
+        Ok(value) => Promise::resolve(&value).into(),
+        Err(error) => Promise::reject(&error).into(),
+    }
+}
+
+fn js_error(error: anyhow::Error) -> JsValue {
+    js_sys::Error::new(&format!("{error:#}")).into()
+}
+
+/// An injected provider that approves every request and signs with a known keypair.
+/// It is removed from `window` again when dropped.
+pub struct FakeProvider {
+    window_key: &'static str,
+    inner: Rc<Inner>,
+    _methods: Vec<Method>,
+}
+
+impl FakeProvider {
+    /// Inject a provider shaped like the wallet of `config` into `window`,
+    /// install it before creating the adapter so it is detected right away
+    pub fn install(config: &InjectedWalletConfig, keypair: Keypair) -> Result<Self> {
+        let provider = Object::new();
+        reflect_set(&provider, config.detection_flag, &JsValue::TRUE)?;
+        reflect_set(&provider, "isConnected", &JsValue::FALSE)?;
+        reflect_set(&provider, "publicKey", &JsValue::NULL)?;
+
+        let inner = Rc::new(Inner {
+            keypair,
+            provider,
+            state: RefCell::new(State::default()),
+        });
+
+        let mut methods = Vec::new();
+        let mut add = |name: &str, mut f: Handler| {
+            let captured = inner.clone();
+            let method: Method = Closure::new(move |a, b| f(&captured, a, b));
+            reflect_set(&inner.provider, name, method.as_ref())?;
+            methods.push(method);
+            anyhow::Ok(())
+        };
+
+        add(
+            "connect",
+            Box::new(|inner, options, _| {
+                to_promise(
+                    inner
+                        .check_trusted(&options)
+                        .and_then(|()| inner.check_approved())
+                        .and_then(|()| inner.connect().map_err(js_error)),
+                )
+            }),
+        )?;
+        add(
+            "disconnect",
+            Box::new(|inner, _, _| {
+                to_promise(
+                    inner
+                        .check_approved()
+                        .and_then(|()| inner.disconnect().map_err(js_error)),
+                )
+            }),
+        )?;
+        add(
+            "on",
+            Box::new(|inner, event, listener| {
+                if let (Some(event), Ok(listener)) = (event.as_string(), listener.dyn_into()) {
+                    inner.state.borrow_mut().listeners.push((event, listener));
+                }
+                JsValue::UNDEFINED
+            }),
+        )?;
+        add(
+            "off",
+            Box::new(|inner, event, listener| {
+                inner.state.borrow_mut().listeners.retain(|(name, f)| {
+                    !(event.as_string().as_ref() == Some(name) && JsValue::eq(f, &listener))
+                });
+                JsValue::UNDEFINED
+            }),
+        )?;
+        add(
+            "request",
+            Box::new(|inner, request, _| {
+                to_promise(
+                    inner
+                        .check_approved()
+                        .and_then(|()| inner.request(&request).map_err(js_error)),
+                )
+            }),
+        )?;
+        add(
+            "signAndSendTransaction",
+            Box::new(|inner, transaction, _| {
+                to_promise(inner.check_approved().and_then(|()| {
+                    from_js_transaction(&transaction)
+                        .and_then(|transaction| inner.sign_and_send(transaction))
+                        .map_err(js_error)
+                }))
+            }),
+        )?;
+        add(
+            "signAndSendAllTransactions",
+            Box::new(|inner, transactions, _| {
+                to_promise(
+                    inner
+                        .check_approved()
+                        .and_then(|()| inner.sign_and_send_all(&transactions).map_err(js_error)),
+                )
+            }),
+        )?;
+        add(
+            "signTransaction",
+            Box::new(|inner, transaction, _| {
+                to_promise(
+                    inner
+                        .check_approved()
+                        .and_then(|()| inner.sign_transaction(&transaction).map_err(js_error)),
+                )
+            }),
+        )?;
+        add(
+            "signMessage",
+            Box::new(|inner, message, _| {
+                to_promise(
+                    inner
+                        .check_approved()
+                        .and_then(|()| inner.sign_message(&message).map_err(js_error)),
+                )
+            }),
+        )?;
+
+        let window = web_sys::window().context("could not get window")?;
+        reflect_set(&window, config.window_key, &inner.provider)?;
+
+        Ok(Self {
+            window_key: config.window_key,
+            inner,
+            _methods: methods,
+        })
+    }
+
+    pub fn public_key(&self) -> Pubkey {
+        self.inner.keypair.pubkey()
+    }
+
+    /// Reject every following request like a user closing the popup (code 4001)
+    pub fn set_rejecting(&self, rejecting: bool) {
+        self.inner.state.borrow_mut().rejecting = rejecting;
+    }
+
+    /// Let `connect({ onlyIfTrusted: true })` succeed, like for an app the user approved before
+    pub fn set_trusted(&self, trusted: bool) {
+        self.inner.state.borrow_mut().trusted = trusted;
+    }
+
+    /// Transactions passed to `signAndSendTransaction`, signed by the provider
+    pub fn sent_transactions(&self) -> Vec<TransactionOrVersionedTransaction> {
+        self.inner.state.borrow().sent.clone()
+    }
+
+    /// Number of `signAndSendAllTransactions` calls, their transactions are in [`Self::sent_transactions`]
+    pub fn sent_batches(&self) -> usize {
+        self.inner.state.borrow().batches
+    }
+
+    /// Answer `null` for the `index`th transaction of the following batches, like a wallet that
+    /// couldn't send it
+    pub fn set_unsent_in_batches(&self, index: Option<usize>) {
+        self.inner.state.borrow_mut().unsent = index;
+    }
+
+    pub fn signed_messages(&self) -> Vec<Vec<u8>> {
+        self.inner.state.borrow().signed_messages.clone()
+    }
+
+    /// Connect from the wallet's side, like a session approved in the extension
+    pub fn connect_from_wallet(&self) -> Result<()> {
+        self.inner.connect().map(|_| ())
+    }
+
+    /// Disconnect from the wallet's side, like a session revoked in the extension
+    pub fn disconnect_from_wallet(&self) -> Result<()> {
+        self.inner.disconnect().map(|_| ())
+    }
+
+    /// Trigger the provider's `accountChanged` listeners, `None` like a wallet switching
+    /// to an account that hasn't authorized the app yet
+    pub fn change_account(&self, public_key: Option<&Pubkey>) {
+        let argument = public_key.map_or(JsValue::NULL, to_js_public_key);
+        self.inner.emit("accountChanged", &argument);
+    }
+}
+
+impl Drop for FakeProvider {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            let _ = js_sys::Reflect::delete_property(&window, &JsValue::from_str(self.window_key));
+        }
+    }
+}
+
+/// Dispatch the window event `name`, eg. the `#initialized` event of a wallet that was injected
+pub fn dispatch_window_event(name: &str) -> Result<()> {
+    let window = web_sys::window().context("could not get window")?;
+    let event = web_sys::Event::new(name).map_err(|err| anyhow!("{err:?}"))?;
+    window
+        .dispatch_event(&event)
+        .map_err(|err| anyhow!("{err:?}"))?;
+    Ok(())
+}
+
+/// Connection handing out a random blockhash, enough for adapters preparing transactions
+/// that are sent by the wallet. Everything else fails.
+#[derive(Debug, Default)]
+pub struct FakeConnection;
+
+#[async_trait::async_trait(?Send)]
+impl Connection for FakeConnection {
+    async fn get_latest_blockhash_with_context(
+        &self,
+        _commitment: Option<CommitmentLevel>,
+        _min_context_slots: Option<u32>,
+    ) -> Result<LatestBlockhash> {
+        Ok(LatestBlockhash {
+            blockhash: Hash::new_unique(),
+            last_valid_block_height: u64::MAX,
+            slot: 0,
+        })
+    }
+
+    async fn send_raw_transaction(
+        &self,
+        _raw_transaction: Vec<u8>,
+        _options: Option<&SendTransactionOptions>,
+    ) -> Result<Signature> {
+        bail!("FakeConnection can't send transactions")
+    }
+
+    async fn request_airdrop(&self, _pubkey: &Pubkey, _lamports: u64) -> Result<Signature> {
+        bail!("FakeConnection can't request airdrops")
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>> {
+        Ok(vec![None; signatures.len()])
+    }
+
+    async fn confirm_transaction(
+        &self,
+        signature: &Signature,
+        _commitment: CommitmentLevel,
+        _timeout_ms: u32,
+    ) -> Result<()> {
+        Err(anyhow!("FakeConnection can't confirm {signature}"))
+    }
+
+    async fn get_token_accounts(&self, _owner: &Pubkey) -> Result<Vec<KeyedTokenAccount>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_account_info(&self, _pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(None)
+    }
+
+    async fn get_parsed_account_info(&self, _pubkey: &Pubkey) -> Result<Option<ParsedAccount>> {
+        Ok(None)
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        _address: &Pubkey,
+        _options: Option<&SignaturesForAddressOptions>,
+    ) -> Result<Vec<SignatureInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_transaction(
+        &self,
+        _signature: &Signature,
+    ) -> Result<Option<ParsedTransactionWithMeta>> {
+        Ok(None)
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Ok(solana_sdk::rent::Rent::default().minimum_balance(data_len))
+    }
+
+    async fn get_slot(&self) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn get_epoch_info(&self) -> Result<EpochInfo> {
+        bail!("FakeConnection has no epochs")
+    }
+
+    async fn get_health(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_version(&self) -> Result<RpcVersion> {
+        bail!("FakeConnection has no version")
+    }
+
+    async fn get_genesis_hash(&self) -> Result<Hash> {
+        bail!("FakeConnection has no cluster")
+    }
+}
+
+/// Connect `adapter` to `provider`, send a transfer through it and disconnect again,
+/// panicking when any step doesn't reach the provider or returns something unexpected
+pub async fn assert_connect_and_send(
+    mut adapter: Box<dyn BaseWalletAdapter>,
+    provider: &FakeProvider,
+) {
+    adapter.connect().await.expect("connect failed");
+    assert_eq!(adapter.public_key(), Some(provider.public_key()));
+
+    let instruction =
+        system_instruction::transfer(&provider.public_key(), &Pubkey::new_unique(), 1);
+    let transaction = TransactionBuilder::new()
+        .fee_payer(provider.public_key())
+        .add_instruction(instruction)
+        .build_legacy()
+        .expect("failed to build the transaction");
+
+    let signature = adapter
+        .send_transaction(transaction, &FakeConnection, None)
+        .await
+        .expect("send_transaction failed");
+
+    let sent = provider.sent_transactions();
+    assert_eq!(sent.len(), 1, "the provider didn't receive the transaction");
+    let TransactionOrVersionedTransaction::Transaction(tx) = &sent[0] else {
+        panic!("the provider received a versioned transaction");
+    };
+    assert_eq!(tx.signatures[0], signature);
+    assert!(tx.verify().is_ok(), "the sent transaction isn't signed");
+
+    adapter.disconnect().await.expect("disconnect failed");
+    assert_eq!(adapter.public_key(), None);
+}
+
+fn call_bytes(object: &JsValue, method: &str) -> Vec<u8> {
+    let function: Function = reflect_get(object, &JsValue::from_str(method))
+        .unwrap()
+        .dyn_into()
+        .unwrap_or_else(|_| panic!("no {method} method"));
+    Uint8Array::new(&function.call0(object).unwrap()).to_vec()
+}
+
+/// Check the web3.js shaped objects of the golden vectors against the bytes web3.js produces,
+/// see [`wallet_adapter_base::conformance`]
+pub fn assert_js_conformance() {
+    for vector in GOLDEN_VECTORS {
+        let transaction = vector.transaction().unwrap();
+        let object = to_js_transaction(&transaction).unwrap();
+
+        assert_eq!(
+            call_bytes(&object, "serialize"),
+            vector.bytes(),
+            "{}: serialize() differs",
+            vector.name
+        );
+        if let TransactionOrVersionedTransaction::Transaction(tx) = &transaction {
+            assert_eq!(
+                call_bytes(&object, "serializeMessage"),
+                tx.message.serialize(),
+                "{}: serializeMessage() differs",
+                vector.name
+            );
+        }
+        assert_eq!(
+            from_js_transaction(&object).unwrap(),
+            transaction,
+            "{}: reading the object back differs",
+            vector.name
+        );
+    }
+}
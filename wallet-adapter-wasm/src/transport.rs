@@ -0,0 +1,154 @@
+use anyhow::{anyhow, bail, Result};
+use wallet_adapter_common::transport::RpcTransport;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, RequestInit, Response};
+
+/// The default transport, calls `fetch` directly to keep HTTP client crates out of the binary.
+/// Works on the page and in Web Workers, which have no `window`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FetchTransport;
+
+async fn fetch(url: &str, init: &RequestInit) -> Result<Vec<u8>> {
+    let global = js_sys::global();
+    let request = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+        window.fetch_with_str_and_init(url, init)
+    } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        worker.fetch_with_str_and_init(url, init)
+    } else {
+        bail!("fetch is only available in windows and workers");
+    };
+    let resp: Response = JsFuture::from(request)
+        .await
+        .map_err(|e| anyhow!("{url} failed: {e:?}"))?
+        .dyn_into()
+        .map_err(|_| anyhow!("fetch did not return a Response"))?;
+    if !resp.ok() {
+        bail!("{url} failed with {}", resp.status());
+    }
+
+    let body = resp.array_buffer().map_err(|e| anyhow!("{e:?}"))?;
+    let body = JsFuture::from(body).await.map_err(|e| anyhow!("{e:?}"))?;
+    Ok(js_sys::Uint8Array::new(&body).to_vec())
+}
+
+#[async_trait::async_trait(?Send)]
+impl RpcTransport for FetchTransport {
+    async fn post_json(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+        let headers = Headers::new().map_err(|e| anyhow!("{e:?}"))?;
+        headers
+            .set("Content-Type", "application/json")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let init = RequestInit::new();
+        init.set_method("POST");
+        init.set_headers(&headers);
+        init.set_body(&JsValue::from(js_sys::Uint8Array::from(body.as_slice())));
+        fetch(url, &init).await
+    }
+
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        fetch(url, &RequestInit::new()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use wallet_adapter_common::connection::Connection;
+    use wallet_adapter_common::rpc_connection::RpcConnection;
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen_test::*;
+    use web_sys::ResponseInit;
+
+    use super::*;
+    use crate::util::{reflect_get, reflect_set};
+
+    /// A request seen by [`FakeFetch`]
+    struct Seen {
+        url: String,
+        method: Option<String>,
+        content_type: Option<String>,
+        body: Vec<u8>,
+    }
+
+    /// Replaces the global `fetch` until dropped, answering every request with `status` and `body`
+    struct FakeFetch {
+        original: JsValue,
+        seen: Rc<RefCell<Vec<Seen>>>,
+        _fetch: Closure<dyn FnMut(JsValue, JsValue) -> JsValue>,
+    }
+
+    impl FakeFetch {
+        fn install(status: u16, body: &'static str) -> Self {
+            let seen = Rc::new(RefCell::new(vec![]));
+            let recorded = seen.clone();
+            let fetch = Closure::new(move |url: JsValue, init: JsValue| {
+                let get = |key: &str| reflect_get(&init, &JsValue::from_str(key)).unwrap();
+                let content_type = get("headers")
+                    .dyn_into::<Headers>()
+                    .ok()
+                    .and_then(|headers| headers.get("Content-Type").unwrap());
+                let sent = get("body");
+                recorded.borrow_mut().push(Seen {
+                    url: url.as_string().unwrap(),
+                    method: get("method").as_string(),
+                    content_type,
+                    body: match sent.is_undefined() {
+                        true => vec![],
+                        false => js_sys::Uint8Array::new(&sent).to_vec(),
+                    },
+                });
+
+                let init = ResponseInit::new();
+                init.set_status(status);
+                let response = Response::new_with_opt_str_and_init(Some(body), &init).unwrap();
+                js_sys::Promise::resolve(&response).into()
+            });
+
+            let global = js_sys::global();
+            let original = reflect_get(&global, &JsValue::from_str("fetch")).unwrap();
+            reflect_set(&global, "fetch", fetch.as_ref()).unwrap();
+            Self {
+                original,
+                seen,
+                _fetch: fetch,
+            }
+        }
+    }
+
+    impl Drop for FakeFetch {
+        fn drop(&mut self) {
+            let _ = reflect_set(&js_sys::global(), "fetch", &self.original);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn posts_the_json_request() {
+        let fetch = FakeFetch::install(200, r#"{"jsonrpc":"2.0","result":42,"id":1}"#);
+        let connection =
+            RpcConnection::with_transport("https://rpc.example".to_string(), FetchTransport);
+
+        assert_eq!(connection.get_slot().await.unwrap(), 42);
+
+        let seen = fetch.seen.borrow();
+        assert_eq!(seen[0].url, "https://rpc.example");
+        assert_eq!(seen[0].method.as_deref(), Some("POST"));
+        assert_eq!(seen[0].content_type.as_deref(), Some("application/json"));
+        let request: serde_json::Value = serde_json::from_slice(&seen[0].body).unwrap();
+        assert_eq!(request["method"], "getSlot");
+    }
+
+    #[wasm_bindgen_test]
+    async fn fails_on_error_statuses() {
+        let _fetch = FakeFetch::install(503, "unavailable");
+
+        let err = FetchTransport
+            .get("https://rpc.example/health")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("failed with 503"), "{err}");
+    }
+}
@@ -1,4 +1,11 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
 use anyhow::{anyhow, Result};
+use wallet_adapter_base::{
+    EventSubscription, WalletAdapterEvent, WalletAdapterEventEmitter, WalletError,
+};
 use wasm_bindgen::JsValue;
 
 // TODO: improve this function
@@ -13,7 +20,123 @@ pub async fn sleep_ms(millis: i32) {
     wasm_bindgen_futures::JsFuture::from(p).await.unwrap();
 }
 
+/// Await `value` if it is a promise, some providers return one only from some of their methods
+pub async fn resolve_maybe_promise(value: JsValue) -> std::result::Result<JsValue, JsValue> {
+    wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&value)).await
+}
+
 pub fn reflect_get(target: &JsValue, key: &JsValue) -> Result<JsValue> {
     let result = js_sys::Reflect::get(target, key).map_err(|e| anyhow!("{:?}", e))?;
     Ok(result)
 }
+
+pub fn reflect_set(target: &JsValue, key: &str, value: &JsValue) -> Result<()> {
+    js_sys::Reflect::set(target, &JsValue::from_str(key), value).map_err(|e| anyhow!("{:?}", e))?;
+    Ok(())
+}
+
+/// `{ code, name, message }` object of a [`WalletError`] for JS consumers
+pub fn wallet_error_to_js(error: &WalletError) -> JsValue {
+    let object = js_sys::Object::new();
+    let _ = reflect_set(&object, "code", &error.code().into());
+    let _ = reflect_set(&object, "name", &error.code_name().into());
+    let _ = reflect_set(&object, "message", &error.message().into());
+    object.into()
+}
+
+/// iOS Safari can't run wallet extensions, but wallets can open the page in their in-app browser.
+/// Mirrors `isIosAndRedirectable` of the js wallet adapter, iPads report themselves as Macs
+/// with touch support.
+pub fn is_ios_and_redirectable() -> bool {
+    let Some(navigator) = web_sys::window().map(|window| window.navigator()) else {
+        return false;
+    };
+    is_ios_safari(
+        &navigator.user_agent().unwrap_or_default(),
+        &navigator.platform().unwrap_or_default(),
+        navigator.max_touch_points(),
+    )
+}
+
+fn is_ios_safari(user_agent: &str, platform: &str, max_touch_points: i32) -> bool {
+    let user_agent = user_agent.to_lowercase();
+
+    let is_ipad_os = platform == "MacIntel" && max_touch_points > 1;
+    let is_ios = user_agent.contains("iphone") || user_agent.contains("ipad") || is_ipad_os;
+    let is_safari = user_agent.contains("safari");
+
+    is_ios && is_safari
+}
+
+type LocalListener = Rc<dyn Fn(&WalletAdapterEvent)>;
+
+thread_local! {
+    static LOCAL_LISTENERS: RefCell<HashMap<u64, LocalListener>> = RefCell::new(HashMap::new());
+    static NEXT_LOCAL_LISTENER: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Keeps a listener registered with [`on_event_local`], removes it when dropped
+#[must_use = "the listener is removed when the subscription is dropped"]
+pub struct LocalEventSubscription {
+    id: u64,
+    _subscription: EventSubscription,
+}
+
+impl Drop for LocalEventSubscription {
+    fn drop(&mut self) {
+        LOCAL_LISTENERS.with(|listeners| listeners.borrow_mut().remove(&self.id));
+    }
+}
+
+/// [`WalletAdapterEventEmitter::on`] for listeners that aren't `Send`, eg. holding JS callbacks.
+/// The listener stays in a thread local, the emitter only gets its id.
+pub fn on_event_local(
+    emitter: &WalletAdapterEventEmitter,
+    listener: impl Fn(&WalletAdapterEvent) + 'static,
+) -> LocalEventSubscription {
+    let id = NEXT_LOCAL_LISTENER.with(|next| {
+        next.set(next.get() + 1);
+        next.get()
+    });
+    LOCAL_LISTENERS.with(|listeners| listeners.borrow_mut().insert(id, Rc::new(listener)));
+
+    let subscription = emitter.on(move |event| {
+        let listener = LOCAL_LISTENERS.with(|listeners| listeners.borrow().get(&id).cloned());
+        if let Some(listener) = listener {
+            listener(event);
+        }
+    });
+
+    LocalEventSubscription {
+        id,
+        _subscription: subscription,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPHONE_SAFARI: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) \
+        AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1";
+    const MAC_SAFARI: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) \
+        AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Safari/605.1.15";
+    const ANDROID_CHROME: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 8) \
+        AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Mobile Safari/537.36";
+
+    #[test]
+    fn detects_ios_safari() {
+        assert!(is_ios_safari(IPHONE_SAFARI, "iPhone", 5));
+        // iPadOS asks for the desktop site
+        assert!(is_ios_safari(MAC_SAFARI, "MacIntel", 5));
+
+        assert!(!is_ios_safari(MAC_SAFARI, "MacIntel", 0));
+        assert!(!is_ios_safari(ANDROID_CHROME, "Linux armv8l", 5));
+        // the in-app browsers of the wallets aren't Safari
+        assert!(!is_ios_safari(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148",
+            "iPhone",
+            5
+        ));
+    }
+}
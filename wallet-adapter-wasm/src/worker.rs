@@ -0,0 +1,332 @@
+//! Wallet access for code running in a Web Worker, where there is no `window` and no
+//! injected provider. The adapter stays on the main thread behind a [`WorkerProxy`], the
+//! worker talks to it through a [`MessagingWallet`](crate::messaging::MessagingWallet) over
+//! [`WorkerTransport`].
+//!
+//! ```ignore
+//! // main thread
+//! let worker = web_sys::Worker::new("./game.js")?;
+//! let _proxy = WorkerProxy::serve(worker, phantom_adapter, connection)?;
+//!
+//! // worker
+//! let wallet = MessagingWallet::new(WALLET_INFO, WorkerTransport);
+//! let adapter = GenericWasmWalletAdapter::new(wallet)?;
+//! ```
+
+use std::rc::Rc;
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use solana_sdk::bs58;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseWalletAdapter, TransactionOrVersionedTransaction,
+    WalletAdapterEvent, WalletError,
+};
+use wallet_adapter_common::connection::Connection;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::error::provider_error_code;
+use crate::messaging::{to_js, MessageChannel, MessageError, MessageRequest, MessageTransport};
+use crate::util::{on_event_local, LocalEventSubscription};
+
+/// Messages to and from the main thread, seen from inside a dedicated worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WorkerTransport;
+
+impl WorkerTransport {
+    fn scope() -> Result<web_sys::DedicatedWorkerGlobalScope> {
+        js_sys::global()
+            .dyn_into()
+            .map_err(|_| anyhow!("not running in a dedicated worker"))
+    }
+}
+
+impl MessageTransport for WorkerTransport {
+    fn is_available(&self) -> bool {
+        Self::scope().is_ok()
+    }
+
+    fn open(&self, channel: &MessageChannel) -> Result<()> {
+        let scope = Self::scope()?;
+        let on_message = {
+            let channel = channel.clone();
+            Closure::wrap(Box::new(move |event: JsValue| {
+                let event: web_sys::MessageEvent = event.unchecked_into();
+                channel.handle(&event.data());
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        scope
+            .add_event_listener_with_callback("message", on_message.as_ref().unchecked_ref())
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        channel.keep_closure(on_message);
+        channel.set_target(scope.into());
+        Ok(())
+    }
+
+    fn post(&self, target: &JsValue, message: &JsValue) -> Result<()> {
+        let scope: &web_sys::DedicatedWorkerGlobalScope = target.unchecked_ref();
+        scope.post_message(message).map_err(|e| anyhow!("{e:?}"))
+    }
+}
+
+/// Answers the requests of a worker's [`WorkerTransport`] with an adapter of the main thread
+/// and forwards the adapter's session events, until dropped
+pub struct WorkerProxy {
+    worker: web_sys::Worker,
+    on_message: Closure<dyn FnMut(JsValue)>,
+    _subscription: LocalEventSubscription,
+}
+
+impl WorkerProxy {
+    /// Serve `adapter` to `worker`, transactions are sent through `connection`
+    pub fn serve(
+        worker: web_sys::Worker,
+        adapter: impl BaseMessageSignerWalletAdapter + 'static,
+        connection: impl Connection + 'static,
+    ) -> Result<Self> {
+        let adapter: Rc<dyn BaseMessageSignerWalletAdapter> = Rc::new(adapter);
+        let connection: Rc<dyn Connection> = Rc::new(connection);
+
+        let on_message = {
+            let worker = worker.clone();
+            let adapter = adapter.clone();
+            Closure::wrap(Box::new(move |event: JsValue| {
+                let event: web_sys::MessageEvent = event.unchecked_into();
+                let Some(request) = js_sys::JSON::stringify(&event.data())
+                    .ok()
+                    .and_then(|json| json.as_string())
+                    .and_then(|json| serde_json::from_str::<MessageRequest>(&json).ok())
+                else {
+                    return;
+                };
+
+                let worker = worker.clone();
+                let adapter = dyn_clone::clone_box(&*adapter);
+                let connection = connection.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let response = match answer(adapter, connection.as_ref(), &request).await {
+                        Ok(result) => json!({ "id": request.id, "result": result }),
+                        Err(error) => json!({
+                            "id": request.id,
+                            "error": MessageError {
+                                code: provider_error_code(&error),
+                                message: error.to_string(),
+                            },
+                        }),
+                    };
+                    if let Err(err) = post(&worker, &response) {
+                        tracing::error!("could not answer {}: {err:?}", request.method);
+                    }
+                });
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        worker
+            .add_event_listener_with_callback("message", on_message.as_ref().unchecked_ref())
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let subscription = {
+            let worker = worker.clone();
+            on_event_local(&adapter.event_emitter(), move |event| {
+                let message = match event {
+                    WalletAdapterEvent::Connect(public_key) => {
+                        json!({ "event": "connect", "data": public_key.to_string() })
+                    }
+                    WalletAdapterEvent::AccountChanged(public_key) => {
+                        json!({ "event": "accountChanged", "data": public_key.to_string() })
+                    }
+                    WalletAdapterEvent::Disconnect => json!({ "event": "disconnect" }),
+                    _ => return,
+                };
+                if let Err(err) = post(&worker, &message) {
+                    tracing::error!("could not forward wallet event: {err:?}");
+                }
+            })
+        };
+
+        Ok(Self {
+            worker,
+            on_message,
+            _subscription: subscription,
+        })
+    }
+}
+
+impl Drop for WorkerProxy {
+    fn drop(&mut self) {
+        let _ = self.worker.remove_event_listener_with_callback(
+            "message",
+            self.on_message.as_ref().unchecked_ref(),
+        );
+    }
+}
+
+fn post(worker: &web_sys::Worker, message: &Value) -> Result<()> {
+    worker
+        .post_message(&to_js(message)?)
+        .map_err(|e| anyhow!("{e:?}"))
+}
+
+fn decode(params: &Value, name: &str) -> Result<Vec<u8>> {
+    let encoded = params[name]
+        .as_str()
+        .with_context(|| format!("request has no {name}"))?;
+    bs58::decode(encoded)
+        .into_vec()
+        .map_err(|err| anyhow!("{name} is not base58: {err}"))
+}
+
+/// Run `request` on the adapter, the result the way the worker's
+/// [`MessagingWallet`](crate::messaging::MessagingWallet) expects it
+async fn answer(
+    mut adapter: Box<dyn BaseMessageSignerWalletAdapter>,
+    connection: &dyn Connection,
+    request: &MessageRequest,
+) -> wallet_adapter_base::Result<Value> {
+    let params = &request.params;
+    match request.method.as_str() {
+        "connect" => {
+            if params["onlyIfTrusted"].as_bool().unwrap_or(false) {
+                adapter.auto_connect().await?;
+            } else {
+                adapter.connect().await?;
+            }
+            // adapters emit connection errors as events instead of failing
+            let public_key = adapter
+                .public_key()
+                .ok_or(WalletError::WalletNotConnected)?;
+            Ok(json!({ "publicKey": public_key.to_string() }))
+        }
+        "disconnect" => {
+            adapter.disconnect().await?;
+            Ok(Value::Null)
+        }
+        "signTransaction" => {
+            let transaction =
+                TransactionOrVersionedTransaction::deserialize(&decode(params, "transaction")?)?;
+            let signed = adapter.sign_transaction(transaction).await?;
+            Ok(json!({ "transaction": bs58::encode(signed.serialize()?).into_string() }))
+        }
+        "signMessage" => {
+            let signature = adapter.sign_message(&decode(params, "message")?).await?;
+            Ok(json!({ "signature": bs58::encode(signature).into_string() }))
+        }
+        "signAndSendTransaction" => {
+            let transaction =
+                TransactionOrVersionedTransaction::deserialize(&decode(params, "transaction")?)?;
+            let signature =
+                BaseWalletAdapter::send_transaction(&*adapter, transaction, connection, None)
+                    .await?;
+            Ok(json!({ "signature": signature.to_string() }))
+        }
+        method => Err(WalletError::Anyhow(anyhow!(
+            "unknown wallet method {method}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js_sys::Array;
+    use solana_sdk::signature::{Signature, Signer};
+    use wallet_adapter_mock::{MockConnection, MockStep, MockWalletAdapter};
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::util::sleep_ms;
+
+    #[wasm_bindgen(inline_js = "
+        export function echo_worker() {
+            const code = 'onmessage = ({ data }) => postMessage(data.forward ?? { answered: data });';
+            return new Worker(URL.createObjectURL(new Blob([code], { type: 'text/javascript' })));
+        }
+
+        export function answered(worker) {
+            const answers = [];
+            worker.addEventListener('message', ({ data }) => data.answered && answers.push(data.answered));
+            return answers;
+        }
+
+        export function forward(worker, request) {
+            worker.postMessage({ forward: request });
+        }
+    ")]
+    extern "C" {
+        /// A worker posting the requests it is told to forward and sending back what it
+        /// receives as `{ answered }`
+        fn echo_worker() -> web_sys::Worker;
+        /// What the worker received from the proxy
+        fn answered(worker: &web_sys::Worker) -> Array;
+        fn forward(worker: &web_sys::Worker, request: JsValue);
+    }
+
+    /// Have the worker send `method` to the proxy
+    fn request(worker: &web_sys::Worker, id: u64, method: &str, params: Value) {
+        let request = json!({ "id": id, "method": method, "params": params });
+        forward(worker, to_js(&request).unwrap());
+    }
+
+    /// The message the worker received matching `predicate`, waiting for it a little
+    async fn received(answers: &Array, predicate: impl Fn(&Value) -> bool) -> Option<Value> {
+        for _ in 0..50 {
+            let found = answers.iter().find_map(|answer| {
+                let json = js_sys::JSON::stringify(&answer).ok()?.as_string()?;
+                serde_json::from_str(&json).ok().filter(&predicate)
+            });
+            if found.is_some() {
+                return found;
+            }
+            sleep_ms(10).await;
+        }
+        None
+    }
+
+    #[wasm_bindgen_test]
+    async fn answers_the_worker_with_the_adapter() {
+        let wallet = MockWalletAdapter::new();
+        let public_key = wallet.keypair().pubkey();
+        let worker = echo_worker();
+        let answers = answered(&worker);
+        let proxy =
+            WorkerProxy::serve(worker.clone(), wallet.clone(), MockConnection::new()).unwrap();
+
+        request(&worker, 1, "connect", json!({ "onlyIfTrusted": false }));
+        let connected = received(&answers, |answer| answer["id"] == 1)
+            .await
+            .unwrap();
+        assert_eq!(connected["result"]["publicKey"], public_key.to_string());
+        let event = received(&answers, |answer| answer["event"] == "connect")
+            .await
+            .unwrap();
+        assert_eq!(event["data"], public_key.to_string());
+
+        let message = bs58::encode(b"hello").into_string();
+        request(&worker, 2, "signMessage", json!({ "message": message }));
+        let signed = received(&answers, |answer| answer["id"] == 2)
+            .await
+            .unwrap();
+        let signature = bs58::decode(signed["result"]["signature"].as_str().unwrap())
+            .into_vec()
+            .unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        assert!(signature.verify(public_key.as_ref(), b"hello"));
+
+        wallet.set_scenario([MockStep::user_rejected()]);
+        request(&worker, 3, "signMessage", json!({ "message": message }));
+        let rejected = received(&answers, |answer| answer["id"] == 3)
+            .await
+            .unwrap();
+        assert_eq!(rejected["error"]["code"], 4001);
+
+        drop(proxy);
+        request(&worker, 4, "disconnect", Value::Null);
+        assert!(received(&answers, |answer| answer["id"] == 4)
+            .await
+            .is_none());
+        assert!(wallet.connected());
+
+        worker.terminate();
+    }
+}
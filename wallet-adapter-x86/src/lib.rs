@@ -1,2 +1,6 @@
 pub mod connection;
+pub mod pubsub;
 pub mod storage;
+pub mod transport;
+#[cfg(feature = "yellowstone")]
+pub mod yellowstone;
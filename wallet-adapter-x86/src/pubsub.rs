@@ -0,0 +1,352 @@
+//! [`PubSub`] over the JSON-RPC websocket of a node, eg. `wss://api.devnet.solana.com`
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use futures::channel::mpsc as notification_channel;
+use futures::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use wallet_adapter_common::connection::RpcRequest;
+use wallet_adapter_common::pubsub::{
+    Backoff, Notification, Notifications, PubSub, PubSubStatus, PubSubStatusChanges,
+    StatusListeners, Subscription,
+};
+
+type Socket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+struct Subscribe {
+    subscription: Subscription,
+    notifications: notification_channel::UnboundedSender<Notification>,
+}
+
+/// Clones share the websocket. It reconnects with exponential backoff whenever it drops and
+/// renews the subscriptions, the task behind it runs until the clones and all their
+/// subscriptions are dropped.
+#[derive(Debug, Clone)]
+pub struct WebSocketPubSub {
+    commands: mpsc::UnboundedSender<Subscribe>,
+    status: StatusListeners,
+}
+
+impl WebSocketPubSub {
+    /// Connect to `url` in the background, call this from within a tokio runtime
+    pub fn new(url: impl ToString) -> Self {
+        Self::with_backoff(url, Backoff::default())
+    }
+
+    pub fn with_backoff(url: impl ToString, backoff: Backoff) -> Self {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let status = StatusListeners::default();
+        let worker = Worker {
+            url: url.to_string(),
+            backoff,
+            status: status.clone(),
+            commands: receiver,
+            next_id: 1,
+            subscriptions: HashMap::new(),
+        };
+        tokio::spawn(worker.run());
+
+        Self { commands, status }
+    }
+
+    pub fn devnet() -> Self {
+        Self::new("wss://api.devnet.solana.com")
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new("wss://api.mainnet-beta.solana.com")
+    }
+
+    pub fn testnet() -> Self {
+        Self::new("wss://api.testnet.solana.com")
+    }
+}
+
+#[async_trait::async_trait]
+impl PubSub for WebSocketPubSub {
+    async fn subscribe(&self, subscription: Subscription) -> Result<Notifications> {
+        let (notifications, receiver) = notification_channel::unbounded();
+        self.commands
+            .send(Subscribe {
+                subscription,
+                notifications,
+            })
+            .map_err(|_| anyhow!("the websocket task stopped"))?;
+        Ok(receiver)
+    }
+
+    fn status_changes(&self) -> PubSubStatusChanges {
+        self.status.listen()
+    }
+}
+
+struct Active {
+    subscription: Subscription,
+    notifications: notification_channel::UnboundedSender<Notification>,
+}
+
+/// The subscriptions of one websocket: those waiting for the node to confirm them,
+/// and the others by the id the node gave them
+#[derive(Default)]
+struct Session {
+    pending: HashSet<u64>,
+    subscribed: HashMap<u64, u64>,
+}
+
+struct Worker {
+    url: String,
+    backoff: Backoff,
+    status: StatusListeners,
+    commands: mpsc::UnboundedReceiver<Subscribe>,
+    next_id: u64,
+    /// By their id in this worker, which is also the id of their subscribe requests
+    subscriptions: HashMap<u64, Active>,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        let mut delay = self.backoff.initial;
+        let mut degraded = false;
+
+        while !self.is_done() {
+            let error = match tokio_tungstenite::connect_async(&self.url).await {
+                Ok((socket, _)) => {
+                    delay = self.backoff.initial;
+                    match self.serve(socket, degraded).await {
+                        Ok(()) => break,
+                        Err(err) => err,
+                    }
+                }
+                Err(err) => err.into(),
+            };
+
+            degraded = true;
+            tracing::warn!("websocket {} dropped: {error}", self.url);
+            self.status.emit(PubSubStatus::Degraded {
+                error: error.to_string(),
+                retry_in_ms: delay.as_millis() as u64,
+            });
+            tokio::time::sleep(delay).await;
+            delay = self.backoff.next(delay);
+        }
+    }
+
+    /// Whether every handle and subscription is gone
+    fn is_done(&mut self) -> bool {
+        self.subscriptions
+            .retain(|_, active| !active.notifications.is_closed());
+        self.commands_drained() && self.subscriptions.is_empty()
+    }
+
+    /// Whether the handles are gone and their last subscriptions taken
+    fn commands_drained(&self) -> bool {
+        self.commands.is_closed() && self.commands.is_empty()
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Renew the subscriptions and relay the notifications until the socket fails,
+    /// `Ok` once there is nothing left to serve
+    async fn serve(&mut self, mut socket: Socket, reconnected: bool) -> Result<()> {
+        let mut session = Session::default();
+        for (&id, active) in &self.subscriptions {
+            send_subscribe(&mut socket, id, &active.subscription).await?;
+            session.pending.insert(id);
+        }
+        if reconnected {
+            self.status.emit(PubSubStatus::Reconnected);
+        }
+
+        loop {
+            tokio::select! {
+                command = self.commands.recv(), if !self.commands_drained() => {
+                    let Some(Subscribe { subscription, notifications }) = command else {
+                        continue;
+                    };
+                    let id = self.next_id();
+                    send_subscribe(&mut socket, id, &subscription).await?;
+                    session.pending.insert(id);
+                    self.subscriptions.insert(id, Active { subscription, notifications });
+                }
+                message = socket.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        self.handle(&mut socket, &mut session, &text).await?;
+                        if self.is_done() {
+                            let _ = socket.close(None).await;
+                            return Ok(());
+                        }
+                    }
+                    Some(Ok(Message::Close(frame))) => {
+                        return Err(anyhow!("closed by the node: {frame:?}"));
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => return Err(err.into()),
+                    None => return Err(anyhow!("connection closed")),
+                }
+            }
+        }
+    }
+
+    async fn handle(
+        &mut self,
+        socket: &mut Socket,
+        session: &mut Session,
+        text: &str,
+    ) -> Result<()> {
+        let message: serde_json::Value = serde_json::from_str(text)?;
+
+        if let Some(id) = message["id"].as_u64() {
+            if !session.pending.remove(&id) {
+                return Ok(());
+            }
+            match message["result"].as_u64() {
+                Some(server_id) => {
+                    session.subscribed.insert(server_id, id);
+                }
+                None => {
+                    tracing::warn!("subscription refused: {}", message["error"]);
+                    self.subscriptions.remove(&id);
+                }
+            }
+            return Ok(());
+        }
+
+        let (Some(method), Some(server_id)) = (
+            message["method"].as_str(),
+            message["params"]["subscription"].as_u64(),
+        ) else {
+            return Ok(());
+        };
+        let Some(id) = session.subscribed.get(&server_id).copied() else {
+            return Ok(());
+        };
+        let Some(active) = self.subscriptions.get(&id) else {
+            return Ok(());
+        };
+
+        let notification =
+            Notification::from_websocket(method, message["params"]["result"].clone())?;
+        let delivered = active.notifications.unbounded_send(notification).is_ok();
+        if active.subscription.is_one_shot() {
+            session.subscribed.remove(&server_id);
+            self.subscriptions.remove(&id);
+        } else if !delivered {
+            let method = active.subscription.unsubscribe_method();
+            session.subscribed.remove(&server_id);
+            self.subscriptions.remove(&id);
+            let request_id = self.next_id();
+            send(socket, request_id, method, serde_json::json!([server_id])).await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn send_subscribe(socket: &mut Socket, id: u64, subscription: &Subscription) -> Result<()> {
+    let (method, params) = subscription.subscribe_request();
+    send(socket, id, method, params).await
+}
+
+async fn send(socket: &mut Socket, id: u64, method: &str, params: serde_json::Value) -> Result<()> {
+    let request = RpcRequest {
+        id,
+        ..RpcRequest::new(method, params)
+    };
+    socket
+        .send(Message::Text(serde_json::to_string(&request)?))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Accept a websocket, answer its slot subscription with `server_id` and notify `slot`
+    async fn serve_slot(listener: &TcpListener, server_id: u64, slot: u64) -> Socket {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(MaybeTlsStream::Plain(stream))
+            .await
+            .unwrap();
+
+        let Some(Ok(Message::Text(text))) = socket.next().await else {
+            panic!("expected a subscribe request");
+        };
+        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(request["method"], "slotSubscribe");
+
+        for message in [
+            json!({ "jsonrpc": "2.0", "result": server_id, "id": request["id"] }),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "slotNotification",
+                "params": {
+                    "result": { "parent": slot - 1, "root": slot - 32, "slot": slot },
+                    "subscription": server_id
+                }
+            }),
+        ] {
+            socket
+                .send(Message::Text(message.to_string()))
+                .await
+                .unwrap();
+        }
+        socket
+    }
+
+    #[test]
+    fn resubscribes_after_reconnecting() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let backoff = Backoff {
+                initial: Duration::from_millis(10),
+                max: Duration::from_millis(10),
+            };
+            let pubsub = WebSocketPubSub::with_backoff(
+                format!("ws://{}", listener.local_addr().unwrap()),
+                backoff,
+            );
+            let mut status = pubsub.status_changes();
+            let mut slots = pubsub.subscribe(Subscription::Slot).await.unwrap();
+
+            let socket = serve_slot(&listener, 7, 100).await;
+            let Some(Notification::Slot { slot, .. }) = slots.next().await else {
+                panic!("expected a slot notification");
+            };
+            assert_eq!(slot, 100);
+
+            drop(socket);
+            assert!(matches!(
+                status.next().await,
+                Some(PubSubStatus::Degraded {
+                    retry_in_ms: 10,
+                    ..
+                })
+            ));
+
+            let _socket = serve_slot(&listener, 8, 101).await;
+            assert_eq!(status.next().await, Some(PubSubStatus::Reconnected));
+            let Some(Notification::Slot { slot, .. }) = slots.next().await else {
+                panic!("expected a slot notification");
+            };
+            assert_eq!(slot, 101);
+        });
+    }
+}
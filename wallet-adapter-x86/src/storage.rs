@@ -1,57 +1,165 @@
-use std::fs::File;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use platform_dirs::AppDirs;
+use solana_sdk::bs58;
 use solana_sdk::signature::Keypair;
 use wallet_adapter_common::storage::KeypairStorage;
 
+/// Stores the keypair as a base58 JSON string.
+///
+/// Saves write a temporary file and rename it over the previous one, which is kept as `<path>.bak`.
+/// On Unix the files are only readable by their owner.
 #[derive(Debug)]
 pub struct X86Storage {
-    config_dir_name: String,
+    path: PathBuf,
 }
 
 impl X86Storage {
+    /// Store the keypair in `key.json` of the platform config directory `config_dir_name`
     pub fn new(config_dir_name: impl ToString) -> Result<Self> {
-        Ok(Self {
-            config_dir_name: config_dir_name.to_string(),
-        })
+        let app_dirs = AppDirs::new(Some(&config_dir_name.to_string()), true)
+            .context("Unable to get app dirs")?;
+        Ok(Self::with_path(app_dirs.config_dir.join("key.json")))
+    }
+
+    /// Store the keypair at `path`, its parent directories are created on the first save
+    pub fn with_path(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn sibling(&self, extension: &str) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(extension);
+        path.into()
+    }
+
+    /// A new file only the owner can read, replacing any leftover that others might read
+    fn create_private(path: &Path) -> Result<File> {
+        match std::fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("Unable to remove {}", path.display()))
+            }
+            _ => {}
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let file = options
+            .open(path)
+            .with_context(|| format!("Unable to create {}", path.display()))?;
+        // the mode is masked by the umask, set it explicitly
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(file)
     }
 }
 
 impl KeypairStorage for X86Storage {
     fn get_keypair(&self) -> Result<Option<Keypair>> {
-        let app_dirs =
-            AppDirs::new(Some(&self.config_dir_name), true).context("Unable to get app dirs")?;
-        let config_file_path = app_dirs.config_dir.join("key.json");
-
-        let file = if config_file_path.exists() {
-            File::open(config_file_path)?
-        } else {
+        if !self.path.exists() {
             return Ok(None);
-        };
+        }
 
+        let file = File::open(&self.path)?;
         let keypair_str: String =
             serde_json::from_reader(file).context("Unable to read keypair from file")?;
 
-        // TODO: this panics if the keypair_str is invalid
-        Ok(Some(Keypair::from_base58_string(&keypair_str)))
+        let bytes = bs58::decode(&keypair_str)
+            .into_vec()
+            .map_err(|err| anyhow!("Invalid keypair in {}: {err}", self.path.display()))?;
+        Ok(Some(Keypair::try_from(&bytes[..]).map_err(|err| {
+            anyhow!("Invalid keypair in {}: {err}", self.path.display())
+        })?))
     }
 
     fn set_keypair(&self, keypair: Keypair) -> Result<()> {
-        let app_dirs =
-            AppDirs::new(Some(&self.config_dir_name), true).context("Unable to get app dirs")?;
-        let config_file_path = app_dirs.config_dir.join("key.json");
-        std::fs::create_dir_all(&app_dirs.config_dir).unwrap();
-
-        let file = if config_file_path.exists() {
-            File::open(config_file_path)?
-        } else {
-            File::create(config_file_path)?
-        };
-
-        serde_json::to_writer(file, &keypair.to_base58_string())
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Unable to create {}", dir.display()))?;
+        }
+
+        let tmp_path = self.sibling(".tmp");
+        let mut file = Self::create_private(&tmp_path)?;
+        serde_json::to_writer(&mut file, &keypair.to_base58_string())
             .context("Unable to write keypair to file")?;
+        file.flush()?;
+        file.sync_all()?;
 
+        if self.path.exists() {
+            let backup = std::fs::read(&self.path)?;
+            Self::create_private(&self.sibling(".bak"))?.write_all(&backup)?;
+        }
+
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Unable to replace {}", self.path.display()))?;
+
+        // persist the rename itself
+        #[cfg(unix)]
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            File::open(dir)
+                .and_then(|dir| dir.sync_all())
+                .with_context(|| format!("Unable to sync {}", dir.display()))?;
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    #[test]
+    fn saves_replace_the_keypair_and_keep_a_backup() {
+        let dir = std::env::temp_dir().join(format!("x86-storage-{}", std::process::id()));
+        let storage = X86Storage::with_path(dir.join("nested").join("key.json"));
+        assert!(storage.get_keypair().unwrap().is_none());
+
+        let first = Keypair::new();
+        let second = Keypair::new();
+        storage.set_keypair(first.insecure_clone()).unwrap();
+        // a temporary file left behind by a crashed save, readable by everyone
+        std::fs::write(storage.sibling(".tmp"), "leftover").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let readable = std::fs::Permissions::from_mode(0o644);
+            std::fs::set_permissions(storage.sibling(".tmp"), readable).unwrap();
+        }
+        storage.set_keypair(second.insecure_clone()).unwrap();
+
+        let stored = storage.get_keypair().unwrap().unwrap();
+        assert_eq!(stored.pubkey(), second.pubkey());
+        let backup = X86Storage::with_path(storage.sibling(".bak"));
+        assert_eq!(
+            backup.get_keypair().unwrap().unwrap().pubkey(),
+            first.pubkey()
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(storage.path())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,677 @@
+//! [`PubSub`] over the Yellowstone Geyser gRPC stream of a node, for more subscriptions and
+//! lower latency than public websockets
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use futures::channel::mpsc as notification_channel;
+use futures::StreamExt;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::TransactionError;
+use tokio::sync::mpsc;
+use tonic::codec::{ProstCodec, Streaming};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::MetadataValue;
+use tonic::transport::{ClientTlsConfig, Endpoint};
+use wallet_adapter_common::pubsub::{
+    Backoff, Notification, Notifications, PubSub, PubSubStatus, PubSubStatusChanges,
+    StatusListeners, Subscription,
+};
+
+use self::proto::{subscribe_update::UpdateOneof, SubscribeRequest, SubscribeUpdate};
+
+const SUBSCRIBE_PATH: &str = "/geyser.Geyser/Subscribe";
+
+struct Subscribe {
+    subscription: Subscription,
+    notifications: notification_channel::UnboundedSender<Notification>,
+}
+
+/// Clones share the stream. It reconnects with exponential backoff whenever it fails and
+/// renews the subscriptions, the task behind it runs until the clones and all their
+/// subscriptions are dropped.
+///
+/// A Geyser stream has a single commitment, subscriptions asking for another are refused.
+#[derive(Debug, Clone)]
+pub struct YellowstonePubSub {
+    commands: mpsc::UnboundedSender<Subscribe>,
+    status: StatusListeners,
+    commitment: CommitmentLevel,
+}
+
+impl YellowstonePubSub {
+    /// Stream `commitment` notifications from the Geyser endpoint at `url`, sending `x_token`
+    /// to providers that need one. Call this from within a tokio runtime.
+    pub fn new(url: impl ToString, x_token: Option<String>, commitment: CommitmentLevel) -> Self {
+        Self::with_backoff(url, x_token, commitment, Backoff::default())
+    }
+
+    pub fn with_backoff(
+        url: impl ToString,
+        x_token: Option<String>,
+        commitment: CommitmentLevel,
+        backoff: Backoff,
+    ) -> Self {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let status = StatusListeners::default();
+        let worker = Worker {
+            url: url.to_string(),
+            x_token,
+            commitment,
+            backoff,
+            status: status.clone(),
+            commands: receiver,
+            next_id: 1,
+            subscriptions: HashMap::new(),
+            root: 0,
+        };
+        tokio::spawn(worker.run());
+
+        Self {
+            commands,
+            status,
+            commitment,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PubSub for YellowstonePubSub {
+    async fn subscribe(&self, subscription: Subscription) -> Result<Notifications> {
+        if let Some(commitment) = subscription
+            .commitment()
+            .filter(|commitment| *commitment != self.commitment)
+        {
+            bail!(
+                "the Geyser stream is {:?}, open another one for {commitment:?}",
+                self.commitment
+            );
+        }
+
+        let (notifications, receiver) = notification_channel::unbounded();
+        self.commands
+            .send(Subscribe {
+                subscription,
+                notifications,
+            })
+            .map_err(|_| anyhow!("the Geyser task stopped"))?;
+        Ok(receiver)
+    }
+
+    fn status_changes(&self) -> PubSubStatusChanges {
+        self.status.listen()
+    }
+}
+
+struct Active {
+    subscription: Subscription,
+    notifications: notification_channel::UnboundedSender<Notification>,
+}
+
+struct Worker {
+    url: String,
+    x_token: Option<String>,
+    commitment: CommitmentLevel,
+    backoff: Backoff,
+    status: StatusListeners,
+    commands: mpsc::UnboundedReceiver<Subscribe>,
+    next_id: u64,
+    /// By their id, which is also the name of their filter
+    subscriptions: HashMap<u64, Active>,
+    /// The last finalized slot, the root of slot notifications
+    root: u64,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        let mut delay = self.backoff.initial;
+        let mut degraded = false;
+
+        while !self.is_done() {
+            let error = match self.connect().await {
+                Ok((requests, updates)) => {
+                    delay = self.backoff.initial;
+                    match self.serve(requests, updates, degraded).await {
+                        Ok(()) => break,
+                        Err(err) => err,
+                    }
+                }
+                Err(err) => err,
+            };
+
+            degraded = true;
+            tracing::warn!("Geyser stream {} failed: {error}", self.url);
+            self.status.emit(PubSubStatus::Degraded {
+                error: error.to_string(),
+                retry_in_ms: delay.as_millis() as u64,
+            });
+            tokio::time::sleep(delay).await;
+            delay = self.backoff.next(delay);
+        }
+    }
+
+    /// Whether every handle and subscription is gone
+    fn is_done(&mut self) -> bool {
+        self.subscriptions
+            .retain(|_, active| !active.notifications.is_closed());
+        self.commands_drained() && self.subscriptions.is_empty()
+    }
+
+    /// Whether the handles are gone and their last subscriptions taken
+    fn commands_drained(&self) -> bool {
+        self.commands.is_closed() && self.commands.is_empty()
+    }
+
+    async fn connect(
+        &self,
+    ) -> Result<(
+        notification_channel::UnboundedSender<SubscribeRequest>,
+        Streaming<SubscribeUpdate>,
+    )> {
+        let mut endpoint = Endpoint::from_shared(self.url.clone())?;
+        if self.url.starts_with("https://") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new().with_webpki_roots())?;
+        }
+        let mut client = tonic::client::Grpc::new(endpoint.connect().await?);
+        client.ready().await?;
+
+        let (requests, outbound) = notification_channel::unbounded();
+        let mut request = tonic::Request::new(outbound);
+        if let Some(x_token) = &self.x_token {
+            request
+                .metadata_mut()
+                .insert("x-token", MetadataValue::try_from(x_token)?);
+        }
+        let codec: ProstCodec<SubscribeRequest, SubscribeUpdate> = ProstCodec::default();
+        let updates = client
+            .streaming(request, PathAndQuery::from_static(SUBSCRIBE_PATH), codec)
+            .await?
+            .into_inner();
+
+        Ok((requests, updates))
+    }
+
+    /// Send the filters and relay the updates until the stream fails,
+    /// `Ok` once there is nothing left to serve
+    async fn serve(
+        &mut self,
+        requests: notification_channel::UnboundedSender<SubscribeRequest>,
+        mut updates: Streaming<SubscribeUpdate>,
+        reconnected: bool,
+    ) -> Result<()> {
+        requests.unbounded_send(self.request())?;
+        if reconnected {
+            self.status.emit(PubSubStatus::Reconnected);
+        }
+
+        loop {
+            tokio::select! {
+                command = self.commands.recv(), if !self.commands_drained() => {
+                    let Some(Subscribe { subscription, notifications }) = command else {
+                        continue;
+                    };
+                    let id = self.next_id;
+                    self.next_id += 1;
+                    self.subscriptions.insert(id, Active { subscription, notifications });
+                    requests.unbounded_send(self.request())?;
+                }
+                update = updates.next() => match update {
+                    Some(Ok(update)) => {
+                        let filters_changed = self.handle(update, &requests)?;
+                        if self.is_done() {
+                            return Ok(());
+                        }
+                        if filters_changed {
+                            requests.unbounded_send(self.request())?;
+                        }
+                    }
+                    Some(Err(status)) => return Err(status.into()),
+                    None => return Err(anyhow!("the node ended the stream")),
+                }
+            }
+        }
+    }
+
+    /// The filters of every subscription, each named by its id
+    fn request(&self) -> SubscribeRequest {
+        let mut request = SubscribeRequest {
+            commitment: Some(proto::CommitmentLevel::from(self.commitment) as i32),
+            ..Default::default()
+        };
+
+        for (id, active) in &self.subscriptions {
+            let name = id.to_string();
+            match &active.subscription {
+                Subscription::Account { pubkey, .. } => {
+                    let filter = proto::SubscribeRequestFilterAccounts {
+                        account: vec![pubkey.to_string()],
+                        ..Default::default()
+                    };
+                    request.accounts.insert(name, filter);
+                }
+                Subscription::Signature { signature, .. } => {
+                    let filter = proto::SubscribeRequestFilterTransactions {
+                        signature: Some(signature.to_string()),
+                        ..Default::default()
+                    };
+                    request.transactions_status.insert(name, filter);
+                }
+                Subscription::Slot => {
+                    let filter = proto::SubscribeRequestFilterSlots {
+                        filter_by_commitment: Some(false),
+                        interslot_updates: Some(false),
+                    };
+                    request.slots.insert(name, filter);
+                }
+                Subscription::Transactions { account, .. } => {
+                    let filter = proto::SubscribeRequestFilterTransactions {
+                        account_include: vec![account.to_string()],
+                        ..Default::default()
+                    };
+                    request.transactions_status.insert(name, filter);
+                }
+            }
+        }
+
+        request
+    }
+
+    /// Relay `update` to the subscriptions it matched, whether a subscription ended
+    fn handle(
+        &mut self,
+        update: SubscribeUpdate,
+        requests: &notification_channel::UnboundedSender<SubscribeRequest>,
+    ) -> Result<bool> {
+        let filters = update.filters;
+        let update = match update.update_oneof {
+            Some(UpdateOneof::Ping(_)) => {
+                // load balancers drop quiet streams, answering keeps it busy
+                requests.unbounded_send(SubscribeRequest {
+                    ping: Some(proto::SubscribeRequestPing { id: 1 }),
+                    ..Default::default()
+                })?;
+                return Ok(false);
+            }
+            Some(UpdateOneof::Slot(slot)) => {
+                if slot.status == proto::SlotStatus::Finalized as i32 {
+                    self.root = self.root.max(slot.slot);
+                }
+                if slot.status != proto::SlotStatus::Processed as i32 {
+                    return Ok(false);
+                }
+                Update::Slot(Notification::Slot {
+                    slot: slot.slot,
+                    parent: slot.parent.unwrap_or_default(),
+                    root: self.root,
+                })
+            }
+            Some(UpdateOneof::Account(account)) => {
+                let info = account
+                    .account
+                    .ok_or_else(|| anyhow!("account update without the account"))?;
+                Update::Account(Notification::Account {
+                    slot: account.slot,
+                    account: Account {
+                        lamports: info.lamports,
+                        data: info.data,
+                        owner: Pubkey::try_from(info.owner.as_slice())
+                            .map_err(|_| anyhow!("invalid account owner"))?,
+                        executable: info.executable,
+                        rent_epoch: info.rent_epoch,
+                    },
+                })
+            }
+            Some(UpdateOneof::TransactionStatus(status)) => Update::TransactionStatus {
+                slot: status.slot,
+                signature: Signature::try_from(status.signature.as_slice())
+                    .map_err(|_| anyhow!("invalid transaction signature"))?,
+                err: status.err.map(transaction_error).transpose()?,
+            },
+            Some(UpdateOneof::Pong(_)) | None => return Ok(false),
+        };
+
+        let mut ended = false;
+        for id in filters.iter().filter_map(|name| name.parse::<u64>().ok()) {
+            let Some(active) = self.subscriptions.get(&id) else {
+                continue;
+            };
+            let delivered = active
+                .notifications
+                .unbounded_send(update.notification(&active.subscription))
+                .is_ok();
+            if !delivered || active.subscription.is_one_shot() {
+                self.subscriptions.remove(&id);
+                ended = true;
+            }
+        }
+
+        Ok(ended)
+    }
+}
+
+/// An update of the stream, which becomes a different notification depending on the
+/// subscription matching it
+enum Update {
+    Account(Notification),
+    Slot(Notification),
+    TransactionStatus {
+        slot: u64,
+        signature: Signature,
+        err: Option<serde_json::Value>,
+    },
+}
+
+impl Update {
+    fn notification(&self, subscription: &Subscription) -> Notification {
+        match (self, subscription) {
+            (Update::TransactionStatus { slot, err, .. }, Subscription::Signature { .. }) => {
+                Notification::Signature {
+                    slot: *slot,
+                    err: err.clone(),
+                }
+            }
+            (
+                Update::TransactionStatus {
+                    slot,
+                    signature,
+                    err,
+                },
+                _,
+            ) => Notification::Transaction {
+                slot: *slot,
+                signature: *signature,
+                err: err.clone(),
+            },
+            (Update::Account(notification) | Update::Slot(notification), _) => notification.clone(),
+        }
+    }
+}
+
+/// The RPC's JSON form of a bincode serialized transaction error
+fn transaction_error(err: proto::TransactionError) -> Result<serde_json::Value> {
+    let err: TransactionError = bincode::deserialize(&err.err)?;
+    Ok(serde_json::to_value(err)?)
+}
+
+/// The messages of Yellowstone's `geyser.proto` the subscriptions use, unknown fields and
+/// updates are skipped when decoding
+mod proto {
+    use std::collections::HashMap;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum CommitmentLevel {
+        Processed = 0,
+        Confirmed = 1,
+        Finalized = 2,
+    }
+
+    impl From<solana_sdk::commitment_config::CommitmentLevel> for CommitmentLevel {
+        fn from(commitment: solana_sdk::commitment_config::CommitmentLevel) -> Self {
+            match commitment {
+                solana_sdk::commitment_config::CommitmentLevel::Processed => Self::Processed,
+                solana_sdk::commitment_config::CommitmentLevel::Confirmed => Self::Confirmed,
+                solana_sdk::commitment_config::CommitmentLevel::Finalized => Self::Finalized,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum SlotStatus {
+        Processed = 0,
+        Confirmed = 1,
+        Finalized = 2,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequest {
+        #[prost(map = "string, message", tag = "1")]
+        pub accounts: HashMap<String, SubscribeRequestFilterAccounts>,
+        #[prost(map = "string, message", tag = "2")]
+        pub slots: HashMap<String, SubscribeRequestFilterSlots>,
+        #[prost(enumeration = "CommitmentLevel", optional, tag = "6")]
+        pub commitment: Option<i32>,
+        #[prost(message, optional, tag = "8")]
+        pub ping: Option<SubscribeRequestPing>,
+        #[prost(map = "string, message", tag = "10")]
+        pub transactions_status: HashMap<String, SubscribeRequestFilterTransactions>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequestFilterAccounts {
+        #[prost(string, repeated, tag = "2")]
+        pub account: Vec<String>,
+        #[prost(string, repeated, tag = "3")]
+        pub owner: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequestFilterSlots {
+        #[prost(bool, optional, tag = "1")]
+        pub filter_by_commitment: Option<bool>,
+        #[prost(bool, optional, tag = "2")]
+        pub interslot_updates: Option<bool>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequestFilterTransactions {
+        #[prost(bool, optional, tag = "1")]
+        pub vote: Option<bool>,
+        #[prost(bool, optional, tag = "2")]
+        pub failed: Option<bool>,
+        #[prost(string, optional, tag = "5")]
+        pub signature: Option<String>,
+        #[prost(string, repeated, tag = "3")]
+        pub account_include: Vec<String>,
+        #[prost(string, repeated, tag = "4")]
+        pub account_exclude: Vec<String>,
+        #[prost(string, repeated, tag = "6")]
+        pub account_required: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequestPing {
+        #[prost(int32, tag = "1")]
+        pub id: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdate {
+        #[prost(string, repeated, tag = "1")]
+        pub filters: Vec<String>,
+        #[prost(oneof = "subscribe_update::UpdateOneof", tags = "2, 3, 6, 9, 10")]
+        pub update_oneof: Option<subscribe_update::UpdateOneof>,
+    }
+
+    pub mod subscribe_update {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum UpdateOneof {
+            #[prost(message, tag = "2")]
+            Account(super::SubscribeUpdateAccount),
+            #[prost(message, tag = "3")]
+            Slot(super::SubscribeUpdateSlot),
+            #[prost(message, tag = "6")]
+            Ping(super::SubscribeUpdatePing),
+            #[prost(message, tag = "9")]
+            Pong(super::SubscribeUpdatePong),
+            #[prost(message, tag = "10")]
+            TransactionStatus(super::SubscribeUpdateTransactionStatus),
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdateAccount {
+        #[prost(message, optional, tag = "1")]
+        pub account: Option<SubscribeUpdateAccountInfo>,
+        #[prost(uint64, tag = "2")]
+        pub slot: u64,
+        #[prost(bool, tag = "3")]
+        pub is_startup: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdateAccountInfo {
+        #[prost(bytes = "vec", tag = "1")]
+        pub pubkey: Vec<u8>,
+        #[prost(uint64, tag = "2")]
+        pub lamports: u64,
+        #[prost(bytes = "vec", tag = "3")]
+        pub owner: Vec<u8>,
+        #[prost(bool, tag = "4")]
+        pub executable: bool,
+        #[prost(uint64, tag = "5")]
+        pub rent_epoch: u64,
+        #[prost(bytes = "vec", tag = "6")]
+        pub data: Vec<u8>,
+        #[prost(uint64, tag = "7")]
+        pub write_version: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdateSlot {
+        #[prost(uint64, tag = "1")]
+        pub slot: u64,
+        #[prost(uint64, optional, tag = "2")]
+        pub parent: Option<u64>,
+        #[prost(enumeration = "SlotStatus", tag = "3")]
+        pub status: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdateTransactionStatus {
+        #[prost(uint64, tag = "1")]
+        pub slot: u64,
+        #[prost(bytes = "vec", tag = "2")]
+        pub signature: Vec<u8>,
+        #[prost(bool, tag = "3")]
+        pub is_vote: bool,
+        #[prost(uint64, tag = "4")]
+        pub index: u64,
+        #[prost(message, optional, tag = "5")]
+        pub err: Option<TransactionError>,
+    }
+
+    /// `solana.storage.ConfirmedBlock.TransactionError`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TransactionError {
+        #[prost(bytes = "vec", tag = "1")]
+        pub err: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdatePing {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdatePong {
+        #[prost(int32, tag = "1")]
+        pub id: i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+    use solana_sdk::instruction::InstructionError;
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    fn worker(commitment: CommitmentLevel) -> Worker {
+        let (_, commands) = mpsc::unbounded_channel();
+        Worker {
+            url: "http://geyser".to_string(),
+            x_token: None,
+            commitment,
+            backoff: Backoff::default(),
+            status: StatusListeners::default(),
+            commands,
+            next_id: 1,
+            subscriptions: HashMap::new(),
+            root: 0,
+        }
+    }
+
+    #[test]
+    fn relays_updates_to_the_matching_subscriptions() {
+        let mut worker = worker(CommitmentLevel::Confirmed);
+        let (requests, _) = notification_channel::unbounded();
+        let signature = solana_sdk::signature::Keypair::new().sign_message(b"transfer");
+        let account = Pubkey::new_unique();
+
+        let mut streams = Vec::new();
+        for subscription in [
+            Subscription::Signature {
+                signature,
+                commitment: CommitmentLevel::Confirmed,
+            },
+            Subscription::Transactions {
+                account,
+                commitment: CommitmentLevel::Confirmed,
+            },
+        ] {
+            let (notifications, receiver) = notification_channel::unbounded();
+            let id = worker.subscriptions.len() as u64 + 1;
+            worker.subscriptions.insert(
+                id,
+                Active {
+                    subscription,
+                    notifications,
+                },
+            );
+            streams.push(receiver);
+        }
+
+        let request = worker.request();
+        assert_eq!(request.commitment, Some(1));
+        assert_eq!(
+            request.transactions_status["1"].signature,
+            Some(signature.to_string())
+        );
+        assert_eq!(
+            request.transactions_status["2"].account_include,
+            vec![account.to_string()]
+        );
+
+        // through the wire format, as the node sends it
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(1));
+        let update = SubscribeUpdate {
+            filters: vec!["1".to_string(), "2".to_string()],
+            update_oneof: Some(UpdateOneof::TransactionStatus(
+                proto::SubscribeUpdateTransactionStatus {
+                    slot: 42,
+                    signature: signature.as_ref().to_vec(),
+                    err: Some(proto::TransactionError {
+                        err: bincode::serialize(&err).unwrap(),
+                    }),
+                    ..Default::default()
+                },
+            )),
+        };
+        let update = SubscribeUpdate::decode(update.encode_to_vec().as_slice()).unwrap();
+        assert!(worker.handle(update, &requests).unwrap());
+
+        let err = serde_json::json!({ "InstructionError": [0, { "Custom": 1 }] });
+        assert_eq!(
+            streams[0].try_recv().ok(),
+            Some(Notification::Signature {
+                slot: 42,
+                err: Some(err.clone())
+            })
+        );
+        assert_eq!(
+            streams[1].try_recv().ok(),
+            Some(Notification::Transaction {
+                slot: 42,
+                signature,
+                err: Some(err)
+            })
+        );
+        // the signature is done, the transactions keep coming
+        assert_eq!(worker.subscriptions.len(), 1);
+        assert!(worker.request().transactions_status.contains_key("2"));
+    }
+}
@@ -0,0 +1,364 @@
+//! Extension wallets (Phantom, Solflare, Backpack, ...) for native apps.
+//!
+//! A localhost server hands requests to a small page opened in the system browser,
+//! which talks to the user's wallet through the Wallet Standard and posts the results back.
+//! The page has to stay open while the app uses the wallet.
+
+mod server;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context};
+use base64::prelude::*;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::TransactionVersion;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+    SupportedTransactionVersions, TransactionOrVersionedTransaction, WalletAdapterEvent,
+    WalletAdapterEventEmitter, WalletError, WalletReadyState,
+};
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::types::{DynSigner, SendTransactionOptions};
+
+use crate::server::BridgeServer;
+
+/// Left out of the binary when the `icon` feature is disabled
+#[cfg(feature = "icon")]
+const ICON: &str = "data:image/svg+xml;base64,PHN2ZyB4bWxucz0iaHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmciIHdpZHRoPSIzNCIgaGVpZ2h0PSIzNCIgZmlsbD0ibm9uZSIgc3Ryb2tlPSIjOTk0NUZGIiBzdHJva2Utd2lkdGg9IjIiPjxjaXJjbGUgY3g9IjE3IiBjeT0iMTciIHI9IjE1Ii8+PGVsbGlwc2UgY3g9IjE3IiBjeT0iMTciIHJ4PSI2IiByeT0iMTUiLz48cGF0aCBkPSJNMiAxN2gzME01IDloMjRNNSAyNWgyNCIvPjwvc3ZnPg==";
+#[cfg(not(feature = "icon"))]
+const ICON: &str = "";
+
+#[derive(Clone)]
+pub struct BrowserBridgeWallet {
+    server: Arc<BridgeServer>,
+    page_opened: Arc<AtomicBool>,
+    public_key: Arc<Mutex<Option<Pubkey>>>,
+    connecting: Arc<AtomicBool>,
+    event_emitter: WalletAdapterEventEmitter,
+}
+
+impl BrowserBridgeWallet {
+    /// Start the bridge on a free port
+    pub fn new() -> anyhow::Result<Self> {
+        Self::on_port(0)
+    }
+
+    pub fn on_port(port: u16) -> anyhow::Result<Self> {
+        Ok(Self {
+            server: Arc::new(BridgeServer::start(port)?),
+            page_opened: Arc::new(AtomicBool::new(false)),
+            public_key: Arc::new(Mutex::new(None)),
+            connecting: Arc::new(AtomicBool::new(false)),
+            event_emitter: WalletAdapterEventEmitter::new(),
+        })
+    }
+
+    /// Address of the bridge page, eg. to show it when the browser could not be opened
+    pub fn url(&self) -> &str {
+        self.server.url()
+    }
+
+    /// Open the bridge page in the system browser, `connect` does this the first time
+    pub fn open_browser(&self) -> anyhow::Result<()> {
+        webbrowser::open(self.url()).context("could not open the browser")?;
+        self.page_opened.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn check_connected(&self) -> wallet_adapter_base::Result<()> {
+        match self.public_key() {
+            Some(_) => Ok(()),
+            None => Err(WalletError::WalletNotConnected),
+        }
+    }
+
+    async fn request_bytes(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> wallet_adapter_base::Result<Vec<u8>> {
+        let result = self.server.request(method, params).await?;
+        let encoded = result
+            .as_str()
+            .ok_or_else(|| anyhow!("unexpected {method} result: {result}"))?;
+        Ok(BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|err| anyhow!("{err}"))?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl BaseWalletAdapter for BrowserBridgeWallet {
+    fn event_emitter(&self) -> WalletAdapterEventEmitter {
+        self.event_emitter.clone()
+    }
+
+    fn name(&self) -> String {
+        "Browser Wallet".to_string()
+    }
+
+    fn url(&self) -> String {
+        "https://github.com/mucks/wallet-adapter".to_string()
+    }
+
+    fn icon(&self) -> String {
+        ICON.to_string()
+    }
+
+    fn ready_state(&self) -> WalletReadyState {
+        WalletReadyState::Loadable
+    }
+
+    fn public_key(&self) -> Option<Pubkey> {
+        *self.public_key.lock().ok()?
+    }
+
+    fn connecting(&self) -> bool {
+        self.connecting.load(Ordering::SeqCst)
+    }
+
+    fn supported_transaction_versions(&self) -> Option<SupportedTransactionVersions> {
+        Some(vec![
+            TransactionVersion::LEGACY,
+            TransactionVersion::Number(0),
+        ])
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
+        if !self.page_opened.load(Ordering::SeqCst) {
+            self.open_browser()?;
+        }
+
+        self.connecting.store(true, Ordering::SeqCst);
+        let result = self.server.request("connect", json!({})).await;
+        self.connecting.store(false, Ordering::SeqCst);
+
+        let address = result?;
+        let public_key: Pubkey = address
+            .as_str()
+            .ok_or_else(|| anyhow!("unexpected connect result: {address}"))?
+            .parse()
+            .map_err(|err| anyhow!("{err}"))?;
+
+        *self.public_key.lock().map_err(|err| anyhow!("{err:?}"))? = Some(public_key);
+        self.event_emitter
+            .emit(WalletAdapterEvent::Connect(public_key))
+            .await?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn disconnect(&self) -> anyhow::Result<()> {
+        *self.public_key.lock().map_err(|err| anyhow!("{err:?}"))? = None;
+
+        // the page may be gone already, so don't wait for it to acknowledge
+        self.server.cancel_all();
+        self.server.notify("disconnect", json!({}))?;
+
+        self.event_emitter
+            .emit(WalletAdapterEvent::Disconnect)
+            .await?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> wallet_adapter_base::Result<Signature> {
+        if let Some(sponsor) = options.as_ref().and_then(|o| o.sponsor.clone()) {
+            return self
+                .send_sponsored_transaction(transaction, connection, sponsor.as_ref(), options)
+                .await;
+        }
+
+        self.check_if_transaction_is_supported(&transaction)?;
+
+        let transaction = match transaction {
+            TransactionOrVersionedTransaction::Transaction(tx) => {
+                let send_options = options.as_ref().map(|o| o.send_options.clone());
+                let mut tx = self
+                    .prepare_transaction(tx, connection, send_options.as_ref())
+                    .await?;
+
+                if let Some(options) = options.as_ref().filter(|o| !o.signers.is_empty()) {
+                    let signers: Vec<&dyn Signer> = options
+                        .signers
+                        .iter()
+                        .map(|s| s.as_ref() as &dyn Signer)
+                        .collect();
+                    tx.try_partial_sign(&signers, tx.message.recent_blockhash)
+                        .map_err(|err| anyhow!("{err}"))?;
+                }
+
+                TransactionOrVersionedTransaction::Transaction(tx)
+            }
+            versioned => versioned,
+        };
+
+        let signed = self.sign_transaction(transaction).await?;
+
+        Ok(connection
+            .send_raw_transaction(signed.serialize()?, options.as_ref())
+            .await?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl BaseSignerWalletAdapter for BrowserBridgeWallet {
+    /// The key never leaves the browser wallet
+    fn wallet_signer(&self) -> Option<Box<DynSigner>> {
+        None
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> wallet_adapter_base::Result<TransactionOrVersionedTransaction> {
+        self.check_connected()?;
+
+        let params = json!({ "transaction": BASE64_STANDARD.encode(transaction.serialize()?) });
+        let signed = self.request_bytes("signTransaction", params).await?;
+
+        Ok(TransactionOrVersionedTransaction::deserialize(&signed)?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl BaseMessageSignerWalletAdapter for BrowserBridgeWallet {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_message(&self, message: &[u8]) -> wallet_adapter_base::Result<Vec<u8>> {
+        self.check_connected()?;
+
+        let params = json!({ "message": BASE64_STANDARD.encode(message) });
+        self.request_bytes("signMessage", params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::time::Duration;
+
+    use futures::executor::block_on;
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+
+    /// A request to the bridge server like the page sends them, returns the status and body
+    fn http(url: &str, method: &str, path: &str, body: &str) -> (u16, String) {
+        let (addr, query) = url.trim_start_matches("http://").split_once("/?").unwrap();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path}?{query} HTTP/1.0\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response
+            .split_once("\r\n\r\n")
+            .map(|(_, body)| body.to_string())
+            .unwrap_or_default();
+        (status, body)
+    }
+
+    fn next_request(url: &str) -> serde_json::Value {
+        loop {
+            match http(url, "GET", "/requests", "") {
+                (200, body) => return serde_json::from_str(&body).unwrap(),
+                (204, _) => std::thread::sleep(Duration::from_millis(10)),
+                (status, _) => panic!("unexpected status {status}"),
+            }
+        }
+    }
+
+    #[test]
+    fn relays_requests_to_the_page() {
+        let keypair = Keypair::new();
+        let public_key = keypair.pubkey();
+        let mut wallet = BrowserBridgeWallet::new().unwrap();
+        wallet.page_opened.store(true, Ordering::SeqCst);
+        let url = wallet.url().to_string();
+
+        let (status, _) = http(
+            &url.replace("token=", "token=other"),
+            "GET",
+            "/requests",
+            "",
+        );
+        assert_eq!(status, 403);
+
+        // the page, answering like the wallet would
+        let page = std::thread::spawn(move || {
+            for _ in 0..3 {
+                let request = next_request(&url);
+                let response = match request["method"].as_str().unwrap() {
+                    "connect" => json!({ "id": request["id"], "result": public_key.to_string() }),
+                    "signMessage" => {
+                        let message = BASE64_STANDARD
+                            .decode(request["params"]["message"].as_str().unwrap())
+                            .unwrap();
+                        if message == b"reject" {
+                            json!({
+                                "id": request["id"],
+                                "error": { "code": 4001, "message": "User rejected the request." }
+                            })
+                        } else {
+                            let signature = keypair.sign_message(&message);
+                            json!({ "id": request["id"], "result": BASE64_STANDARD.encode(signature) })
+                        }
+                    }
+                    method => panic!("unexpected {method}"),
+                };
+                let (status, _) = http(&url, "POST", "/responses", &response.to_string());
+                assert_eq!(status, 204);
+            }
+        });
+
+        block_on(wallet.connect()).unwrap();
+        assert_eq!(wallet.public_key(), Some(public_key));
+
+        let signature = block_on(wallet.sign_message(b"hello")).unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        assert!(signature.verify(public_key.as_ref(), b"hello"));
+
+        assert!(matches!(
+            block_on(wallet.sign_message(b"reject")),
+            Err(WalletError::WalletUserRejected(_))
+        ));
+        page.join().unwrap();
+    }
+}
@@ -0,0 +1,206 @@
+//! Localhost HTTP server the bridge page polls for requests and posts responses to.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tokio::sync::oneshot;
+use wallet_adapter_base::WalletError;
+
+const PAGE: &str = include_str!("page.html");
+const TOKEN_HEADER: &str = "x-bridge-token";
+
+const USER_REJECTED: i64 = 4001;
+const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Debug, Serialize)]
+struct BridgeRequest {
+    id: u64,
+    method: String,
+    params: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct BridgeError {
+    code: Option<i64>,
+    message: String,
+}
+
+impl From<BridgeError> for WalletError {
+    fn from(err: BridgeError) -> Self {
+        match err.code {
+            Some(USER_REJECTED) => WalletError::WalletUserRejected(err.message),
+            Some(INTERNAL_ERROR) => WalletError::WalletInternalError(err.message),
+            Some(code) => WalletError::WalletProviderError((code, err.message)),
+            None => WalletError::Anyhow(anyhow!(err.message)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BridgeResponse {
+    id: u64,
+    #[serde(default)]
+    result: serde_json::Value,
+    error: Option<BridgeError>,
+}
+
+type Reply = oneshot::Sender<Result<serde_json::Value, WalletError>>;
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    queue: VecDeque<BridgeRequest>,
+    waiting: HashMap<u64, Reply>,
+}
+
+/// Stops the server when the last adapter clone is dropped
+pub(crate) struct BridgeServer {
+    server: Arc<Server>,
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl BridgeServer {
+    /// Listen on `127.0.0.1:port`, `0` picks a free port
+    pub(crate) fn start(port: u16) -> Result<Self> {
+        let server = Server::http(("127.0.0.1", port)).map_err(|err| anyhow!("{err}"))?;
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .context("bridge server has no ip address")?;
+
+        // only the page opened by us knows the token, other sites can't talk to the server
+        let token = hex::encode(rand::random::<[u8; 16]>());
+        let url = format!("http://{addr}/?token={token}");
+
+        let server = Arc::new(server);
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let thread_server = server.clone();
+        let thread_state = state.clone();
+        std::thread::spawn(move || {
+            for request in thread_server.incoming_requests() {
+                if let Err(err) = handle(request, &token, &thread_state) {
+                    tracing::warn!("bridge request failed: {err:?}");
+                }
+            }
+        });
+
+        Ok(Self { server, url, state })
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn push(&self, method: &str, params: serde_json::Value, reply: Option<Reply>) -> Result<()> {
+        let mut state = self.state.lock().map_err(|err| anyhow!("{err:?}"))?;
+        let id = state.next_id;
+        state.next_id += 1;
+        state.queue.push_back(BridgeRequest {
+            id,
+            method: method.to_string(),
+            params,
+        });
+        if let Some(reply) = reply {
+            state.waiting.insert(id, reply);
+        }
+        Ok(())
+    }
+
+    /// Queue a request for the page and wait for its answer
+    pub(crate) async fn request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, WalletError> {
+        let (tx, rx) = oneshot::channel();
+        self.push(method, params, Some(tx))?;
+
+        rx.await.map_err(|_| WalletError::WalletDisconnected)?
+    }
+
+    /// Queue a request without waiting for the page to answer it
+    pub(crate) fn notify(&self, method: &str, params: serde_json::Value) -> Result<()> {
+        self.push(method, params, None)
+    }
+
+    /// Fail every request that is still waiting for the page
+    pub(crate) fn cancel_all(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.queue.clear();
+            state.waiting.clear();
+        }
+    }
+}
+
+impl Drop for BridgeServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+fn handle(mut request: Request, token: &str, state: &Mutex<State>) -> Result<()> {
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let path = path.to_string();
+
+    let authorized = query
+        .split('&')
+        .any(|pair| pair == format!("token={token}"))
+        || request
+            .headers()
+            .iter()
+            .any(|header| header.field.equiv(TOKEN_HEADER) && header.value.as_str() == token);
+    if !authorized {
+        return Ok(request.respond(Response::empty(403))?);
+    }
+
+    match (request.method(), path.as_str()) {
+        (Method::Get, "/") => {
+            let content_type = Header::from_bytes("Content-Type", "text/html; charset=utf-8")
+                .map_err(|_| anyhow!("invalid header"))?;
+            request.respond(Response::from_string(PAGE).with_header(content_type))?;
+        }
+        (Method::Get, "/requests") => {
+            let next = state
+                .lock()
+                .map_err(|err| anyhow!("{err:?}"))?
+                .queue
+                .pop_front();
+            match next {
+                Some(next) => {
+                    let content_type = Header::from_bytes("Content-Type", "application/json")
+                        .map_err(|_| anyhow!("invalid header"))?;
+                    let body = serde_json::to_string(&next)?;
+                    request.respond(Response::from_string(body).with_header(content_type))?;
+                }
+                None => request.respond(Response::empty(204))?,
+            }
+        }
+        (Method::Post, "/responses") => {
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body)?;
+            let response: BridgeResponse = serde_json::from_str(&body)?;
+
+            let reply = state
+                .lock()
+                .map_err(|err| anyhow!("{err:?}"))?
+                .waiting
+                .remove(&response.id);
+            if let Some(reply) = reply {
+                let result = match response.error {
+                    Some(err) => Err(err.into()),
+                    None => Ok(response.result),
+                };
+                let _ = reply.send(result);
+            }
+            request.respond(Response::empty(204))?;
+        }
+        _ => request.respond(Response::empty(404))?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! Experimental: a wallet whose ed25519 key is split among N participants with FROST, so that
+//! any `t` of them sign together, eg. a 2-of-3 shared game treasury.
+//!
+//! [`FrostSigner`] coordinates the two signing rounds with the [`Participant`]s, reached over
+//! any transport, and aggregates their shares into a plain ed25519 signature of the group key.
+//! [`FrostSigner::into_adapter`] exposes it as a normal signer wallet adapter. The key shares
+//! come from `frost-ed25519`'s DKG or trusted dealer, re-exported as [`frost`].
+
+mod local;
+
+pub use frost_ed25519 as frost;
+pub use local::LocalParticipant;
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use frost::keys::PublicKeyPackage;
+use frost::round1::SigningCommitments;
+use frost::round2::SignatureShare;
+use frost::{Identifier, SigningPackage};
+use futures::future::try_join_all;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_common::maybe_send::{MaybeSend, MaybeSync};
+use wallet_adapter_kms::{KmsWalletAdapter, RemoteSigner};
+
+/// The holder of one key share, eg. a player's device or a server
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait Participant: MaybeSend + MaybeSync {
+    fn identifier(&self) -> Identifier;
+
+    /// Round 1: commit to fresh nonces, kept by the participant for round 2
+    async fn commit(&self) -> Result<SigningCommitments>;
+
+    /// Round 2: sign the package's message with the nonces of the participant's commitments in
+    /// it. Each nonce signs at most once.
+    async fn sign(&self, signing_package: &SigningPackage) -> Result<SignatureShare>;
+}
+
+/// Signs for the group key of a [`PublicKeyPackage`] with the first `t` participants that commit
+pub struct FrostSigner {
+    public_key_package: PublicKeyPackage,
+    participants: Vec<Box<dyn Participant>>,
+}
+
+impl FrostSigner {
+    /// Fails when the package doesn't record its threshold or there are fewer participants
+    pub fn new(
+        public_key_package: PublicKeyPackage,
+        participants: Vec<Box<dyn Participant>>,
+    ) -> Result<Self> {
+        let threshold = public_key_package
+            .min_signers()
+            .ok_or_else(|| anyhow!("the public key package doesn't record its threshold"))?;
+        if participants.len() < threshold.into() {
+            bail!(
+                "{} participants can't reach the threshold of {threshold}",
+                participants.len()
+            );
+        }
+
+        Ok(Self {
+            public_key_package,
+            participants,
+        })
+    }
+
+    /// How many participants sign together
+    pub fn threshold(&self) -> usize {
+        self.public_key_package
+            .min_signers()
+            .map_or(self.participants.len(), usize::from)
+    }
+
+    /// A wallet adapter for the group key, named `FROST`
+    pub fn into_adapter(self) -> KmsWalletAdapter<Self> {
+        KmsWalletAdapter::new(self).with_name("FROST")
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl RemoteSigner for FrostSigner {
+    async fn public_key(&self) -> Result<Pubkey> {
+        let key = self.public_key_package.verifying_key().serialize()?;
+        Pubkey::try_from(key.as_slice())
+            .map_err(|_| anyhow!("invalid group key length {}", key.len()))
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Signature> {
+        let threshold = self.threshold();
+
+        let mut commitments = BTreeMap::new();
+        let mut signers = vec![];
+        for participant in &self.participants {
+            if signers.len() == threshold {
+                break;
+            }
+            match participant.commit().await {
+                Ok(commitment) => {
+                    commitments.insert(participant.identifier(), commitment);
+                    signers.push(participant);
+                }
+                Err(err) => tracing::warn!(
+                    "FROST participant {:?} didn't commit: {err:#}",
+                    participant.identifier()
+                ),
+            }
+        }
+        if signers.len() < threshold {
+            bail!(
+                "only {} of the {threshold} participants needed committed",
+                signers.len()
+            );
+        }
+
+        let signing_package = SigningPackage::new(commitments, message);
+        let shares = try_join_all(signers.iter().map(|participant| async {
+            let share = participant.sign(&signing_package).await?;
+            Ok::<_, anyhow::Error>((participant.identifier(), share))
+        }))
+        .await?;
+
+        let signature = frost::aggregate(
+            &signing_package,
+            &shares.into_iter().collect(),
+            &self.public_key_package,
+        )?;
+        let signature = signature.serialize()?;
+        Signature::try_from(signature.as_slice())
+            .map_err(|_| anyhow!("invalid signature length {}", signature.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use wallet_adapter_base::{
+        build_memo, BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+        TransactionBuilder, TransactionOrVersionedTransaction,
+    };
+
+    use super::*;
+    use crate::frost::keys::{generate_with_dealer, IdentifierList, KeyPackage};
+
+    /// A participant that is offline
+    struct Offline(Identifier);
+
+    #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+    impl Participant for Offline {
+        fn identifier(&self) -> Identifier {
+            self.0
+        }
+
+        async fn commit(&self) -> Result<SigningCommitments> {
+            bail!("offline")
+        }
+
+        async fn sign(&self, _: &SigningPackage) -> Result<SignatureShare> {
+            bail!("offline")
+        }
+    }
+
+    /// A 2-of-3 group whose first participant is offline
+    fn two_of_three() -> FrostSigner {
+        let (shares, public_key_package) =
+            generate_with_dealer(3, 2, IdentifierList::Default, rand::thread_rng()).unwrap();
+        let mut participants: Vec<Box<dyn Participant>> = vec![];
+        for (identifier, share) in shares {
+            if participants.is_empty() {
+                participants.push(Box::new(Offline(identifier)));
+            } else {
+                let key_package = KeyPackage::try_from(share).unwrap();
+                participants.push(Box::new(LocalParticipant::new(key_package)));
+            }
+        }
+
+        FrostSigner::new(public_key_package, participants).unwrap()
+    }
+
+    #[test]
+    fn two_of_three_sign_for_the_group_key() {
+        let mut adapter = two_of_three().into_adapter();
+        block_on(adapter.connect()).unwrap();
+        let public_key = adapter.public_key().unwrap();
+        assert_eq!(adapter.name(), "FROST");
+
+        let signature = block_on(adapter.sign_message(b"treasury")).unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        assert!(signature.verify(public_key.as_ref(), b"treasury"));
+
+        let transaction = TransactionBuilder::new()
+            .fee_payer(public_key)
+            .add_instruction(build_memo(b"frost", &[]))
+            .build_legacy()
+            .unwrap();
+        let TransactionOrVersionedTransaction::Transaction(signed) =
+            block_on(adapter.sign_transaction(transaction)).unwrap()
+        else {
+            panic!("expected a legacy transaction");
+        };
+        assert!(signed.verify().is_ok());
+    }
+
+    #[test]
+    fn fails_below_the_threshold() {
+        let mut signer = two_of_three();
+        signer.participants.truncate(2);
+
+        let err = block_on(RemoteSigner::sign(&signer, b"treasury")).unwrap_err();
+        assert!(err.to_string().contains("only 1 of the 2"), "{err}");
+    }
+}
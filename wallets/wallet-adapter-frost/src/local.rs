@@ -0,0 +1,62 @@
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+
+use crate::frost::keys::KeyPackage;
+use crate::frost::round1::{self, SigningCommitments, SigningNonces};
+use crate::frost::round2::{self, SignatureShare};
+use crate::frost::{Identifier, SigningPackage};
+use crate::Participant;
+
+/// A participant whose key share is in this process, eg. behind the server of a transport
+pub struct LocalParticipant {
+    key_package: KeyPackage,
+    /// The nonces of the commitments that haven't signed yet
+    nonces: Mutex<Vec<SigningNonces>>,
+}
+
+impl LocalParticipant {
+    pub fn new(key_package: KeyPackage) -> Self {
+        Self {
+            key_package,
+            nonces: Mutex::new(vec![]),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl Participant for LocalParticipant {
+    fn identifier(&self) -> Identifier {
+        *self.key_package.identifier()
+    }
+
+    async fn commit(&self) -> Result<SigningCommitments> {
+        let (nonces, commitments) =
+            round1::commit(self.key_package.signing_share(), &mut rand::thread_rng());
+        self.nonces
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .push(nonces);
+
+        Ok(commitments)
+    }
+
+    async fn sign(&self, signing_package: &SigningPackage) -> Result<SignatureShare> {
+        let commitments = signing_package
+            .signing_commitment(&self.identifier())
+            .ok_or_else(|| anyhow!("the signing package has no commitments of ours"))?;
+
+        // take the nonces out, so they can't sign twice
+        let nonces = {
+            let mut nonces = self.nonces.lock().map_err(|err| anyhow!("{err:?}"))?;
+            let index = nonces
+                .iter()
+                .position(|nonces| nonces.commitments() == &commitments)
+                .ok_or_else(|| anyhow!("unknown or already used commitments"))?;
+            nonces.swap_remove(index)
+        };
+
+        Ok(round2::sign(signing_package, &nonces, &self.key_package)?)
+    }
+}
@@ -0,0 +1,25 @@
+use tonic_build::manual::{Builder, Method, Service};
+
+/// The client and server of the signer service, its messages are written by hand in
+/// `src/proto.rs` so building needs no protoc
+fn main() {
+    let method = |name: &str, route_name: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route_name)
+            .input_type(format!("crate::proto::{route_name}Request"))
+            .output_type(format!("crate::proto::{route_name}Response"))
+            .codec_path("tonic::codec::ProstCodec")
+            .build()
+    };
+
+    let signer = Service::builder()
+        .name("Signer")
+        .package("wallet_adapter.signer")
+        .method(method("get_pubkey", "GetPubkey"))
+        .method(method("sign_transaction", "SignTransaction"))
+        .method(method("sign_message", "SignMessage"))
+        .build();
+
+    Builder::new().compile(&[signer]);
+}
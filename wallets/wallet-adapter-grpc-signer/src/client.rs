@@ -0,0 +1,243 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::TransactionVersion;
+use tonic::transport::{Channel, Endpoint};
+use tonic::Status;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+    SupportedTransactionVersions, TransactionOrVersionedTransaction, WalletAdapterEvent,
+    WalletAdapterEventEmitter, WalletError, WalletReadyState,
+};
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::types::{DynSigner, SendTransactionOptions};
+
+use crate::proto::signer_client::SignerClient;
+use crate::proto::{
+    GetPubkeyRequest, SignMessageRequest, SignTransactionRequest, WALLET_ERROR_CODE,
+};
+use crate::MutualTls;
+
+/// A wallet whose key stays on a [`GrpcSignerService`](crate::GrpcSignerService).
+///
+/// Clones share the channel, the connection state and the events.
+#[derive(Clone)]
+pub struct GrpcSignerWalletAdapter {
+    client: SignerClient<Channel>,
+    public_key: Arc<Mutex<Option<Pubkey>>>,
+    connecting: Arc<AtomicBool>,
+    event_emitter: WalletAdapterEventEmitter,
+}
+
+impl GrpcSignerWalletAdapter {
+    /// The signer at `url`, eg. `https://signer.internal:50051`, reached with `tls`.
+    /// Nothing is sent until [`connect`](BaseWalletAdapter::connect), call this from within
+    /// a tokio runtime.
+    pub fn new(url: impl ToString, tls: &MutualTls) -> anyhow::Result<Self> {
+        let channel = Endpoint::from_shared(url.to_string())?
+            .tls_config(tls.client_config())?
+            .connect_lazy();
+        Ok(Self::with_channel(channel))
+    }
+
+    /// The signer behind `channel`, eg. one configured with timeouts or a proxy
+    pub fn with_channel(channel: Channel) -> Self {
+        Self {
+            client: SignerClient::new(channel),
+            public_key: Arc::new(Mutex::new(None)),
+            connecting: Arc::new(AtomicBool::new(false)),
+            event_emitter: WalletAdapterEventEmitter::new(),
+        }
+    }
+
+    fn connected_key(&self) -> wallet_adapter_base::Result<Pubkey> {
+        self.public_key().ok_or(WalletError::WalletNotConnected)
+    }
+}
+
+/// The error of the signer's wallet, or the transport error when the request failed before
+fn wallet_error(status: Status) -> WalletError {
+    let code = status
+        .metadata()
+        .get(WALLET_ERROR_CODE)
+        .and_then(|code| code.to_str().ok()?.parse().ok());
+    match code {
+        Some(code) => WalletError::from_code(code, status.message().to_string()),
+        None => WalletError::Anyhow(anyhow!("signer: {status}")),
+    }
+}
+
+#[async_trait::async_trait]
+impl BaseWalletAdapter for GrpcSignerWalletAdapter {
+    fn event_emitter(&self) -> WalletAdapterEventEmitter {
+        self.event_emitter.clone()
+    }
+
+    fn name(&self) -> String {
+        "gRPC Signer".to_string()
+    }
+
+    fn url(&self) -> String {
+        "https://github.com/mucks/wallet-adapter".to_string()
+    }
+
+    fn icon(&self) -> String {
+        String::new()
+    }
+
+    fn ready_state(&self) -> WalletReadyState {
+        WalletReadyState::Loadable
+    }
+
+    fn public_key(&self) -> Option<Pubkey> {
+        *self.public_key.lock().ok()?
+    }
+
+    fn connecting(&self) -> bool {
+        self.connecting.load(Ordering::SeqCst)
+    }
+
+    fn supported_transaction_versions(&self) -> Option<SupportedTransactionVersions> {
+        Some(vec![
+            TransactionVersion::LEGACY,
+            TransactionVersion::Number(0),
+        ])
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
+        self.connecting.store(true, Ordering::SeqCst);
+        let result = self.client.clone().get_pubkey(GetPubkeyRequest {}).await;
+        self.connecting.store(false, Ordering::SeqCst);
+
+        let pubkey = result.map_err(wallet_error)?.into_inner().pubkey;
+        let public_key = Pubkey::try_from(pubkey.as_slice())
+            .map_err(|_| anyhow!("invalid public key length {}", pubkey.len()))?;
+        *self.public_key.lock().map_err(|err| anyhow!("{err:?}"))? = Some(public_key);
+        self.event_emitter
+            .emit(WalletAdapterEvent::Connect(public_key))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> anyhow::Result<()> {
+        *self.public_key.lock().map_err(|err| anyhow!("{err:?}"))? = None;
+        self.event_emitter
+            .emit(WalletAdapterEvent::Disconnect)
+            .await?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> wallet_adapter_base::Result<Signature> {
+        if let Some(sponsor) = options.as_ref().and_then(|o| o.sponsor.clone()) {
+            return self
+                .send_sponsored_transaction(transaction, connection, sponsor.as_ref(), options)
+                .await;
+        }
+
+        self.check_if_transaction_is_supported(&transaction)?;
+
+        let transaction = match transaction {
+            TransactionOrVersionedTransaction::Transaction(tx) => {
+                let send_options = options.as_ref().map(|o| o.send_options.clone());
+                let mut tx = self
+                    .prepare_transaction(tx, connection, send_options.as_ref())
+                    .await?;
+
+                if let Some(options) = options.as_ref().filter(|o| !o.signers.is_empty()) {
+                    let signers: Vec<&dyn Signer> = options
+                        .signers
+                        .iter()
+                        .map(|s| s.as_ref() as &dyn Signer)
+                        .collect();
+                    tx.try_partial_sign(&signers, tx.message.recent_blockhash)
+                        .map_err(|err| anyhow!("{err}"))?;
+                }
+
+                TransactionOrVersionedTransaction::Transaction(tx)
+            }
+            versioned => versioned,
+        };
+
+        let signed = self.sign_transaction(transaction).await?;
+
+        Ok(connection
+            .send_raw_transaction(signed.serialize()?, options.as_ref())
+            .await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl BaseSignerWalletAdapter for GrpcSignerWalletAdapter {
+    /// The key never leaves the signer
+    fn wallet_signer(&self) -> Option<Box<DynSigner>> {
+        None
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> wallet_adapter_base::Result<TransactionOrVersionedTransaction> {
+        self.connected_key()?;
+
+        let response = self
+            .client
+            .clone()
+            .sign_transaction(SignTransactionRequest {
+                transaction: transaction.serialize()?,
+            })
+            .await
+            .map_err(wallet_error)?;
+        let signed =
+            TransactionOrVersionedTransaction::deserialize(&response.into_inner().transaction)?;
+        if signed.message_data() != transaction.message_data() {
+            return Err(anyhow!("the signer changed the transaction").into());
+        }
+
+        Ok(signed)
+    }
+}
+
+#[async_trait::async_trait]
+impl BaseMessageSignerWalletAdapter for GrpcSignerWalletAdapter {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_message(&self, message: &[u8]) -> wallet_adapter_base::Result<Vec<u8>> {
+        self.connected_key()?;
+
+        let response = self
+            .client
+            .clone()
+            .sign_message(SignMessageRequest {
+                message: message.to_vec(),
+            })
+            .await
+            .map_err(wallet_error)?;
+
+        Ok(response.into_inner().signature)
+    }
+}
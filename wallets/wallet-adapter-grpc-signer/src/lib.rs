@@ -0,0 +1,121 @@
+//! Route the signing of a fleet, eg. game servers, to one hardened signer box.
+//!
+//! The box wraps its wallet in a [`GrpcSignerService`] and the fleet signs through a
+//! [`GrpcSignerWalletAdapter`], the usual adapter API over the `GetPubkey`, `SignTransaction`
+//! and `SignMessage` RPCs. Both sides authenticate each other with certificates of one CA,
+//! see [`MutualTls`].
+
+mod client;
+pub mod proto;
+mod server;
+
+pub use client::GrpcSignerWalletAdapter;
+pub use server::GrpcSignerService;
+
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+
+/// The PEM certificate and key of one side and the CA its peer's certificate must chain to
+#[derive(Debug, Clone)]
+pub struct MutualTls {
+    pub cert: Vec<u8>,
+    pub key: Vec<u8>,
+    pub ca: Vec<u8>,
+}
+
+impl MutualTls {
+    pub fn new(cert: impl AsRef<[u8]>, key: impl AsRef<[u8]>, ca: impl AsRef<[u8]>) -> Self {
+        Self {
+            cert: cert.as_ref().to_vec(),
+            key: key.as_ref().to_vec(),
+            ca: ca.as_ref().to_vec(),
+        }
+    }
+
+    /// Present the server certificate and refuse clients without a certificate of the CA
+    pub fn server_config(&self) -> ServerTlsConfig {
+        ServerTlsConfig::new()
+            .identity(Identity::from_pem(&self.cert, &self.key))
+            .client_ca_root(Certificate::from_pem(&self.ca))
+    }
+
+    /// Present the client certificate and only trust a server certificate of the CA
+    pub fn client_config(&self) -> ClientTlsConfig {
+        ClientTlsConfig::new()
+            .identity(Identity::from_pem(&self.cert, &self.key))
+            .ca_certificate(Certificate::from_pem(&self.ca))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rcgen::{BasicConstraints, CertificateParams, CertifiedKey, IsCa, KeyPair};
+    use solana_sdk::signature::Signature;
+    use tokio::net::TcpListener;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Server;
+    use wallet_adapter_base::{
+        build_memo, BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+        TransactionBuilder, TransactionOrVersionedTransaction,
+    };
+    use wallet_adapter_mock::MockWalletAdapter;
+
+    use super::*;
+
+    fn ca() -> CertifiedKey {
+        let mut params = CertificateParams::new(vec![]).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let key_pair = KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key_pair).unwrap();
+        CertifiedKey { cert, key_pair }
+    }
+
+    /// A certificate for `127.0.0.1` issued by `ca`
+    fn tls(ca: &CertifiedKey) -> MutualTls {
+        let key_pair = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec!["127.0.0.1".to_string()])
+            .unwrap()
+            .signed_by(&key_pair, &ca.cert, &ca.key_pair)
+            .unwrap();
+        MutualTls::new(cert.pem(), key_pair.serialize_pem(), ca.cert.pem())
+    }
+
+    #[tokio::test]
+    async fn signs_for_clients_of_the_ca() {
+        let ca = ca();
+        let mut wallet = MockWalletAdapter::new();
+        wallet.connect().await.unwrap();
+        let public_key = wallet.public_key().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("https://{}", listener.local_addr().unwrap());
+        let server = Server::builder()
+            .tls_config(tls(&ca).server_config())
+            .unwrap()
+            .add_service(GrpcSignerService::new(wallet).into_server())
+            .serve_with_incoming(TcpIncoming::from_listener(listener, true, None).unwrap());
+        tokio::spawn(server);
+
+        let mut client = GrpcSignerWalletAdapter::new(&url, &tls(&ca)).unwrap();
+        client.connect().await.unwrap();
+        assert_eq!(client.public_key(), Some(public_key));
+
+        let signature = client.sign_message(b"hello").await.unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        assert!(signature.verify(public_key.as_ref(), b"hello"));
+
+        let transaction = TransactionBuilder::new()
+            .fee_payer(public_key)
+            .add_instruction(build_memo(b"grpc", &[]))
+            .build_legacy()
+            .unwrap();
+        let TransactionOrVersionedTransaction::Transaction(signed) =
+            client.sign_transaction(transaction).await.unwrap()
+        else {
+            panic!("expected a legacy transaction");
+        };
+        assert!(signed.verify().is_ok());
+
+        let mut stranger = GrpcSignerWalletAdapter::new(&url, &tls(&self::ca())).unwrap();
+        assert!(stranger.connect().await.is_err());
+    }
+}
@@ -0,0 +1,41 @@
+//! The messages of the `wallet_adapter.signer.Signer` service, the client and server are
+//! generated by `build.rs`. Transactions travel in their wire format and errors carry the
+//! [`WalletError::code`](wallet_adapter_base::WalletError::code) in [`WALLET_ERROR_CODE`].
+
+/// Metadata key of the wallet error code of a failed request
+pub const WALLET_ERROR_CODE: &str = "wallet-error-code";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetPubkeyRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetPubkeyResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub pubkey: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SignTransactionRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub transaction: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SignTransactionResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub transaction: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SignMessageRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub message: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SignMessageResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub signature: Vec<u8>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/wallet_adapter.signer.Signer.rs"));
@@ -0,0 +1,109 @@
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Server;
+use tonic::{Code, Request, Response, Status};
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, TransactionOrVersionedTransaction, WalletError,
+};
+
+use crate::proto::signer_server::{Signer, SignerServer};
+use crate::proto::{
+    GetPubkeyRequest, GetPubkeyResponse, SignMessageRequest, SignMessageResponse,
+    SignTransactionRequest, SignTransactionResponse, WALLET_ERROR_CODE,
+};
+use crate::MutualTls;
+
+/// Signs with `wallet` for the clients holding a certificate of the CA of [`MutualTls`].
+/// Connect the wallet before serving, until then every request fails with `WalletNotConnected`.
+pub struct GrpcSignerService<W> {
+    wallet: W,
+}
+
+impl<W: BaseMessageSignerWalletAdapter + 'static> GrpcSignerService<W> {
+    pub fn new(wallet: W) -> Self {
+        Self { wallet }
+    }
+
+    /// The tonic service, eg. to add it to a router next to other services
+    pub fn into_server(self) -> SignerServer<Self> {
+        SignerServer::new(self)
+    }
+
+    /// Serve on `addr` until the server fails
+    pub async fn serve(self, addr: SocketAddr, tls: &MutualTls) -> Result<()> {
+        Server::builder()
+            .tls_config(tls.server_config())?
+            .add_service(self.into_server())
+            .serve(addr)
+            .await?;
+        Ok(())
+    }
+}
+
+fn status(err: WalletError) -> Status {
+    let code = match err {
+        WalletError::WalletNotConnected | WalletError::WalletLocked => Code::FailedPrecondition,
+        WalletError::WalletUserRejected(_) | WalletError::WalletPolicyViolation(_) => {
+            Code::PermissionDenied
+        }
+        WalletError::BincodeSerializationError(_) => Code::InvalidArgument,
+        _ => Code::Internal,
+    };
+    let mut status = Status::new(code, err.message());
+    status
+        .metadata_mut()
+        .insert(WALLET_ERROR_CODE, MetadataValue::from(err.code()));
+    status
+}
+
+#[tonic::async_trait]
+impl<W: BaseMessageSignerWalletAdapter + 'static> Signer for GrpcSignerService<W> {
+    async fn get_pubkey(
+        &self,
+        _request: Request<GetPubkeyRequest>,
+    ) -> Result<Response<GetPubkeyResponse>, Status> {
+        let public_key = self
+            .wallet
+            .public_key()
+            .ok_or_else(|| status(WalletError::WalletNotConnected))?;
+
+        Ok(Response::new(GetPubkeyResponse {
+            pubkey: public_key.to_bytes().to_vec(),
+        }))
+    }
+
+    async fn sign_transaction(
+        &self,
+        request: Request<SignTransactionRequest>,
+    ) -> Result<Response<SignTransactionResponse>, Status> {
+        let transaction =
+            TransactionOrVersionedTransaction::deserialize(&request.into_inner().transaction)
+                .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        tracing::debug!("signing for {:?}", transaction.signer_keys());
+
+        let signed = self
+            .wallet
+            .sign_transaction(transaction)
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(SignTransactionResponse {
+            transaction: signed.serialize().map_err(|err| status(err.into()))?,
+        }))
+    }
+
+    async fn sign_message(
+        &self,
+        request: Request<SignMessageRequest>,
+    ) -> Result<Response<SignMessageResponse>, Status> {
+        let signature = self
+            .wallet
+            .sign_message(&request.into_inner().message)
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(SignMessageResponse { signature }))
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::prelude::*;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use crate::RemoteSigner;
+
+/// DER prefix of an ed25519 SubjectPublicKeyInfo, the 32 key bytes follow it
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+#[cfg(not(target_arch = "wasm32"))]
+type TokenProvider = dyn Fn() -> Result<String> + Send + Sync;
+#[cfg(target_arch = "wasm32")]
+type TokenProvider = dyn Fn() -> Result<String>;
+
+/// Signs with an `EC_SIGN_ED25519` key version of Google Cloud KMS
+#[derive(Clone)]
+pub struct GcpKmsSigner {
+    key_version: String,
+    access_token: Arc<TokenProvider>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+impl GcpKmsSigner {
+    /// `key_version` is the full resource name, eg.
+    /// `projects/p/locations/global/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1`.
+    /// `access_token` is called before every request, so it can refresh expired OAuth tokens.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(
+        key_version: impl ToString,
+        access_token: impl Fn() -> Result<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self::with_token_provider(key_version.to_string(), Arc::new(access_token))
+    }
+
+    /// `key_version` is the full resource name, eg.
+    /// `projects/p/locations/global/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1`.
+    /// `access_token` is called before every request, so it can refresh expired OAuth tokens.
+    #[cfg(target_arch = "wasm32")]
+    pub fn new(
+        key_version: impl ToString,
+        access_token: impl Fn() -> Result<String> + 'static,
+    ) -> Self {
+        Self::with_token_provider(key_version.to_string(), Arc::new(access_token))
+    }
+
+    fn with_token_provider(key_version: String, access_token: Arc<TokenProvider>) -> Self {
+        Self {
+            key_version,
+            access_token,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send the requests with `client`, eg. one with a proxy or timeouts
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn url(&self, suffix: &str) -> String {
+        format!(
+            "https://cloudkms.googleapis.com/v1/{}{suffix}",
+            self.key_version
+        )
+    }
+}
+
+/// Extract the key of an ed25519 public key PEM as returned by Cloud KMS
+fn pubkey_from_pem(pem: &str) -> Result<Pubkey> {
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = BASE64_STANDARD
+        .decode(body.trim())
+        .context("invalid public key PEM")?;
+
+    match der.strip_prefix(&ED25519_SPKI_PREFIX[..]) {
+        Some(key) => Ok(Pubkey::try_from(key).map_err(|_| anyhow!("invalid ed25519 key length"))?),
+        None => bail!("the KMS key is not an ed25519 key"),
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl RemoteSigner for GcpKmsSigner {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(key = %self.key_version))
+    )]
+    async fn public_key(&self) -> Result<Pubkey> {
+        let resp: PublicKeyResponse = self
+            .client
+            .get(self.url("/publicKey"))
+            .bearer_auth((self.access_token)()?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        pubkey_from_pem(&resp.pem)
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(key = %self.key_version))
+    )]
+    async fn sign(&self, message: &[u8]) -> Result<Signature> {
+        let resp: SignResponse = self
+            .client
+            .post(self.url(":asymmetricSign"))
+            .bearer_auth((self.access_token)()?)
+            .json(&serde_json::json!({ "data": BASE64_STANDARD.encode(message) }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let signature = BASE64_STANDARD
+            .decode(resp.signature)
+            .context("invalid signature encoding")?;
+        Signature::try_from(signature).map_err(|_| anyhow!("invalid ed25519 signature length"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_key_out_of_the_pem() {
+        let key = Pubkey::new_unique();
+        let der = [&ED25519_SPKI_PREFIX[..], key.as_ref()].concat();
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            BASE64_STANDARD.encode(der)
+        );
+        assert_eq!(pubkey_from_pem(&pem).unwrap(), key);
+
+        let rsa = "-----BEGIN PUBLIC KEY-----\nMA0GCSqGSIb3DQEBAQUAA==\n-----END PUBLIC KEY-----";
+        assert!(pubkey_from_pem(rsa).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! A wallet whose ed25519 key stays in a cloud KMS or HSM, for backend services and bots.
+//!
+//! The adapter fetches the public key on connect and sends every signature request to a
+//! [`RemoteSigner`], eg. [`gcp::GcpKmsSigner`]. Implement the trait for other services.
+
+pub mod gcp;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::TransactionVersion;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+    SupportedTransactionVersions, TransactionOrVersionedTransaction, WalletAdapterEvent,
+    WalletAdapterEventEmitter, WalletError, WalletReadyState,
+};
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::maybe_send::{MaybeSend, MaybeSync};
+use wallet_adapter_common::types::{DynSigner, SendTransactionOptions};
+
+/// An ed25519 key held by a remote service
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait RemoteSigner: MaybeSend + MaybeSync {
+    async fn public_key(&self) -> anyhow::Result<Pubkey>;
+
+    /// Sign `message` itself, not a digest of it
+    async fn sign(&self, message: &[u8]) -> anyhow::Result<Signature>;
+}
+
+/// Clones share the signer, the connection state and the events
+pub struct KmsWalletAdapter<S> {
+    signer: Arc<S>,
+    name: String,
+    public_key: Arc<Mutex<Option<Pubkey>>>,
+    connecting: Arc<AtomicBool>,
+    event_emitter: WalletAdapterEventEmitter,
+}
+
+impl<S> Clone for KmsWalletAdapter<S> {
+    fn clone(&self) -> Self {
+        Self {
+            signer: self.signer.clone(),
+            name: self.name.clone(),
+            public_key: self.public_key.clone(),
+            connecting: self.connecting.clone(),
+            event_emitter: self.event_emitter.clone(),
+        }
+    }
+}
+
+impl<S: RemoteSigner + 'static> KmsWalletAdapter<S> {
+    pub fn new(signer: S) -> Self {
+        Self {
+            signer: Arc::new(signer),
+            name: "KMS".to_string(),
+            public_key: Arc::new(Mutex::new(None)),
+            connecting: Arc::new(AtomicBool::new(false)),
+            event_emitter: WalletAdapterEventEmitter::new(),
+        }
+    }
+
+    /// The name shown for the wallet, `KMS` by default
+    pub fn with_name(mut self, name: impl ToString) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    fn connected_key(&self) -> wallet_adapter_base::Result<Pubkey> {
+        self.public_key().ok_or(WalletError::WalletNotConnected)
+    }
+}
+
+/// Put `signature` of `signer` into the signer's slot of `transaction`
+fn add_signature(
+    transaction: &mut TransactionOrVersionedTransaction,
+    signer: &Pubkey,
+    signature: Signature,
+) -> anyhow::Result<()> {
+    let (account_keys, num_signers, signatures) = match transaction {
+        TransactionOrVersionedTransaction::Transaction(tx) => (
+            &tx.message.account_keys[..],
+            tx.message.header.num_required_signatures,
+            &mut tx.signatures,
+        ),
+        TransactionOrVersionedTransaction::VersionedTransaction(vtx) => (
+            vtx.message.static_account_keys(),
+            vtx.message.header().num_required_signatures,
+            &mut vtx.signatures,
+        ),
+    };
+
+    let index = account_keys
+        .iter()
+        .take(num_signers.into())
+        .position(|key| key == signer)
+        .ok_or_else(|| anyhow!("{signer} is not a signer of the transaction"))?;
+    let slot = signatures
+        .get_mut(index)
+        .ok_or_else(|| anyhow!("the transaction has no room for the signature of {signer}"))?;
+    *slot = signature;
+    Ok(())
+}
+
+fn message_data(transaction: &TransactionOrVersionedTransaction) -> Vec<u8> {
+    match transaction {
+        TransactionOrVersionedTransaction::Transaction(tx) => tx.message_data(),
+        TransactionOrVersionedTransaction::VersionedTransaction(vtx) => vtx.message.serialize(),
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<S: RemoteSigner + 'static> BaseWalletAdapter for KmsWalletAdapter<S> {
+    fn event_emitter(&self) -> WalletAdapterEventEmitter {
+        self.event_emitter.clone()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn url(&self) -> String {
+        "https://github.com/mucks/wallet-adapter".to_string()
+    }
+
+    fn icon(&self) -> String {
+        String::new()
+    }
+
+    fn ready_state(&self) -> WalletReadyState {
+        WalletReadyState::Installed
+    }
+
+    fn public_key(&self) -> Option<Pubkey> {
+        *self.public_key.lock().ok()?
+    }
+
+    fn connecting(&self) -> bool {
+        self.connecting.load(Ordering::SeqCst)
+    }
+
+    fn supported_transaction_versions(&self) -> Option<SupportedTransactionVersions> {
+        Some(vec![
+            TransactionVersion::LEGACY,
+            TransactionVersion::Number(0),
+        ])
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
+        self.connecting.store(true, Ordering::SeqCst);
+        let result = self.signer.public_key().await;
+        self.connecting.store(false, Ordering::SeqCst);
+
+        let public_key = result?;
+        *self.public_key.lock().map_err(|err| anyhow!("{err:?}"))? = Some(public_key);
+        self.event_emitter
+            .emit(WalletAdapterEvent::Connect(public_key))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> anyhow::Result<()> {
+        *self.public_key.lock().map_err(|err| anyhow!("{err:?}"))? = None;
+        self.event_emitter
+            .emit(WalletAdapterEvent::Disconnect)
+            .await?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> wallet_adapter_base::Result<Signature> {
+        if let Some(sponsor) = options.as_ref().and_then(|o| o.sponsor.clone()) {
+            return self
+                .send_sponsored_transaction(transaction, connection, sponsor.as_ref(), options)
+                .await;
+        }
+
+        self.check_if_transaction_is_supported(&transaction)?;
+
+        let transaction = match transaction {
+            TransactionOrVersionedTransaction::Transaction(tx) => {
+                let send_options = options.as_ref().map(|o| o.send_options.clone());
+                let mut tx = self
+                    .prepare_transaction(tx, connection, send_options.as_ref())
+                    .await?;
+
+                if let Some(options) = options.as_ref().filter(|o| !o.signers.is_empty()) {
+                    let signers: Vec<&dyn Signer> = options
+                        .signers
+                        .iter()
+                        .map(|s| s.as_ref() as &dyn Signer)
+                        .collect();
+                    tx.try_partial_sign(&signers, tx.message.recent_blockhash)
+                        .map_err(|err| anyhow!("{err}"))?;
+                }
+
+                TransactionOrVersionedTransaction::Transaction(tx)
+            }
+            versioned => versioned,
+        };
+
+        let signed = self.sign_transaction(transaction).await?;
+
+        Ok(connection
+            .send_raw_transaction(signed.serialize()?, options.as_ref())
+            .await?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<S: RemoteSigner + 'static> BaseSignerWalletAdapter for KmsWalletAdapter<S> {
+    /// The key never leaves the KMS
+    fn wallet_signer(&self) -> Option<Box<DynSigner>> {
+        None
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_transaction(
+        &self,
+        mut transaction: TransactionOrVersionedTransaction,
+    ) -> wallet_adapter_base::Result<TransactionOrVersionedTransaction> {
+        let public_key = self.connected_key()?;
+
+        let signature = self.signer.sign(&message_data(&transaction)).await?;
+        add_signature(&mut transaction, &public_key, signature)?;
+
+        Ok(transaction)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<S: RemoteSigner + 'static> BaseMessageSignerWalletAdapter for KmsWalletAdapter<S> {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_message(&self, message: &[u8]) -> wallet_adapter_base::Result<Vec<u8>> {
+        self.connected_key()?;
+
+        Ok(self.signer.sign(message).await?.as_ref().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use solana_sdk::signature::Keypair;
+    use wallet_adapter_base::{build_memo, TransactionBuilder};
+
+    use super::*;
+
+    /// A "remote" key living in memory
+    struct LocalSigner(Keypair);
+
+    #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+    impl RemoteSigner for LocalSigner {
+        async fn public_key(&self) -> anyhow::Result<Pubkey> {
+            Ok(self.0.pubkey())
+        }
+
+        async fn sign(&self, message: &[u8]) -> anyhow::Result<Signature> {
+            Ok(self.0.sign_message(message))
+        }
+    }
+
+    #[test]
+    fn signs_transactions_with_the_remote_key() {
+        let keypair = Keypair::new();
+        let public_key = keypair.pubkey();
+        let mut adapter = KmsWalletAdapter::new(LocalSigner(keypair));
+        assert!(matches!(
+            block_on(adapter.sign_message(b"hello")),
+            Err(WalletError::WalletNotConnected)
+        ));
+
+        block_on(adapter.connect()).unwrap();
+        assert_eq!(adapter.public_key(), Some(public_key));
+
+        let transaction = TransactionBuilder::new()
+            .fee_payer(public_key)
+            .add_instruction(build_memo(b"kms", &[]))
+            .build_legacy()
+            .unwrap();
+        let TransactionOrVersionedTransaction::Transaction(signed) =
+            block_on(adapter.sign_transaction(transaction)).unwrap()
+        else {
+            panic!("expected a legacy transaction");
+        };
+        assert!(signed.verify().is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use wallet_adapter_common::account::ParsedAccount;
+use wallet_adapter_common::connection::{
+    Connection, EpochInfo, LatestBlockhash, RpcError, RpcVersion, SignatureStatus,
+};
+use wallet_adapter_common::history::{
+    ParsedTransactionWithMeta, SignatureInfo, SignaturesForAddressOptions,
+};
+use wallet_adapter_common::token::KeyedTokenAccount;
+use wallet_adapter_common::types::SendTransactionOptions;
+
+/// A connection accepting every transaction without a cluster, to go with
+/// [`MockWalletAdapter`](crate::MockWalletAdapter).
+///
+/// It hands out fresh blockhashes and records the raw transactions sent,
+/// the other requests fail. Clones share the recorded transactions.
+#[derive(Debug, Clone, Default)]
+pub struct MockConnection {
+    sent: Arc<Mutex<Vec<Vec<u8>>>>,
+    expiring: Arc<AtomicU32>,
+}
+
+impl MockConnection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse the next `count` transactions like a cluster that no longer knows their blockhash
+    pub fn expire_next(&self, count: u32) {
+        self.expiring.store(count, Ordering::SeqCst);
+    }
+
+    /// The raw transactions accepted so far
+    pub fn sent(&self) -> Vec<Vec<u8>> {
+        self.sent
+            .lock()
+            .map(|sent| sent.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl Connection for MockConnection {
+    async fn get_latest_blockhash_with_context(
+        &self,
+        _commitment: Option<CommitmentLevel>,
+        _min_context_slots: Option<u32>,
+    ) -> Result<LatestBlockhash> {
+        Ok(LatestBlockhash {
+            blockhash: Hash::new_unique(),
+            last_valid_block_height: 0,
+            slot: 0,
+        })
+    }
+
+    async fn send_raw_transaction(
+        &self,
+        raw_transaction: Vec<u8>,
+        _options: Option<&SendTransactionOptions>,
+    ) -> Result<Signature> {
+        let expired = self
+            .expiring
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok();
+        if expired {
+            return Err(RpcError {
+                code: -32002,
+                message: "Transaction simulation failed: Blockhash not found".to_string(),
+                data: Some(serde_json::json!({ "err": "BlockhashNotFound", "logs": [] })),
+            }
+            .into());
+        }
+
+        self.sent
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .push(raw_transaction);
+        Ok(Signature::new_unique())
+    }
+
+    async fn request_airdrop(&self, _pubkey: &Pubkey, _lamports: u64) -> Result<Signature> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        _signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn confirm_transaction(
+        &self,
+        _signature: &Signature,
+        _commitment: CommitmentLevel,
+        _timeout_ms: u32,
+    ) -> Result<()> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_token_accounts(&self, _owner: &Pubkey) -> Result<Vec<KeyedTokenAccount>> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_account_info(&self, _pubkey: &Pubkey) -> Result<Option<Account>> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_parsed_account_info(&self, _pubkey: &Pubkey) -> Result<Option<ParsedAccount>> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        _address: &Pubkey,
+        _options: Option<&SignaturesForAddressOptions>,
+    ) -> Result<Vec<SignatureInfo>> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_transaction(
+        &self,
+        _signature: &Signature,
+    ) -> Result<Option<ParsedTransactionWithMeta>> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, _data_len: usize) -> Result<u64> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_slot(&self) -> Result<u64> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_epoch_info(&self) -> Result<EpochInfo> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_health(&self) -> Result<()> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_version(&self) -> Result<RpcVersion> {
+        bail!("the mock connection has no cluster")
+    }
+
+    async fn get_genesis_hash(&self) -> Result<Hash> {
+        bail!("the mock connection has no cluster")
+    }
+}
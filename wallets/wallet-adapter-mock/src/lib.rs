@@ -0,0 +1,633 @@
+//! A scriptable wallet for testing wallet flows without a browser or extension.
+//!
+//! The mock approves everything by default, use [`MockBehavior`] to make it reject
+//! requests and [`MockWalletAdapter::with_latency`] to simulate a user taking their time.
+//! For end-to-end tests of a whole flow, [`MockWalletAdapter::with_scenario`] scripts the
+//! answer to each request, eg. approve after 500ms, reject the second request, or
+//! disconnect while signing.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::TransactionVersion,
+};
+use wallet_adapter_base::{
+    check_versioned_send_options, BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter,
+    BaseWalletAdapter, SupportedTransactionVersions, TransactionOrVersionedTransaction,
+    WalletAdapterEvent, WalletAdapterEventEmitter, WalletError, WalletReadyState,
+};
+use wallet_adapter_common::{
+    connection::Connection,
+    types::{DynSigner, SendOptions, SendTransactionOptions},
+};
+
+mod connection;
+
+pub use connection::MockConnection;
+
+/// How the mock answers a request
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum MockBehavior {
+    /// Approve the request
+    #[default]
+    Approve,
+    /// Fail with the error `WalletError::from_code(code, message)` rebuilds
+    Reject { code: u32, message: String },
+    /// Disconnect while the request is pending, failing it with `WalletDisconnected`
+    Disconnect,
+}
+
+impl MockBehavior {
+    /// Fail with (a copy of) `error`
+    pub fn reject(error: &WalletError) -> Self {
+        Self::Reject {
+            code: error.code(),
+            message: error.message(),
+        }
+    }
+
+    /// Fail like a user declining the wallet popup
+    pub fn user_rejected() -> Self {
+        Self::reject(&WalletError::WalletUserRejected(
+            "User rejected the request.".to_string(),
+        ))
+    }
+
+    fn result(&self) -> wallet_adapter_base::Result<()> {
+        match self {
+            Self::Approve => Ok(()),
+            Self::Reject { code, message } => Err(WalletError::from_code(*code, message.clone())),
+            Self::Disconnect => Err(WalletError::WalletDisconnected),
+        }
+    }
+}
+
+/// The scripted answer to one request, see [`MockWalletAdapter::with_scenario`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MockStep {
+    pub behavior: MockBehavior,
+    /// Wait this long instead of the configured latency
+    pub delay_ms: Option<u32>,
+}
+
+impl MockStep {
+    pub fn new(behavior: MockBehavior) -> Self {
+        Self {
+            behavior,
+            delay_ms: None,
+        }
+    }
+
+    pub fn approve() -> Self {
+        Self::new(MockBehavior::Approve)
+    }
+
+    pub fn reject(error: &WalletError) -> Self {
+        Self::new(MockBehavior::reject(error))
+    }
+
+    pub fn user_rejected() -> Self {
+        Self::new(MockBehavior::user_rejected())
+    }
+
+    /// Disconnect while the request is pending
+    pub fn disconnect() -> Self {
+        Self::new(MockBehavior::Disconnect)
+    }
+
+    /// Answer after `delay_ms`, eg. `MockStep::approve().after_ms(500)`
+    pub fn after_ms(mut self, delay_ms: u32) -> Self {
+        self.delay_ms = Some(delay_ms);
+        self
+    }
+}
+
+impl From<MockBehavior> for MockStep {
+    fn from(behavior: MockBehavior) -> Self {
+        Self::new(behavior)
+    }
+}
+
+/// A request the mock received, in the order of [`MockWalletAdapter::requests`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockRequest {
+    Connect,
+    Disconnect,
+    SignTransaction,
+    /// One approval for this many transactions, eg. by `send_all_transactions`
+    SignAllTransactions(usize),
+    SignMessage(Vec<u8>),
+}
+
+#[derive(Debug, Default)]
+struct Behaviors {
+    connect: MockBehavior,
+    sign_transaction: MockBehavior,
+    sign_message: MockBehavior,
+}
+
+/// Clones share their state, so a clone kept by the test can change the behavior
+/// of the adapter handed to the code under test
+#[derive(Debug, Clone)]
+pub struct MockWalletAdapter {
+    keypair: Arc<Keypair>,
+    connected: Arc<AtomicBool>,
+    connecting: Arc<AtomicBool>,
+    latency_ms: Arc<AtomicU32>,
+    behaviors: Arc<Mutex<Behaviors>>,
+    scenario: Arc<Mutex<VecDeque<MockStep>>>,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    provider_events: Arc<Mutex<HashSet<String>>>,
+    event_emitter: WalletAdapterEventEmitter,
+}
+
+impl Default for MockWalletAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockWalletAdapter {
+    /// A mock with a random keypair that approves every request immediately
+    pub fn new() -> Self {
+        Self::with_keypair(Keypair::new())
+    }
+
+    /// A mock signing with `keypair`, eg. to assert on a known public key
+    pub fn with_keypair(keypair: Keypair) -> Self {
+        Self {
+            keypair: Arc::new(keypair),
+            connected: Arc::new(AtomicBool::new(false)),
+            connecting: Arc::new(AtomicBool::new(false)),
+            latency_ms: Arc::new(AtomicU32::new(0)),
+            behaviors: Arc::new(Mutex::new(Behaviors::default())),
+            scenario: Arc::new(Mutex::new(VecDeque::new())),
+            requests: Arc::new(Mutex::new(Vec::new())),
+            provider_events: Arc::new(Mutex::new(HashSet::new())),
+            event_emitter: WalletAdapterEventEmitter::new(),
+        }
+    }
+
+    /// Wait `latency_ms` before answering connect and sign requests
+    pub fn with_latency(self, latency_ms: u32) -> Self {
+        self.set_latency(latency_ms);
+        self
+    }
+
+    pub fn with_connect_behavior(self, behavior: MockBehavior) -> Self {
+        self.set_connect_behavior(behavior);
+        self
+    }
+
+    /// Behavior of both transaction and message signing
+    pub fn with_sign_behavior(self, behavior: MockBehavior) -> Self {
+        self.set_sign_transaction_behavior(behavior.clone());
+        self.set_sign_message_behavior(behavior);
+        self
+    }
+
+    /// Answer the next requests with `steps`, one step per connect or sign request in the
+    /// order they arrive. Once the steps run out the configured behaviors apply again.
+    pub fn with_scenario(self, steps: impl IntoIterator<Item = MockStep>) -> Self {
+        self.set_scenario(steps);
+        self
+    }
+
+    pub fn set_latency(&self, latency_ms: u32) {
+        self.latency_ms.store(latency_ms, Ordering::SeqCst);
+    }
+
+    pub fn set_connect_behavior(&self, behavior: MockBehavior) {
+        if let Ok(mut behaviors) = self.behaviors.lock() {
+            behaviors.connect = behavior;
+        }
+    }
+
+    pub fn set_sign_transaction_behavior(&self, behavior: MockBehavior) {
+        if let Ok(mut behaviors) = self.behaviors.lock() {
+            behaviors.sign_transaction = behavior;
+        }
+    }
+
+    pub fn set_sign_message_behavior(&self, behavior: MockBehavior) {
+        if let Ok(mut behaviors) = self.behaviors.lock() {
+            behaviors.sign_message = behavior;
+        }
+    }
+
+    /// Replace the steps left of the scenario with `steps`
+    pub fn set_scenario(&self, steps: impl IntoIterator<Item = MockStep>) {
+        if let Ok(mut scenario) = self.scenario.lock() {
+            *scenario = steps.into_iter().collect();
+        }
+    }
+
+    /// How many steps of the scenario have not been played yet
+    pub fn remaining_steps(&self) -> usize {
+        self.scenario
+            .lock()
+            .map(|scenario| scenario.len())
+            .unwrap_or_default()
+    }
+
+    /// The keypair the mock signs with
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+
+    /// Every request received so far
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests
+            .lock()
+            .map(|requests| requests.clone())
+            .unwrap_or_default()
+    }
+
+    /// Emit `event` like a wallet-specific provider event, if it is subscribed to,
+    /// see [`BaseWalletAdapter::subscribe_provider_event`]
+    pub fn emit_provider_event(
+        &self,
+        event: &str,
+        payload: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let subscribed = self
+            .provider_events
+            .lock()
+            .is_ok_and(|events| events.contains(event));
+        if !subscribed {
+            return Ok(());
+        }
+
+        self.event_emitter
+            .try_emit(WalletAdapterEvent::ProviderEvent {
+                name: event.to_string(),
+                payload,
+            })
+    }
+
+    /// Record `request`, wait for the configured latency and answer with the next step of the
+    /// scenario, or as configured
+    async fn answer(
+        &self,
+        request: MockRequest,
+        behavior: impl FnOnce(&Behaviors) -> &MockBehavior,
+    ) -> wallet_adapter_base::Result<()> {
+        self.requests
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .push(request);
+
+        let step = self
+            .scenario
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .pop_front();
+        let step = match step {
+            Some(step) => step,
+            None => MockStep::new(
+                behavior(&*self.behaviors.lock().map_err(|err| anyhow!("{err:?}"))?).clone(),
+            ),
+        };
+
+        let delay_ms = step
+            .delay_ms
+            .unwrap_or_else(|| self.latency_ms.load(Ordering::SeqCst));
+        if delay_ms > 0 {
+            sleep_ms(delay_ms).await;
+        }
+
+        if step.behavior == MockBehavior::Disconnect {
+            self.connected.store(false, Ordering::SeqCst);
+            self.event_emitter
+                .try_emit(WalletAdapterEvent::Disconnect)?;
+        }
+
+        step.behavior.result()
+    }
+
+    fn sign(
+        &self,
+        mut transaction: TransactionOrVersionedTransaction,
+    ) -> wallet_adapter_base::Result<TransactionOrVersionedTransaction> {
+        match transaction {
+            TransactionOrVersionedTransaction::Transaction(ref mut tx) => {
+                tx.try_partial_sign(&[self.keypair.as_ref()], tx.message.recent_blockhash)
+                    .map_err(|err| anyhow!("{err}"))?;
+            }
+            TransactionOrVersionedTransaction::VersionedTransaction(ref mut vtx) => {
+                let signer_index = vtx
+                    .message
+                    .static_account_keys()
+                    .iter()
+                    .take(vtx.message.header().num_required_signatures.into())
+                    .position(|key| *key == self.keypair.pubkey())
+                    .ok_or_else(|| anyhow!("{} is not a signer", self.keypair.pubkey()))?;
+                vtx.signatures[signer_index] = self.keypair.sign_message(&vtx.message.serialize());
+            }
+        }
+
+        Ok(transaction)
+    }
+
+    fn check_connected(&self) -> wallet_adapter_base::Result<()> {
+        if !self.connected.load(Ordering::SeqCst) {
+            return Err(WalletError::WalletNotConnected);
+        }
+        Ok(())
+    }
+}
+
+/// Sleeps on a thread rather than a runtime timer, so tests can `block_on` the mock
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep_ms(millis: u32) {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(millis.into()));
+        let _ = tx.send(());
+    });
+    let _ = rx.await;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep_ms(millis: u32) {
+    wallet_adapter_wasm::util::sleep_ms(millis as i32).await;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl BaseWalletAdapter for MockWalletAdapter {
+    fn event_emitter(&self) -> WalletAdapterEventEmitter {
+        self.event_emitter.clone()
+    }
+
+    fn name(&self) -> String {
+        "MockWallet".to_string()
+    }
+
+    fn url(&self) -> String {
+        "https://github.com/mucks/wallet-adapter".to_string()
+    }
+
+    fn icon(&self) -> String {
+        String::new()
+    }
+
+    fn ready_state(&self) -> WalletReadyState {
+        WalletReadyState::Installed
+    }
+
+    fn public_key(&self) -> Option<Pubkey> {
+        self.connected
+            .load(Ordering::SeqCst)
+            .then(|| self.keypair.pubkey())
+    }
+
+    fn connecting(&self) -> bool {
+        self.connecting.load(Ordering::SeqCst)
+    }
+
+    fn supported_transaction_versions(&self) -> Option<SupportedTransactionVersions> {
+        Some(vec![
+            TransactionVersion::LEGACY,
+            TransactionVersion::Number(0),
+        ])
+    }
+
+    fn subscribe_provider_event(&self, event: &str) -> wallet_adapter_base::Result<()> {
+        self.provider_events
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .insert(event.to_string());
+        Ok(())
+    }
+
+    fn unsubscribe_provider_event(&self, event: &str) -> wallet_adapter_base::Result<()> {
+        self.provider_events
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .remove(event);
+        Ok(())
+    }
+
+    async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
+        self.connecting.store(true, Ordering::SeqCst);
+        let result = self
+            .answer(MockRequest::Connect, |behaviors| &behaviors.connect)
+            .await;
+        self.connecting.store(false, Ordering::SeqCst);
+        result?;
+
+        self.connected.store(true, Ordering::SeqCst);
+        self.event_emitter
+            .emit(WalletAdapterEvent::Connect(self.keypair.pubkey()))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> anyhow::Result<()> {
+        self.requests
+            .lock()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .push(MockRequest::Disconnect);
+
+        self.connected.store(false, Ordering::SeqCst);
+        self.event_emitter
+            .try_emit(WalletAdapterEvent::Disconnect)?;
+
+        Ok(())
+    }
+
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> wallet_adapter_base::Result<Signature> {
+        <Self as BaseSignerWalletAdapter>::send_transaction(self, transaction, connection, options)
+            .await
+    }
+
+    /// Prepares the legacy transactions, signs them all with a single approval and sends them
+    /// until one fails
+    async fn send_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+        connection: &dyn Connection,
+        options: Option<SendOptions>,
+    ) -> wallet_adapter_base::Result<Vec<Option<Signature>>> {
+        let mut prepared = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            prepared.push(match transaction {
+                TransactionOrVersionedTransaction::Transaction(tx) => {
+                    TransactionOrVersionedTransaction::Transaction(
+                        self.prepare_transaction(tx, connection, options.as_ref())
+                            .await?,
+                    )
+                }
+                versioned => {
+                    check_versioned_send_options(options.as_ref())?;
+                    versioned
+                }
+            });
+        }
+
+        let options = options.map(SendTransactionOptions::from);
+        let count = prepared.len();
+        let mut signatures = Vec::with_capacity(count);
+        for transaction in self.sign_all_transactions(prepared).await? {
+            let sent = connection
+                .send_raw_transaction(transaction.serialize()?, options.as_ref())
+                .await;
+            match sent {
+                Ok(signature) => signatures.push(Some(signature)),
+                Err(err) if signatures.is_empty() => return Err(err.into()),
+                // the later transactions may depend on this one
+                Err(_) => break,
+            }
+        }
+        signatures.resize(count, None);
+        Ok(signatures)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl BaseSignerWalletAdapter for MockWalletAdapter {
+    fn wallet_signer(&self) -> Option<Box<DynSigner>> {
+        self.connected
+            .load(Ordering::SeqCst)
+            .then(|| Box::new(self.keypair.insecure_clone()) as Box<DynSigner>)
+    }
+
+    async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> wallet_adapter_base::Result<TransactionOrVersionedTransaction> {
+        self.check_connected()?;
+        self.answer(MockRequest::SignTransaction, |behaviors| {
+            &behaviors.sign_transaction
+        })
+        .await?;
+
+        self.sign(transaction)
+    }
+
+    /// Asks once for the whole batch, like wallets with `signAllTransactions`
+    async fn sign_all_transactions(
+        &self,
+        transactions: Vec<TransactionOrVersionedTransaction>,
+    ) -> wallet_adapter_base::Result<Vec<TransactionOrVersionedTransaction>> {
+        self.check_connected()?;
+        for transaction in transactions.iter() {
+            self.check_if_transaction_is_supported(transaction)?;
+        }
+        self.answer(
+            MockRequest::SignAllTransactions(transactions.len()),
+            |behaviors| &behaviors.sign_transaction,
+        )
+        .await?;
+
+        transactions
+            .into_iter()
+            .map(|transaction| self.sign(transaction))
+            .collect()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl BaseMessageSignerWalletAdapter for MockWalletAdapter {
+    async fn sign_message(&self, message: &[u8]) -> wallet_adapter_base::Result<Vec<u8>> {
+        self.check_connected()?;
+        self.answer(MockRequest::SignMessage(message.to_vec()), |behaviors| {
+            &behaviors.sign_message
+        })
+        .await?;
+
+        Ok(self.keypair.sign_message(message).as_ref().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    #[test]
+    fn signs_with_the_fixed_keypair_until_told_to_reject() {
+        let keypair = Keypair::new();
+        let public_key = keypair.pubkey();
+        let mut wallet = MockWalletAdapter::with_keypair(keypair);
+
+        block_on(wallet.connect()).unwrap();
+        assert_eq!(wallet.public_key(), Some(public_key));
+
+        let signature = block_on(wallet.sign_message(b"hello")).unwrap();
+        assert!(Signature::try_from(signature.as_slice())
+            .unwrap()
+            .verify(public_key.as_ref(), b"hello"));
+
+        wallet.set_sign_message_behavior(MockBehavior::user_rejected());
+        assert!(matches!(
+            block_on(wallet.sign_message(b"hello")),
+            Err(WalletError::WalletUserRejected(_))
+        ));
+
+        assert_eq!(
+            wallet.requests(),
+            vec![
+                MockRequest::Connect,
+                MockRequest::SignMessage(b"hello".to_vec()),
+                MockRequest::SignMessage(b"hello".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn plays_the_scenario_before_the_configured_behaviors() {
+        let mut wallet = MockWalletAdapter::new().with_scenario([
+            MockStep::approve(),
+            MockStep::user_rejected(),
+            MockStep::disconnect(),
+        ]);
+        block_on(wallet.connect()).unwrap();
+        let events = wallet.event_emitter();
+        while events.try_recv().is_some() {}
+
+        assert!(matches!(
+            block_on(wallet.sign_message(b"first")),
+            Err(WalletError::WalletUserRejected(_))
+        ));
+        assert!(matches!(
+            block_on(wallet.sign_message(b"second")),
+            Err(WalletError::WalletDisconnected)
+        ));
+        assert_eq!(wallet.public_key(), None);
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Disconnect)
+        ));
+        assert_eq!(wallet.remaining_steps(), 0);
+
+        block_on(wallet.connect()).unwrap();
+        block_on(wallet.sign_message(b"third")).unwrap();
+    }
+
+    #[test]
+    fn waits_for_the_latency_without_a_runtime() {
+        let mut wallet = MockWalletAdapter::new().with_latency(20);
+
+        let started = std::time::Instant::now();
+        block_on(wallet.connect()).unwrap();
+        assert!(started.elapsed() >= std::time::Duration::from_millis(20));
+        assert!(wallet.connected());
+    }
+}
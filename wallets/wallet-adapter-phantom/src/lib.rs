@@ -1,236 +1,168 @@
-use anyhow::{anyhow, Context, Result};
-use serde::{Deserialize, Serialize};
-use solana_sdk::{bs58, pubkey::Pubkey};
-use wallet_adapter_base::{BaseWalletAdapter, TransactionOrVersionedTransaction};
-use wallet_adapter_wasm::generic_wallet::{GenericWasmWallet, GenericWasmWalletAdapter};
-use wallet_adapter_wasm::util::reflect_get;
-use wallet_binding::solana;
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsValue;
-use web_sys::Window;
-
-mod wallet_binding {
-    use super::*;
-
-    // PhantomRequestResponse
-    #[wasm_bindgen]
-    extern "C" {
-        #[wasm_bindgen]
-        #[derive(Clone, Debug)]
-        pub type PhantomRequestResponse;
-
-        #[wasm_bindgen(method, getter)]
-        pub fn signature(this: &PhantomRequestResponse) -> Option<String>;
-    }
-
-    // PhantomError
-    #[wasm_bindgen]
-    extern "C" {
-        #[wasm_bindgen]
-        #[derive(Clone, Debug)]
-        pub type PhantomError;
-
-        #[wasm_bindgen(method, getter)]
-        pub fn message(this: &PhantomError) -> Option<String>;
-    }
-
-    // Pubkey
-    #[wasm_bindgen]
-    extern "C" {
-        #[wasm_bindgen]
-        #[derive(Clone, Debug)]
-        pub type Pubkey;
-
-        #[wasm_bindgen(method, js_name = toBytes)]
-        pub fn to_bytes(this: &Pubkey) -> Vec<u8>;
-
-    }
-
-    // Phantom
-    #[wasm_bindgen]
-    extern "C" {
-        #[wasm_bindgen(thread_local, js_namespace = window, js_name = solana)]
-        pub static SOLANA: Solana;
-
-        #[wasm_bindgen]
-        #[derive(Clone)]
-        pub type Solana;
-
-        #[wasm_bindgen(method, catch)]
-        pub async fn connect(
-            this: &Solana,
-            options: &JsValue,
-        ) -> std::result::Result<JsValue, PhantomError>;
-
-        #[wasm_bindgen(method, getter, js_name = publicKey)]
-        pub fn public_key(this: &Solana) -> Pubkey;
-
-        #[wasm_bindgen(method, getter, js_name = isConnected)]
-        pub fn is_connected(this: &Solana) -> bool;
-
-        #[wasm_bindgen(method, catch)]
-        pub fn disconnect(this: &Solana) -> std::result::Result<(), PhantomError>;
-
-        #[wasm_bindgen(method, catch)]
-        pub async fn request(
-            this: &Solana,
-            options: &JsValue,
-        ) -> std::result::Result<PhantomRequestResponse, PhantomError>;
-
-        #[wasm_bindgen(method)]
-        pub fn on(this: &Solana, event: &str, cb: &js_sys::Function);
-        #[wasm_bindgen(method)]
-        pub fn off(this: &Solana, event: &str, cb: &js_sys::Function);
-
-    }
-
-    pub fn solana() -> Solana {
-        SOLANA.with(|solana| solana.clone())
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PhantomRequest {
-    pub method: String,
-    pub params: PhantomRequestParams,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PhantomRequestParams {
-    pub message: String,
-}
+#![cfg(target_arch = "wasm32")]
+
+use anyhow::Result;
+use wallet_adapter_base::BaseWalletAdapter;
+use wallet_adapter_wasm::generic_wallet::GenericWasmWalletAdapter;
+use wallet_adapter_wasm::injected::{InjectedWallet, InjectedWalletConfig, RequestStyle};
+
+/// Left out of the binary when the `icon` feature is disabled
+#[cfg(feature = "icon")]
+const ICON: &str = "data:image/svg+xml;base64,PHN2ZyB4bWxucz0iaHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmciIHdpZHRoPSIxMDgiIGhlaWdodD0iMTA4IiB2aWV3Qm94PSIwIDAgMTA4IDEwOCIgZmlsbD0ibm9uZSI+CjxyZWN0IHdpZHRoPSIxMDgiIGhlaWdodD0iMTA4IiByeD0iMjYiIGZpbGw9IiNBQjlGRjIiLz4KPHBhdGggZmlsbC1ydWxlPSJldmVub2RkIiBjbGlwLXJ1bGU9ImV2ZW5vZGQiIGQ9Ik00Ni41MjY3IDY5LjkyMjlDNDIuMDA1NCA3Ni44NTA5IDM0LjQyOTIgODUuNjE4MiAyNC4zNDggODUuNjE4MkMxOS41ODI0IDg1LjYxODIgMTUgODMuNjU2MyAxNSA3NS4xMzQyQzE1IDUzLjQzMDUgNDQuNjMyNiAxOS44MzI3IDcyLjEyNjggMTkuODMyN0M4Ny43NjggMTkuODMyNyA5NCAzMC42ODQ2IDk0IDQzLjAwNzlDOTQgNTguODI1OCA4My43MzU1IDc2LjkxMjIgNzMuNTMyMSA3Ni45MTIyQzcwLjI5MzkgNzYuOTEyMiA2OC43MDUzIDc1LjEzNDIgNjguNzA1MyA3Mi4zMTRDNjguNzA1MyA3MS41NzgzIDY4LjgyNzUgNzAuNzgxMiA2OS4wNzE5IDY5LjkyMjlDNjUuNTg5MyA3NS44Njk5IDU4Ljg2ODUgODEuMzg3OCA1Mi41NzU0IDgxLjM4NzhDNDcuOTkzIDgxLjM4NzggNDUuNjcxMyA3OC41MDYzIDQ1LjY3MTMgNzQuNDU5OEM0NS42NzEzIDcyLjk4ODQgNDUuOTc2OCA3MS40NTU2IDQ2LjUyNjcgNjkuOTIyOVpNODMuNjc2MSA0Mi41Nzk0QzgzLjY3NjEgNDYuMTcwNCA4MS41NTc1IDQ3Ljk2NTggNzkuMTg3NSA0Ny45NjU4Qzc2Ljc4MTYgNDcuOTY1OCA3NC42OTg5IDQ2LjE3MDQgNzQuNjk4OSA0Mi41Nzk0Qzc0LjY5ODkgMzguOTg4NSA3Ni43ODE2IDM3LjE5MzEgNzkuMTg3NSAzNy4xOTMxQzgxLjU1NzUgMzcuMTkzMSA4My42NzYxIDM4Ljk4ODUgODMuNjc2MSA0Mi41Nzk0Wk03MC4yMTAzIDQyLjU3OTVDNzAuMjEwMyA0Ni4xNzA0IDY4LjA5MTYgNDcuOTY1OCA2NS43MjE2IDQ3Ljk2NThDNjMuMzE1NyA0Ny45NjU4IDYxLjIzMyA0Ni4xNzA0IDYxLjIzMyA0Mi41Nzk1QzYxLjIzMyAzOC45ODg1IDYzLjMxNTcgMzcuMTkzMSA2NS43MjE2IDM3LjE5MzFDNjguMDkxNiAzNy4xOTMxIDcwLjIxMDMgMzguOTg4NSA3MC4yMTAzIDQyLjU3OTVaIiBmaWxsPSIjRkZGREY4Ii8+Cjwvc3ZnPg==";
+#[cfg(not(feature = "icon"))]
+const ICON: &str = "";
+
+// docs found here: https://docs.phantom.app/solana/sending-a-transaction
+pub const PHANTOM: InjectedWalletConfig = InjectedWalletConfig {
+    name: "Phantom",
+    url: "https://phantom.app",
+    icon: ICON,
+    window_key: "solana",
+    detection_flag: "isPhantom",
+    alternative_detection_flags: &[],
+    request_style: RequestStyle::Request { param: "message" },
+    browse_url: Some("https://phantom.app/ul/browse/"),
+};
 
-fn set_phantom_url(window: Window) -> std::result::Result<(), JsValue> {
-    // redirect to the Phantom /browse universal link
-    // this will open the current URL in the Phantom in-wallet browser
-    let url = window.location().href()?;
-    let origin = window.location().origin()?;
-
-    let href = format!("https://phantom.app/ul/browse/${url}?ref=${origin}");
-    window.location().set_href(&href)?;
-
-    Ok(())
+pub struct PhantomWalletAdapter {
+    adapter: GenericWasmWalletAdapter<InjectedWallet>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct PhantomWallet;
-
-#[async_trait::async_trait(?Send)]
-impl GenericWasmWallet for PhantomWallet {
-    fn name(&self) -> String {
-        "Phantom".to_string()
-    }
-
-    fn url(&self) -> String {
-        "https://phantom.app".into()
-    }
-
-    fn icon(&self) -> String {
-        "data:image/svg+xml;base64,PHN2ZyB4bWxucz0iaHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmciIHdpZHRoPSIxMDgiIGhlaWdodD0iMTA4IiB2aWV3Qm94PSIwIDAgMTA4IDEwOCIgZmlsbD0ibm9uZSI+CjxyZWN0IHdpZHRoPSIxMDgiIGhlaWdodD0iMTA4IiByeD0iMjYiIGZpbGw9IiNBQjlGRjIiLz4KPHBhdGggZmlsbC1ydWxlPSJldmVub2RkIiBjbGlwLXJ1bGU9ImV2ZW5vZGQiIGQ9Ik00Ni41MjY3IDY5LjkyMjlDNDIuMDA1NCA3Ni44NTA5IDM0LjQyOTIgODUuNjE4MiAyNC4zNDggODUuNjE4MkMxOS41ODI0IDg1LjYxODIgMTUgODMuNjU2MyAxNSA3NS4xMzQyQzE1IDUzLjQzMDUgNDQuNjMyNiAxOS44MzI3IDcyLjEyNjggMTkuODMyN0M4Ny43NjggMTkuODMyNyA5NCAzMC42ODQ2IDk0IDQzLjAwNzlDOTQgNTguODI1OCA4My43MzU1IDc2LjkxMjIgNzMuNTMyMSA3Ni45MTIyQzcwLjI5MzkgNzYuOTEyMiA2OC43MDUzIDc1LjEzNDIgNjguNzA1MyA3Mi4zMTRDNjguNzA1MyA3MS41NzgzIDY4LjgyNzUgNzAuNzgxMiA2OS4wNzE5IDY5LjkyMjlDNjUuNTg5MyA3NS44Njk5IDU4Ljg2ODUgODEuMzg3OCA1Mi41NzU0IDgxLjM4NzhDNDcuOTkzIDgxLjM4NzggNDUuNjcxMyA3OC41MDYzIDQ1LjY3MTMgNzQuNDU5OEM0NS42NzEzIDcyLjk4ODQgNDUuOTc2OCA3MS40NTU2IDQ2LjUyNjcgNjkuOTIyOVpNODMuNjc2MSA0Mi41Nzk0QzgzLjY3NjEgNDYuMTcwNCA4MS41NTc1IDQ3Ljk2NTggNzkuMTg3NSA0Ny45NjU4Qzc2Ljc4MTYgNDcuOTY1OCA3NC42OTg5IDQ2LjE3MDQgNzQuNjk4OSA0Mi41Nzk0Qzc0LjY5ODkgMzguOTg4NSA3Ni43ODE2IDM3LjE5MzEgNzkuMTg3NSAzNy4xOTMxQzgxLjU1NzUgMzcuMTkzMSA4My42NzYxIDM4Ljk4ODUgODMuNjc2MSA0Mi41Nzk0Wk03MC4yMTAzIDQyLjU3OTVDNzAuMjEwMyA0Ni4xNzA0IDY4LjA5MTYgNDcuOTY1OCA2NS43MjE2IDQ3Ljk2NThDNjMuMzE1NyA0Ny45NjU4IDYxLjIzMyA0Ni4xNzA0IDYxLjIzMyA0Mi41Nzk1QzYxLjIzMyAzOC45ODg1IDYzLjMxNTcgMzcuMTkzMSA2NS43MjE2IDM3LjE5MzFDNjguMDkxNiAzNy4xOTMxIDcwLjIxMDMgMzguOTg4NSA3MC4yMTAzIDQyLjU3OTVaIiBmaWxsPSIjRkZGREY4Ii8+Cjwvc3ZnPg==".into()
-    }
-
-    fn is_correct_wallet(&self) -> bool {
-        match reflect_get(&solana(), &JsValue::from_str("isPhantom")) {
-            Ok(val) => val.as_bool().unwrap_or(false),
-            Err(_) => false,
-        }
-    }
-
-    fn is_connected(&self) -> bool {
-        solana().is_connected()
-    }
-
-    fn disconnect(&self) -> Result<()> {
-        solana()
-            .disconnect()
-            .map_err(|err| anyhow!("{:?}", err).into())
-    }
-
-    fn on(&self, event: &str, cb: js_sys::Function) -> Result<()> {
-        solana().on(event, &cb);
-        Ok(())
-    }
-
-    fn off(&self, event: &str, cb: js_sys::Function) -> Result<()> {
-        solana().off(event, &cb);
-        Ok(())
-    }
-
-    fn public_key(&self) -> Result<Pubkey> {
-        tracing::debug!("public_key");
-
-        let public_key = solana().public_key();
-
-        let bytes = public_key.to_bytes();
-
-        Ok(bytes.try_into().map_err(|e| anyhow!("{e:?}"))?)
+impl PhantomWalletAdapter {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            adapter: GenericWasmWalletAdapter::new(InjectedWallet::new(PHANTOM))?,
+        })
     }
 
-    async fn connect(&self) -> Result<()> {
-        tracing::debug!("phantom wallet connect");
-
-        let result = solana()
-            .connect(&JsValue::NULL)
-            .await
-            .map_err(|err| anyhow!("{:?}", err))?;
-
-        tracing::debug!("{:?}", result);
-
-        Ok(())
+    pub fn to_dyn_adapter(&self) -> Box<dyn BaseWalletAdapter> {
+        Box::new(self.adapter.clone())
     }
+}
 
-    // docs found here: https://docs.phantom.app/solana/sending-a-transaction
-    async fn sign_and_send_transaction(
-        &self,
-        transaction: TransactionOrVersionedTransaction,
-    ) -> Result<solana_sdk::signature::Signature> {
-        let tx_bytes = transaction.serialize()?;
-        let tx_bs58 = bs58::encode(tx_bytes).into_string();
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+    use wallet_adapter_base::{
+        build_memo, TransactionBuilder, TransactionOrVersionedTransaction, WalletAdapterEvent,
+        WalletError, WalletOperation, WalletReadyState,
+    };
+    use wallet_adapter_wasm::generic_wallet::GenericWasmWallet;
+    use wallet_adapter_wasm::testing::{self, FakeConnection, FakeProvider};
+    use wasm_bindgen_test::*;
 
-        tracing::debug!("tx_bs58: {}", tx_bs58);
+    use super::*;
 
-        let req = PhantomRequest {
-            method: "signAndSendTransaction".to_string(),
-            params: PhantomRequestParams { message: tx_bs58 },
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn connects_and_sends_through_the_provider() {
+        let provider = FakeProvider::install(&PHANTOM, Keypair::new()).unwrap();
+        let adapter = PhantomWalletAdapter::new().unwrap();
+
+        testing::assert_connect_and_send(adapter.to_dyn_adapter(), &provider).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn disconnecting_is_emitted_before_disconnect() {
+        FakeProvider::install(&PHANTOM, Keypair::new()).unwrap();
+        let mut adapter = PhantomWalletAdapter::new().unwrap().to_dyn_adapter();
+        adapter.connect().await.unwrap();
+        let events = adapter.event_emitter();
+        while events.try_recv().is_some() {}
+
+        adapter.disconnect().await.unwrap();
+
+        assert!(!adapter.disconnecting());
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Disconnecting)
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Disconnect)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    async fn sends_all_transactions_with_one_approval() {
+        let provider = FakeProvider::install(&PHANTOM, Keypair::new()).unwrap();
+        let mut adapter = PhantomWalletAdapter::new().unwrap().to_dyn_adapter();
+        adapter.connect().await.unwrap();
+
+        let transactions = || {
+            (0..2)
+                .map(|_| {
+                    TransactionBuilder::new()
+                        .fee_payer(provider.public_key())
+                        .add_instruction(build_memo(b"mint", &[]))
+                        .build_legacy()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
         };
-
-        let js_value = serde_wasm_bindgen::to_value(&req).map_err(|e| anyhow!("{:?}", e))?;
-
-        tracing::debug!("js_value: {:?}", js_value);
-
-        let resp = solana()
-            .request(&js_value)
+        let signatures = adapter
+            .send_all_transactions(transactions(), &FakeConnection, None)
             .await
-            .map_err(|err| anyhow!("{:?}", err))?;
-
-        let signature = resp.signature().context("signature not found")?;
-
-        tracing::debug!("result: {}", signature);
+            .unwrap();
 
-        Ok(signature.parse()?)
-    }
-
-    fn is_ios_redirectable(&self) -> Result<bool> {
-        Ok(false)
-    }
-    fn set_wallet_url(&self) -> Result<()> {
-        set_phantom_url(web_sys::window().context("could not get window")?)
-            .map_err(|e| anyhow!("{:?}", e))
-    }
-}
-
-pub struct PhantomWalletAdapter {
-    adapter: GenericWasmWalletAdapter<PhantomWallet>,
-}
+        assert_eq!(provider.sent_batches(), 1);
+        assert_eq!(provider.sent_transactions().len(), 2);
+        assert!(signatures.iter().all(Option::is_some));
 
-impl PhantomWalletAdapter {
-    pub fn new() -> Result<Self> {
-        Ok(Self {
-            adapter: GenericWasmWalletAdapter::new(PhantomWallet)?,
-        })
-    }
+        // the wallet answers null for the transactions it couldn't send
+        provider.set_unsent_in_batches(Some(0));
+        let signatures = adapter
+            .send_all_transactions(transactions(), &FakeConnection, None)
+            .await
+            .unwrap();
+        let sent = provider.sent_transactions();
+        assert_eq!(sent.len(), 3);
+        let TransactionOrVersionedTransaction::Transaction(last) = &sent[2] else {
+            panic!("expected a legacy transaction");
+        };
+        assert_eq!(signatures, [None, Some(last.signatures[0])]);
+    }
+
+    #[wasm_bindgen_test]
+    fn missing_provider_is_not_ready() {
+        let wallet = InjectedWallet::new(InjectedWalletConfig {
+            window_key: "notInstalled",
+            ..PHANTOM
+        });
+
+        assert!(!wallet.is_correct_wallet());
+        assert!(!wallet.is_connected());
+        assert!(matches!(
+            WalletError::from(wallet.public_key().unwrap_err()),
+            WalletError::WalletNotReady
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    async fn rejected_connect_is_emitted_as_an_error() {
+        let provider = FakeProvider::install(&PHANTOM, Keypair::new()).unwrap();
+        provider.set_rejecting(true);
+        let mut adapter = PhantomWalletAdapter::new().unwrap().to_dyn_adapter();
+
+        // the ready states of the construction come first
+        let events = adapter.event_emitter();
+        for ready_state in [WalletReadyState::NotDetected, WalletReadyState::Installed] {
+            assert!(matches!(
+                events.try_recv(),
+                Some(WalletAdapterEvent::ReadyStateChange(state)) if state == ready_state
+            ));
+        }
 
-    pub fn to_dyn_adapter(&self) -> Box<dyn BaseWalletAdapter> {
-        Box::new(self.adapter.clone())
+        adapter.connect().await.unwrap();
+
+        assert_eq!(adapter.public_key(), None);
+        assert!(matches!(
+            adapter.event_emitter().try_recv(),
+            Some(WalletAdapterEvent::Error {
+                wallet,
+                operation: WalletOperation::Connect,
+                error: WalletError::WalletUserRejected(_),
+            }) if wallet == "Phantom"
+        ));
     }
 }
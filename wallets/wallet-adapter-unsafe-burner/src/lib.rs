@@ -1,12 +1,47 @@
+use std::fmt;
 use std::sync::{Arc, Mutex};
 
 use anyhow::anyhow;
-use solana_sdk::{signature::Keypair, signer::Signer, transaction::TransactionVersion};
+use solana_sdk::{
+    commitment_config::CommitmentLevel,
+    hash::{hashv, Hash},
+    signature::{Keypair, Signature},
+    signer::{keypair::keypair_from_seed, Signer},
+    transaction::TransactionVersion,
+};
 use wallet_adapter_base::{
     BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter, WalletAdapterEvent,
-    WalletAdapterEventEmitter, WalletError, WalletReadyState,
+    WalletAdapterEventEmitter, WalletError, WalletOperation, WalletReadyState,
+};
+use wallet_adapter_common::{
+    connection::Connection,
+    explorer::Cluster,
+    types::{DynSigner, SendTransactionOptions},
 };
-use wallet_adapter_common::{connection::Connection, types::SendTransactionOptions};
+
+/// Left out of the binary when the `icon` feature is disabled
+#[cfg(feature = "icon")]
+const ICON: &str = "data:image/svg+xml;base64,PHN2ZyB3aWR0aD0iMzQiIGhlaWdodD0iMzAiIGZpbGw9Im5vbmUiIHhtbG5zPSJodHRwOi8vd3d3LnczLm9yZy8yMDAwL3N2ZyI+PHBhdGggZmlsbC1ydWxlPSJldmVub2RkIiBjbGlwLXJ1bGU9ImV2ZW5vZGQiIGQ9Ik0zNCAxMC42djIuN2wtOS41IDE2LjVoLTQuNmw2LTEwLjVhMi4xIDIuMSAwIDEgMCAyLTMuNGw0LjgtOC4zYTQgNCAwIDAgMSAxLjMgM1ptLTQuMyAxOS4xaC0uNmw0LjktOC40djQuMmMwIDIuMy0yIDQuMy00LjMgNC4zWm0yLTI4LjRjLS4zLS44LTEtMS4zLTItMS4zaC0xLjlsLTIuNCA0LjNIMzBsMS43LTNabS0zIDVoLTQuNkwxMC42IDI5LjhoNC43TDI4LjggNi40Wk0xOC43IDBoNC42bC0yLjUgNC4zaC00LjZMMTguNiAwWk0xNSA2LjRoNC42TDYgMjkuOEg0LjJjLS44IDAtMS43LS4zLTIuNC0uOEwxNSA2LjRaTTE0IDBIOS40TDcgNC4zaDQuNkwxNCAwWm0tMy42IDYuNEg1LjdMMCAxNi4ydjhMMTAuMyA2LjRaTTQuMyAwaC40TDAgOC4ydi00QzAgMiAxLjkgMCA0LjMgMFoiIGZpbGw9IiM5OTQ1RkYiLz48L3N2Zz4=";
+#[cfg(not(feature = "icon"))]
+const ICON: &str = "";
+
+/// How long connect waits for an auto-fund airdrop to confirm
+const AUTO_FUND_TIMEOUT_MS: u32 = 60_000;
+
+/// Airdrop requested on every connect, see [`UnsafeBurnerWallet::with_auto_fund`]
+#[derive(Clone)]
+struct AutoFund {
+    connection: Arc<dyn Connection + Send + Sync>,
+    lamports: u64,
+}
+
+impl fmt::Debug for AutoFund {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoFund")
+            .field("lamports", &self.lamports)
+            .finish_non_exhaustive()
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct UnsafeBurnerWallet {
@@ -15,19 +50,118 @@ pub struct UnsafeBurnerWallet {
      * secret key, and because the keypair will be lost any time the wallet is disconnected or the window is refreshed.
      */
     keypair: Arc<Mutex<Option<Keypair>>>,
+    /// When set, every connect derives the same keypair from it instead of a random one
+    seed: Option<Hash>,
+    auto_fund: Option<AutoFund>,
     event_emitter: WalletAdapterEventEmitter,
 }
 
+/// Environment variable read by [`UnsafeBurnerWallet::from_env`]
+pub const SEED_ENV_VAR: &str = "UNSAFE_BURNER_SEED";
+
+impl Default for UnsafeBurnerWallet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl UnsafeBurnerWallet {
     pub fn new() -> Self {
         Self {
             keypair: Arc::new(Mutex::new(None)),
+            seed: None,
+            auto_fund: None,
             event_emitter: WalletAdapterEventEmitter::new(),
         }
     }
+
+    /// Deterministic burner, the same seed gives the same address across runs (eg. in CI)
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self {
+            seed: Some(hashv(&[seed])),
+            ..Self::new()
+        }
+    }
+
+    /// Use the seed in `UNSAFE_BURNER_SEED` if it is set, a random keypair otherwise
+    pub fn from_env() -> Self {
+        match std::env::var(SEED_ENV_VAR) {
+            Ok(seed) if !seed.is_empty() => Self::from_seed(seed.as_bytes()),
+            _ => Self::new(),
+        }
+    }
+
+    /// Request `lamports` from the faucet of `connection` on every connect and wait for the
+    /// airdrop to confirm, emitting [`WalletAdapterEvent::AirdropRequested`] and
+    /// [`WalletAdapterEvent::AirdropConfirmed`]. Connections to mainnet, told by their genesis
+    /// hash, are refused. A failed airdrop is emitted as an error event and doesn't fail
+    /// the connect.
+    pub fn with_auto_fund(
+        mut self,
+        connection: Arc<dyn Connection + Send + Sync>,
+        lamports: u64,
+    ) -> Self {
+        self.auto_fund = Some(AutoFund {
+            connection,
+            lamports,
+        });
+        self
+    }
+
+    async fn fund(&self, auto_fund: &AutoFund) -> anyhow::Result<()> {
+        if auto_fund.connection.cluster().await? == Some(Cluster::MainnetBeta) {
+            anyhow::bail!("refusing to auto-fund the burner wallet on mainnet");
+        }
+
+        let signature = self
+            .request_airdrop(auto_fund.connection.as_ref(), auto_fund.lamports)
+            .await?;
+        self.event_emitter
+            .try_emit(WalletAdapterEvent::AirdropRequested(signature))?;
+
+        auto_fund
+            .connection
+            .confirm_transaction(&signature, CommitmentLevel::Confirmed, AUTO_FUND_TIMEOUT_MS)
+            .await?;
+        self.event_emitter
+            .try_emit(WalletAdapterEvent::AirdropConfirmed(signature))?;
+
+        Ok(())
+    }
+
+    fn new_keypair(&self) -> anyhow::Result<Keypair> {
+        match &self.seed {
+            Some(seed) => keypair_from_seed(seed.as_ref()).map_err(|err| anyhow!("{err}")),
+            None => Ok(Keypair::new()),
+        }
+    }
+
+    /// Fund the connected keypair from the faucet of `connection`'s cluster (devnet/testnet)
+    pub async fn request_airdrop(
+        &self,
+        connection: &dyn Connection,
+        lamports: u64,
+    ) -> wallet_adapter_base::Result<Signature> {
+        let public_key = self.public_key().ok_or(WalletError::WalletNotConnected)?;
+        Ok(connection.request_airdrop(&public_key, lamports).await?)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl UnsafeBurnerWallet {
+    /// Register the burner as a Wallet Standard wallet on `window`, so JS dApps and E2E tests
+    /// see it like any injected wallet, see [`wallet_adapter_wasm::standard_wallet`]
+    pub fn register_in_page(
+        &self,
+        connection: impl Connection + 'static,
+        chain: wallet_adapter_common::standard::SolanaChain,
+    ) -> anyhow::Result<wallet_adapter_wasm::standard_wallet::RegisteredWallet> {
+        wallet_adapter_wasm::standard_wallet::register(self.clone(), connection, chain)
+    }
 }
 
-#[async_trait::async_trait(?Send)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 impl BaseWalletAdapter for UnsafeBurnerWallet {
     fn event_emitter(&self) -> wallet_adapter_base::WalletAdapterEventEmitter {
         self.event_emitter.clone()
@@ -42,7 +176,7 @@ impl BaseWalletAdapter for UnsafeBurnerWallet {
     }
 
     fn icon(&self) -> String {
-        "data:image/svg+xml;base64,PHN2ZyB3aWR0aD0iMzQiIGhlaWdodD0iMzAiIGZpbGw9Im5vbmUiIHhtbG5zPSJodHRwOi8vd3d3LnczLm9yZy8yMDAwL3N2ZyI+PHBhdGggZmlsbC1ydWxlPSJldmVub2RkIiBjbGlwLXJ1bGU9ImV2ZW5vZGQiIGQ9Ik0zNCAxMC42djIuN2wtOS41IDE2LjVoLTQuNmw2LTEwLjVhMi4xIDIuMSAwIDEgMCAyLTMuNGw0LjgtOC4zYTQgNCAwIDAgMSAxLjMgM1ptLTQuMyAxOS4xaC0uNmw0LjktOC40djQuMmMwIDIuMy0yIDQuMy00LjMgNC4zWm0yLTI4LjRjLS4zLS44LTEtMS4zLTItMS4zaC0xLjlsLTIuNCA0LjNIMzBsMS43LTNabS0zIDVoLTQuNkwxMC42IDI5LjhoNC43TDI4LjggNi40Wk0xOC43IDBoNC42bC0yLjUgNC4zaC00LjZMMTguNiAwWk0xNSA2LjRoNC42TDYgMjkuOEg0LjJjLS44IDAtMS43LS4zLTIuNC0uOEwxNSA2LjRaTTE0IDBIOS40TDcgNC4zaDQuNkwxNCAwWm0tMy42IDYuNEg1LjdMMCAxNi4ydjhMMTAuMyA2LjRaTTQuMyAwaC40TDAgOC4ydi00QzAgMiAxLjkgMCA0LjMgMFoiIGZpbGw9IiM5OTQ1RkYiLz48L3N2Zz4=".to_string()
+        ICON.to_string()
     }
 
     fn ready_state(&self) -> WalletReadyState {
@@ -70,22 +204,39 @@ impl BaseWalletAdapter for UnsafeBurnerWallet {
         ])
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
-        let kp = Keypair::new();
+        let kp = self.new_keypair()?;
         let public_key = kp.pubkey();
         *self.keypair.lock().map_err(|err| anyhow!("{err:?}"))? = Some(kp);
         self.event_emitter
             .emit(WalletAdapterEvent::Connect(public_key))
             .await?;
 
+        if let Some(auto_fund) = &self.auto_fund {
+            if let Err(err) = self.fund(auto_fund).await {
+                self.event_emitter.try_emit(WalletAdapterEvent::Error {
+                    wallet: self.name(),
+                    operation: WalletOperation::Airdrop,
+                    error: err.into(),
+                })?;
+            }
+        }
+
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn disconnect(&self) -> anyhow::Result<()> {
         *self.keypair.lock().map_err(|err| anyhow!("{err:?}"))? = None;
         self.event_emitter
-            .emit(WalletAdapterEvent::Disconnect)
-            .await?;
+            .try_emit(WalletAdapterEvent::Disconnect)?;
 
         Ok(())
     }
@@ -101,15 +252,20 @@ impl BaseWalletAdapter for UnsafeBurnerWallet {
     }
 }
 
-#[async_trait::async_trait(?Send)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 impl BaseSignerWalletAdapter for UnsafeBurnerWallet {
-    fn wallet_signer(&self) -> Option<Box<dyn Signer>> {
+    fn wallet_signer(&self) -> Option<Box<DynSigner>> {
         let opt_kp = self.keypair.lock().ok().unwrap();
         let kp = opt_kp.as_ref()?;
 
         Some(Box::new(kp.insecure_clone()))
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn sign_transaction(
         &self,
         mut transaction: wallet_adapter_base::TransactionOrVersionedTransaction,
@@ -119,24 +275,19 @@ impl BaseSignerWalletAdapter for UnsafeBurnerWallet {
             .as_ref()
             .ok_or_else(|| WalletError::WalletNotConnected)?;
 
-        match transaction {
-            wallet_adapter_base::TransactionOrVersionedTransaction::VersionedTransaction(
-                ref mut vtx,
-            ) => {
-                // TODO: implement support for VersionedTransaction
-                return Err(anyhow!("Unsupported transaction version: {:?}", vtx.version()).into());
-            }
-            wallet_adapter_base::TransactionOrVersionedTransaction::Transaction(ref mut tx) => {
-                tx.partial_sign(&[kp], tx.message.recent_blockhash);
-            }
-        }
+        transaction.partial_sign(&[Box::new(kp.insecure_clone()) as Box<dyn Signer>])?;
 
         Ok(transaction)
     }
 }
 
-#[async_trait::async_trait(?Send)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 impl BaseMessageSignerWalletAdapter for UnsafeBurnerWallet {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn sign_message(&self, message: &[u8]) -> wallet_adapter_base::Result<Vec<u8>> {
         let opt_kp = self.keypair.lock().map_err(|err| anyhow!("{err:?}"))?;
         let kp = opt_kp
@@ -148,3 +299,215 @@ impl BaseMessageSignerWalletAdapter for UnsafeBurnerWallet {
         Ok(sig_bytes.to_vec())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+    use futures::executor::block_on;
+    use serde_json::json;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::pubkey::Pubkey;
+    use wallet_adapter_base::{build_memo, TransactionBuilder, TransactionOrVersionedTransaction};
+    use wallet_adapter_common::explorer::DEVNET_GENESIS_HASH;
+    use wallet_adapter_common::offchain_message::{serialize_message, verify_message};
+    use wallet_adapter_common::rpc_connection::RpcConnection;
+    use wallet_adapter_common::transport::RpcTransport;
+
+    use super::*;
+
+    /// A devnet node granting every airdrop, remembering who asked for it
+    #[derive(Default)]
+    struct Faucet {
+        funded: Arc<Mutex<Vec<(Pubkey, u64)>>>,
+    }
+
+    #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+    impl RpcTransport for Faucet {
+        async fn post_json(&self, _url: &str, body: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+            let request: serde_json::Value = serde_json::from_slice(&body)?;
+            let result = match request["method"].as_str() {
+                Some("getGenesisHash") => json!(DEVNET_GENESIS_HASH),
+                Some("requestAirdrop") => {
+                    let public_key = request["params"][0].as_str().unwrap().parse()?;
+                    let lamports = request["params"][1].as_u64().unwrap();
+                    self.funded.lock().unwrap().push((public_key, lamports));
+                    json!(Signature::default().to_string())
+                }
+                method => bail!("unexpected {method:?}"),
+            };
+            Ok(serde_json::to_vec(
+                &json!({ "jsonrpc": "2.0", "result": result, "id": 1 }),
+            )?)
+        }
+
+        async fn get(&self, _url: &str) -> anyhow::Result<Vec<u8>> {
+            bail!("unused")
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_keypair() {
+        let connect = |mut wallet: UnsafeBurnerWallet| {
+            block_on(wallet.connect()).unwrap();
+            wallet.public_key().unwrap()
+        };
+
+        let mut wallet = UnsafeBurnerWallet::from_seed(b"ci");
+        let public_key = connect(wallet.clone());
+        block_on(wallet.disconnect()).unwrap();
+        block_on(wallet.connect()).unwrap();
+        assert_eq!(wallet.public_key(), Some(public_key));
+        assert_eq!(connect(UnsafeBurnerWallet::from_seed(b"ci")), public_key);
+
+        assert_ne!(connect(UnsafeBurnerWallet::from_seed(b"other")), public_key);
+        assert_ne!(
+            connect(UnsafeBurnerWallet::new()),
+            connect(UnsafeBurnerWallet::new())
+        );
+    }
+
+    #[test]
+    fn airdrops_to_the_connected_keypair() {
+        let faucet = Faucet::default();
+        let funded = faucet.funded.clone();
+        let connection = RpcConnection::with_transport("http://rpc".to_string(), faucet);
+        let mut wallet = UnsafeBurnerWallet::new();
+        assert!(matches!(
+            block_on(wallet.request_airdrop(&connection, 1_000)),
+            Err(WalletError::WalletNotConnected)
+        ));
+
+        block_on(wallet.connect()).unwrap();
+        let signature = block_on(wallet.request_airdrop(&connection, 1_000)).unwrap();
+
+        assert_eq!(signature, Signature::default());
+        assert_eq!(
+            *funded.lock().unwrap(),
+            vec![(wallet.public_key().unwrap(), 1_000)]
+        );
+    }
+
+    #[test]
+    fn the_icon_is_left_out_without_its_feature() {
+        let icon = UnsafeBurnerWallet::new().icon();
+        if cfg!(feature = "icon") {
+            assert!(icon.starts_with("data:image/svg+xml;base64,"));
+        } else {
+            assert!(icon.is_empty());
+        }
+    }
+
+    #[test]
+    fn signs_messages_raw_unless_asked_for_off_chain() {
+        let mut wallet = UnsafeBurnerWallet::new();
+        block_on(wallet.connect()).unwrap();
+        let public_key = wallet.public_key().unwrap();
+
+        let raw = block_on(wallet.sign_message(b"hello")).unwrap();
+        let raw = Signature::try_from(raw.as_slice()).unwrap();
+        assert!(raw.verify(public_key.as_ref(), b"hello"));
+
+        let offchain = block_on(wallet.sign_offchain_message(b"hello")).unwrap();
+        let offchain = Signature::try_from(offchain.as_slice()).unwrap();
+        assert!(!offchain.verify(public_key.as_ref(), b"hello"));
+        assert!(offchain.verify(public_key.as_ref(), &serialize_message(b"hello").unwrap()));
+        assert!(verify_message(&public_key, b"hello", &offchain));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn blocking_signer_signs_the_raw_bytes() {
+        let mut wallet = UnsafeBurnerWallet::new();
+        block_on(wallet.connect()).unwrap();
+        let public_key = wallet.public_key().unwrap();
+        let signer = wallet_adapter_base::BlockingSigner::with_message_signing(wallet);
+
+        let signature = signer.try_sign_message(b"hello").unwrap();
+        assert!(signature.verify(public_key.as_ref(), b"hello"));
+        assert!(!signature.verify(public_key.as_ref(), &serialize_message(b"hello").unwrap()));
+    }
+
+    #[test]
+    fn signs_legacy_and_v0_transactions() {
+        let mut wallet = UnsafeBurnerWallet::new();
+        block_on(wallet.connect()).unwrap();
+        let builder = TransactionBuilder::new()
+            .fee_payer(wallet.public_key().unwrap())
+            .add_instruction(build_memo(b"burner", &[]))
+            .recent_blockhash(Hash::new_unique());
+
+        let TransactionOrVersionedTransaction::Transaction(legacy) =
+            block_on(wallet.sign_transaction(builder.clone().build_legacy().unwrap())).unwrap()
+        else {
+            panic!("expected a legacy transaction");
+        };
+        assert!(legacy.verify().is_ok());
+
+        let TransactionOrVersionedTransaction::VersionedTransaction(v0) =
+            block_on(wallet.sign_transaction(builder.build_v0().unwrap())).unwrap()
+        else {
+            panic!("expected a versioned transaction");
+        };
+        assert!(v0.verify_with_results().into_iter().all(|ok| ok));
+    }
+
+    /// Records the name and `wallet` field of every span
+    #[cfg(feature = "instrument")]
+    #[derive(Default)]
+    struct Spans(Mutex<Vec<(&'static str, String)>>);
+
+    #[cfg(feature = "instrument")]
+    impl tracing::Subscriber for Spans {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut wallet = String::new();
+            span.record(
+                &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                    if field.name() == "wallet" {
+                        wallet = format!("{value:?}");
+                    }
+                },
+            );
+            let mut spans = self.0.lock().unwrap();
+            spans.push((span.metadata().name(), wallet));
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "instrument")]
+    #[test]
+    fn operations_run_in_spans_of_the_wallet() {
+        let spans = std::sync::Arc::new(Spans::default());
+        tracing::subscriber::with_default(spans.clone(), || {
+            let mut wallet = UnsafeBurnerWallet::new();
+            block_on(wallet.connect()).unwrap();
+            block_on(wallet.sign_message(b"hello")).unwrap();
+            block_on(wallet.disconnect()).unwrap();
+        });
+
+        let spans = spans.0.lock().unwrap().clone();
+        let wallet = "UnsafeBurnerWallet".to_string();
+        assert_eq!(
+            spans,
+            [
+                ("connect", wallet.clone()),
+                ("sign_message", wallet.clone()),
+                ("disconnect", wallet),
+            ]
+        );
+    }
+}
@@ -2,14 +2,26 @@ use std::sync::{Arc, Mutex};
 
 use anyhow::anyhow;
 use anyhow::Result;
-use solana_sdk::{signature::Keypair, signer::Signer, transaction::TransactionVersion};
+use solana_sdk::{
+    hash::{hashv, Hash},
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::TransactionVersion,
+};
 use wallet_adapter_base::{
-    BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter, WalletAdapterEvent,
-    WalletAdapterEventEmitter, WalletError, WalletReadyState,
+    BaseLockableWalletAdapter, BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter,
+    BaseWalletAdapter, WalletAdapterEvent, WalletAdapterEventEmitter, WalletError,
+    WalletReadyState,
 };
 use wallet_adapter_common::connection::Connection;
 use wallet_adapter_common::storage::KeypairStorage;
-use wallet_adapter_common::types::SendTransactionOptions;
+use wallet_adapter_common::types::{DynSigner, SendTransactionOptions};
+
+/// Left out of the binary when the `icon` feature is disabled
+#[cfg(feature = "icon")]
+const ICON: &str = "data:image/svg+xml;base64,PHN2ZyB3aWR0aD0iMzQiIGhlaWdodD0iMzAiIGZpbGw9Im5vbmUiIHhtbG5zPSJodHRwOi8vd3d3LnczLm9yZy8yMDAwL3N2ZyI+PHBhdGggZmlsbC1ydWxlPSJldmVub2RkIiBjbGlwLXJ1bGU9ImV2ZW5vZGQiIGQ9Ik0zNCAxMC42djIuN2wtOS41IDE2LjVoLTQuNmw2LTEwLjVhMi4xIDIuMSAwIDEgMCAyLTMuNGw0LjgtOC4zYTQgNCAwIDAgMSAxLjMgM1ptLTQuMyAxOS4xaC0uNmw0LjktOC40djQuMmMwIDIuMy0yIDQuMy00LjMgNC4zWm0yLTI4LjRjLS4zLS44LTEtMS4zLTItMS4zaC0xLjlsLTIuNCA0LjNIMzBsMS43LTNabS0zIDVoLTQuNkwxMC42IDI5LjhoNC43TDI4LjggNi40Wk0xOC43IDBoNC42bC0yLjUgNC4zaC00LjZMMTguNiAwWk0xNSA2LjRoNC42TDYgMjkuOEg0LjJjLS44IDAtMS43LS4zLTIuNC0uOEwxNSA2LjRaTTE0IDBIOS40TDcgNC4zaDQuNkwxNCAwWm0tMy42IDYuNEg1LjdMMCAxNi4ydjhMMTAuMyA2LjRaTTQuMyAwaC40TDAgOC4ydi00QzAgMiAxLjkgMCA0LjMgMFoiIGZpbGw9IiM5OTQ1RkYiLz48L3N2Zz4=";
+#[cfg(not(feature = "icon"))]
+const ICON: &str = "";
 
 #[derive(Debug, Clone)]
 pub struct UnsafePersistentWallet {
@@ -19,6 +31,9 @@ pub struct UnsafePersistentWallet {
      */
     keypair: Arc<Mutex<Option<Keypair>>>,
     keypair_storage: Arc<Box<dyn KeypairStorage>>,
+    locked: Arc<Mutex<bool>>,
+    /// Hash of the password `unlock` expects, any credentials unlock when unset
+    password_hash: Option<Hash>,
     event_emitter: WalletAdapterEventEmitter,
 }
 
@@ -31,12 +46,51 @@ impl UnsafePersistentWallet {
         Ok(Self {
             keypair: Arc::new(Mutex::new(None)),
             keypair_storage: Arc::new(Box::new(keypair_storage)),
+            locked: Arc::new(Mutex::new(false)),
+            password_hash: None,
             event_emitter: WalletAdapterEventEmitter::new(),
         })
     }
+
+    /// Require `password` to unlock the wallet
+    pub fn with_lock_password(mut self, password: &str) -> Self {
+        self.password_hash = Some(hashv(&[password.as_bytes()]));
+        self
+    }
+
+    fn check_unlocked(&self) -> wallet_adapter_base::Result<()> {
+        if *self.locked.lock().map_err(|err| anyhow!("{err:?}"))? {
+            return Err(WalletError::WalletLocked);
+        }
+        Ok(())
+    }
+
+    /// Fund the connected keypair from the faucet of `connection`'s cluster (devnet/testnet)
+    pub async fn request_airdrop(
+        &self,
+        connection: &dyn Connection,
+        lamports: u64,
+    ) -> wallet_adapter_base::Result<Signature> {
+        let public_key = self.public_key().ok_or(WalletError::WalletNotConnected)?;
+        Ok(connection.request_airdrop(&public_key, lamports).await?)
+    }
 }
 
-#[async_trait::async_trait(?Send)]
+#[cfg(target_arch = "wasm32")]
+impl UnsafePersistentWallet {
+    /// Register the wallet as a Wallet Standard wallet on `window`, so JS dApps and E2E tests
+    /// see it like any injected wallet, see [`wallet_adapter_wasm::standard_wallet`]
+    pub fn register_in_page(
+        &self,
+        connection: impl Connection + 'static,
+        chain: wallet_adapter_common::standard::SolanaChain,
+    ) -> anyhow::Result<wallet_adapter_wasm::standard_wallet::RegisteredWallet> {
+        wallet_adapter_wasm::standard_wallet::register(self.clone(), connection, chain)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 impl BaseWalletAdapter for UnsafePersistentWallet {
     fn event_emitter(&self) -> wallet_adapter_base::WalletAdapterEventEmitter {
         self.event_emitter.clone()
@@ -51,7 +105,7 @@ impl BaseWalletAdapter for UnsafePersistentWallet {
     }
 
     fn icon(&self) -> String {
-        "data:image/svg+xml;base64,PHN2ZyB3aWR0aD0iMzQiIGhlaWdodD0iMzAiIGZpbGw9Im5vbmUiIHhtbG5zPSJodHRwOi8vd3d3LnczLm9yZy8yMDAwL3N2ZyI+PHBhdGggZmlsbC1ydWxlPSJldmVub2RkIiBjbGlwLXJ1bGU9ImV2ZW5vZGQiIGQ9Ik0zNCAxMC42djIuN2wtOS41IDE2LjVoLTQuNmw2LTEwLjVhMi4xIDIuMSAwIDEgMCAyLTMuNGw0LjgtOC4zYTQgNCAwIDAgMSAxLjMgM1ptLTQuMyAxOS4xaC0uNmw0LjktOC40djQuMmMwIDIuMy0yIDQuMy00LjMgNC4zWm0yLTI4LjRjLS4zLS44LTEtMS4zLTItMS4zaC0xLjlsLTIuNCA0LjNIMzBsMS43LTNabS0zIDVoLTQuNkwxMC42IDI5LjhoNC43TDI4LjggNi40Wk0xOC43IDBoNC42bC0yLjUgNC4zaC00LjZMMTguNiAwWk0xNSA2LjRoNC42TDYgMjkuOEg0LjJjLS44IDAtMS43LS4zLTIuNC0uOEwxNSA2LjRaTTE0IDBIOS40TDcgNC4zaDQuNkwxNCAwWm0tMy42IDYuNEg1LjdMMCAxNi4ydjhMMTAuMyA2LjRaTTQuMyAwaC40TDAgOC4ydi00QzAgMiAxLjkgMCA0LjMgMFoiIGZpbGw9IiM5OTQ1RkYiLz48L3N2Zz4=".to_string()
+        ICON.to_string()
     }
 
     fn ready_state(&self) -> WalletReadyState {
@@ -79,6 +133,10 @@ impl BaseWalletAdapter for UnsafePersistentWallet {
         ])
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
         let kp = self
             .keypair_storage
@@ -95,6 +153,10 @@ impl BaseWalletAdapter for UnsafePersistentWallet {
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn disconnect(&self) -> anyhow::Result<()> {
         *self.keypair.lock().map_err(|err| anyhow!("{err:?}"))? = None;
         self.event_emitter
@@ -115,43 +177,51 @@ impl BaseWalletAdapter for UnsafePersistentWallet {
     }
 }
 
-#[async_trait::async_trait(?Send)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 impl BaseSignerWalletAdapter for UnsafePersistentWallet {
-    fn wallet_signer(&self) -> Option<Box<dyn Signer>> {
+    fn wallet_signer(&self) -> Option<Box<DynSigner>> {
+        if self.locked() {
+            return None;
+        }
+
         let opt_kp = self.keypair.lock().ok().unwrap();
         let kp = opt_kp.as_ref()?;
 
         Some(Box::new(kp.insecure_clone()))
     }
 
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn sign_transaction(
         &self,
         mut transaction: wallet_adapter_base::TransactionOrVersionedTransaction,
     ) -> wallet_adapter_base::Result<wallet_adapter_base::TransactionOrVersionedTransaction> {
+        self.check_unlocked()?;
+
         let opt_kp = self.keypair.lock().map_err(|err| anyhow!("{err:?}"))?;
         let kp = opt_kp
             .as_ref()
             .ok_or_else(|| WalletError::WalletNotConnected)?;
 
-        match transaction {
-            wallet_adapter_base::TransactionOrVersionedTransaction::VersionedTransaction(
-                ref mut vtx,
-            ) => {
-                // TODO: implement support for VersionedTransaction
-                return Err(anyhow!("Unsupported transaction version: {:?}", vtx.version()).into());
-            }
-            wallet_adapter_base::TransactionOrVersionedTransaction::Transaction(ref mut tx) => {
-                tx.partial_sign(&[kp], tx.message.recent_blockhash);
-            }
-        }
+        transaction.partial_sign(&[Box::new(kp.insecure_clone()) as Box<dyn Signer>])?;
 
         Ok(transaction)
     }
 }
 
-#[async_trait::async_trait(?Send)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 impl BaseMessageSignerWalletAdapter for UnsafePersistentWallet {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
     async fn sign_message(&self, message: &[u8]) -> wallet_adapter_base::Result<Vec<u8>> {
+        self.check_unlocked()?;
+
         let opt_kp = self.keypair.lock().map_err(|err| anyhow!("{err:?}"))?;
         let kp = opt_kp
             .as_ref()
@@ -162,3 +232,89 @@ impl BaseMessageSignerWalletAdapter for UnsafePersistentWallet {
         Ok(sig_bytes.to_vec())
     }
 }
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl BaseLockableWalletAdapter for UnsafePersistentWallet {
+    fn locked(&self) -> bool {
+        self.locked.lock().map(|locked| *locked).unwrap_or(true)
+    }
+
+    async fn lock(&self) -> wallet_adapter_base::Result<()> {
+        *self.locked.lock().map_err(|err| anyhow!("{err:?}"))? = true;
+        self.event_emitter.emit(WalletAdapterEvent::Locked).await?;
+
+        Ok(())
+    }
+
+    async fn unlock(&self, credentials: &str) -> wallet_adapter_base::Result<()> {
+        if let Some(password_hash) = self.password_hash {
+            if hashv(&[credentials.as_bytes()]) != password_hash {
+                return Err(anyhow!("invalid password").into());
+            }
+        }
+
+        *self.locked.lock().map_err(|err| anyhow!("{err:?}"))? = false;
+        self.event_emitter
+            .emit(WalletAdapterEvent::Unlocked)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    /// Keeps the keypair in memory
+    #[derive(Debug, Default)]
+    struct MemoryStorage(Mutex<Option<Keypair>>);
+
+    impl KeypairStorage for MemoryStorage {
+        fn get_keypair(&self) -> Result<Option<Keypair>> {
+            Ok(self.0.lock().unwrap().as_ref().map(Keypair::insecure_clone))
+        }
+
+        fn set_keypair(&self, keypair: Keypair) -> Result<()> {
+            *self.0.lock().unwrap() = Some(keypair);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn refuses_to_sign_while_locked() {
+        let mut wallet = UnsafePersistentWallet::new(MemoryStorage::default())
+            .unwrap()
+            .with_lock_password("hunter2");
+        block_on(wallet.connect()).unwrap();
+        let events = wallet.event_emitter();
+        while events.try_recv().is_some() {}
+
+        block_on(wallet.lock()).unwrap();
+        assert!(wallet.locked());
+        assert!(wallet.connected());
+        assert!(matches!(
+            block_on(wallet.sign_message(b"hello")),
+            Err(WalletError::WalletLocked)
+        ));
+        assert!(wallet.wallet_signer().is_none());
+
+        assert!(block_on(wallet.unlock("wrong")).is_err());
+        assert!(wallet.locked());
+
+        block_on(wallet.unlock("hunter2")).unwrap();
+        assert!(!wallet.locked());
+        assert!(block_on(wallet.sign_message(b"hello")).is_ok());
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Locked)
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Some(WalletAdapterEvent::Unlocked)
+        ));
+    }
+}
@@ -0,0 +1,341 @@
+//! A wallet whose ed25519 key never leaves a YubiKey, for desktop users who won't keep keys on disk.
+//!
+//! The adapter talks to the key through the [`HardwareKey`] trait, implemented for YubiKey PIV
+//! slots by [`pkcs11::Pkcs11Key`]. Signing runs on its own thread, the key may wait for a touch.
+
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use futures::channel::oneshot;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use solana_sdk::transaction::TransactionVersion;
+use wallet_adapter_base::{
+    BaseMessageSignerWalletAdapter, BaseSignerWalletAdapter, BaseWalletAdapter,
+    SupportedTransactionVersions, TransactionOrVersionedTransaction, WalletAdapterEvent,
+    WalletAdapterEventEmitter, WalletError, WalletReadyState,
+};
+use wallet_adapter_common::connection::Connection;
+use wallet_adapter_common::types::{DynSigner, SendTransactionOptions};
+
+/// An ed25519 key on a hardware token. The calls block until the token answers.
+pub trait HardwareKey: Send + Sync {
+    fn public_key(&self) -> anyhow::Result<Pubkey>;
+
+    /// Sign `message` itself, not a digest of it
+    fn sign(&self, message: &[u8]) -> anyhow::Result<Signature>;
+}
+
+/// Clones share the key, the connection state and the events
+pub struct YubiKeyWalletAdapter<K> {
+    key: Arc<K>,
+    public_key: Arc<Mutex<Option<Pubkey>>>,
+    connecting: Arc<AtomicBool>,
+    event_emitter: WalletAdapterEventEmitter,
+}
+
+impl<K> Clone for YubiKeyWalletAdapter<K> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            public_key: self.public_key.clone(),
+            connecting: self.connecting.clone(),
+            event_emitter: self.event_emitter.clone(),
+        }
+    }
+}
+
+impl<K: HardwareKey + 'static> YubiKeyWalletAdapter<K> {
+    pub fn new(key: K) -> Self {
+        Self {
+            key: Arc::new(key),
+            public_key: Arc::new(Mutex::new(None)),
+            connecting: Arc::new(AtomicBool::new(false)),
+            event_emitter: WalletAdapterEventEmitter::new(),
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Run `f` with the key on its own thread, so waiting for a touch doesn't block the executor
+    async fn with_key<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(Arc<K>) -> anyhow::Result<T> + Send + 'static,
+    ) -> anyhow::Result<T> {
+        let key = self.key.clone();
+        let (sender, receiver) = oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(f(key));
+        });
+        receiver
+            .await
+            .map_err(|_| anyhow!("the hardware key thread panicked"))?
+    }
+
+    fn connected_key(&self) -> wallet_adapter_base::Result<Pubkey> {
+        self.public_key().ok_or(WalletError::WalletNotConnected)
+    }
+}
+
+/// The key as a [`Signer`], to reuse the signature placement of
+/// [`TransactionOrVersionedTransaction::partial_sign`]
+struct KeySigner<K> {
+    key: Arc<K>,
+    public_key: Pubkey,
+}
+
+impl<K: HardwareKey> Signer for KeySigner<K> {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.public_key)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.key
+            .sign(message)
+            .map_err(|err| SignerError::Custom(err.to_string()))
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<K: HardwareKey + 'static> BaseWalletAdapter for YubiKeyWalletAdapter<K> {
+    fn event_emitter(&self) -> WalletAdapterEventEmitter {
+        self.event_emitter.clone()
+    }
+
+    fn name(&self) -> String {
+        "YubiKey".to_string()
+    }
+
+    fn url(&self) -> String {
+        "https://www.yubico.com".to_string()
+    }
+
+    fn icon(&self) -> String {
+        String::new()
+    }
+
+    fn ready_state(&self) -> WalletReadyState {
+        WalletReadyState::Installed
+    }
+
+    fn public_key(&self) -> Option<Pubkey> {
+        *self.public_key.lock().ok()?
+    }
+
+    fn connecting(&self) -> bool {
+        self.connecting.load(Ordering::SeqCst)
+    }
+
+    fn supported_transaction_versions(&self) -> Option<SupportedTransactionVersions> {
+        Some(vec![
+            TransactionVersion::LEGACY,
+            TransactionVersion::Number(0),
+        ])
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn connect(&mut self) -> wallet_adapter_base::Result<()> {
+        self.connecting.store(true, Ordering::SeqCst);
+        let result = self.with_key(|key| key.public_key()).await;
+        self.connecting.store(false, Ordering::SeqCst);
+
+        let public_key = result?;
+        *self.public_key.lock().map_err(|err| anyhow!("{err:?}"))? = Some(public_key);
+        self.event_emitter
+            .emit(WalletAdapterEvent::Connect(public_key))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> anyhow::Result<()> {
+        *self.public_key.lock().map_err(|err| anyhow!("{err:?}"))? = None;
+        self.event_emitter
+            .emit(WalletAdapterEvent::Disconnect)
+            .await?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn send_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+        connection: &dyn Connection,
+        options: Option<SendTransactionOptions>,
+    ) -> wallet_adapter_base::Result<Signature> {
+        if let Some(sponsor) = options.as_ref().and_then(|o| o.sponsor.clone()) {
+            return self
+                .send_sponsored_transaction(transaction, connection, sponsor.as_ref(), options)
+                .await;
+        }
+
+        self.check_if_transaction_is_supported(&transaction)?;
+
+        let transaction = match transaction {
+            TransactionOrVersionedTransaction::Transaction(tx) => {
+                let send_options = options.as_ref().map(|o| o.send_options.clone());
+                let mut tx = self
+                    .prepare_transaction(tx, connection, send_options.as_ref())
+                    .await?;
+
+                if let Some(options) = options.as_ref().filter(|o| !o.signers.is_empty()) {
+                    let signers: Vec<&dyn Signer> = options
+                        .signers
+                        .iter()
+                        .map(|s| s.as_ref() as &dyn Signer)
+                        .collect();
+                    tx.try_partial_sign(&signers, tx.message.recent_blockhash)
+                        .map_err(|err| anyhow!("{err}"))?;
+                }
+
+                TransactionOrVersionedTransaction::Transaction(tx)
+            }
+            versioned => versioned,
+        };
+
+        let signed = self.sign_transaction(transaction).await?;
+
+        Ok(connection
+            .send_raw_transaction(signed.serialize()?, options.as_ref())
+            .await?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<K: HardwareKey + 'static> BaseSignerWalletAdapter for YubiKeyWalletAdapter<K> {
+    /// The key never leaves the YubiKey
+    fn wallet_signer(&self) -> Option<Box<DynSigner>> {
+        None
+    }
+
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_transaction(
+        &self,
+        transaction: TransactionOrVersionedTransaction,
+    ) -> wallet_adapter_base::Result<TransactionOrVersionedTransaction> {
+        let public_key = self.connected_key()?;
+
+        let mut transaction = transaction;
+        Ok(self
+            .with_key(move |key| {
+                transaction.partial_sign(&[Box::new(KeySigner { key, public_key })])?;
+                Ok(transaction)
+            })
+            .await?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl<K: HardwareKey + 'static> BaseMessageSignerWalletAdapter for YubiKeyWalletAdapter<K> {
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(skip_all, fields(wallet = %self.name()))
+    )]
+    async fn sign_message(&self, message: &[u8]) -> wallet_adapter_base::Result<Vec<u8>> {
+        self.connected_key()?;
+
+        let message = message.to_vec();
+        let signature = self.with_key(move |key| key.sign(&message)).await?;
+
+        Ok(signature.as_ref().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use solana_sdk::message::{v0, VersionedMessage};
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::transaction::VersionedTransaction;
+    use wallet_adapter_base::{build_memo, TransactionBuilder};
+
+    use super::*;
+
+    /// A "hardware" key living in memory
+    struct SoftKey(Keypair);
+
+    impl HardwareKey for SoftKey {
+        fn public_key(&self) -> anyhow::Result<Pubkey> {
+            Ok(self.0.pubkey())
+        }
+
+        fn sign(&self, message: &[u8]) -> anyhow::Result<Signature> {
+            Ok(self.0.sign_message(message))
+        }
+    }
+
+    #[test]
+    fn signs_with_the_hardware_key() {
+        let keypair = Keypair::new();
+        let public_key = keypair.pubkey();
+        let mut adapter = YubiKeyWalletAdapter::new(SoftKey(keypair));
+        assert!(matches!(
+            block_on(adapter.sign_message(b"hello")),
+            Err(WalletError::WalletNotConnected)
+        ));
+
+        block_on(adapter.connect()).unwrap();
+        assert_eq!(adapter.public_key(), Some(public_key));
+
+        let signature = block_on(adapter.sign_message(b"hello")).unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        assert!(signature.verify(public_key.as_ref(), b"hello"));
+
+        let transaction = TransactionBuilder::new()
+            .fee_payer(public_key)
+            .add_instruction(build_memo(b"yubikey", &[]))
+            .build_legacy()
+            .unwrap();
+        let TransactionOrVersionedTransaction::Transaction(signed) =
+            block_on(adapter.sign_transaction(transaction)).unwrap()
+        else {
+            panic!("expected a legacy transaction");
+        };
+        assert!(signed.verify().is_ok());
+
+        let message = v0::Message::try_compile(
+            &public_key,
+            &[build_memo(b"yubikey", &[])],
+            &[],
+            Default::default(),
+        )
+        .unwrap();
+        let transaction = VersionedTransaction {
+            signatures: vec![],
+            message: VersionedMessage::V0(message),
+        };
+        let TransactionOrVersionedTransaction::VersionedTransaction(signed) =
+            block_on(adapter.sign_transaction(
+                TransactionOrVersionedTransaction::VersionedTransaction(transaction),
+            ))
+            .unwrap()
+        else {
+            panic!("expected a versioned transaction");
+        };
+        assert!(signed.verify_with_results().into_iter().all(|ok| ok));
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Context, Result};
+use cryptoki::context::{CInitializeArgs, CInitializeFlags, Pkcs11};
+use cryptoki::mechanism::eddsa::{EddsaParams, EddsaSignatureScheme};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use crate::HardwareKey;
+
+/// The PIV slots of a YubiKey that can hold a signing key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivSlot {
+    /// 9a
+    Authentication,
+    /// 9c, the PIN is required for every signature
+    Signature,
+    /// 9d
+    KeyManagement,
+    /// 9e, usable without the PIN
+    CardAuthentication,
+}
+
+impl PivSlot {
+    /// The `CKA_ID` of the slot's objects in Yubico's `libykcs11`
+    fn object_id(self) -> u8 {
+        match self {
+            Self::Authentication => 1,
+            Self::Signature => 2,
+            Self::KeyManagement => 3,
+            Self::CardAuthentication => 4,
+        }
+    }
+}
+
+impl fmt::Display for PivSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slot = match self {
+            Self::Authentication => "9a",
+            Self::Signature => "9c",
+            Self::KeyManagement => "9d",
+            Self::CardAuthentication => "9e",
+        };
+        f.write_str(slot)
+    }
+}
+
+/// An ed25519 key in a PIV slot of a YubiKey (firmware 5.7+), through a logged in PKCS#11 session
+pub struct Pkcs11Key {
+    session: Mutex<Session>,
+    private_key: ObjectHandle,
+    public_key: Pubkey,
+}
+
+impl Pkcs11Key {
+    /// Load the PKCS#11 `module`, eg. `/usr/local/lib/libykcs11.so`, log in to the first
+    /// YubiKey with the PIV `pin` and use the key in `slot`
+    pub fn open(module: impl AsRef<Path>, slot: PivSlot, pin: &str) -> Result<Self> {
+        let module = module.as_ref();
+        let pkcs11 =
+            Pkcs11::new(module).with_context(|| format!("loading {}", module.display()))?;
+        pkcs11.initialize(CInitializeArgs::new(CInitializeFlags::OS_LOCKING_OK))?;
+
+        let token = *pkcs11
+            .get_slots_with_token()?
+            .first()
+            .ok_or_else(|| anyhow!("no YubiKey is plugged in"))?;
+        let session = pkcs11.open_ro_session(token)?;
+        session
+            .login(UserType::User, Some(&AuthPin::from(pin)))
+            .context("PIV login")?;
+
+        let private_key = find_key(&session, ObjectClass::PRIVATE_KEY, slot)?;
+        let public_key = find_key(&session, ObjectClass::PUBLIC_KEY, slot)?;
+        let ec_point = match session
+            .get_attributes(public_key, &[AttributeType::EcPoint])?
+            .pop()
+        {
+            Some(Attribute::EcPoint(ec_point)) => ec_point,
+            _ => return Err(anyhow!("the public key in slot {slot} has no EC point")),
+        };
+
+        Ok(Self {
+            session: Mutex::new(session),
+            private_key,
+            public_key: ed25519_public_key(&ec_point)?,
+        })
+    }
+}
+
+fn find_key(session: &Session, class: ObjectClass, slot: PivSlot) -> Result<ObjectHandle> {
+    let template = [
+        Attribute::Class(class),
+        Attribute::KeyType(KeyType::EC_EDWARDS),
+        Attribute::Id(vec![slot.object_id()]),
+    ];
+    session
+        .find_objects(&template)?
+        .pop()
+        .ok_or_else(|| anyhow!("no ed25519 {class} in PIV slot {slot}"))
+}
+
+/// `CKA_EC_POINT` is the key in a DER OCTET STRING, some modules leave out the wrapping
+fn ed25519_public_key(ec_point: &[u8]) -> Result<Pubkey> {
+    let key = match ec_point {
+        [0x04, 0x20, key @ ..] if key.len() == 32 => key,
+        key => key,
+    };
+    Pubkey::try_from(key).map_err(|_| anyhow!("not an ed25519 EC point: {ec_point:?}"))
+}
+
+impl HardwareKey for Pkcs11Key {
+    fn public_key(&self) -> Result<Pubkey> {
+        Ok(self.public_key)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature> {
+        let session = self.session.lock().map_err(|err| anyhow!("{err:?}"))?;
+        let mechanism = Mechanism::Eddsa(EddsaParams::new(EddsaSignatureScheme::Pure));
+        let signature = session.sign(&mechanism, self.private_key, message)?;
+
+        Signature::try_from(signature.as_slice())
+            .map_err(|_| anyhow!("invalid signature length {}", signature.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_wrapped_and_bare_ec_points() {
+        let key = Pubkey::new_unique();
+        let mut wrapped = vec![0x04, 0x20];
+        wrapped.extend_from_slice(key.as_ref());
+
+        assert_eq!(ed25519_public_key(&wrapped).unwrap(), key);
+        assert_eq!(ed25519_public_key(key.as_ref()).unwrap(), key);
+        assert!(ed25519_public_key(&wrapped[..20]).is_err());
+    }
+}